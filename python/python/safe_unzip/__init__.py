@@ -61,9 +61,13 @@ from safe_unzip._safe_unzip import (
     list_tar_entries,
     list_tar_gz_entries,
     list_tar_bytes,
+    list_7z_entries,
+    list_7z_bytes,
     # Functions - Verification (no extraction)
     verify_file,
     verify_bytes,
+    is_safe_file,
+    validate_against_manifest,
     # Exceptions
     SafeUnzipError,
     PathEscapeError,
@@ -72,12 +76,14 @@ from safe_unzip._safe_unzip import (
     AlreadyExistsError,
     EncryptedArchiveError,
     UnsupportedEntryTypeError,
+    ManifestMismatchError,
+    EntryNotFoundError,
 )
 
 _PathType = Union[str, PathLike, Path]
-_OverwritePolicy = Literal["error", "skip", "overwrite"]
-_SymlinkPolicy = Literal["skip", "error"]
-_ExtractionMode = Literal["streaming", "validate_first"]
+_OverwritePolicy = Literal["error", "skip", "overwrite", "error_if_different"]
+_SymlinkPolicy = Literal["skip", "error", "allow_contained", "contained_or_skip"]
+_ExtractionMode = Literal["streaming", "validate_first", "validate_first_deep"]
 
 
 # ============================================================================
@@ -100,9 +106,12 @@ class Extractor:
         )
     """
     
-    def __init__(self, destination: _PathType) -> None:
-        """Create extractor for the given destination directory."""
-        self._inner = _RustExtractor(destination)
+    def __init__(self, destination: _PathType, hardened: bool = False) -> None:
+        """Create extractor for the given destination directory.
+
+        ``hardened=True`` starts from paranoid defaults for untrusted input.
+        """
+        self._inner = _RustExtractor(destination, hardened)
     
     def __enter__(self) -> "Extractor":
         """Enter the context manager."""
@@ -134,17 +143,17 @@ class Extractor:
         return self
     
     def overwrite(self, policy: _OverwritePolicy) -> "Extractor":
-        """Set overwrite policy: 'error', 'skip', or 'overwrite'."""
+        """Set overwrite policy: 'error', 'skip', 'overwrite', or 'error_if_different'."""
         self._inner.overwrite(policy)
         return self
     
     def symlinks(self, policy: _SymlinkPolicy) -> "Extractor":
-        """Set symlink policy: 'skip' or 'error'."""
+        """Set symlink policy: 'skip', 'error', 'allow_contained', or 'contained_or_skip'."""
         self._inner.symlinks(policy)
         return self
     
     def mode(self, mode: _ExtractionMode) -> "Extractor":
-        """Set extraction mode: 'streaming' or 'validate_first'."""
+        """Set extraction mode: 'streaming', 'validate_first' or 'validate_first_deep'."""
         self._inner.mode(mode)
         return self
     
@@ -203,6 +212,14 @@ class Extractor:
         """Extract a ZIP file."""
         return self._inner.extract_file(path)
     
+    def extract_selected(self, path: _PathType, names: list[str]) -> Report:
+        """Extract exactly the named entries from a ZIP file.
+
+        Raises EntryNotFoundError, before extracting anything, if a name
+        is not in the archive.
+        """
+        return self._inner.extract_selected(path, names)
+    
     def extract_bytes(self, data: bytes) -> Report:
         """Extract ZIP from bytes."""
         return self._inner.extract_bytes(data)
@@ -321,9 +338,12 @@ class AsyncExtractor:
             await e.extract_file("archive.zip")
     """
     
-    def __init__(self, destination: _PathType) -> None:
-        """Create async extractor for the given destination directory."""
-        self._extractor = _RustExtractor(destination)
+    def __init__(self, destination: _PathType, hardened: bool = False) -> None:
+        """Create async extractor for the given destination directory.
+
+        ``hardened=True`` starts from paranoid defaults for untrusted input.
+        """
+        self._extractor = _RustExtractor(destination, hardened)
     
     async def __aenter__(self) -> "AsyncExtractor":
         """Enter the async context manager."""
@@ -354,17 +374,17 @@ class AsyncExtractor:
         return self
     
     def overwrite(self, policy: _OverwritePolicy) -> "AsyncExtractor":
-        """Set overwrite policy: 'error', 'skip', or 'overwrite'."""
+        """Set overwrite policy: 'error', 'skip', 'overwrite', or 'error_if_different'."""
         self._extractor.overwrite(policy)
         return self
     
     def symlinks(self, policy: _SymlinkPolicy) -> "AsyncExtractor":
-        """Set symlink policy: 'skip' or 'error'."""
+        """Set symlink policy: 'skip', 'error', 'allow_contained', or 'contained_or_skip'."""
         self._extractor.symlinks(policy)
         return self
     
     def mode(self, mode: _ExtractionMode) -> "AsyncExtractor":
-        """Set extraction mode: 'streaming' or 'validate_first'."""
+        """Set extraction mode: 'streaming', 'validate_first' or 'validate_first_deep'."""
         self._extractor.mode(mode)
         return self
     
@@ -398,6 +418,10 @@ class AsyncExtractor:
         """Extract a ZIP file asynchronously."""
         return await asyncio.to_thread(self._extractor.extract_file, path)
     
+    async def extract_selected(self, path: _PathType, names: list[str]) -> Report:
+        """Extract exactly the named entries from a ZIP file asynchronously."""
+        return await asyncio.to_thread(self._extractor.extract_selected, path, names)
+    
     async def extract_bytes(self, data: bytes) -> Report:
         """Extract ZIP from bytes asynchronously."""
         return await asyncio.to_thread(self._extractor.extract_bytes, data)
@@ -440,9 +464,13 @@ __all__ = [
     "list_tar_entries",
     "list_tar_gz_entries",
     "list_tar_bytes",
+    "list_7z_entries",
+    "list_7z_bytes",
     # Sync Functions - Verification (no extraction)
     "verify_file",
     "verify_bytes",
+    "is_safe_file",
+    "validate_against_manifest",
     # Async Functions - ZIP
     "async_extract_file",
     "async_extract_bytes",
@@ -464,6 +492,8 @@ __all__ = [
     "AlreadyExistsError",
     "EncryptedArchiveError",
     "UnsupportedEntryTypeError",
+    "ManifestMismatchError",
+    "EntryNotFoundError",
 ]
 
 __version__ = "0.1.4"
@@ -2,12 +2,12 @@
 
 from os import PathLike
 from pathlib import Path
-from typing import Union, Literal, Coroutine, List, Optional, Callable
+from typing import Union, Literal, Coroutine, List, Optional, Callable, Dict
 
 _PathType = Union[str, PathLike[str], Path]
-_OverwritePolicy = Literal["error", "skip", "overwrite"]
-_SymlinkPolicy = Literal["skip", "error"]
-_ExtractionMode = Literal["streaming", "validate_first"]
+_OverwritePolicy = Literal["error", "skip", "overwrite", "error_if_different"]
+_SymlinkPolicy = Literal["skip", "error", "allow_contained", "contained_or_skip"]
+_ExtractionMode = Literal["streaming", "validate_first", "validate_first_deep"]
 _EntryKind = Literal["file", "directory", "symlink"]
 
 
@@ -41,6 +41,22 @@ class EntryInfo:
     def symlink_target(self) -> Optional[str]:
         """The target path if this is a symlink, None otherwise."""
         ...
+    @property
+    def uid(self) -> Optional[int]:
+        """Owner user id recorded in the archive, if any. Not applied on extraction."""
+        ...
+    @property
+    def gid(self) -> Optional[int]:
+        """Owner group id recorded in the archive, if any. Not applied on extraction."""
+        ...
+    @property
+    def mtime(self) -> Optional[float]:
+        """Stored modification time as seconds since the epoch, if any.
+
+        Not applied on extraction. ZIP entries only have one if they carry
+        an NTFS or Unix timestamp extra field.
+        """
+        ...
 
 
 class Report:
@@ -61,6 +77,14 @@ class Report:
     def entries_skipped(self) -> int:
         """Number of entries skipped (symlinks, filtered, existing)."""
         ...
+    @property
+    def bytes_read(self) -> int:
+        """Bytes consumed from the archive source."""
+        ...
+    @property
+    def was_empty(self) -> bool:
+        """True if the archive had no entries (not just all filtered out)."""
+        ...
 
 
 class VerifyReport:
@@ -83,8 +107,18 @@ class Extractor:
             e.extract_file("archive.zip")
     """
     
-    def __init__(self, destination: _PathType) -> None:
-        """Create extractor for the given destination directory."""
+    def __init__(self, destination: _PathType, hardened: bool = False) -> None:
+        """Create extractor for the given destination directory.
+
+        With ``hardened=True`` the extractor starts from paranoid defaults
+        for untrusted archives: strict limits (100 MB total, 1,000 files,
+        10 MB per file, depth 10), symlinks raise an error, entries are
+        validated and their CRCs checked before anything is written
+        (``validate_first_deep``), deceptive Unicode, Windows
+        reserved names (``CON``, ``aux.py``, ...) and absolute paths are
+        rejected, and execute bits are cleared. Later builder calls
+        override these.
+        """
         ...
     
     def __enter__(self) -> "Extractor":
@@ -117,15 +151,15 @@ class Extractor:
         ...
     
     def overwrite(self, policy: _OverwritePolicy) -> "Extractor":
-        """Set overwrite policy: 'error', 'skip', or 'overwrite'."""
+        """Set overwrite policy: 'error', 'skip', 'overwrite', or 'error_if_different'."""
         ...
     
     def symlinks(self, policy: _SymlinkPolicy) -> "Extractor":
-        """Set symlink policy: 'skip' or 'error'."""
+        """Set symlink policy: 'skip', 'error', 'allow_contained', or 'contained_or_skip'."""
         ...
     
     def mode(self, mode: _ExtractionMode) -> "Extractor":
-        """Set extraction mode: 'streaming' or 'validate_first'."""
+        """Set extraction mode: 'streaming', 'validate_first' or 'validate_first_deep'."""
         ...
     
     def only(self, names: list[str]) -> "Extractor":
@@ -149,6 +183,14 @@ class Extractor:
         """Extract a ZIP file."""
         ...
     
+    def extract_selected(self, path: _PathType, names: list[str]) -> Report:
+        """Extract exactly the named entries from a ZIP file.
+
+        Unlike only(), every name must exist: a missing one raises
+        EntryNotFoundError before anything is extracted.
+        """
+        ...
+    
     def extract_bytes(self, data: bytes) -> Report:
         """Extract ZIP from bytes."""
         ...
@@ -179,8 +221,11 @@ class AsyncExtractor:
             await e.extract_file("archive.zip")
     """
     
-    def __init__(self, destination: _PathType) -> None:
-        """Create async extractor for the given destination directory."""
+    def __init__(self, destination: _PathType, hardened: bool = False) -> None:
+        """Create async extractor for the given destination directory.
+
+        See ``Extractor`` for what ``hardened=True`` sets.
+        """
         ...
     
     async def __aenter__(self) -> "AsyncExtractor":
@@ -213,15 +258,15 @@ class AsyncExtractor:
         ...
     
     def overwrite(self, policy: _OverwritePolicy) -> "AsyncExtractor":
-        """Set overwrite policy: 'error', 'skip', or 'overwrite'."""
+        """Set overwrite policy: 'error', 'skip', 'overwrite', or 'error_if_different'."""
         ...
     
     def symlinks(self, policy: _SymlinkPolicy) -> "AsyncExtractor":
-        """Set symlink policy: 'skip' or 'error'."""
+        """Set symlink policy: 'skip', 'error', 'allow_contained', or 'contained_or_skip'."""
         ...
     
     def mode(self, mode: _ExtractionMode) -> "AsyncExtractor":
-        """Set extraction mode: 'streaming' or 'validate_first'."""
+        """Set extraction mode: 'streaming', 'validate_first' or 'validate_first_deep'."""
         ...
     
     def only(self, names: list[str]) -> "AsyncExtractor":
@@ -245,6 +290,10 @@ class AsyncExtractor:
         """Extract a ZIP file asynchronously."""
         ...
     
+    async def extract_selected(self, path: _PathType, names: list[str]) -> Report:
+        """Extract exactly the named entries from a ZIP file asynchronously."""
+        ...
+    
     async def extract_bytes(self, data: bytes) -> Report:
         """Extract ZIP from bytes asynchronously."""
         ...
@@ -347,6 +396,15 @@ def list_tar_bytes(data: bytes) -> List[EntryInfo]:
     """List entries in TAR bytes without extracting."""
     ...
 
+# 7z
+def list_7z_entries(path: _PathType) -> List[EntryInfo]:
+    """List entries in a 7z file from its headers, without decompressing."""
+    ...
+
+def list_7z_bytes(data: bytes) -> List[EntryInfo]:
+    """List entries in 7z bytes from their headers, without decompressing."""
+    ...
+
 
 # ============================================================================
 # Sync Verification Functions
@@ -366,6 +424,33 @@ def verify_bytes(data: bytes) -> VerifyReport:
     ...
 
 
+def is_safe_file(
+    path: _PathType,
+    max_total_mb: Optional[int] = None,
+    max_files: Optional[int] = None,
+    max_single_file_mb: Optional[int] = None,
+    max_depth: Optional[int] = None,
+    symlinks: Literal["skip", "error"] = "skip",
+) -> bool:
+    """Check whether an archive would extract cleanly, without extracting it.
+    
+    Only entry metadata is read. Returns False if the archive breaks a
+    security rule or limit; raises only for I/O errors or unreadable archives.
+    Limits default to the same values as Extractor.
+    """
+    ...
+
+
+def validate_against_manifest(path: _PathType, manifest: Dict[str, int]) -> None:
+    """Check that an archive contains exactly the files in `manifest`.
+    
+    `manifest` maps entry names to sizes in bytes. Only metadata is read;
+    directory entries are ignored. Raises ManifestMismatchError listing
+    every missing, unexpected, and resized entry.
+    """
+    ...
+
+
 # ============================================================================
 # Async Listing Functions
 # ============================================================================
@@ -437,3 +522,11 @@ class UnsupportedEntryTypeError(SafeUnzipError):
     """Archive contains unsupported entry type (device file, fifo, etc.)."""
     ...
 
+class ManifestMismatchError(SafeUnzipError):
+    """Archive contents differ from the expected manifest."""
+    ...
+
+class EntryNotFoundError(SafeUnzipError):
+    """A requested entry is not in the archive."""
+    ...
+
@@ -1,4 +1,4 @@
-use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::exceptions::{PyIOError, PyTimeoutError, PyValueError};
 use pyo3::prelude::*;
 use std::path::PathBuf;
 
@@ -13,12 +13,17 @@ pyo3::create_exception!(safe_unzip, QuotaError, SafeUnzipError);
 pyo3::create_exception!(safe_unzip, AlreadyExistsError, SafeUnzipError);
 pyo3::create_exception!(safe_unzip, EncryptedArchiveError, SafeUnzipError);
 pyo3::create_exception!(safe_unzip, UnsupportedEntryTypeError, SafeUnzipError);
+pyo3::create_exception!(safe_unzip, ManifestMismatchError, SafeUnzipError);
+pyo3::create_exception!(safe_unzip, EntryNotFoundError, SafeUnzipError);
 
 fn to_py_err(err: safe_unzip::Error) -> PyErr {
     match err {
         safe_unzip::Error::PathEscape { entry, detail } => {
             PathEscapeError::new_err(format!("path '{}' escapes destination: {}", entry, detail))
         }
+        safe_unzip::Error::AbsolutePath { entry } => {
+            PathEscapeError::new_err(format!("entry '{}' has an absolute path", entry))
+        }
         safe_unzip::Error::SymlinkNotAllowed { entry, target } => {
             if target.is_empty() {
                 SymlinkNotAllowedError::new_err(format!(
@@ -36,14 +41,34 @@ fn to_py_err(err: safe_unzip::Error) -> PyErr {
             "extraction would write {} bytes, exceeding the {} byte limit",
             would_be, limit
         )),
+        safe_unzip::Error::CompressedSizeExceeded { limit, size } => QuotaError::new_err(format!(
+            "archive is at least {} bytes, exceeding the {} byte compressed input limit",
+            size, limit
+        )),
         safe_unzip::Error::FileCountExceeded { limit, attempted } => QuotaError::new_err(format!(
             "archive contains {} files, exceeding the {} file limit",
             attempted, limit
         )),
+        safe_unzip::Error::DirCountExceeded { limit, attempted } => QuotaError::new_err(format!(
+            "archive contains {} directories, exceeding the {} directory limit",
+            attempted, limit
+        )),
+        safe_unzip::Error::EntryCountExceeded { limit, attempted } => QuotaError::new_err(format!(
+            "archive has at least {} entries, exceeding the {} entry limit",
+            attempted, limit
+        )),
         safe_unzip::Error::FileTooLarge { entry, limit, size } => QuotaError::new_err(format!(
             "file '{}' is {} bytes (limit: {} bytes)",
             entry, size, limit
         )),
+        safe_unzip::Error::CompressionRatioExceeded {
+            entry,
+            ratio,
+            limit,
+        } => QuotaError::new_err(format!(
+            "file '{}' expands {}x from its compressed size (limit: {}x)",
+            entry, ratio, limit
+        )),
         safe_unzip::Error::SizeMismatch {
             entry,
             declared,
@@ -63,6 +88,10 @@ fn to_py_err(err: safe_unzip::Error) -> PyErr {
         safe_unzip::Error::AlreadyExists { entry } => {
             AlreadyExistsError::new_err(format!("file '{}' already exists", entry))
         }
+        safe_unzip::Error::ContentConflict { entry } => AlreadyExistsError::new_err(format!(
+            "file '{}' already exists with different contents",
+            entry
+        )),
         safe_unzip::Error::InvalidFilename { entry, reason } => {
             PathEscapeError::new_err(format!("invalid filename '{}': {}", entry, reason))
         }
@@ -70,15 +99,42 @@ fn to_py_err(err: safe_unzip::Error) -> PyErr {
             "entry '{}' is encrypted (encrypted archives not supported)",
             entry
         )),
+        safe_unzip::Error::UnsupportedEncryption { entry, scheme } => {
+            EncryptedArchiveError::new_err(format!(
+                "entry '{}' is encrypted with {} (encrypted archives not supported)",
+                entry, scheme
+            ))
+        }
         safe_unzip::Error::UnsupportedEntryType { entry, entry_type } => {
             UnsupportedEntryTypeError::new_err(format!(
                 "entry '{}' has unsupported type '{}' (device files, fifos not allowed)",
                 entry, entry_type
             ))
         }
+        err @ safe_unzip::Error::ManifestMismatch { .. } => {
+            ManifestMismatchError::new_err(err.to_string())
+        }
         safe_unzip::Error::DestinationNotFound { path } => {
             PyIOError::new_err(format!("destination directory '{}' does not exist", path))
         }
+        safe_unzip::Error::DeadlineExceeded => {
+            PyTimeoutError::new_err("extraction deadline exceeded")
+        }
+        safe_unzip::Error::Timeout { entry } => {
+            PyTimeoutError::new_err(format!("entry '{}' exceeded the per-entry deadline", entry))
+        }
+        safe_unzip::Error::SignatureInvalid { reason } => {
+            SafeUnzipError::new_err(format!("archive signature verification failed: {}", reason))
+        }
+        safe_unzip::Error::DecodeMemoryExceeded { limit, required } => {
+            QuotaError::new_err(format!(
+                "decoding requires a {} byte dictionary (limit: {} bytes)",
+                required, limit
+            ))
+        }
+        safe_unzip::Error::InvalidOption { option, reason } => {
+            PyValueError::new_err(format!("invalid option '{}': {}", option, reason))
+        }
         safe_unzip::Error::Zip(e) => PyValueError::new_err(format!("zip format error: {}", e)),
         safe_unzip::Error::Io(e) => PyIOError::new_err(format!("I/O error: {}", e)),
         safe_unzip::Error::Jail(e) => {
@@ -103,15 +159,24 @@ struct PyReport {
     #[pyo3(get)]
     bytes_written: u64,
     #[pyo3(get)]
+    bytes_read: u64,
+    #[pyo3(get)]
     entries_skipped: usize,
+    #[pyo3(get)]
+    was_empty: bool,
 }
 
 #[pymethods]
 impl PyReport {
     fn __repr__(&self) -> String {
         format!(
-            "Report(files_extracted={}, dirs_created={}, bytes_written={}, entries_skipped={})",
-            self.files_extracted, self.dirs_created, self.bytes_written, self.entries_skipped
+            "Report(files_extracted={}, dirs_created={}, bytes_written={}, bytes_read={}, entries_skipped={}, was_empty={})",
+            self.files_extracted,
+            self.dirs_created,
+            self.bytes_written,
+            self.bytes_read,
+            self.entries_skipped,
+            if self.was_empty { "True" } else { "False" }
         )
     }
 }
@@ -122,7 +187,9 @@ impl From<safe_unzip::Report> for PyReport {
             files_extracted: r.files_extracted,
             dirs_created: r.dirs_created,
             bytes_written: r.bytes_written,
+            bytes_read: r.bytes_read,
             entries_skipped: r.entries_skipped,
+            was_empty: r.was_empty,
         }
     }
 }
@@ -133,7 +200,9 @@ impl From<safe_unzip::ExtractionReport> for PyReport {
             files_extracted: r.files_extracted,
             dirs_created: r.dirs_created,
             bytes_written: r.bytes_written,
+            bytes_read: r.bytes_read,
             entries_skipped: r.entries_skipped,
+            was_empty: r.was_empty,
         }
     }
 }
@@ -159,6 +228,12 @@ struct PyEntryInfo {
     is_symlink: bool,
     #[pyo3(get)]
     symlink_target: Option<String>,
+    #[pyo3(get)]
+    uid: Option<u32>,
+    #[pyo3(get)]
+    gid: Option<u32>,
+    #[pyo3(get)]
+    mtime: Option<f64>,
 }
 
 #[pymethods]
@@ -192,6 +267,14 @@ impl From<safe_unzip::EntryInfo> for PyEntryInfo {
             is_dir,
             is_symlink,
             symlink_target,
+            uid: e.uid,
+            gid: e.gid,
+            mtime: e
+                .mtime
+                .map(|time| match time.duration_since(std::time::UNIX_EPOCH) {
+                    Ok(since) => since.as_secs_f64(),
+                    Err(before) => -before.duration().as_secs_f64(),
+                }),
         }
     }
 }
@@ -210,6 +293,7 @@ struct PyExtractor {
     overwrite: String,
     symlinks: String,
     mode: String,
+    hardened: bool,
     // Filter options
     only_names: Option<Vec<String>>,
     include_patterns: Option<Vec<String>>,
@@ -221,8 +305,18 @@ struct PyExtractor {
 #[pymethods]
 impl PyExtractor {
     #[new]
-    fn new(destination: PathBuf) -> Self {
-        let defaults = safe_unzip::Limits::default();
+    #[pyo3(signature = (destination, hardened=false))]
+    fn new(destination: PathBuf, hardened: bool) -> Self {
+        let defaults = if hardened {
+            safe_unzip::Limits::strict()
+        } else {
+            safe_unzip::Limits::default()
+        };
+        let (symlinks, mode) = if hardened {
+            ("error", "validate_first_deep")
+        } else {
+            ("skip", "streaming")
+        };
         PyExtractor {
             destination,
             max_total_bytes: defaults.max_total_bytes,
@@ -230,8 +324,9 @@ impl PyExtractor {
             max_single_file: defaults.max_single_file,
             max_path_depth: defaults.max_path_depth,
             overwrite: "error".to_string(),
-            symlinks: "skip".to_string(),
-            mode: "streaming".to_string(),
+            symlinks: symlinks.to_string(),
+            mode: mode.to_string(),
+            hardened,
             only_names: None,
             include_patterns: None,
             exclude_patterns: None,
@@ -263,39 +358,44 @@ impl PyExtractor {
         slf
     }
 
-    /// Set overwrite policy: "error", "skip", or "overwrite".
+    /// Set overwrite policy: "error", "skip", "overwrite", or
+    /// "error_if_different".
     fn overwrite(mut slf: PyRefMut<'_, Self>, policy: String) -> PyResult<PyRefMut<'_, Self>> {
         match policy.as_str() {
-            "error" | "skip" | "overwrite" => {
+            "error" | "skip" | "overwrite" | "error_if_different" => {
                 slf.overwrite = policy;
                 Ok(slf)
             }
             _ => Err(PyValueError::new_err(
-                "overwrite must be 'error', 'skip', or 'overwrite'",
+                "overwrite must be 'error', 'skip', 'overwrite', or 'error_if_different'",
             )),
         }
     }
 
-    /// Set symlink policy: "skip" or "error".
+    /// Set symlink policy: "skip", "error", "allow_contained", or
+    /// "contained_or_skip".
     fn symlinks(mut slf: PyRefMut<'_, Self>, policy: String) -> PyResult<PyRefMut<'_, Self>> {
         match policy.as_str() {
-            "skip" | "error" => {
+            "skip" | "error" | "allow_contained" | "contained_or_skip" => {
                 slf.symlinks = policy;
                 Ok(slf)
             }
-            _ => Err(PyValueError::new_err("symlinks must be 'skip' or 'error'")),
+            _ => Err(PyValueError::new_err(
+                "symlinks must be 'skip', 'error', 'allow_contained', or 'contained_or_skip'",
+            )),
         }
     }
 
-    /// Set extraction mode: "streaming" or "validate_first".
+    /// Set extraction mode: "streaming", "validate_first" or
+    /// "validate_first_deep".
     fn mode(mut slf: PyRefMut<'_, Self>, mode: String) -> PyResult<PyRefMut<'_, Self>> {
         match mode.as_str() {
-            "streaming" | "validate_first" => {
+            "streaming" | "validate_first" | "validate_first_deep" => {
                 slf.mode = mode;
                 Ok(slf)
             }
             _ => Err(PyValueError::new_err(
-                "mode must be 'streaming' or 'validate_first'",
+                "mode must be 'streaming', 'validate_first' or 'validate_first_deep'",
             )),
         }
     }
@@ -353,15 +453,58 @@ impl PyExtractor {
 
     /// Extract from a file path.
     fn extract_file(&self, path: PathBuf) -> PyResult<PyReport> {
+        if self.needs_driver()? {
+            let driver = self.build_driver()?;
+            let report = driver.extract_zip_file(path).map_err(to_py_err)?;
+            return Ok(report.into());
+        }
         let extractor = self.build_extractor()?;
         let report = extractor.extract_file(path).map_err(to_py_err)?;
         Ok(report.into())
     }
 
+    /// Extract exactly the named entries from a ZIP file.
+    ///
+    /// Unlike `only()`, which skips names the archive doesn't have, every
+    /// name must exist: a missing one raises EntryNotFoundError before
+    /// anything is extracted. The names replace any `only`,
+    /// `include_glob`, or `exclude_glob` filter for this call.
+    ///
+    /// Example:
+    ///     names = [e.name for e in list_zip_entries("archive.zip") if e.is_file]
+    ///     extractor.extract_selected("archive.zip", names[:3])
+    fn extract_selected(&self, path: PathBuf, names: Vec<String>) -> PyResult<PyReport> {
+        let entries = safe_unzip::list_zip_entries(&path).map_err(to_py_err)?;
+        if let Some(missing) = names
+            .iter()
+            .find(|name| !entries.iter().any(|entry| &entry.name == *name))
+        {
+            return Err(EntryNotFoundError::new_err(format!(
+                "entry '{}' not found in archive",
+                missing
+            )));
+        }
+
+        if self.needs_driver()? {
+            let driver = self.build_driver()?.only(&names);
+            let report = driver.extract_zip_file(path).map_err(to_py_err)?;
+            return Ok(report.into());
+        }
+        let extractor = self.build_extractor()?.only(&names);
+        let report = extractor.extract_file(path).map_err(to_py_err)?;
+        Ok(report.into())
+    }
+
     /// Extract from bytes.
     fn extract_bytes(&self, data: &[u8]) -> PyResult<PyReport> {
-        let extractor = self.build_extractor()?;
         let cursor = std::io::Cursor::new(data.to_vec());
+        if self.needs_driver()? {
+            let driver = self.build_driver()?;
+            let adapter = safe_unzip::ZipAdapter::new(cursor).map_err(to_py_err)?;
+            let report = driver.extract_zip(adapter).map_err(to_py_err)?;
+            return Ok(report.into());
+        }
+        let extractor = self.build_extractor()?;
         let report = extractor.extract(cursor).map_err(to_py_err)?;
         Ok(report.into())
     }
@@ -416,14 +559,35 @@ impl PyExtractor {
 }
 
 impl PyExtractor {
+    /// True if a ZIP must go through the Driver because the legacy
+    /// Extractor has no equivalent for the chosen overwrite or symlink mode.
+    fn needs_driver(&self) -> PyResult<bool> {
+        let needs = self.overwrite == "error_if_different"
+            || matches!(
+                self.symlinks.as_str(),
+                "allow_contained" | "contained_or_skip"
+            );
+        if needs && self.progress_callback.is_some() {
+            return Err(PyValueError::new_err(format!(
+                "on_progress is not supported for ZIP with overwrite='{}', symlinks='{}'",
+                self.overwrite, self.symlinks
+            )));
+        }
+        Ok(needs)
+    }
+
     fn build_extractor(&self) -> PyResult<safe_unzip::Extractor> {
         let mut extractor = safe_unzip::Extractor::new(&self.destination).map_err(to_py_err)?;
+        if self.hardened {
+            extractor = extractor.hardened();
+        }
 
         extractor = extractor.limits(safe_unzip::Limits {
             max_total_bytes: self.max_total_bytes,
             max_file_count: self.max_file_count,
             max_single_file: self.max_single_file,
             max_path_depth: self.max_path_depth,
+            ..Default::default()
         });
 
         extractor = match self.overwrite.as_str() {
@@ -439,6 +603,7 @@ impl PyExtractor {
 
         extractor = match self.mode.as_str() {
             "validate_first" => extractor.mode(safe_unzip::ExtractionMode::ValidateFirst),
+            "validate_first_deep" => extractor.mode(safe_unzip::ExtractionMode::ValidateFirstDeep),
             _ => extractor.mode(safe_unzip::ExtractionMode::Streaming),
         };
 
@@ -482,24 +647,42 @@ impl PyExtractor {
             max_file_count: self.max_file_count,
             max_single_file: self.max_single_file,
             max_path_depth: self.max_path_depth,
+            ..Default::default()
         });
 
         driver = match self.overwrite.as_str() {
             "skip" => driver.overwrite(safe_unzip::OverwriteMode::Skip),
             "overwrite" => driver.overwrite(safe_unzip::OverwriteMode::Overwrite),
+            "error_if_different" => driver.overwrite(safe_unzip::OverwriteMode::ErrorIfDifferent),
             _ => driver.overwrite(safe_unzip::OverwriteMode::Error),
         };
 
         driver = match self.symlinks.as_str() {
             "error" => driver.symlinks(safe_unzip::SymlinkBehavior::Error),
+            "allow_contained" => driver.symlinks(safe_unzip::SymlinkBehavior::AllowContained),
+            "contained_or_skip" => driver.symlinks(safe_unzip::SymlinkBehavior::ContainedOrSkip),
             _ => driver.symlinks(safe_unzip::SymlinkBehavior::Skip),
         };
 
         driver = match self.mode.as_str() {
             "validate_first" => driver.validation(safe_unzip::ValidationMode::ValidateFirst),
+            "validate_first_deep" => {
+                driver.validation(safe_unzip::ValidationMode::ValidateFirstDeep)
+            }
             _ => driver.validation(safe_unzip::ValidationMode::Streaming),
         };
 
+        if self.hardened {
+            driver = driver
+                .reject_deceptive_unicode(true)
+                .windows_name_check(true)
+                .absolute_paths(safe_unzip::AbsolutePathPolicy::Reject)
+                .permissions(|entry, mode| match entry.kind {
+                    safe_unzip::EntryKind::File => mode & !0o111,
+                    _ => mode,
+                });
+        }
+
         // Apply filters
         if let Some(ref names) = self.only_names {
             driver = driver.only(names);
@@ -618,6 +801,20 @@ fn list_tar_bytes(data: &[u8]) -> PyResult<Vec<PyEntryInfo>> {
     Ok(entries.into_iter().map(PyEntryInfo::from).collect())
 }
 
+/// List entries in a 7z file from its headers, without decompressing.
+#[pyfunction]
+fn list_7z_entries(path: PathBuf) -> PyResult<Vec<PyEntryInfo>> {
+    let entries = safe_unzip::list_7z_entries(&path).map_err(to_py_err)?;
+    Ok(entries.into_iter().map(PyEntryInfo::from).collect())
+}
+
+/// List entries in 7z bytes from their headers, without decompressing.
+#[pyfunction]
+fn list_7z_bytes(data: &[u8]) -> PyResult<Vec<PyEntryInfo>> {
+    let entries = safe_unzip::list_7z_bytes(data).map_err(to_py_err)?;
+    Ok(entries.into_iter().map(PyEntryInfo::from).collect())
+}
+
 // ============================================================================
 // Verification Report
 // ============================================================================
@@ -672,6 +869,51 @@ fn verify_bytes(data: &[u8]) -> PyResult<PyVerifyReport> {
     Ok(PyVerifyReport::from(report))
 }
 
+/// Check whether an archive would extract cleanly, without extracting it.
+///
+/// Returns False for archives that break a security rule or limit; raises
+/// only for I/O errors or unreadable archives.
+#[pyfunction]
+#[pyo3(signature = (path, max_total_mb=None, max_files=None, max_single_file_mb=None, max_depth=None, symlinks="skip"))]
+fn is_safe_file(
+    path: PathBuf,
+    max_total_mb: Option<u64>,
+    max_files: Option<usize>,
+    max_single_file_mb: Option<u64>,
+    max_depth: Option<usize>,
+    symlinks: &str,
+) -> PyResult<bool> {
+    let defaults = safe_unzip::Limits::default();
+    let limits = safe_unzip::Limits {
+        max_total_bytes: max_total_mb.map_or(defaults.max_total_bytes, |mb| mb * 1024 * 1024),
+        max_file_count: max_files.unwrap_or(defaults.max_file_count),
+        max_single_file: max_single_file_mb.map_or(defaults.max_single_file, |mb| mb * 1024 * 1024),
+        max_path_depth: max_depth.unwrap_or(defaults.max_path_depth),
+        ..defaults
+    };
+    let symlinks = match symlinks {
+        "skip" => safe_unzip::SymlinkBehavior::Skip,
+        "error" => safe_unzip::SymlinkBehavior::Error,
+        _ => return Err(PyValueError::new_err("symlinks must be 'skip' or 'error'")),
+    };
+
+    safe_unzip::is_safe_file(&path, &limits, symlinks).map_err(to_py_err)
+}
+
+/// Check that an archive contains exactly the files in `manifest`
+/// (name -> size), without extracting it.
+///
+/// Raises ManifestMismatchError listing every missing, unexpected, and
+/// resized entry.
+#[pyfunction]
+fn validate_against_manifest(
+    path: PathBuf,
+    manifest: std::collections::HashMap<String, u64>,
+) -> PyResult<()> {
+    let manifest: safe_unzip::Manifest = manifest.into_iter().collect();
+    safe_unzip::validate_against_manifest(&path, &manifest).map_err(to_py_err)
+}
+
 // ============================================================================
 // Module
 // ============================================================================
@@ -703,10 +945,14 @@ fn _safe_unzip(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(list_tar_entries, m)?)?;
     m.add_function(wrap_pyfunction!(list_tar_gz_entries, m)?)?;
     m.add_function(wrap_pyfunction!(list_tar_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(list_7z_entries, m)?)?;
+    m.add_function(wrap_pyfunction!(list_7z_bytes, m)?)?;
 
     // Functions - Verification (no extraction)
     m.add_function(wrap_pyfunction!(verify_file, m)?)?;
     m.add_function(wrap_pyfunction!(verify_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(is_safe_file, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_against_manifest, m)?)?;
 
     // Exceptions
     m.add("SafeUnzipError", py.get_type::<SafeUnzipError>())?;
@@ -725,6 +971,11 @@ fn _safe_unzip(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
         "UnsupportedEntryTypeError",
         py.get_type::<UnsupportedEntryTypeError>(),
     )?;
+    m.add(
+        "ManifestMismatchError",
+        py.get_type::<ManifestMismatchError>(),
+    )?;
+    m.add("EntryNotFoundError", py.get_type::<EntryNotFoundError>())?;
 
     Ok(())
 }
@@ -27,10 +27,15 @@ from safe_unzip import (
     list_tar_entries,
     list_tar_bytes,
     async_list_zip_bytes,
+    is_safe_file,
+    validate_against_manifest,
+    ManifestMismatchError,
+    EntryNotFoundError,
     PathEscapeError,
     QuotaError,
     AlreadyExistsError,
     UnsupportedEntryTypeError,
+    SymlinkNotAllowedError,
 )
 
 
@@ -233,6 +238,32 @@ def test_overwrite_policy_overwrite(tmp_path):
     assert (tmp_path / "existing.txt").read_bytes() == b"new"
 
 
+def test_overwrite_policy_error_if_different(tmp_path):
+    """Test that 'error_if_different' skips identical files and rejects changed ones."""
+    (tmp_path / "same.txt").write_bytes(b"same")
+    report = (
+        Extractor(tmp_path)
+        .overwrite("error_if_different")
+        .extract_bytes(create_simple_zip("same.txt", b"same"))
+    )
+    assert report.entries_skipped == 1
+
+    (tmp_path / "changed.txt").write_bytes(b"local edit")
+    with pytest.raises(AlreadyExistsError):
+        (
+            Extractor(tmp_path)
+            .overwrite("error_if_different")
+            .extract_tar_bytes(create_simple_tar("changed.txt", b"upstream"))
+        )
+    assert (tmp_path / "changed.txt").read_bytes() == b"local edit"
+
+
+def test_invalid_overwrite_policy():
+    """Test that unknown overwrite policies list the accepted values."""
+    with pytest.raises(ValueError, match="error_if_different"):
+        Extractor(".").overwrite("newer")
+
+
 # ============================================================================
 # Security Tests: Symlinks (Unix only)
 # ============================================================================
@@ -790,6 +821,43 @@ def test_list_with_directory():
     assert "file" in kinds
 
 
+
+def test_list_reports_owner():
+    """Listing exposes the uid/gid recorded in TAR headers."""
+    buffer = io.BytesIO()
+    with tarfile.open(fileobj=buffer, mode="w") as tf:
+        data = b"content"
+        info = tarfile.TarInfo(name="owned.txt")
+        info.size = len(data)
+        info.uid = 1000
+        info.gid = 100
+        tf.addfile(info, io.BytesIO(data))
+
+    entries = list_tar_bytes(buffer.getvalue())
+
+    assert entries[0].uid == 1000
+    assert entries[0].gid == 100
+
+    # ZIPs without Unix extra fields carry no owner
+    zip_entries = list_zip_bytes(create_multi_file_zip({"a.txt": b"a"}))
+    assert zip_entries[0].uid is None
+    assert zip_entries[0].gid is None
+
+
+def test_list_reports_mtime():
+    """Listing exposes the modification time stored in TAR headers."""
+    buffer = io.BytesIO()
+    with tarfile.open(fileobj=buffer, mode="w") as tf:
+        data = b"content"
+        info = tarfile.TarInfo(name="dated.txt")
+        info.size = len(data)
+        info.mtime = 1_700_000_000
+        tf.addfile(info, io.BytesIO(data))
+
+    entries = list_tar_bytes(buffer.getvalue())
+
+    assert entries[0].mtime == 1_700_000_000
+
 @pytest.mark.asyncio
 async def test_async_list_zip_bytes():
     """Test async listing of ZIP entries."""
@@ -875,6 +943,30 @@ def test_only_filter(tmp_path):
     assert not (tmp_path / "code.py").exists()
 
 
+def test_extract_selected(tmp_path):
+    """Test pick-and-extract requires every requested name to exist."""
+    archive = tmp_path / "archive.zip"
+    archive.write_bytes(create_multi_file_zip({
+        "readme.txt": b"readme",
+        "code.py": b"code",
+    }))
+    dest = tmp_path / "out"
+    dest.mkdir()
+
+    picked = [e.name for e in list_zip_entries(archive) if e.name.endswith(".txt")]
+    report = Extractor(dest).extract_selected(archive, picked)
+    assert report.files_extracted == 1
+    assert (dest / "readme.txt").exists()
+    assert not (dest / "code.py").exists()
+
+    with pytest.raises(EntryNotFoundError, match="missing.txt"):
+        Extractor(dest).overwrite("overwrite").extract_selected(
+            archive, ["code.py", "missing.txt"]
+        )
+    # Nothing is extracted when a name is missing
+    assert not (dest / "code.py").exists()
+
+
 def test_include_glob_filter(tmp_path):
     """Test include_glob pattern matching."""
     zip_data = create_multi_file_zip({
@@ -937,6 +1029,60 @@ def test_only_filter_on_tar(tmp_path):
     assert (tmp_path / "c.txt").exists()
 
 
+def test_report_was_empty(tmp_path):
+    """Test was_empty distinguishes empty archives from fully filtered ones."""
+    empty = Extractor(tmp_path).extract_bytes(create_multi_file_zip({}))
+    assert empty.files_extracted == 0
+    assert empty.was_empty
+
+    filtered = (
+        Extractor(tmp_path)
+        .only(["missing.txt"])
+        .extract_bytes(create_simple_zip("a.txt", b"a"))
+    )
+    assert filtered.files_extracted == 0
+    assert not filtered.was_empty
+
+
+def test_is_safe_file(tmp_path):
+    """Test is_safe_file returns False for violations without extracting."""
+    good = tmp_path / "good.zip"
+    good.write_bytes(create_simple_zip("a.txt", b"a"))
+    evil = tmp_path / "evil.zip"
+    evil.write_bytes(create_simple_zip("../../etc/passwd", b"x"))
+    many = tmp_path / "many.zip"
+    many.write_bytes(create_multi_file_zip({"a": b"a", "b": b"b", "c": b"c"}))
+
+    assert is_safe_file(good)
+    assert not is_safe_file(evil)
+    assert not is_safe_file(many, max_files=2)
+
+    with pytest.raises(IOError):
+        is_safe_file(tmp_path / "missing.zip")
+
+
+def test_validate_against_manifest(tmp_path):
+    """Test manifest validation reports every discrepancy."""
+    bundle = tmp_path / "bundle.zip"
+    bundle.write_bytes(create_multi_file_zip({"a.txt": b"aaa", "b.txt": b"bb"}))
+
+    validate_against_manifest(bundle, {"a.txt": 3, "b.txt": 2})
+
+    with pytest.raises(ManifestMismatchError) as exc:
+        validate_against_manifest(bundle, {"a.txt": 4, "c.txt": 1})
+    message = str(exc.value)
+    assert "c.txt" in message
+    assert "b.txt" in message
+    assert "'a.txt' is 3 bytes, expected 4" in message
+
+
+def test_report_bytes_read(tmp_path):
+    """Test bytes_read covers the whole archive after extraction."""
+    zip_data = create_simple_zip("a.txt", b"hello")
+    report = Extractor(tmp_path).extract_bytes(zip_data)
+    assert report.bytes_read >= len(zip_data)
+
+
 @pytest.mark.asyncio
 async def test_async_filter(tmp_path):
     """Test filtering with async extractor."""
@@ -955,3 +1101,41 @@ async def test_async_filter(tmp_path):
     assert (tmp_path / "keep.txt").exists()
     assert not (tmp_path / "skip.txt").exists()
 
+
+def test_hardened_extractor(tmp_path):
+    """hardened=True rejects symlinks and reserved names, clears execute bits."""
+    buffer = io.BytesIO()
+    with tarfile.open(fileobj=buffer, mode="w") as tf:
+        info = tarfile.TarInfo(name="link")
+        info.type = tarfile.SYMTYPE
+        info.linkname = "target.txt"
+        tf.addfile(info)
+
+    with pytest.raises(SymlinkNotAllowedError):
+        Extractor(tmp_path, hardened=True).extract_tar_bytes(buffer.getvalue())
+
+    # Windows reserved names are rejected on every platform
+    buffer = io.BytesIO()
+    with tarfile.open(fileobj=buffer, mode="w") as tf:
+        info = tarfile.TarInfo(name="aux.py")
+        info.size = 1
+        tf.addfile(info, io.BytesIO(b"x"))
+
+    with pytest.raises(PathEscapeError):
+        Extractor(tmp_path, hardened=True).extract_tar_bytes(buffer.getvalue())
+    assert not (tmp_path / "aux.py").exists()
+
+    buffer = io.BytesIO()
+    with tarfile.open(fileobj=buffer, mode="w") as tf:
+        data = b"#!/bin/sh\n"
+        info = tarfile.TarInfo(name="run.sh")
+        info.size = len(data)
+        info.mode = 0o755
+        tf.addfile(info, io.BytesIO(data))
+
+    dest = tmp_path / "tool"
+    dest.mkdir()
+    report = Extractor(dest, hardened=True).extract_tar_bytes(buffer.getvalue())
+    assert report.files_extracted == 1
+    if os.name == "posix":
+        assert (dest / "run.sh").stat().st_mode & 0o777 == 0o644
@@ -3,6 +3,7 @@
 //! Adapters normalize different archive formats into a common interface
 //! for the extraction engine.
 
+mod counting;
 #[cfg(feature = "tar")]
 mod tar_adapter;
 mod zip_adapter;
@@ -12,7 +13,35 @@ mod sevenz_adapter;
 
 #[cfg(feature = "tar")]
 pub use tar_adapter::{copy_limited, TarAdapter};
+#[cfg(feature = "tar")]
+pub(crate) use tar_adapter::{header_mtime, read_pax};
 pub use zip_adapter::ZipAdapter;
+pub(crate) use zip_adapter::{
+    check_central_directory, check_declared_size, raw_entry_info, reject_encrypted,
+};
+
+pub(crate) use counting::CountingReader;
+
+use std::io::Read;
+
+use crate::entry::EntryInfo;
+use crate::error::Error;
+
+/// Sequential, one entry at a time access to an archive.
+///
+/// This is what [`Driver::open`](crate::Driver::open) pulls entries
+/// through. [`ZipAdapter`] and `TarAdapter` implement it.
+pub trait ArchiveReader {
+    /// Advance to the next entry and return its metadata, or `None` at the
+    /// end of the archive. Whatever was not read of the previous entry's
+    /// content is skipped.
+    fn next_entry(&mut self) -> Option<Result<EntryInfo, Error>>;
+
+    /// Reader over the content of the entry last returned by
+    /// [`next_entry`](Self::next_entry). Empty for directories and
+    /// symlinks, and before the first entry.
+    fn entry_reader(&mut self) -> Result<Box<dyn Read + '_>, Error>;
+}
 
 #[cfg(feature = "sevenz")]
 pub use sevenz_adapter::SevenZAdapter;
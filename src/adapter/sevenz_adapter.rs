@@ -3,17 +3,23 @@
 //! Provides read-only extraction of 7z archives with the same security
 //! guarantees as ZIP and TAR.
 
-use std::io::Write;
-use std::path::Path;
+use std::cell::OnceCell;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 
-use crate::entry::{EntryInfo, EntryKind};
+use sevenz_rust::{Password, SevenZArchiveEntry, SevenZMethod};
+
+use crate::entry::{filetime, EntryInfo, EntryKind};
 use crate::error::Error;
+use crate::limits::Limits;
 
 /// Adapter for 7z archives.
 ///
-/// Uses the `sevenz-rust` crate for decompression. Note that 7z archives
-/// are fully decompressed into memory before extraction, so very large
-/// archives may use significant RAM.
+/// Uses the `sevenz-rust` crate for decompression. Opening only reads the
+/// archive headers. [`extract_each`](Self::extract_each) decodes entries
+/// one at a time without keeping them; everything else decompresses the
+/// whole archive into memory on first use, so very large archives may use
+/// significant RAM.
 ///
 /// # Example
 ///
@@ -24,8 +30,26 @@ use crate::error::Error;
 /// let report = Driver::new("/tmp/out")?.extract_7z(adapter)?;
 /// ```
 pub struct SevenZAdapter {
+    /// Archive on disk.
+    path: PathBuf,
+    /// Keeps the backing file alive for archives opened from bytes.
+    _temp: Option<tempfile::NamedTempFile>,
+    /// Number of entries, from the archive header.
+    len: usize,
+    /// Size of the archive file.
+    file_len: u64,
+    /// Largest decoder dictionary the archive may declare.
+    max_decode_memory: Option<u64>,
+    /// Password for AES-encrypted archives; empty if none was given.
+    password: Password,
+    /// Most bytes a single entry may decompress to.
+    max_single_file: u64,
+    /// Most bytes all entries together may decompress to.
+    max_total_bytes: u64,
+    /// Entries were decoded by [`extract_each`](Self::extract_each).
+    streamed: bool,
     /// Cached entries (7z requires full decompression)
-    entries: Vec<SevenZEntry>,
+    entries: OnceCell<Vec<SevenZEntry>>,
 }
 
 struct SevenZEntry {
@@ -35,10 +59,47 @@ struct SevenZEntry {
 
 impl SevenZAdapter {
     /// Open a 7z file from a path.
+    ///
+    /// An archive whose headers are encrypted fails with
+    /// [`Error::EncryptedEntry`] naming the archive file. One with
+    /// encrypted contents opens, and fails the same way, naming the first
+    /// encrypted entry, once its data is needed.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        let path = path.as_ref();
-        let entries = Self::decompress_all(path)?;
-        Ok(Self { entries })
+        Self::open_with(path.as_ref(), Password::empty())
+    }
+
+    /// Open an AES-encrypted 7z file from a path.
+    ///
+    /// Decryption changes nothing else: entries go through the same size,
+    /// path, and policy checks as those of an unencrypted archive. A wrong
+    /// password fails with [`Error::Io`] when the archive is decoded.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use safe_unzip::{Driver, SevenZAdapter};
+    ///
+    /// let adapter = SevenZAdapter::open_with_password("secret.7z", "hunter2")?;
+    /// let report = Driver::new("/tmp/out")?.extract_7z(adapter)?;
+    /// ```
+    pub fn open_with_password<P: AsRef<Path>>(path: P, password: &str) -> Result<Self, Error> {
+        Self::open_with(path.as_ref(), Password::from(password))
+    }
+
+    fn open_with(path: &Path, password: Password) -> Result<Self, Error> {
+        let archive = Self::open_reader(path, &password)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            _temp: None,
+            len: archive.archive().files.len(),
+            file_len: std::fs::metadata(path)?.len(),
+            max_decode_memory: None,
+            password,
+            max_single_file: u64::MAX,
+            max_total_bytes: u64::MAX,
+            streamed: false,
+            entries: OnceCell::new(),
+        })
     }
 
     /// Open a 7z file from bytes.
@@ -47,62 +108,290 @@ impl SevenZAdapter {
         let mut temp = tempfile::NamedTempFile::new()?;
         temp.write_all(data)?;
         temp.flush()?;
-        Self::open(temp.path())
+        let mut adapter = Self::open(temp.path()).map_err(|e| match e {
+            // The temporary file's name means nothing to the caller
+            Error::EncryptedEntry { .. } => Error::EncryptedEntry {
+                entry: "<7z bytes>".to_string(),
+            },
+            e => e,
+        })?;
+        adapter._temp = Some(temp);
+        Ok(adapter)
+    }
+
+    /// Refuse archives whose decoders need more than `bytes` of dictionary
+    /// (window) memory.
+    ///
+    /// 7z solid blocks decode as a unit, so a crafted header can demand
+    /// gigabytes of working memory regardless of per-file size limits.
+    /// The check runs against the headers before anything is decompressed
+    /// and fails with [`Error::DecodeMemoryExceeded`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use safe_unzip::{Driver, SevenZAdapter};
+    ///
+    /// let adapter = SevenZAdapter::open("upload.7z")?.max_decode_memory(64 * 1024 * 1024);
+    /// let report = Driver::new("/tmp/out")?.extract_7z(adapter)?;
+    /// ```
+    pub fn max_decode_memory(mut self, bytes: u64) -> Self {
+        self.max_decode_memory = Some(bytes);
+        self
+    }
+
+    /// Stop decompressing once an entry exceeds `limits.max_single_file`
+    /// or all entries together exceed `limits.max_total_bytes`.
+    ///
+    /// Entries are decompressed into memory, so without a cap a small
+    /// archive can exhaust RAM before any policy sees its entries. The
+    /// [`Driver`](crate::Driver) applies its own limits on top of these.
+    /// The total counts every file entry, including ones a filter would
+    /// skip, since all of them are held in memory.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.max_single_file = limits.max_single_file;
+        self.max_total_bytes = limits.max_total_bytes;
+        self
     }
 
-    fn decompress_all(path: &Path) -> Result<Vec<SevenZEntry>, Error> {
+    /// Tighten the decompression caps to `limits`, keeping any stricter
+    /// ones already set.
+    pub(crate) fn cap_sizes(&mut self, limits: &Limits) {
+        self.max_single_file = self.max_single_file.min(limits.max_single_file);
+        self.max_total_bytes = self.max_total_bytes.min(limits.max_total_bytes);
+    }
+
+    fn open_reader(
+        path: &Path,
+        password: &Password,
+    ) -> Result<sevenz_rust::SevenZReader<std::fs::File>, Error> {
+        sevenz_rust::SevenZReader::open(path, password.clone()).map_err(|e| match e {
+            // Encrypted headers: no entry names are readable
+            sevenz_rust::Error::PasswordRequired => Error::EncryptedEntry {
+                entry: path
+                    .file_name()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy()
+                    .into_owned(),
+            },
+            e => Error::Io(std::io::Error::other(format!("7z open error: {}", e))),
+        })
+    }
+
+    /// Decompressed entries, decoding the archive on first call.
+    fn entries(&self) -> Result<&[SevenZEntry], Error> {
+        if let Some(entries) = self.entries.get() {
+            return Ok(entries);
+        }
+        let entries = self.decompress_all()?;
+        Ok(self.entries.get_or_init(|| entries))
+    }
+
+    fn decompress_all(&self) -> Result<Vec<SevenZEntry>, Error> {
         let mut entries = Vec::new();
 
         // Use the lower-level API to iterate entries
-        let mut archive = sevenz_rust::SevenZReader::open(path, sevenz_rust::Password::empty())
-            .map_err(|e| Error::Io(std::io::Error::other(format!("7z open error: {}", e))))?;
-
-        // Iterate through all entries
-        archive
-            .for_each_entries(|entry, reader| {
-                let name = entry.name().to_string();
-
-                // Determine entry kind
-                let kind = if entry.is_directory() {
-                    EntryKind::Directory
-                } else {
-                    EntryKind::File
-                };
-
-                // Read content for files
-                let mut data = Vec::new();
-                if matches!(kind, EntryKind::File) {
-                    reader.read_to_end(&mut data)?;
+        let mut archive = self.open_for_decoding()?;
+
+        // Iterate through all entries. A limit error is kept here and the
+        // walk aborted, since returning false only ends the current block
+        let mut exceeded = None;
+        let mut total = 0u64;
+        let result = archive.for_each_entries(|entry, reader| {
+            // Read content for files, never more than one byte past the cap
+            let mut data = Vec::new();
+            if !entry.is_directory() {
+                if entry.size > self.max_single_file {
+                    exceeded = Some(self.file_too_large(entry, entry.size));
+                    return Err(sevenz_rust::Error::other("size limit exceeded"));
                 }
+                reader
+                    .take(self.max_single_file.saturating_add(1))
+                    .read_to_end(&mut data)?;
+            }
 
-                let info = EntryInfo {
-                    name,
-                    size: data.len() as u64,
-                    kind,
-                    mode: None, // 7z doesn't preserve Unix permissions
-                };
+            let size = data.len() as u64;
+            if size > self.max_single_file {
+                exceeded = Some(self.file_too_large(entry, size));
+                return Err(sevenz_rust::Error::other("size limit exceeded"));
+            }
+            total += size;
+            if total > self.max_total_bytes {
+                exceeded = Some(Error::TotalSizeExceeded {
+                    limit: self.max_total_bytes,
+                    would_be: total,
+                });
+                return Err(sevenz_rust::Error::other("size limit exceeded"));
+            }
 
-                entries.push(SevenZEntry { info, data });
-                Ok(true)
-            })
-            .map_err(|e| Error::Io(std::io::Error::other(format!("7z read error: {}", e))))?;
+            let info = entry_info(entry, size);
+            entries.push(SevenZEntry { info, data });
+            Ok(true)
+        });
+        if let Some(err) = exceeded {
+            return Err(err);
+        }
+        result.map_err(|e| Error::Io(std::io::Error::other(format!("7z read error: {}", e))))?;
 
         Ok(entries)
     }
 
+    fn file_too_large(&self, entry: &SevenZArchiveEntry, size: u64) -> Error {
+        Error::FileTooLarge {
+            entry: entry.name().to_string(),
+            limit: self.max_single_file,
+            size,
+        }
+    }
+
+    /// Reader for decoding entry data, once the headers show nothing
+    /// this adapter would refuse to decode.
+    fn open_for_decoding(&self) -> Result<sevenz_rust::SevenZReader<std::fs::File>, Error> {
+        let archive = Self::open_reader(&self.path, &self.password)?;
+        self.check_decode_memory(archive.archive())?;
+        if self.password.is_empty() {
+            if let Some(entry) = first_encrypted(archive.archive()) {
+                return Err(Error::EncryptedEntry { entry });
+            }
+        }
+        Ok(archive)
+    }
+
+    fn check_decode_memory(&self, archive: &sevenz_rust::Archive) -> Result<(), Error> {
+        let Some(limit) = self.max_decode_memory else {
+            return Ok(());
+        };
+
+        for coder in archive.folders.iter().flat_map(|folder| &folder.coders) {
+            let required = dictionary_size(coder.decompression_method_id(), &coder.properties);
+            if required > limit {
+                return Err(Error::DecodeMemoryExceeded { limit, required });
+            }
+        }
+        Ok(())
+    }
+
+    /// Entry metadata from the archive headers, without decompressing
+    /// any entry data. Sizes are the ones the headers declare.
+    pub(crate) fn header_entries(&self) -> Result<Vec<EntryInfo>, Error> {
+        let archive = Self::open_reader(&self.path, &self.password)?;
+        Ok(archive
+            .archive()
+            .files
+            .iter()
+            .map(|entry| entry_info(entry, entry.size))
+            .collect())
+    }
+
     /// Get all entry metadata.
-    pub fn entries_metadata(&self) -> Vec<EntryInfo> {
-        self.entries.iter().map(|e| e.info.clone()).collect()
+    ///
+    /// Decompresses the archive if it hasn't been already.
+    pub fn entries_metadata(&self) -> Result<Vec<EntryInfo>, Error> {
+        Ok(self.entries()?.iter().map(|e| e.info.clone()).collect())
     }
 
     /// Get the number of entries.
     pub fn len(&self) -> usize {
-        self.entries.len()
+        self.len
     }
 
     /// Check if the archive is empty.
     pub fn is_empty(&self) -> bool {
-        self.entries.is_empty()
+        self.len == 0
+    }
+
+    /// Size of the archive file in bytes.
+    pub fn archive_len(&self) -> u64 {
+        self.file_len
+    }
+
+    /// Bytes read from the archive so far.
+    ///
+    /// 7z decodes the whole archive at once, so this is 0 before the first
+    /// access to entry data and the full archive size after.
+    pub fn bytes_read(&self) -> u64 {
+        if self.streamed || self.entries.get().is_some() {
+            self.file_len
+        } else {
+            0
+        }
+    }
+
+    /// Reorder entries by name.
+    ///
+    /// The sort is stable, so entries with the same name keep archive order.
+    pub fn sort_by_name(&mut self) -> Result<(), Error> {
+        self.entries()?;
+        if let Some(entries) = self.entries.get_mut() {
+            entries.sort_by(|a, b| a.info.name.cmp(&b.info.name));
+        }
+        Ok(())
+    }
+
+    /// Up to `len` bytes from the start of the entry at `index` (empty for
+    /// directories).
+    ///
+    /// Unlike [`ZipAdapter::peek_entry`](crate::ZipAdapter::peek_entry),
+    /// this is not cheap: in a solid archive an entry can only be reached
+    /// by decoding everything before it in its block, so the first call
+    /// decompresses the whole archive, as any other access to entry data
+    /// does. Later calls read from memory.
+    pub fn peek_entry(&self, index: usize, len: usize) -> Result<Vec<u8>, Error> {
+        let entry = self.entries()?.get(index).ok_or_else(|| {
+            Error::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no entry at index {}", index),
+            ))
+        })?;
+        Ok(entry.data[..len.min(entry.data.len())].to_vec())
+    }
+
+    /// Decode the archive once, in archive order, handing each entry's
+    /// content to `callback` as it is decompressed.
+    ///
+    /// Unlike [`for_each`](Self::for_each), nothing is cached: at most one
+    /// read buffer of entry data is in memory at a time, so the caps set
+    /// with [`limits`](Self::limits) don't apply and bounding what is read
+    /// is up to the callback. Whatever it leaves unread is decoded and
+    /// discarded, as solid blocks can't be skipped. Directories get an
+    /// empty reader.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use safe_unzip::SevenZAdapter;
+    ///
+    /// let mut adapter = SevenZAdapter::open("archive.7z")?;
+    /// adapter.extract_each(|info, reader| {
+    ///     let size = std::io::copy(reader, &mut std::io::sink())?;
+    ///     println!("{}: {} bytes", info.name, size);
+    ///     Ok(())
+    /// })?;
+    /// ```
+    pub fn extract_each<F>(&mut self, mut callback: F) -> Result<(), Error>
+    where
+        F: FnMut(&EntryInfo, &mut dyn Read) -> Result<(), Error>,
+    {
+        let mut archive = self.open_for_decoding()?;
+        self.streamed = true;
+
+        // As in decompress_all, a callback error is kept and the walk
+        // aborted through the decoder's own error type
+        let mut failed = None;
+        let result = archive.for_each_entries(|entry, reader| {
+            let info = entry_info(entry, entry.size);
+            if let Err(err) = callback(&info, reader) {
+                failed = Some(err);
+                return Err(sevenz_rust::Error::other("extraction failed"));
+            }
+            // The next entry starts where this one's data ends
+            std::io::copy(reader, &mut std::io::sink())?;
+            Ok(true)
+        });
+        if let Some(err) = failed {
+            return Err(err);
+        }
+        result.map_err(|e| Error::Io(std::io::Error::other(format!("7z read error: {}", e))))
     }
 
     /// Process each entry with a callback.
@@ -110,7 +399,7 @@ impl SevenZAdapter {
     where
         F: FnMut(&EntryInfo, Option<&[u8]>) -> Result<bool, Error>,
     {
-        for entry in &self.entries {
+        for entry in self.entries()? {
             let data = if matches!(entry.info.kind, EntryKind::File) {
                 Some(entry.data.as_slice())
             } else {
@@ -124,3 +413,66 @@ impl SevenZAdapter {
         Ok(())
     }
 }
+
+/// Name of the first entry stored in an AES-encrypted block.
+fn first_encrypted(archive: &sevenz_rust::Archive) -> Option<String> {
+    archive
+        .files
+        .iter()
+        .zip(&archive.stream_map.file_folder_index)
+        .find(|(_, folder)| {
+            folder
+                .and_then(|folder| archive.folders.get(folder))
+                .is_some_and(|folder| {
+                    folder.coders.iter().any(|coder| {
+                        coder.decompression_method_id() == SevenZMethod::ID_AES256SHA256
+                    })
+                })
+        })
+        .map(|(entry, _)| entry.name().to_string())
+}
+
+/// Metadata for `entry`, whose data is `size` bytes.
+fn entry_info(entry: &SevenZArchiveEntry, size: u64) -> EntryInfo {
+    let kind = if entry.is_directory() {
+        EntryKind::Directory
+    } else {
+        EntryKind::File
+    };
+    EntryInfo {
+        name: entry.name().to_string(),
+        size,
+        compressed_size: None,
+        kind,
+        mode: None, // 7z doesn't preserve Unix permissions
+        uid: None,
+        gid: None,
+        mtime: entry
+            .has_last_modified_date
+            .then(|| filetime(entry.last_modified_date().to_raw()))
+            .flatten(),
+        size_known: true,
+        xattrs: Vec::new(),
+    }
+}
+
+/// Dictionary size a coder needs for decoding, from its header properties.
+///
+/// Filters without a window (copy, BCJ, delta, ...) report 0. Malformed
+/// properties report `u64::MAX` so any configured limit rejects them.
+fn dictionary_size(method_id: &[u8], properties: &[u8]) -> u64 {
+    if method_id == SevenZMethod::ID_LZMA {
+        match properties.get(1..5) {
+            Some(&[a, b, c, d]) => u32::from_le_bytes([a, b, c, d]) as u64,
+            _ => u64::MAX,
+        }
+    } else if method_id == SevenZMethod::ID_LZMA2 {
+        match properties.first() {
+            Some(&bits) if bits < 40 => (2 | (bits as u64 & 1)) << (bits / 2 + 11),
+            Some(40) => u32::MAX as u64,
+            _ => u64::MAX,
+        }
+    } else {
+        0
+    }
+}
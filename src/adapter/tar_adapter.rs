@@ -1,17 +1,23 @@
 //! TAR archive adapter.
 
 use std::fs::File;
-use std::io::{BufReader, Read, Write};
+use std::io::{self, BufReader, Read, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
 
 use flate2::read::GzDecoder;
 
-use crate::entry::{EntryInfo, EntryKind};
+use super::{ArchiveReader, CountingReader};
+use crate::entry::{unix_time, EntryInfo, EntryKind};
 use crate::error::Error;
+use crate::policy::{SkippedUnsupported, UnsupportedPolicy};
 
 /// Adapter for TAR archives.
 ///
-/// Supports both plain `.tar` and gzip-compressed `.tar.gz` / `.tgz` files.
+/// Supports plain `.tar` and gzip-compressed `.tar.gz` / `.tgz` files, and
+/// with the `xz` feature xz-compressed `.tar.xz` / `.txz` files.
 ///
 /// Unlike ZIP, TAR is a sequential format without a central directory.
 /// This means:
@@ -19,9 +25,21 @@ use crate::error::Error;
 /// - `ValidateFirst` mode requires reading the entire archive twice
 /// - Random access to entries is not supported
 pub struct TarAdapter<R: Read> {
-    archive: tar::Archive<R>,
+    reader: CountingReader<R>,
+    bytes_read: Arc<AtomicU64>,
+    /// Bytes fed to the decoder `R` wraps, when the driver counts them.
+    input_read: Option<Arc<AtomicU64>>,
     /// Cached entries for validation mode (read once, extract later)
     cached_entries: Option<Vec<CachedEntry>>,
+    unsupported: UnsupportedPolicy,
+    skipped_unsupported: Vec<SkippedUnsupported>,
+    /// Stream offset where the current [`ArchiveReader`] entry's content
+    /// ends, and where its padding ends.
+    file_end: u64,
+    padded_end: u64,
+    /// Content of the current entry already read by
+    /// [`peek_entry`](TarAdapter::peek_entry), replayed by `entry_reader`.
+    peeked: Vec<u8>,
 }
 
 /// Cached entry data for two-pass extraction.
@@ -36,12 +54,91 @@ impl<R: Read> TarAdapter<R> {
     /// For `.tar.gz` files, wrap the reader in `GzDecoder` first,
     /// or use `TarAdapter::open_gz()`.
     pub fn new(reader: R) -> Self {
+        let reader = CountingReader::new(reader);
         Self {
-            archive: tar::Archive::new(reader),
+            bytes_read: reader.counter(),
+            input_read: None,
+            reader,
             cached_entries: None,
+            unsupported: UnsupportedPolicy::default(),
+            skipped_unsupported: Vec::new(),
+            file_end: 0,
+            padded_end: 0,
+            peeked: Vec::new(),
         }
     }
 
+    /// Up to `len` bytes from the start of the entry last returned by
+    /// [`ArchiveReader::next_entry`], without consuming them. Call it
+    /// before reading the content through `entry_reader`.
+    ///
+    /// TAR has no index to seek to, so this previews the current entry of
+    /// the stream rather than one by position. Only the prefix is read;
+    /// [`entry_reader`](ArchiveReader::entry_reader) still returns the
+    /// whole content afterwards. Empty for directories and symlinks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{ArchiveReader, TarAdapter};
+    ///
+    /// let mut adapter = TarAdapter::open("upload.tar")?;
+    /// while let Some(info) = adapter.next_entry() {
+    ///     let info = info?;
+    ///     let head = adapter.peek_entry(512)?;
+    ///     println!("{}: {} byte preview", info.name, head.len());
+    /// }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn peek_entry(&mut self, len: usize) -> Result<Vec<u8>, Error> {
+        let position = self.bytes_read.load(Ordering::Relaxed);
+        let wanted = (len.saturating_sub(self.peeked.len()) as u64)
+            .min(self.file_end.saturating_sub(position));
+        (&mut self.reader)
+            .take(wanted)
+            .read_to_end(&mut self.peeked)?;
+        Ok(self.peeked[..len.min(self.peeked.len())].to_vec())
+    }
+
+    /// Set what happens to device nodes, fifos, and other entry types that
+    /// cannot be extracted. Default: [`UnsupportedPolicy::Abort`].
+    ///
+    /// Skipped entries are not passed to the callback; collect them with
+    /// [`take_skipped_unsupported`](Self::take_skipped_unsupported).
+    pub fn unsupported_entries(mut self, policy: UnsupportedPolicy) -> Self {
+        self.unsupported = policy;
+        self
+    }
+
+    /// Entries skipped under [`UnsupportedPolicy::Skip`] since the last
+    /// call, in archive order.
+    pub fn take_skipped_unsupported(&mut self) -> Vec<SkippedUnsupported> {
+        std::mem::take(&mut self.skipped_unsupported)
+    }
+
+    /// Bytes of TAR stream read from the reader so far.
+    ///
+    /// For gzip-compressed archives this counts the decompressed stream,
+    /// since the adapter only sees the decoder's output. To reconcile
+    /// against the compressed size, count the bytes fed to the decoder.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read.load(Ordering::Relaxed)
+    }
+
+    /// Take `counter` as the bytes read from the compressed input, for a
+    /// TAR stream that `R` decompresses.
+    pub(crate) fn count_input(mut self, counter: Arc<AtomicU64>) -> Self {
+        self.input_read = Some(counter);
+        self
+    }
+
+    /// Shared handle to the bytes read from the input: the compressed
+    /// bytes if [`count_input`](Self::count_input) was given them, the TAR
+    /// stream otherwise.
+    pub(crate) fn input_counter(&self) -> Arc<AtomicU64> {
+        Arc::clone(self.input_read.as_ref().unwrap_or(&self.bytes_read))
+    }
+
     /// Process each entry with a callback.
     ///
     /// TAR is sequential, so entries are processed in order.
@@ -52,42 +149,17 @@ impl<R: Read> TarAdapter<R> {
     where
         F: FnMut(EntryInfo, Option<&mut dyn Read>) -> Result<bool, Error>,
     {
-        let entries = self.archive.entries()?;
+        let mut archive = tar::Archive::new(&mut self.reader);
+        let entries = archive.entries()?;
 
         for entry_result in entries {
             let mut entry = entry_result?;
-            let header = entry.header();
-
-            let name = entry.path()?.to_string_lossy().into_owned();
-
-            let entry_type = header.entry_type();
-            let kind = match entry_type {
-                tar::EntryType::Regular | tar::EntryType::Continuous => EntryKind::File,
-                tar::EntryType::Directory => EntryKind::Directory,
-                tar::EntryType::Symlink | tar::EntryType::Link => {
-                    let target = entry
-                        .link_name()?
-                        .map(|p| p.to_string_lossy().into_owned())
-                        .unwrap_or_default();
-                    EntryKind::Symlink { target }
-                }
-                // Reject device files, fifos, etc. - these are security risks
-                other => {
-                    return Err(Error::UnsupportedEntryType {
-                        entry: name,
-                        entry_type: entry_type_name(other),
-                    });
-                }
-            };
-
-            let info = EntryInfo {
-                name,
-                size: header.size()?,
-                kind: kind.clone(),
-                mode: header.mode().ok(),
+            let Some(info) =
+                read_info(&mut entry, self.unsupported, &mut self.skipped_unsupported)?
+            else {
+                continue;
             };
-
-            let continue_extraction = if matches!(kind, EntryKind::File) {
+            let continue_extraction = if matches!(info.kind, EntryKind::File) {
                 callback(info, Some(&mut entry))?
             } else {
                 callback(info, None)?
@@ -106,47 +178,33 @@ impl<R: Read> TarAdapter<R> {
     /// This is used by `ValidateFirst` mode to check all entries
     /// before extracting any. The data is cached for later extraction.
     pub fn cache_all(&mut self) -> Result<Vec<EntryInfo>, Error> {
+        self.cache_all_checked(|_| Ok(()))
+    }
+
+    /// [`cache_all`](Self::cache_all), calling `check` with the number of
+    /// entries so far (this one included) as each entry is reached, before
+    /// its content is read. An error from `check` stops the caching.
+    pub(crate) fn cache_all_checked<F>(&mut self, mut check: F) -> Result<Vec<EntryInfo>, Error>
+    where
+        F: FnMut(usize) -> Result<(), Error>,
+    {
         let mut entries = Vec::new();
         let mut cached = Vec::new();
 
-        let tar_entries = self.archive.entries()?;
+        let mut archive = tar::Archive::new(&mut self.reader);
+        let tar_entries = archive.entries()?;
 
         for entry_result in tar_entries {
             let mut entry = entry_result?;
-            let header = entry.header();
-
-            let name = entry.path()?.to_string_lossy().into_owned();
-
-            let entry_type = header.entry_type();
-            let kind = match entry_type {
-                tar::EntryType::Regular | tar::EntryType::Continuous => EntryKind::File,
-                tar::EntryType::Directory => EntryKind::Directory,
-                tar::EntryType::Symlink | tar::EntryType::Link => {
-                    let target = entry
-                        .link_name()?
-                        .map(|p| p.to_string_lossy().into_owned())
-                        .unwrap_or_default();
-                    EntryKind::Symlink { target }
-                }
-                // Reject device files, fifos, etc. - these are security risks
-                other => {
-                    return Err(Error::UnsupportedEntryType {
-                        entry: name,
-                        entry_type: entry_type_name(other),
-                    });
-                }
-            };
-
-            let info = EntryInfo {
-                name: name.clone(),
-                size: header.size()?,
-                kind: kind.clone(),
-                mode: header.mode().ok(),
+            let Some(info) =
+                read_info(&mut entry, self.unsupported, &mut self.skipped_unsupported)?
+            else {
+                continue;
             };
-
+            check(entries.len() + 1)?;
             // Read file content into memory
             let mut data = Vec::new();
-            if matches!(kind, EntryKind::File) {
+            if matches!(info.kind, EntryKind::File) {
                 entry.read_to_end(&mut data)?;
             }
 
@@ -158,6 +216,15 @@ impl<R: Read> TarAdapter<R> {
         Ok(entries)
     }
 
+    /// Reorder cached entries by name (after cache_all was called).
+    ///
+    /// The sort is stable, so entries with the same name keep archive order.
+    pub fn sort_cached_by_name(&mut self) {
+        if let Some(cached) = self.cached_entries.as_mut() {
+            cached.sort_by(|a, b| a.info.name.cmp(&b.info.name));
+        }
+    }
+
     /// Extract cached entries (after cache_all was called).
     pub fn extract_cached<F>(&mut self, mut callback: F) -> Result<(), Error>
     where
@@ -185,6 +252,165 @@ impl<R: Read> TarAdapter<R> {
     }
 }
 
+impl<R: Read> ArchiveReader for TarAdapter<R> {
+    fn next_entry(&mut self) -> Option<Result<EntryInfo, Error>> {
+        loop {
+            // Skip what is left of the previous entry, padding included
+            let position = self.bytes_read.load(Ordering::Relaxed);
+            let unread = self.padded_end.saturating_sub(position);
+            if let Err(e) = io::copy(&mut (&mut self.reader).take(unread), &mut io::sink()) {
+                return Some(Err(e.into()));
+            }
+            self.file_end = self.padded_end;
+            self.peeked.clear();
+
+            // A tar::Archive borrows its reader for as long as entries are
+            // alive, so each header is parsed by a fresh one. That leaves
+            // the reader at the start of the entry's content.
+            let start = self.bytes_read.load(Ordering::Relaxed);
+            let mut archive = tar::Archive::new(&mut self.reader);
+            let mut entry = match archive.entries().map(|mut entries| entries.next()) {
+                Ok(Some(Ok(entry))) => entry,
+                Ok(None) => return None,
+                Ok(Some(Err(e))) | Err(e) => return Some(Err(e.into())),
+            };
+
+            let content_start = start + entry.raw_file_position();
+            let size = entry.size();
+            self.padded_end = content_start + size.div_ceil(512) * 512;
+
+            match read_info(&mut entry, self.unsupported, &mut self.skipped_unsupported) {
+                Ok(Some(info)) => {
+                    if matches!(info.kind, EntryKind::File) {
+                        self.file_end = content_start + size;
+                    }
+                    return Some(Ok(info));
+                }
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    fn entry_reader(&mut self) -> Result<Box<dyn Read + '_>, Error> {
+        let position = self.bytes_read.load(Ordering::Relaxed);
+        let remaining = self.file_end.saturating_sub(position);
+        let peeked = io::Cursor::new(std::mem::take(&mut self.peeked));
+        Ok(Box::new(peeked.chain((&mut self.reader).take(remaining))))
+    }
+}
+
+/// Metadata for a TAR entry, or `None` if it is an unsupported type that
+/// `unsupported` says to skip (it is then recorded in `skipped`).
+fn read_info<S: Read>(
+    entry: &mut tar::Entry<'_, S>,
+    unsupported: UnsupportedPolicy,
+    skipped: &mut Vec<SkippedUnsupported>,
+) -> Result<Option<EntryInfo>, Error> {
+    let pax = read_pax(entry)?;
+    let header = entry.header();
+    let name = entry.path()?.to_string_lossy().into_owned();
+
+    let kind = match header.entry_type() {
+        tar::EntryType::Regular | tar::EntryType::Continuous => EntryKind::File,
+        tar::EntryType::Directory => EntryKind::Directory,
+        tar::EntryType::Symlink | tar::EntryType::Link => {
+            let target = entry
+                .link_name()?
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            EntryKind::Symlink { target }
+        }
+        // Device files, fifos, etc. are security risks
+        other => {
+            let entry_type = entry_type_name(other);
+            if unsupported == UnsupportedPolicy::Skip {
+                skipped.push(SkippedUnsupported { name, entry_type });
+                return Ok(None);
+            }
+            return Err(Error::UnsupportedEntryType {
+                entry: name,
+                entry_type,
+            });
+        }
+    };
+
+    Ok(Some(EntryInfo {
+        name,
+        size: header.size()?,
+        compressed_size: None,
+        kind,
+        mode: header.mode().ok(),
+        uid: header.uid().ok().and_then(|id| u32::try_from(id).ok()),
+        gid: header.gid().ok().and_then(|id| u32::try_from(id).ok()),
+        // A PAX `size` record overrides the ustar field (often 0)
+        mtime: pax.mtime.or_else(|| header_mtime(header)),
+        size_known: header.size()? == entry.size(),
+        xattrs: pax.xattrs,
+    }))
+}
+
+/// What [`EntryInfo`] takes from an entry's PAX records.
+#[derive(Default)]
+pub(crate) struct PaxInfo {
+    /// `SCHILY.xattr.<name>` records, the encoding GNU tar and libarchive
+    /// use for extended attributes.
+    pub(crate) xattrs: Vec<(String, Vec<u8>)>,
+    /// The `mtime` record, which can be fractional or negative.
+    pub(crate) mtime: Option<SystemTime>,
+}
+
+/// Read the PAX records that apply to `entry`.
+pub(crate) fn read_pax<S: Read>(entry: &mut tar::Entry<'_, S>) -> io::Result<PaxInfo> {
+    let mut pax = PaxInfo::default();
+    if let Some(extensions) = entry.pax_extensions()? {
+        for extension in extensions {
+            let extension = extension?;
+            let Ok(key) = extension.key() else {
+                continue;
+            };
+            if let Some(name) = key.strip_prefix("SCHILY.xattr.") {
+                pax.xattrs
+                    .push((name.to_string(), extension.value_bytes().to_vec()));
+            } else if key == "mtime" {
+                pax.mtime = extension.value().ok().and_then(parse_pax_time);
+            }
+        }
+    }
+    Ok(pax)
+}
+
+/// The ustar header's whole-second modification time.
+pub(crate) fn header_mtime(header: &tar::Header) -> Option<SystemTime> {
+    let secs = i64::try_from(header.mtime().ok()?).ok()?;
+    unix_time(secs, 0)
+}
+
+/// A PAX time value: decimal seconds since the epoch, optionally negative
+/// and with a fraction.
+fn parse_pax_time(value: &str) -> Option<SystemTime> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let negative = whole.starts_with('-');
+    let secs: i64 = whole.parse().ok()?;
+    if fraction.is_empty() {
+        return unix_time(secs, 0);
+    }
+    if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    // Nanosecond precision; any further digits are dropped
+    let digits = &fraction[..fraction.len().min(9)];
+    let nanos = digits.parse::<u32>().ok()? * 10u32.pow(9 - digits.len() as u32);
+    if nanos == 0 {
+        unix_time(secs, 0)
+    } else if negative {
+        // -1.25 is 1.25 s before the epoch: -2 s plus 0.75 s
+        unix_time(secs - 1, 1_000_000_000 - nanos)
+    } else {
+        unix_time(secs, nanos)
+    }
+}
+
 impl TarAdapter<BufReader<File>> {
     /// Open a plain TAR file from a path.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
@@ -204,6 +430,17 @@ impl TarAdapter<GzDecoder<BufReader<File>>> {
     }
 }
 
+#[cfg(feature = "xz")]
+impl TarAdapter<xz2::read::XzDecoder<BufReader<File>>> {
+    /// Open an xz-compressed TAR file (.tar.xz, .txz) from a path.
+    pub fn open_xz<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let decoder = xz2::read::XzDecoder::new(reader);
+        Ok(Self::new(decoder))
+    }
+}
+
 /// Helper to copy with a byte limit.
 pub fn copy_limited<R: Read + ?Sized, W: Write>(
     reader: &mut R,
@@ -1,24 +1,77 @@
 //! ZIP archive adapter.
 
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::{BufReader, Read, Seek, Write};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
 
-use crate::entry::{EntryInfo, EntryKind};
+use super::{ArchiveReader, CountingReader};
+use crate::entry::{filetime, unix_time, EntryInfo, EntryKind};
 use crate::error::Error;
+use zip::CompressionMethod;
 
 /// Adapter for ZIP archives.
 ///
 /// Wraps the `zip` crate and provides a format-agnostic interface for extraction.
 pub struct ZipAdapter<R> {
-    archive: zip::ZipArchive<R>,
+    archive: zip::ZipArchive<CountingReader<R>>,
+    bytes_read: Arc<AtomicU64>,
+    archive_len: u64,
+    /// Index the next [`ArchiveReader::next_entry`] call returns.
+    next_index: usize,
+    /// Current entry for [`ArchiveReader::entry_reader`], if it is a file.
+    current_file: Option<usize>,
+    /// First file name with more than one central directory record. The
+    /// `zip` crate only shows the last of them.
+    repeated_name: Option<String>,
 }
 
 impl<R: Read + Seek> ZipAdapter<R> {
     /// Create a new ZipAdapter from a reader.
-    pub fn new(reader: R) -> Result<Self, Error> {
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::EntryCountMismatch` if the end-of-central-directory
+    /// record and the central directory disagree on the number of entries.
+    pub fn new(mut reader: R) -> Result<Self, Error> {
+        let start = reader.stream_position()?;
+        let archive_len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+        let repeated_name = check_central_directory(&mut reader)?;
+
+        let reader = CountingReader::new(reader);
+        let bytes_read = reader.counter();
         let archive = zip::ZipArchive::new(reader)?;
-        Ok(Self { archive })
+        Ok(Self {
+            archive,
+            bytes_read,
+            archive_len,
+            next_index: 0,
+            current_file: None,
+            repeated_name,
+        })
+    }
+
+    /// Size of the archive in bytes (the full length of the reader).
+    pub fn archive_len(&self) -> u64 {
+        self.archive_len
+    }
+
+    /// Bytes read from the underlying reader so far.
+    ///
+    /// Includes the central directory and every local header and entry
+    /// body read since the adapter was created.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read.load(Ordering::Relaxed)
+    }
+
+    /// First file name stored more than once, of which only the last
+    /// entry is visible.
+    pub(crate) fn repeated_name(&self) -> Option<&str> {
+        self.repeated_name.as_deref()
     }
 
     /// Returns the number of entries in the archive.
@@ -31,43 +84,53 @@ impl<R: Read + Seek> ZipAdapter<R> {
         self.archive.is_empty()
     }
 
+    /// Sum of the uncompressed sizes the central directory declares, read
+    /// without decompressing anything. See
+    /// [`zip_total_uncompressed`](crate::zip_total_uncompressed) for why it
+    /// can't be trusted on its own.
+    pub fn total_uncompressed(&mut self) -> Result<u64, Error> {
+        let mut total = 0u64;
+        for i in 0..self.archive.len() {
+            total = total.saturating_add(self.archive.by_index_raw(i)?.size());
+        }
+        Ok(total)
+    }
+
     /// Get all entry metadata without decompressing (for validation).
     ///
     /// Uses `by_index_raw()` to read only headers, not content.
     ///
     /// # Errors
     ///
-    /// Returns `Error::EncryptedEntry` if any entry is encrypted.
+    /// Returns `Error::UnsupportedEncryption` if any entry is encrypted.
     pub fn entries_metadata(&mut self) -> Result<Vec<EntryInfo>, Error> {
         let mut entries = Vec::with_capacity(self.archive.len());
 
         for i in 0..self.archive.len() {
             let entry = self.archive.by_index_raw(i)?;
-            let name = entry.name().to_string();
-
-            // Reject encrypted entries
-            if entry.encrypted() {
-                return Err(Error::EncryptedEntry { entry: name });
-            }
+            reject_encrypted(&entry)?;
+            entries.push(raw_entry_info(&entry));
+        }
 
-            let kind = if entry.is_dir() {
-                EntryKind::Directory
-            } else if entry.is_symlink() {
-                EntryKind::Symlink {
-                    target: String::new(), // Can't read target without decompressing
-                }
-            } else {
-                EntryKind::File
-            };
+        Ok(entries)
+    }
 
-            entries.push(EntryInfo {
-                name,
-                size: entry.size(),
-                kind,
-                mode: entry.unix_mode(),
-            });
+    /// Like [`entries_metadata`](Self::entries_metadata), with each
+    /// symlink's target filled in. ZIP stores the target as the entry's
+    /// content, so symlink entries (and only those) are decompressed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::UnsupportedEncryption` if any entry is encrypted, and
+    /// `Error::InvalidFilename` for a target longer than the default
+    /// [`Limits::max_absolute_path`](crate::Limits::max_absolute_path).
+    pub fn entries_with_targets(&mut self) -> Result<Vec<EntryInfo>, Error> {
+        let mut entries = self.entries_metadata()?;
+        for (i, info) in entries.iter_mut().enumerate() {
+            if let EntryKind::Symlink { ref mut target } = info.kind {
+                *target = read_link_target(&info.name, self.archive.by_index(i)?)?;
+            }
         }
-
         Ok(entries)
     }
 
@@ -84,36 +147,39 @@ impl<R: Read + Seek> ZipAdapter<R> {
     ///
     /// # Errors
     ///
-    /// Returns `Error::EncryptedEntry` if any entry is encrypted.
+    /// Returns `Error::UnsupportedEncryption` if any entry is encrypted.
     pub fn for_each<F>(&mut self, mut callback: F) -> Result<(), Error>
     where
         F: FnMut(EntryInfo, Option<&mut dyn Read>) -> Result<bool, Error>,
     {
         for i in 0..self.archive.len() {
+            reject_encrypted(&self.archive.by_index_raw(i)?)?;
             let mut entry = self.archive.by_index(i)?;
             let name = entry.name().to_string();
 
-            // Reject encrypted entries
-            if entry.encrypted() {
-                return Err(Error::EncryptedEntry { entry: name });
-            }
-
             // Determine entry kind and read symlink target if applicable
             let kind = if entry.is_dir() {
                 EntryKind::Directory
             } else if entry.is_symlink() {
-                let mut target = String::new();
-                entry.read_to_string(&mut target)?;
+                let target = read_link_target(&name, &mut entry)?;
                 EntryKind::Symlink { target }
             } else {
                 EntryKind::File
             };
 
+            let (uid, gid) = unix_owner(entry.extra_data());
+
             let info = EntryInfo {
                 name,
                 size: entry.size(),
+                compressed_size: Some(entry.compressed_size()),
                 kind: kind.clone(),
                 mode: entry.unix_mode(),
+                uid,
+                gid,
+                mtime: zip_mtime(entry.extra_data()),
+                size_known: size_known(entry.size(), entry.compressed_size(), entry.compression()),
+                xattrs: Vec::new(),
             };
 
             // For files, provide the reader; for dirs/symlinks, no reader needed
@@ -137,39 +203,51 @@ impl<R: Read + Seek> ZipAdapter<R> {
     ///
     /// # Errors
     ///
-    /// Returns `Error::EncryptedEntry` if the entry is encrypted.
+    /// Returns `Error::UnsupportedEncryption` if the entry is encrypted.
     pub fn extract_to<W: Write>(
         &mut self,
         index: usize,
         writer: &mut W,
         limit: u64,
     ) -> Result<(EntryInfo, u64), Error> {
+        reject_encrypted(&self.archive.by_index_raw(index)?)?;
         let mut entry = self.archive.by_index(index)?;
         let name = entry.name().to_string();
 
-        // Reject encrypted entries
-        if entry.encrypted() {
-            return Err(Error::EncryptedEntry { entry: name });
-        }
-
         let kind = if entry.is_dir() {
             EntryKind::Directory
         } else if entry.is_symlink() {
-            let mut target = String::new();
-            entry.read_to_string(&mut target)?;
+            let target = read_link_target(&name, &mut entry)?;
             EntryKind::Symlink { target }
         } else {
             EntryKind::File
         };
 
+        let (uid, gid) = unix_owner(entry.extra_data());
+
         let info = EntryInfo {
             name,
             size: entry.size(),
+            compressed_size: Some(entry.compressed_size()),
             kind: kind.clone(),
             mode: entry.unix_mode(),
+            uid,
+            gid,
+            mtime: zip_mtime(entry.extra_data()),
+            size_known: size_known(entry.size(), entry.compressed_size(), entry.compression()),
+            xattrs: Vec::new(),
         };
 
-        let bytes_written = if matches!(kind, EntryKind::File) {
+        let bytes_written = if matches!(kind, EntryKind::File) && info.size_known {
+            // One byte past the declared size is enough to catch a lie;
+            // stopping at `limit` short of it is for the caller to judge
+            let cap = limit.min(info.size.saturating_add(1));
+            let written = copy_limited(&mut entry, writer, cap)?;
+            if written > info.size || written < cap.min(info.size) {
+                check_declared_size(&info.name, info.size, written)?;
+            }
+            written
+        } else if matches!(kind, EntryKind::File) {
             copy_limited(&mut entry, writer, limit)?
         } else {
             0
@@ -178,36 +256,91 @@ impl<R: Read + Seek> ZipAdapter<R> {
         Ok((info, bytes_written))
     }
 
+    /// Decompress up to `len` bytes from the start of the entry at `index`.
+    ///
+    /// Only the requested prefix is decoded, so sniffing the type of a
+    /// large entry is cheap. Directories and symlinks yield their stored
+    /// bytes (empty, or the link target).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::ZipAdapter;
+    ///
+    /// let mut adapter = ZipAdapter::open("upload.zip")?;
+    /// let head = adapter.peek_entry(0, 512)?;
+    /// println!("starts with {:02x?}", &head[..head.len().min(4)]);
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn peek_entry(&mut self, index: usize, len: usize) -> Result<Vec<u8>, Error> {
+        reject_encrypted(&self.archive.by_index_raw(index)?)?;
+        let entry = self.archive.by_index(index)?;
+        let mut prefix = Vec::with_capacity(len);
+        entry.take(len as u64).read_to_end(&mut prefix)?;
+        Ok(prefix)
+    }
+
+    /// Get the name of the entry at `index` from the central directory.
+    pub fn entry_name(&self, index: usize) -> Option<&str> {
+        self.archive.name_for_index(index)
+    }
+
     /// Get entry info by index without reading content.
     ///
     /// # Errors
     ///
-    /// Returns `Error::EncryptedEntry` if the entry is encrypted.
+    /// Returns `Error::UnsupportedEncryption` if the entry is encrypted.
     pub fn entry_info(&mut self, index: usize) -> Result<EntryInfo, Error> {
         let entry = self.archive.by_index_raw(index)?;
-        let name = entry.name().to_string();
+        reject_encrypted(&entry)?;
+        Ok(raw_entry_info(&entry))
+    }
+}
 
-        // Reject encrypted entries
-        if entry.encrypted() {
-            return Err(Error::EncryptedEntry { entry: name });
-        }
+/// Read a symlink entry's target, which ZIP stores as its content. Only
+/// up to the default [`Limits::max_absolute_path`](crate::Limits::max_absolute_path)
+/// is read, so a target that decompresses to gigabytes is rejected
+/// instead of buffered.
+fn read_link_target<R: Read>(name: &str, entry: R) -> Result<String, Error> {
+    let max = crate::Limits::default().max_absolute_path;
+    let mut target = String::new();
+    entry.take(max as u64 + 1).read_to_string(&mut target)?;
+    if target.len() > max {
+        return Err(Error::InvalidFilename {
+            entry: name.to_string(),
+            reason: format!("symlink target longer than {} bytes", max),
+        });
+    }
+    Ok(target)
+}
 
-        let kind = if entry.is_dir() {
-            EntryKind::Directory
-        } else if entry.is_symlink() {
-            EntryKind::Symlink {
-                target: String::new(),
+impl<R: Read + Seek> ArchiveReader for ZipAdapter<R> {
+    fn next_entry(&mut self) -> Option<Result<EntryInfo, Error>> {
+        let index = self.next_index;
+        if index >= self.archive.len() {
+            return None;
+        }
+        self.next_index += 1;
+        self.current_file = None;
+
+        Some(self.entry_info(index).and_then(|mut info| {
+            match info.kind {
+                // The raw header does not carry the target
+                EntryKind::Symlink { ref mut target } => {
+                    *target = read_link_target(&info.name, self.archive.by_index(index)?)?;
+                }
+                EntryKind::File => self.current_file = Some(index),
+                EntryKind::Directory => {}
             }
-        } else {
-            EntryKind::File
-        };
+            Ok(info)
+        }))
+    }
 
-        Ok(EntryInfo {
-            name,
-            size: entry.size(),
-            kind,
-            mode: entry.unix_mode(),
-        })
+    fn entry_reader(&mut self) -> Result<Box<dyn Read + '_>, Error> {
+        match self.current_file {
+            Some(index) => Ok(Box::new(self.archive.by_index(index)?)),
+            None => Ok(Box::new(std::io::empty())),
+        }
     }
 }
 
@@ -247,3 +380,320 @@ fn copy_limited<R: Read, W: Write>(
 
     Ok(total)
 }
+
+/// Fail with [`Error::SizeMismatch`] unless an entry decompressed to
+/// exactly its declared size.
+///
+/// Callers stop reading one byte past `declared`, so `actual` only says
+/// that much about an entry that overruns.
+pub(crate) fn check_declared_size(entry: &str, declared: u64, actual: u64) -> Result<(), Error> {
+    if actual == declared {
+        return Ok(());
+    }
+    Err(Error::SizeMismatch {
+        entry: entry.to_string(),
+        declared,
+        actual,
+    })
+}
+
+/// Metadata of a raw (not decompressed) entry. A symlink's target is its
+/// content, so it is left empty.
+pub(crate) fn raw_entry_info(entry: &zip::read::ZipFile<'_>) -> EntryInfo {
+    let kind = if entry.is_dir() {
+        EntryKind::Directory
+    } else if entry.is_symlink() {
+        EntryKind::Symlink {
+            target: String::new(),
+        }
+    } else {
+        EntryKind::File
+    };
+    let (uid, gid) = unix_owner(entry.extra_data());
+
+    EntryInfo {
+        name: entry.name().to_string(),
+        size: entry.size(),
+        compressed_size: Some(entry.compressed_size()),
+        kind,
+        mode: entry.unix_mode(),
+        uid,
+        gid,
+        mtime: zip_mtime(entry.extra_data()),
+        size_known: size_known(entry.size(), entry.compressed_size(), entry.compression()),
+        xattrs: Vec::new(),
+    }
+}
+
+/// Reject an encrypted entry, naming the scheme it uses.
+///
+/// Checked on the raw entry, since `by_index` already fails on encrypted
+/// entries with a generic "password required" error.
+pub(crate) fn reject_encrypted(entry: &zip::read::ZipFile<'_>) -> Result<(), Error> {
+    if !entry.encrypted() {
+        return Ok(());
+    }
+    Err(Error::UnsupportedEncryption {
+        entry: entry.name().to_string(),
+        scheme: encryption_scheme(entry.extra_data(), entry.compression()).to_string(),
+    })
+}
+
+/// Name of the encryption scheme of an entry with the encrypted flag set.
+///
+/// WinZip AES entries carry an "AE-x" extra field (0x9901) whose strength
+/// byte gives the key size; anything else is traditional PKWARE encryption.
+fn encryption_scheme(extra: Option<&[u8]>, method: CompressionMethod) -> &'static str {
+    let mut rest = extra.unwrap_or_default();
+
+    while rest.len() >= 4 {
+        let id = u16::from_le_bytes([rest[0], rest[1]]);
+        let len = usize::from(u16::from_le_bytes([rest[2], rest[3]]));
+        let Some(data) = rest.get(4..4 + len) else {
+            break;
+        };
+        rest = &rest[4 + len..];
+
+        if id == 0x9901 {
+            // vendor version(2), vendor id(2), strength(1), method(2)
+            return match data.get(4) {
+                Some(1) => "AES-128",
+                Some(2) => "AES-192",
+                Some(3) => "AES-256",
+                _ => "AES",
+            };
+        }
+    }
+
+    // Method 99 marks AES even if the extra field is missing
+    #[allow(deprecated)]
+    if method.to_u16() == 99 {
+        return "AES";
+    }
+    "ZipCrypto"
+}
+
+/// Compare the entry count in the end-of-central-directory record with the
+/// number of records the central directory actually holds, and return the
+/// first file name that more than one record carries.
+///
+/// The `zip` crate reads exactly the declared number of records, so extra
+/// records after them go unseen by it but not by other unzip tools. It
+/// also keeps only the last record of each name, hiding the earlier ones
+/// the same way. The walk starts `cd_size` bytes before the EOCD rather
+/// than at the stored offset, which also works for archives with
+/// prepended data. ZIP64 archives keep their real counts in another
+/// record and are left to the `zip` crate. The reader is rewound to where
+/// it was.
+pub(crate) fn check_central_directory<R: Read + Seek>(
+    reader: &mut R,
+) -> Result<Option<String>, Error> {
+    const EOCD_SIG: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
+    const CDH_SIG: [u8; 4] = [0x50, 0x4b, 0x01, 0x02];
+    const ZIP64_LOCATOR_SIG: [u8; 4] = [0x50, 0x4b, 0x06, 0x07];
+    // 22-byte record plus a comment of up to 64 KiB
+    const MAX_TAIL: u64 = 22 + 0xFFFF;
+
+    let start = reader.stream_position()?;
+    let len = reader.seek(SeekFrom::End(0))?;
+    let tail_start = len.saturating_sub(MAX_TAIL);
+    reader.seek(SeekFrom::Start(tail_start))?;
+    let mut tail = Vec::new();
+    reader.by_ref().take(MAX_TAIL).read_to_end(&mut tail)?;
+
+    let result = (|| {
+        // Prefer the record whose comment runs exactly to the end, since a
+        // comment can contain the signature too. Without any record this
+        // isn't a ZIP we can make sense of, and the zip crate reports that.
+        let candidates = || {
+            (0..tail.len().saturating_sub(21))
+                .rev()
+                .filter(|&i| tail[i..i + 4] == EOCD_SIG)
+        };
+        let Some(at) = candidates()
+            .find(|&i| {
+                let comment_len = u16::from_le_bytes([tail[i + 20], tail[i + 21]]);
+                i + 22 + usize::from(comment_len) == tail.len()
+            })
+            .or_else(|| candidates().next())
+        else {
+            return Ok(None);
+        };
+        let eocd = &tail[at..at + 22];
+        let declared = u16::from_le_bytes([eocd[10], eocd[11]]);
+        let cd_size = u32::from_le_bytes([eocd[12], eocd[13], eocd[14], eocd[15]]);
+        let cd_offset = u32::from_le_bytes([eocd[16], eocd[17], eocd[18], eocd[19]]);
+        let eocd_pos = tail_start + at as u64;
+
+        let zip64_locator = eocd_pos >= 20 && {
+            reader.seek(SeekFrom::Start(eocd_pos - 20))?;
+            let mut sig = [0u8; 4];
+            reader.read_exact(&mut sig)?;
+            sig == ZIP64_LOCATOR_SIG
+        };
+        if declared == u16::MAX || cd_size == u32::MAX || cd_offset == u32::MAX || zip64_locator {
+            return Ok(None);
+        }
+        let Some(cd_start) = eocd_pos.checked_sub(u64::from(cd_size)) else {
+            return Ok(None);
+        };
+
+        let mut actual = 0u64;
+        let mut pos = cd_start;
+        let mut header = [0u8; 46];
+        let mut names = HashSet::new();
+        let mut repeated = None;
+        reader.seek(SeekFrom::Start(pos))?;
+        while pos + 46 <= eocd_pos {
+            reader.read_exact(&mut header)?;
+            if header[..4] != CDH_SIG {
+                break;
+            }
+            let name_len = u16::from_le_bytes([header[28], header[29]]);
+            let extra_len = u16::from_le_bytes([header[30], header[31]]);
+            let comment_len = u16::from_le_bytes([header[32], header[33]]);
+            let mut name = vec![0u8; usize::from(name_len)];
+            reader.read_exact(&mut name)?;
+            reader.seek(SeekFrom::Current(
+                i64::from(extra_len) + i64::from(comment_len),
+            ))?;
+            actual += 1;
+            pos += 46 + u64::from(name_len) + u64::from(extra_len) + u64::from(comment_len);
+
+            // Directories repeat harmlessly
+            if repeated.is_none() && !name.ends_with(b"/") && !names.insert(name.clone()) {
+                repeated = Some(String::from_utf8_lossy(&name).into_owned());
+            }
+        }
+
+        if actual != u64::from(declared) {
+            return Err(Error::EntryCountMismatch {
+                declared: u64::from(declared),
+                actual,
+            });
+        }
+        Ok(repeated)
+    })();
+
+    reader.seek(SeekFrom::Start(start))?;
+    result
+}
+
+/// Owner uid/gid from Info-ZIP Unix extra fields, if present.
+///
+/// Prefers the variable-width "ux" field (0x7875). Falls back to the 16-bit
+/// ids in "Ux" (0x7855) or the older "UX" (0x5855), which only carry them in
+/// local headers.
+fn unix_owner(extra: Option<&[u8]>) -> (Option<u32>, Option<u32>) {
+    let mut fallback = (None, None);
+    let mut rest = extra.unwrap_or_default();
+
+    while rest.len() >= 4 {
+        let id = u16::from_le_bytes([rest[0], rest[1]]);
+        let len = usize::from(u16::from_le_bytes([rest[2], rest[3]]));
+        let Some(data) = rest.get(4..4 + len) else {
+            break;
+        };
+        rest = &rest[4 + len..];
+
+        let le16 = |at: usize| {
+            data.get(at..at + 2)
+                .map(|b| u32::from(u16::from_le_bytes([b[0], b[1]])))
+        };
+        match id {
+            0x7875 => {
+                // version(1), uid size(1), uid, gid size(1), gid
+                let mut fields = data.get(1..).unwrap_or_default();
+                let mut next_id = || {
+                    let (&size, tail) = fields.split_first()?;
+                    let bytes = tail.get(..usize::from(size))?;
+                    fields = &tail[usize::from(size)..];
+                    if bytes.len() > 4 {
+                        return None;
+                    }
+                    let mut value = [0u8; 4];
+                    value[..bytes.len()].copy_from_slice(bytes);
+                    Some(u32::from_le_bytes(value))
+                };
+                let uid = next_id();
+                let gid = next_id();
+                if uid.is_some() || gid.is_some() {
+                    return (uid, gid);
+                }
+            }
+            0x7855 => fallback = (le16(0), le16(2)),
+            0x5855 if fallback == (None, None) => fallback = (le16(8), le16(10)),
+            _ => {}
+        }
+    }
+
+    fallback
+}
+
+/// Modification time from the NTFS (0x000a) extra field, or else the
+/// Info-ZIP extended timestamp (0x5455) or older "UX" (0x5855) field.
+fn zip_mtime(extra: Option<&[u8]>) -> Option<SystemTime> {
+    let mut fallback = None;
+    let mut rest = extra.unwrap_or_default();
+
+    while rest.len() >= 4 {
+        let id = u16::from_le_bytes([rest[0], rest[1]]);
+        let len = usize::from(u16::from_le_bytes([rest[2], rest[3]]));
+        let Some(data) = rest.get(4..4 + len) else {
+            break;
+        };
+        rest = &rest[4 + len..];
+
+        let le32 = |at: usize| {
+            data.get(at..at + 4)
+                .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        };
+        match id {
+            0x000a => {
+                // reserved(4), then tag(2), size(2), data attributes; tag 1
+                // holds mtime, atime, ctime as FILETIMEs
+                let mut attrs = data.get(4..).unwrap_or_default();
+                while attrs.len() >= 4 {
+                    let tag = u16::from_le_bytes([attrs[0], attrs[1]]);
+                    let size = usize::from(u16::from_le_bytes([attrs[2], attrs[3]]));
+                    let Some(value) = attrs.get(4..4 + size) else {
+                        break;
+                    };
+                    attrs = &attrs[4 + size..];
+                    if tag == 1 && size >= 8 {
+                        let mut ticks = [0u8; 8];
+                        ticks.copy_from_slice(&value[..8]);
+                        return filetime(u64::from_le_bytes(ticks));
+                    }
+                }
+            }
+            // flags(1), then mtime if flag bit 0 is set
+            0x5455 if data.first().is_some_and(|flags| flags & 1 != 0) => {
+                fallback = le32(1).and_then(|secs| unix_time(secs.into(), 0));
+            }
+            // atime(4), mtime(4)
+            0x5855 if fallback.is_none() => {
+                fallback = le32(4).and_then(|secs| unix_time(secs.into(), 0));
+            }
+            _ => {}
+        }
+    }
+
+    fallback
+}
+
+/// Whether an entry's declared size can be trusted for preflight checks.
+///
+/// The central directory always holds a size, but a file declaring zero
+/// bytes while carrying more compressed data than an empty stream needs
+/// (a few bytes for deflate, a few dozen at most for bzip2/zstd/lzma
+/// framing) will decompress to an unknown amount.
+fn size_known(size: u64, compressed_size: u64, method: CompressionMethod) -> bool {
+    const EMPTY_STREAM_MAX: u64 = 32;
+
+    size > 0
+        || match method {
+            CompressionMethod::Stored => compressed_size == 0,
+            _ => compressed_size <= EMPTY_STREAM_MAX,
+        }
+}
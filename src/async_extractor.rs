@@ -37,9 +37,16 @@
 
 #[cfg(feature = "tar")]
 use crate::{Driver, ExtractionReport, OverwriteMode, SymlinkBehavior, TarAdapter, ValidationMode};
-use crate::{Error, ExtractionMode, Extractor, Limits, OverwritePolicy, Report, SymlinkPolicy};
+use crate::{
+    Error, ExtractionMode, Extractor, Limits, OverwritePolicy, Progress, Report, SymlinkPolicy,
+};
 use std::path::{Path, PathBuf};
-use tokio::task::spawn_blocking;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::{spawn_blocking, JoinHandle};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::Stream;
 
 /// Async extractor with the same security guarantees as [`Extractor`].
 ///
@@ -53,6 +60,7 @@ pub struct AsyncExtractor {
     symlinks: SymlinkPolicy,
     mode: ExtractionMode,
     create_destination: bool,
+    cancel: Option<Arc<AtomicBool>>,
 }
 
 impl AsyncExtractor {
@@ -74,6 +82,7 @@ impl AsyncExtractor {
             symlinks: SymlinkPolicy::default(),
             mode: ExtractionMode::default(),
             create_destination: false,
+            cancel: None,
         })
     }
 
@@ -90,6 +99,7 @@ impl AsyncExtractor {
             symlinks: SymlinkPolicy::default(),
             mode: ExtractionMode::default(),
             create_destination: true,
+            cancel: None,
         })
     }
 
@@ -111,6 +121,18 @@ impl AsyncExtractor {
         self
     }
 
+    /// Set maximum number of directory entries to extract.
+    pub fn max_dir_count(mut self, count: usize) -> Self {
+        self.limits.max_dir_count = count;
+        self
+    }
+
+    /// Set maximum number of archive entries of any kind.
+    pub fn max_entries(mut self, count: usize) -> Self {
+        self.limits.max_entries = count;
+        self
+    }
+
     /// Set maximum size for a single file.
     pub fn max_single_file(mut self, bytes: u64) -> Self {
         self.limits.max_single_file = bytes;
@@ -141,6 +163,15 @@ impl AsyncExtractor {
         self
     }
 
+    /// Stop with [`Error::Cancelled`] once `flag` is set.
+    ///
+    /// Dropping the returned future does not stop the blocking task; set
+    /// the flag to do that. See [`Extractor::cancel_on`].
+    pub fn cancel_on(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(flag);
+        self
+    }
+
     /// Extract a ZIP file asynchronously.
     ///
     /// The actual extraction runs in a blocking thread pool.
@@ -153,6 +184,58 @@ impl AsyncExtractor {
             .map_err(|e| Error::Io(std::io::Error::other(e)))?
     }
 
+    /// Extract a ZIP file, streaming progress as entries complete.
+    ///
+    /// Extraction starts immediately on the blocking thread pool. Each
+    /// [`Progress`] event the sync extractor would pass to
+    /// [`Extractor::on_progress`] is sent over a channel and yielded by the
+    /// returned stream, which ends when extraction finishes. Await the
+    /// handle for the final report or error.
+    ///
+    /// The channel is unbounded so a slow consumer never stalls
+    /// extraction; it holds at most one event per entry. Dropping the
+    /// stream discards the remaining events without stopping extraction.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::r#async::AsyncExtractor;
+    /// use tokio_stream::StreamExt;
+    ///
+    /// # async fn run() -> Result<(), safe_unzip::Error> {
+    /// let (mut progress, handle) = AsyncExtractor::new("/var/uploads")?
+    ///     .extract_file_with_progress("archive.zip");
+    /// while let Some(p) = progress.next().await {
+    ///     println!("{}/{} {}", p.entry_index + 1, p.total_entries, p.entry_name);
+    /// }
+    /// let report = handle.await.map_err(|e| safe_unzip::Error::Io(std::io::Error::other(e)))??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn extract_file_with_progress<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> (
+        impl Stream<Item = Progress> + Send + Unpin,
+        JoinHandle<Result<Report, Error>>,
+    ) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let extractor = self.build_sync_extractor();
+        let path = path.as_ref().to_path_buf();
+
+        let handle = spawn_blocking(move || {
+            extractor?
+                .on_progress(move |progress| {
+                    // A dropped receiver only means nobody is watching
+                    let _ = tx.send(progress.clone());
+                })
+                .extract_file(path)
+        });
+        (UnboundedReceiverStream::new(rx), handle)
+    }
+
     /// Extract a ZIP from bytes asynchronously.
     pub async fn extract_bytes(&self, data: Vec<u8>) -> Result<Report, Error> {
         let extractor = self.build_sync_extractor()?;
@@ -227,11 +310,15 @@ impl AsyncExtractor {
             Extractor::new(&self.destination)?
         };
 
-        Ok(extractor
+        let extractor = extractor
             .limits(self.limits)
             .overwrite(self.overwrite)
             .symlinks(self.symlinks)
-            .mode(self.mode))
+            .mode(self.mode);
+        Ok(match self.cancel {
+            Some(ref flag) => extractor.cancel_on(Arc::clone(flag)),
+            None => extractor,
+        })
     }
 
     #[cfg(feature = "tar")]
@@ -242,11 +329,15 @@ impl AsyncExtractor {
             Driver::new(&self.destination)?
         };
 
-        Ok(driver
+        let driver = driver
             .limits(self.limits)
             .overwrite(convert_overwrite_policy(self.overwrite))
             .symlinks(convert_symlink_policy(self.symlinks))
-            .validation(convert_extraction_mode(self.mode)))
+            .validation(convert_extraction_mode(self.mode));
+        Ok(match self.cancel {
+            Some(ref flag) => driver.cancel_on(Arc::clone(flag)),
+            None => driver,
+        })
     }
 }
 
@@ -257,7 +348,11 @@ fn extraction_report_to_report(report: ExtractionReport) -> Report {
         files_extracted: report.files_extracted,
         dirs_created: report.dirs_created,
         bytes_written: report.bytes_written,
+        bytes_read: report.bytes_read,
         entries_skipped: report.entries_skipped,
+        symlinks_created: report.symlinks_created,
+        was_empty: report.was_empty,
+        extracted_paths: report.extracted_paths,
     }
 }
 
@@ -276,6 +371,7 @@ fn convert_symlink_policy(policy: SymlinkPolicy) -> SymlinkBehavior {
     match policy {
         SymlinkPolicy::Skip => SymlinkBehavior::Skip,
         SymlinkPolicy::Error => SymlinkBehavior::Error,
+        SymlinkPolicy::Allow => SymlinkBehavior::AllowContained,
     }
 }
 
@@ -284,6 +380,7 @@ fn convert_extraction_mode(mode: ExtractionMode) -> ValidationMode {
     match mode {
         ExtractionMode::Streaming => ValidationMode::Streaming,
         ExtractionMode::ValidateFirst => ValidationMode::ValidateFirst,
+        ExtractionMode::ValidateFirstDeep => ValidationMode::ValidateFirstDeep,
     }
 }
 
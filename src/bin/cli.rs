@@ -12,12 +12,24 @@
 //! # Filter by pattern
 //! safe_unzip archive.zip -d /tmp/out --include "**/*.py" --exclude "**/test_*"
 //!
+//! # Filter by regular expression
+//! safe_unzip archive.zip -d /tmp/out --include-regex '^src/.*_test\.rs$'
+//!
 //! # Extract specific files
 //! safe_unzip archive.zip -d /tmp/out --only README.md --only LICENSE
 //!
 //! # List contents without extracting
 //! safe_unzip archive.zip --list
 //!
+//! # ... with each entry's stored modification time
+//! safe_unzip archive.zip --list --long
+//!
+//! # Record what was extracted where, with a JSON summary
+//! safe_unzip archive.zip -d /tmp/out --manifest /tmp/out.manifest.json --json
+//!
+//! # Show contents as a directory tree with sizes
+//! safe_unzip archive.zip --tree
+//!
 //! # Generate shell completions
 //! safe_unzip --completions bash > ~/.bash_completion.d/safe_unzip
 //! safe_unzip --completions zsh > ~/.zfunc/_safe_unzip
@@ -27,11 +39,17 @@
 use clap::{CommandFactory, Parser, ValueEnum};
 use clap_complete::{generate, Shell};
 use safe_unzip::{
-    Driver, Error, ExtractionMode, Extractor, Limits, OverwritePolicy, SymlinkPolicy,
+    AbsolutePathPolicy, CompressionMethod, CsvIndex, Driver, EntryInfo, EntryKind, Error,
+    ExtractionMode, ExtractionObserver, Extractor, JsonIndex, Limits, OverwritePolicy, SkipReason,
+    SymlinkPolicy,
 };
+use std::collections::BTreeMap;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Parser)]
 #[command(
@@ -45,7 +63,7 @@ use std::process::ExitCode;
     safe_unzip archive.zip --list"
 )]
 struct Cli {
-    /// Archive file to extract (ZIP, TAR, TAR.GZ)
+    /// Archive file to extract (ZIP, TAR, TAR.GZ, TAR.XZ, or a single-file .gz)
     #[arg(required_unless_present = "completions")]
     archive: Option<PathBuf>,
 
@@ -57,6 +75,14 @@ struct Cli {
     #[arg(short, long)]
     list: bool,
 
+    /// Show stored modification times (UTC) when listing
+    #[arg(long, requires = "list")]
+    long: bool,
+
+    /// List contents as a directory tree with per-directory sizes
+    #[arg(long)]
+    tree: bool,
+
     /// Verify archive integrity (CRC32 check) without extracting
     #[arg(long)]
     verify: bool,
@@ -73,6 +99,14 @@ struct Cli {
     #[arg(long)]
     max_files: Option<usize>,
 
+    /// Maximum number of directories to extract
+    #[arg(long)]
+    max_dirs: Option<usize>,
+
+    /// Maximum number of archive entries of any kind
+    #[arg(long)]
+    max_entries: Option<usize>,
+
     /// Maximum size of a single file (e.g., 50M)
     #[arg(long, value_parser = parse_size)]
     max_single_file: Option<u64>,
@@ -81,6 +115,10 @@ struct Cli {
     #[arg(long)]
     max_depth: Option<usize>,
 
+    /// Maximum size of the archive file itself (e.g., 200M)
+    #[arg(long, value_parser = parse_size)]
+    max_compressed_size: Option<u64>,
+
     /// Extract only files matching glob patterns (can be repeated)
     #[arg(long = "include", value_name = "PATTERN")]
     include_patterns: Vec<String>,
@@ -89,22 +127,76 @@ struct Cli {
     #[arg(long = "exclude", value_name = "PATTERN")]
     exclude_patterns: Vec<String>,
 
+    /// Extract only files whose path matches a regex (can be repeated)
+    #[arg(long = "include-regex", value_name = "REGEX")]
+    include_regex: Vec<String>,
+
+    /// Exclude files whose path matches a regex (can be repeated)
+    #[arg(long = "exclude-regex", value_name = "REGEX")]
+    exclude_regex: Vec<String>,
+
+    /// Read include patterns from a file, one per line (`#` starts a comment)
+    #[arg(long, value_name = "FILE")]
+    include_from: Vec<PathBuf>,
+
+    /// Read exclude patterns from a file, one per line (`#` starts a comment)
+    #[arg(long, value_name = "FILE")]
+    exclude_from: Vec<PathBuf>,
+
     /// Extract only specific files by name (can be repeated)
     #[arg(long = "only", value_name = "FILE")]
     only_files: Vec<String>,
 
+    /// Drop the first N path components of every entry, like tar
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    strip_components: usize,
+
     /// What to do if file already exists
     #[arg(long, value_enum, default_value_t = OverwriteMode::Error)]
     overwrite: OverwriteMode,
 
-    /// What to do with symlinks
-    #[arg(long, value_enum, default_value_t = SymlinkMode::Skip)]
-    symlinks: SymlinkMode,
+    /// What to do with symlinks [default: skip, or error with --hardened]
+    #[arg(long, value_enum)]
+    symlinks: Option<SymlinkMode>,
 
     /// Validate all entries before extracting
     #[arg(long)]
     validate_first: bool,
 
+    /// Like --validate-first, and also decompress every ZIP entry to
+    /// check its CRC before extracting
+    #[arg(long)]
+    validate_deep: bool,
+
+    /// Fail before extracting if the destination lacks room for the
+    /// declared size of the archive (implies --validate-first)
+    #[arg(long)]
+    ensure_free_space: bool,
+
+    /// Only accept ZIP entries compressed with these methods
+    /// (e.g. deflate,stored)
+    #[arg(long, value_name = "METHODS", value_delimiter = ',')]
+    allow_methods: Vec<CompressionMethod>,
+
+    /// Paranoid defaults for untrusted archives: strict limits, error on
+    /// symlinks, validate first with CRC checks, reject deceptive Unicode,
+    /// Windows reserved names and absolute paths, and clear execute bits.
+    /// Explicit options still override.
+    #[arg(long)]
+    hardened: bool,
+
+    /// Write a list of extracted files with size, mode, and SHA-256 digest
+    #[arg(long, value_name = "PATH")]
+    manifest: Option<PathBuf>,
+
+    /// Manifest format [default: csv for a .csv path, json otherwise]
+    #[arg(long, value_enum, requires = "manifest")]
+    manifest_format: Option<ManifestFormat>,
+
+    /// Print the extraction summary as JSON
+    #[arg(long)]
+    json: bool,
+
     /// Quiet mode - only show errors
     #[arg(short, long)]
     quiet: bool,
@@ -114,6 +206,16 @@ struct Cli {
     verbose: bool,
 }
 
+impl Cli {
+    fn symlinks(&self) -> SymlinkMode {
+        self.symlinks.unwrap_or(if self.hardened {
+            SymlinkMode::Error
+        } else {
+            SymlinkMode::Skip
+        })
+    }
+}
+
 #[derive(Clone, Copy, ValueEnum)]
 enum OverwriteMode {
     /// Error if file exists
@@ -122,6 +224,35 @@ enum OverwriteMode {
     Skip,
     /// Overwrite existing files
     Overwrite,
+    /// Skip identical existing files, error if contents differ
+    ErrorIfDifferent,
+}
+
+impl OverwriteMode {
+    /// Equivalent for the ZIP `Extractor`, which lacks the newer modes.
+    fn legacy(self) -> Option<OverwritePolicy> {
+        match self {
+            Self::Error => Some(OverwritePolicy::Error),
+            Self::Skip => Some(OverwritePolicy::Skip),
+            Self::Overwrite => Some(OverwritePolicy::Overwrite),
+            Self::ErrorIfDifferent => None,
+        }
+    }
+
+    fn driver(self) -> safe_unzip::OverwriteMode {
+        match self {
+            Self::Error => safe_unzip::OverwriteMode::Error,
+            Self::Skip => safe_unzip::OverwriteMode::Skip,
+            Self::Overwrite => safe_unzip::OverwriteMode::Overwrite,
+            Self::ErrorIfDifferent => safe_unzip::OverwriteMode::ErrorIfDifferent,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ManifestFormat {
+    Json,
+    Csv,
 }
 
 #[derive(Clone, Copy, ValueEnum)]
@@ -130,6 +261,30 @@ enum SymlinkMode {
     Skip,
     /// Error if archive contains symlinks
     Error,
+    /// Create symlinks that stay inside the destination, error on others
+    AllowContained,
+    /// Create symlinks that stay inside the destination, skip others
+    ContainedOrSkip,
+}
+
+impl SymlinkMode {
+    /// Equivalent for the ZIP `Extractor`, which cannot create symlinks.
+    fn legacy(self) -> Option<SymlinkPolicy> {
+        match self {
+            Self::Skip => Some(SymlinkPolicy::Skip),
+            Self::Error => Some(SymlinkPolicy::Error),
+            Self::AllowContained | Self::ContainedOrSkip => None,
+        }
+    }
+
+    fn driver(self) -> safe_unzip::SymlinkBehavior {
+        match self {
+            Self::Skip => safe_unzip::SymlinkBehavior::Skip,
+            Self::Error => safe_unzip::SymlinkBehavior::Error,
+            Self::AllowContained => safe_unzip::SymlinkBehavior::AllowContained,
+            Self::ContainedOrSkip => safe_unzip::SymlinkBehavior::ContainedOrSkip,
+        }
+    }
 }
 
 fn parse_size(s: &str) -> Result<u64, String> {
@@ -161,8 +316,12 @@ fn detect_format(path: &Path) -> ArchiveFormat {
 
     if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
         ArchiveFormat::TarGz
+    } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+        ArchiveFormat::TarXz
     } else if name.ends_with(".tar") {
         ArchiveFormat::Tar
+    } else if name.ends_with(".gz") {
+        ArchiveFormat::Gz
     } else if name.ends_with(".7z") {
         ArchiveFormat::SevenZ
     } else {
@@ -174,6 +333,9 @@ enum ArchiveFormat {
     Zip,
     Tar,
     TarGz,
+    TarXz,
+    /// A single gzipped file, not a tarball
+    Gz,
     SevenZ,
 }
 
@@ -194,13 +356,32 @@ fn main() -> ExitCode {
     }
 }
 
-fn run(cli: Cli) -> Result<(), Error> {
+/// Read glob patterns from a file: one per line, skipping blank lines and
+/// `#` comments.
+fn read_patterns(path: &Path) -> Result<Vec<String>, Error> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+fn run(mut cli: Cli) -> Result<(), Error> {
+    for path in &cli.include_from {
+        cli.include_patterns.extend(read_patterns(path)?);
+    }
+    for path in &cli.exclude_from {
+        cli.exclude_patterns.extend(read_patterns(path)?);
+    }
+
     let archive = cli.archive.as_ref().expect("archive is required");
     let format = detect_format(archive);
 
     // List mode
-    if cli.list {
-        return list_archive(archive, format, cli.quiet);
+    if cli.list || cli.tree {
+        return list_archive(archive, format, cli.quiet, cli.tree, cli.long);
     }
 
     // Verify mode
@@ -209,39 +390,48 @@ fn run(cli: Cli) -> Result<(), Error> {
     }
 
     // Extract mode
-    let limits = Limits {
-        max_total_bytes: cli.max_size.unwrap_or(Limits::default().max_total_bytes),
-        max_file_count: cli.max_files.unwrap_or(Limits::default().max_file_count),
-        max_single_file: cli
-            .max_single_file
-            .unwrap_or(Limits::default().max_single_file),
-        max_path_depth: cli.max_depth.unwrap_or(Limits::default().max_path_depth),
-    };
-
-    let overwrite = match cli.overwrite {
-        OverwriteMode::Error => OverwritePolicy::Error,
-        OverwriteMode::Skip => OverwritePolicy::Skip,
-        OverwriteMode::Overwrite => OverwritePolicy::Overwrite,
+    let base = if cli.hardened {
+        Limits::strict()
+    } else {
+        Limits::default()
     };
-
-    let symlinks = match cli.symlinks {
-        SymlinkMode::Skip => SymlinkPolicy::Skip,
-        SymlinkMode::Error => SymlinkPolicy::Error,
+    let limits = Limits {
+        max_total_bytes: cli.max_size.unwrap_or(base.max_total_bytes),
+        max_file_count: cli.max_files.unwrap_or(base.max_file_count),
+        max_dir_count: cli.max_dirs.unwrap_or(base.max_dir_count),
+        max_entries: cli.max_entries.unwrap_or(base.max_entries),
+        max_single_file: cli.max_single_file.unwrap_or(base.max_single_file),
+        max_path_depth: cli.max_depth.unwrap_or(base.max_path_depth),
+        max_compressed_bytes: cli.max_compressed_size.unwrap_or(base.max_compressed_bytes),
+        ..base
     };
 
-    let mode = if cli.validate_first {
+    let mode = if cli.validate_deep || (cli.hardened && !cli.validate_first) {
+        ExtractionMode::ValidateFirstDeep
+    } else if cli.validate_first || cli.hardened || cli.ensure_free_space {
         ExtractionMode::ValidateFirst
     } else {
         ExtractionMode::Streaming
     };
 
-    // Build extractor based on format
-    match format {
-        ArchiveFormat::Zip => extract_zip(&cli, archive, limits, overwrite, symlinks, mode),
-        ArchiveFormat::Tar | ArchiveFormat::TarGz => {
-            extract_tar(&cli, archive, format, limits, overwrite, symlinks, mode)
+    // Build extractor based on format. ZIPs go through the Driver only when
+    // the Extractor lacks the mode, regex filters, or a manifest writer.
+    let needs_driver =
+        cli.manifest.is_some() || !cli.include_regex.is_empty() || !cli.exclude_regex.is_empty();
+    match (&format, cli.overwrite.legacy(), cli.symlinks().legacy()) {
+        (ArchiveFormat::Zip, Some(overwrite), Some(symlinks)) if !needs_driver => {
+            extract_zip(&cli, archive, limits, overwrite, symlinks, mode)
         }
-        ArchiveFormat::SevenZ => {
+        (
+            ArchiveFormat::Zip | ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::Gz,
+            _,
+            _,
+        ) => extract_with_driver(&cli, archive, format, limits, mode),
+        #[cfg(feature = "xz")]
+        (ArchiveFormat::TarXz, _, _) => extract_with_driver(&cli, archive, format, limits, mode),
+        #[cfg(not(feature = "xz"))]
+        (ArchiveFormat::TarXz, _, _) => Err(xz_unsupported()),
+        (ArchiveFormat::SevenZ, _, _) => {
             eprintln!("Error: 7z support requires --features sevenz");
             Err(Error::Io(std::io::Error::new(
                 std::io::ErrorKind::Unsupported,
@@ -259,11 +449,20 @@ fn extract_zip(
     symlinks: SymlinkPolicy,
     mode: ExtractionMode,
 ) -> Result<(), Error> {
-    let mut extractor = Extractor::new_or_create(&cli.dest)?
+    let mut extractor = Extractor::new_or_create(&cli.dest)?;
+    if cli.hardened {
+        extractor = extractor.hardened();
+    }
+    extractor = extractor
         .limits(limits)
         .overwrite(overwrite)
         .symlinks(symlinks)
-        .mode(mode);
+        .mode(mode)
+        .ensure_free_space(cli.ensure_free_space)
+        .strip_components(cli.strip_components);
+    if !cli.allow_methods.is_empty() {
+        extractor = extractor.allowed_compression_methods(&cli.allow_methods);
+    }
 
     // Apply filters
     if !cli.only_files.is_empty() {
@@ -290,51 +489,56 @@ fn extract_zip(
 
     let report = extractor.extract_file(archive)?;
 
-    if !cli.quiet {
-        println!(
-            "Extracted {} files ({} bytes) to {}",
-            report.files_extracted,
-            format_bytes(report.bytes_written),
-            cli.dest.display()
-        );
-        if report.entries_skipped > 0 {
-            println!("Skipped {} entries", report.entries_skipped);
-        }
-    }
-
+    print_summary(
+        cli,
+        report.files_extracted,
+        report.bytes_written,
+        report.entries_skipped,
+    );
     Ok(())
 }
 
-fn extract_tar(
+fn extract_with_driver(
     cli: &Cli,
     archive: &Path,
     format: ArchiveFormat,
     limits: Limits,
-    overwrite: OverwritePolicy,
-    symlinks: SymlinkPolicy,
     mode: ExtractionMode,
 ) -> Result<(), Error> {
-    let overwrite_mode = match overwrite {
-        OverwritePolicy::Error => safe_unzip::OverwriteMode::Error,
-        OverwritePolicy::Skip => safe_unzip::OverwriteMode::Skip,
-        OverwritePolicy::Overwrite => safe_unzip::OverwriteMode::Overwrite,
-    };
-
-    let symlink_behavior = match symlinks {
-        SymlinkPolicy::Skip => safe_unzip::SymlinkBehavior::Skip,
-        SymlinkPolicy::Error => safe_unzip::SymlinkBehavior::Error,
-    };
+    // Only the Extractor checks compression methods; don't drop the
+    // restriction silently
+    if matches!(format, ArchiveFormat::Zip) && !cli.allow_methods.is_empty() {
+        return Err(Error::InvalidOption {
+            option: "--allow-methods".to_string(),
+            reason: "cannot be combined with --manifest, regex filters, or this --overwrite/--symlinks mode"
+                .to_string(),
+        });
+    }
 
     let validation = match mode {
         ExtractionMode::Streaming => safe_unzip::ValidationMode::Streaming,
         ExtractionMode::ValidateFirst => safe_unzip::ValidationMode::ValidateFirst,
+        ExtractionMode::ValidateFirstDeep => safe_unzip::ValidationMode::ValidateFirstDeep,
     };
 
     let mut driver = Driver::new_or_create(&cli.dest)?
         .limits(limits)
-        .overwrite(overwrite_mode)
-        .symlinks(symlink_behavior)
-        .validation(validation);
+        .overwrite(cli.overwrite.driver())
+        .symlinks(cli.symlinks().driver())
+        .validation(validation)
+        .ensure_free_space(cli.ensure_free_space)
+        .strip_components(cli.strip_components);
+
+    if cli.hardened {
+        driver = driver
+            .reject_deceptive_unicode(true)
+            .windows_name_check(true)
+            .absolute_paths(AbsolutePathPolicy::Reject)
+            .permissions(|entry, mode| match entry.kind {
+                EntryKind::File => mode & !0o111,
+                _ => mode,
+            });
+    }
 
     // Apply filters
     if !cli.only_files.is_empty() {
@@ -346,100 +550,269 @@ fn extract_tar(
     if !cli.exclude_patterns.is_empty() {
         driver = driver.exclude_glob(&cli.exclude_patterns);
     }
+    if !cli.include_regex.is_empty() {
+        driver = driver.include_regex(&cli.include_regex);
+    }
+    if !cli.exclude_regex.is_empty() {
+        driver = driver.exclude_regex(&cli.exclude_regex);
+    }
+
+    if let Some(ref path) = cli.manifest {
+        let format = cli.manifest_format.unwrap_or_else(|| {
+            let is_csv = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+            if is_csv {
+                ManifestFormat::Csv
+            } else {
+                ManifestFormat::Json
+            }
+        });
+        driver = match format {
+            ManifestFormat::Json => driver.write_index(path, JsonIndex),
+            ManifestFormat::Csv => driver.write_index(path, CsvIndex),
+        };
+    }
+
+    // Streaming TAR has no up-front entry count, so only the number is shown
+    if cli.verbose {
+        let current = Arc::new(AtomicUsize::new(0));
+        let announced = Arc::clone(&current);
+        driver = driver
+            .on_progress(move |p| {
+                announced.store(p.entry_index + 1, Ordering::Relaxed);
+                if p.total_entries > 0 {
+                    println!(
+                        "[{}/{}] {}",
+                        p.entry_index + 1,
+                        p.total_entries,
+                        p.entry_name
+                    );
+                } else {
+                    println!("[{}] {}", p.entry_index + 1, p.entry_name);
+                }
+            })
+            .observe(VerboseObserver { current });
+    }
 
     let report = match format {
+        ArchiveFormat::Zip => driver.extract_zip_file(archive)?,
         ArchiveFormat::Tar => driver.extract_tar_file(archive)?,
         ArchiveFormat::TarGz => driver.extract_tar_gz_file(archive)?,
+        #[cfg(feature = "xz")]
+        ArchiveFormat::TarXz => driver.extract_tar_xz_file(archive)?,
+        ArchiveFormat::Gz => driver.extract_gz_file(archive)?,
         _ => unreachable!(),
     };
 
-    if !cli.quiet {
+    print_summary(
+        cli,
+        report.files_extracted,
+        report.bytes_written,
+        report.entries_skipped,
+    );
+    Ok(())
+}
+
+/// Print the one-line summary, or a JSON object with `--json`.
+fn print_summary(cli: &Cli, files_extracted: usize, bytes_written: u64, entries_skipped: usize) {
+    if cli.json {
+        let manifest = match cli.manifest {
+            Some(ref path) => json_string(&path.to_string_lossy()),
+            None => "null".to_string(),
+        };
         println!(
-            "Extracted {} files ({} bytes) to {}",
-            report.files_extracted,
-            format_bytes(report.bytes_written),
-            cli.dest.display()
+            "{{\"files_extracted\": {}, \"bytes_written\": {}, \"entries_skipped\": {}, \"destination\": {}, \"manifest\": {}}}",
+            files_extracted,
+            bytes_written,
+            entries_skipped,
+            json_string(&cli.dest.to_string_lossy()),
+            manifest
         );
-        if report.entries_skipped > 0 {
-            println!("Skipped {} entries", report.entries_skipped);
+        return;
+    }
+    if cli.quiet {
+        return;
+    }
+    println!(
+        "Extracted {} files ({} bytes) to {}",
+        files_extracted,
+        format_bytes(bytes_written),
+        cli.dest.display()
+    );
+    if entries_skipped > 0 {
+        println!("Skipped {} entries", entries_skipped);
+    }
+}
+
+/// Quote `s` as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
         }
     }
+    out.push('"');
+    out
+}
 
-    Ok(())
+/// Prints why an entry was skipped (`--verbose`), numbered like the
+/// progress line that announced it.
+struct VerboseObserver {
+    current: Arc<AtomicUsize>,
 }
 
-fn list_archive(path: &Path, format: ArchiveFormat, quiet: bool) -> Result<(), Error> {
-    match format {
-        ArchiveFormat::Zip => {
-            let entries = safe_unzip::list_zip_entries(path)?;
+impl ExtractionObserver for VerboseObserver {
+    fn on_skip(&self, entry: &EntryInfo, reason: SkipReason) {
+        let n = self.current.load(Ordering::Relaxed);
+        println!("[{}] {} (skipped: {:?})", n, entry.name, reason);
+    }
+}
 
-            if !quiet {
-                println!("{} entries in {}:", entries.len(), path.display());
-                println!();
-            }
+fn list_archive(
+    path: &Path,
+    format: ArchiveFormat,
+    quiet: bool,
+    tree: bool,
+    long: bool,
+) -> Result<(), Error> {
+    let entries = match format {
+        ArchiveFormat::Zip => safe_unzip::list_zip_entries(path)?,
+        ArchiveFormat::Tar => safe_unzip::list_tar_entries(path)?,
+        ArchiveFormat::TarGz => safe_unzip::list_tar_gz_entries(path)?,
+        #[cfg(feature = "xz")]
+        ArchiveFormat::TarXz => safe_unzip::list_tar_xz_entries(path)?,
+        #[cfg(not(feature = "xz"))]
+        ArchiveFormat::TarXz => return Err(xz_unsupported()),
+        ArchiveFormat::Gz => return Err(single_gz_unsupported("listing")),
+        #[cfg(feature = "sevenz")]
+        ArchiveFormat::SevenZ => safe_unzip::list_7z_entries(path)?,
+        #[cfg(not(feature = "sevenz"))]
+        ArchiveFormat::SevenZ => {
+            eprintln!("Error: 7z listing requires --features sevenz");
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "7z not supported in this build",
+            )));
+        }
+    };
 
-            let mut total_size = 0u64;
-            for entry in &entries {
-                let kind = match entry.kind {
-                    safe_unzip::EntryKind::File => "",
-                    safe_unzip::EntryKind::Directory => "/",
-                    safe_unzip::EntryKind::Symlink { .. } => " -> [symlink]",
-                };
-                println!("{:>10}  {}{}", format_bytes(entry.size), entry.name, kind);
-                total_size += entry.size;
-            }
+    if !quiet {
+        println!("{} entries in {}:", entries.len(), path.display());
+        println!();
+    }
 
-            if !quiet {
-                println!();
+    let mut total_size = 0u64;
+    if tree {
+        let root = TreeNode::build(&entries);
+        println!("{} ({})", path.display(), format_bytes(root.total_size()));
+        root.print("");
+        total_size = root.total_size();
+    } else {
+        for entry in &entries {
+            let kind = match entry.kind {
+                safe_unzip::EntryKind::File => "",
+                safe_unzip::EntryKind::Directory => "/",
+                safe_unzip::EntryKind::Symlink { .. } => " -> [symlink]",
+            };
+            if long {
                 println!(
-                    "Total: {} files, {}",
-                    entries.len(),
-                    format_bytes(total_size)
+                    "{:>10}  {:<19}  {}{}",
+                    format_bytes(entry.size),
+                    entry.mtime.map(format_mtime).unwrap_or_else(|| "-".into()),
+                    entry.name,
+                    kind
                 );
+            } else {
+                println!("{:>10}  {}{}", format_bytes(entry.size), entry.name, kind);
             }
+            total_size += entry.size;
         }
-        ArchiveFormat::Tar | ArchiveFormat::TarGz => {
-            let entries = if matches!(format, ArchiveFormat::TarGz) {
-                safe_unzip::list_tar_gz_entries(path)?
-            } else {
-                safe_unzip::list_tar_entries(path)?
-            };
+    }
 
-            if !quiet {
-                println!("{} entries in {}:", entries.len(), path.display());
-                println!();
-            }
+    if !quiet {
+        println!();
+        println!(
+            "Total: {} files, {}",
+            entries.len(),
+            format_bytes(total_size)
+        );
+    }
 
-            let mut total_size = 0u64;
-            for entry in &entries {
-                let kind = match entry.kind {
-                    safe_unzip::EntryKind::File => "",
-                    safe_unzip::EntryKind::Directory => "/",
-                    safe_unzip::EntryKind::Symlink { .. } => " -> [symlink]",
-                };
-                println!("{:>10}  {}{}", format_bytes(entry.size), entry.name, kind);
-                total_size += entry.size;
+    Ok(())
+}
+
+/// Directory hierarchy for `--tree`, built from entry paths.
+#[derive(Default)]
+struct TreeNode {
+    /// File size; directories report the sum of their children instead.
+    size: u64,
+    is_dir: bool,
+    symlink_target: Option<String>,
+    children: BTreeMap<String, TreeNode>,
+}
+
+impl TreeNode {
+    fn build(entries: &[EntryInfo]) -> Self {
+        let mut root = TreeNode {
+            is_dir: true,
+            ..Default::default()
+        };
+        for entry in entries {
+            let components: Vec<&str> = entry.name.split('/').filter(|c| !c.is_empty()).collect();
+            let Some((leaf, parents)) = components.split_last() else {
+                continue;
+            };
+
+            let mut node = &mut root;
+            for parent in parents {
+                node = node.children.entry(parent.to_string()).or_default();
+                node.is_dir = true;
             }
 
-            if !quiet {
-                println!();
-                println!(
-                    "Total: {} files, {}",
-                    entries.len(),
-                    format_bytes(total_size)
-                );
+            let node = node.children.entry(leaf.to_string()).or_default();
+            match &entry.kind {
+                safe_unzip::EntryKind::File => node.size = entry.size,
+                safe_unzip::EntryKind::Directory => node.is_dir = true,
+                safe_unzip::EntryKind::Symlink { target } => {
+                    node.symlink_target = Some(target.clone())
+                }
             }
         }
-        ArchiveFormat::SevenZ => {
-            eprintln!("Error: 7z listing requires --features sevenz");
-            return Err(Error::Io(std::io::Error::new(
-                std::io::ErrorKind::Unsupported,
-                "7z not supported in this build",
-            )));
+        root
+    }
+
+    fn total_size(&self) -> u64 {
+        if self.is_dir {
+            self.children.values().map(TreeNode::total_size).sum()
+        } else {
+            self.size
         }
     }
 
-    Ok(())
+    /// Print children with `tree`-style connectors, indented by `prefix`.
+    fn print(&self, prefix: &str) {
+        let count = self.children.len();
+        for (i, (name, child)) in self.children.iter().enumerate() {
+            let last = i + 1 == count;
+            let connector = if last { "└── " } else { "├── " };
+            let label = match (&child.symlink_target, child.is_dir) {
+                (Some(target), _) => format!("{} -> {}", name, target),
+                (None, true) => format!("{}/ ({})", name, format_bytes(child.total_size())),
+                (None, false) => format!("{} ({})", name, format_bytes(child.size)),
+            };
+            println!("{}{}{}", prefix, connector, label);
+            if child.is_dir {
+                let indent = if last { "    " } else { "│   " };
+                child.print(&format!("{}{}", prefix, indent));
+            }
+        }
+    }
 }
 
 fn verify_archive(path: &Path, format: ArchiveFormat, quiet: bool) -> Result<(), Error> {
@@ -459,24 +832,25 @@ fn verify_archive(path: &Path, format: ArchiveFormat, quiet: bool) -> Result<(),
                 );
             }
         }
-        ArchiveFormat::Tar | ArchiveFormat::TarGz => {
-            // For TAR, we can list entries (which reads them) as a basic integrity check
-            let entries = if matches!(format, ArchiveFormat::TarGz) {
-                safe_unzip::list_tar_gz_entries(path)?
-            } else {
-                safe_unzip::list_tar_entries(path)?
+        ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::TarXz => {
+            let report = match format {
+                ArchiveFormat::TarGz => safe_unzip::verify_tar_gz_file(path)?,
+                #[cfg(feature = "xz")]
+                ArchiveFormat::TarXz => safe_unzip::verify_tar_xz_file(path)?,
+                #[cfg(not(feature = "xz"))]
+                ArchiveFormat::TarXz => return Err(xz_unsupported()),
+                _ => safe_unzip::verify_tar_file(path)?,
             };
 
-            let total_size: u64 = entries.iter().map(|e| e.size).sum();
-
             if !quiet {
                 println!(
                     "✓ Verified {} entries ({})",
-                    entries.len(),
-                    format_bytes(total_size)
+                    report.entries_verified,
+                    format_bytes(report.bytes_verified)
                 );
             }
         }
+        ArchiveFormat::Gz => return Err(single_gz_unsupported("verification")),
         ArchiveFormat::SevenZ => {
             eprintln!("Error: 7z verification requires --features sevenz");
             return Err(Error::Io(std::io::Error::new(
@@ -489,6 +863,23 @@ fn verify_archive(path: &Path, format: ArchiveFormat, quiet: bool) -> Result<(),
     Ok(())
 }
 
+/// A single `.gz` has no entry list to show or check without decompressing.
+fn single_gz_unsupported(what: &str) -> Error {
+    Error::Io(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("{} is not supported for single-file .gz", what),
+    ))
+}
+
+/// `.tar.xz` needs liblzma, which is only linked with the `xz` feature.
+#[cfg(not(feature = "xz"))]
+fn xz_unsupported() -> Error {
+    Error::Io(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        ".tar.xz support requires --features xz",
+    ))
+}
+
 fn format_bytes(bytes: u64) -> String {
     if bytes >= 1024 * 1024 * 1024 {
         format!("{:.1}G", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
@@ -501,6 +892,36 @@ fn format_bytes(bytes: u64) -> String {
     }
 }
 
+/// `YYYY-MM-DD HH:MM:SS` in UTC.
+fn format_mtime(time: SystemTime) -> String {
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs_f64().ceil() as i64),
+    };
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
 fn format_error(e: &Error) -> String {
     match e {
         Error::PathEscape { entry, detail } => {
@@ -524,12 +945,31 @@ fn format_error(e: &Error) -> String {
         Error::FileCountExceeded { limit, .. } => {
             format!("Too many files (limit: {})", limit)
         }
+        Error::DirCountExceeded { limit, .. } => {
+            format!("Too many directories (limit: {})", limit)
+        }
+        Error::InsufficientSpace {
+            required,
+            available,
+        } => {
+            format!(
+                "Not enough disk space: need {}, only {} available",
+                format_bytes(*required),
+                format_bytes(*available)
+            )
+        }
+        Error::EntryCountExceeded { limit, .. } => {
+            format!("Too many entries (limit: {})", limit)
+        }
         Error::AlreadyExists { entry } => {
             format!("File already exists: {}", entry)
         }
         Error::EncryptedEntry { entry } => {
             format!("Encrypted entry not supported: {}", entry)
         }
+        Error::UnsupportedEncryption { entry, scheme } => {
+            format!("Encrypted entry not supported ({}): {}", scheme, entry)
+        }
         _ => e.to_string(),
     }
 }
@@ -0,0 +1,140 @@
+//! Sentinel file for [`Driver::completion_marker`](crate::Driver::completion_marker).
+//!
+//! The marker holds the extraction report as JSON. It is removed before an
+//! extraction writes anything and created by renaming a finished temporary
+//! file once everything else is done, so whoever sees it sees a complete
+//! tree and a complete marker.
+
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::driver::ExtractionReport;
+use crate::entry::EntryInfo;
+use crate::error::Error;
+use crate::index::json_escape;
+use crate::policy::{ExtractionState, Policy};
+use crate::staging::Staging;
+
+pub(crate) struct CompletionMarker {
+    path: PathBuf,
+}
+
+impl CompletionMarker {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Remove a marker left by an earlier extraction.
+    pub(crate) fn clear(&self) -> Result<(), Error> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => Ok(result?),
+        }
+    }
+
+    /// Atomically create the marker with `report` as its contents.
+    pub(crate) fn write(&self, report: &ExtractionReport) -> Result<(), Error> {
+        let parent = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let tmp = Staging::in_dir(parent);
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(tmp.path())?;
+        let mut out = BufWriter::new(file);
+        write_report(report, &mut out)?;
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(tmp.path(), &self.path)?;
+        tmp.persist();
+        Ok(())
+    }
+
+    /// Policy rejecting entries that would be extracted to the marker path,
+    /// or `None` if the marker is outside `destination`.
+    pub(crate) fn policy(&self, destination: &Path) -> Option<MarkerPolicy> {
+        let name = self.path.file_name()?;
+        let parent = self.path.parent().unwrap_or(Path::new(""));
+        let parent = fs::canonicalize(parent).unwrap_or_else(|_| parent.to_path_buf());
+        let destination =
+            fs::canonicalize(destination).unwrap_or_else(|_| destination.to_path_buf());
+        let relative = parent.strip_prefix(destination).ok()?.join(name);
+        Some(MarkerPolicy { relative })
+    }
+}
+
+/// Keeps archive entries from creating the marker early.
+pub(crate) struct MarkerPolicy {
+    /// Marker path relative to the destination.
+    relative: PathBuf,
+}
+
+impl Policy for MarkerPolicy {
+    fn check(&self, entry: &EntryInfo, _state: &ExtractionState) -> Result<(), Error> {
+        if Path::new(&entry.name) == self.relative {
+            return Err(Error::InvalidFilename {
+                entry: entry.name.clone(),
+                reason: "collides with the completion marker".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn write_report(report: &ExtractionReport, out: &mut dyn Write) -> io::Result<()> {
+    let skipped_symlinks: Vec<_> = report
+        .skipped_symlinks
+        .iter()
+        .map(|s| {
+            format!(
+                "{{\"name\": \"{}\", \"target\": \"{}\"}}",
+                json_escape(&s.name),
+                json_escape(&s.target)
+            )
+        })
+        .collect();
+    let skipped_unsupported: Vec<_> = report
+        .skipped_unsupported
+        .iter()
+        .map(|s| {
+            format!(
+                "{{\"name\": \"{}\", \"entry_type\": \"{}\"}}",
+                json_escape(&s.name),
+                json_escape(&s.entry_type)
+            )
+        })
+        .collect();
+    let source_digest = match report.source_digest {
+        Some(ref digest) => format!("\"{}\"", json_escape(digest)),
+        None => "null".to_string(),
+    };
+
+    writeln!(out, "{{")?;
+    writeln!(out, "  \"files_extracted\": {},", report.files_extracted)?;
+    writeln!(out, "  \"dirs_created\": {},", report.dirs_created)?;
+    writeln!(out, "  \"dirs_pruned\": {},", report.dirs_pruned)?;
+    writeln!(out, "  \"bytes_written\": {},", report.bytes_written)?;
+    writeln!(out, "  \"bytes_read\": {},", report.bytes_read)?;
+    writeln!(out, "  \"entries_skipped\": {},", report.entries_skipped)?;
+    writeln!(out, "  \"symlinks_created\": {},", report.symlinks_created)?;
+    writeln!(
+        out,
+        "  \"skipped_symlinks\": [{}],",
+        skipped_symlinks.join(", ")
+    )?;
+    writeln!(
+        out,
+        "  \"skipped_unsupported\": [{}],",
+        skipped_unsupported.join(", ")
+    )?;
+    writeln!(out, "  \"source_digest\": {},", source_digest)?;
+    writeln!(
+        out,
+        "  \"skipped_duplicate_archive\": {},",
+        report.skipped_duplicate_archive
+    )?;
+    writeln!(out, "  \"was_empty\": {}", report.was_empty)?;
+    writeln!(out, "}}")
+}
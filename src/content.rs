@@ -0,0 +1,189 @@
+//! Content sniffing for [`Driver::allow_content_types`](crate::Driver::allow_content_types).
+//!
+//! Classifies a file by the magic bytes at the start of its decompressed
+//! contents rather than by its name, so `payload.exe` renamed to
+//! `photo.png` is still recognised as an executable.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::Error;
+
+/// Bytes of decompressed content inspected per file.
+pub(crate) const SNIFF_LEN: usize = 1024;
+
+/// Broad category of a file's content, as detected from its magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ContentCategory {
+    /// PNG, JPEG, GIF, WebP, BMP, TIFF, ICO, AVIF, HEIC.
+    Image,
+    /// MP3, FLAC, Ogg, WAV, M4A.
+    Audio,
+    /// MP4, QuickTime, Matroska/WebM, AVI.
+    Video,
+    /// PDF, RTF, and legacy Office (OLE) documents.
+    Document,
+    /// ZIP (including Office Open XML and JAR), gzip, bzip2, xz, zstd, 7z,
+    /// RAR, and TAR.
+    Archive,
+    /// PE, ELF, Mach-O, WebAssembly, and `#!` scripts.
+    Executable,
+    /// UTF-8 text without binary control characters. This includes SVG,
+    /// HTML, and scripts without a `#!` line.
+    Text,
+}
+
+impl ContentCategory {
+    /// Classify content from its first bytes. Returns `None` for empty or
+    /// unrecognised binary content.
+    pub fn sniff(prefix: &[u8]) -> Option<Self> {
+        let starts = |magic: &[u8]| prefix.starts_with(magic);
+        let at = |offset: usize, magic: &[u8]| {
+            prefix.get(offset..).is_some_and(|p| p.starts_with(magic))
+        };
+
+        // Executables first: a `#!` script is text but must not pass as it
+        if starts(b"MZ")
+            || starts(b"\x7fELF")
+            || starts(b"\xfe\xed\xfa\xce")
+            || starts(b"\xce\xfa\xed\xfe")
+            || starts(b"\xfe\xed\xfa\xcf")
+            || starts(b"\xcf\xfa\xed\xfe")
+            || starts(b"\xca\xfe\xba\xbe")
+            || starts(b"\0asm")
+            || starts(b"#!")
+        {
+            return Some(Self::Executable);
+        }
+
+        if starts(b"\x89PNG\r\n\x1a\n")
+            || starts(b"\xff\xd8\xff")
+            || starts(b"GIF87a")
+            || starts(b"GIF89a")
+            || (starts(b"RIFF") && at(8, b"WEBP"))
+            || (starts(b"BM") && has_bmp_header(prefix))
+            || starts(b"II*\0")
+            || starts(b"MM\0*")
+            || starts(b"\0\0\x01\0")
+        {
+            return Some(Self::Image);
+        }
+
+        // ISO base media files share the `ftyp` box; the brand tells them apart
+        if at(4, b"ftyp") {
+            let brand = prefix.get(8..12).unwrap_or_default();
+            return Some(match brand {
+                b"avif" | b"avis" | b"heic" | b"heix" | b"mif1" | b"msf1" => Self::Image,
+                b"M4A " | b"M4B " => Self::Audio,
+                _ => Self::Video,
+            });
+        }
+
+        if starts(b"ID3")
+            || starts(b"\xff\xfb")
+            || starts(b"\xff\xf3")
+            || starts(b"\xff\xf2")
+            || starts(b"fLaC")
+            || starts(b"OggS")
+            || (starts(b"RIFF") && at(8, b"WAVE"))
+        {
+            return Some(Self::Audio);
+        }
+
+        if starts(b"\x1a\x45\xdf\xa3") || (starts(b"RIFF") && at(8, b"AVI ")) {
+            return Some(Self::Video);
+        }
+
+        if starts(b"%PDF-") || starts(b"{\\rtf") || starts(b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1") {
+            return Some(Self::Document);
+        }
+
+        if starts(b"PK\x03\x04")
+            || starts(b"PK\x05\x06")
+            || starts(b"\x1f\x8b")
+            || starts(b"BZh")
+            || starts(b"\xfd7zXZ\0")
+            || starts(b"\x28\xb5\x2f\xfd")
+            || starts(b"7z\xbc\xaf\x27\x1c")
+            || starts(b"Rar!\x1a\x07")
+            || at(257, b"ustar")
+        {
+            return Some(Self::Archive);
+        }
+
+        if is_text(prefix) {
+            return Some(Self::Text);
+        }
+
+        None
+    }
+
+    /// Lowercase name, as accepted by [`FromStr`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Audio => "audio",
+            Self::Video => "video",
+            Self::Document => "document",
+            Self::Archive => "archive",
+            Self::Executable => "executable",
+            Self::Text => "text",
+        }
+    }
+}
+
+impl fmt::Display for ContentCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ContentCategory {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "image" => Ok(Self::Image),
+            "audio" => Ok(Self::Audio),
+            "video" => Ok(Self::Video),
+            "document" => Ok(Self::Document),
+            "archive" => Ok(Self::Archive),
+            "executable" => Ok(Self::Executable),
+            "text" => Ok(Self::Text),
+            _ => Err(Error::InvalidOption {
+                option: "content type".to_string(),
+                reason: format!("unknown category '{}'", s),
+            }),
+        }
+    }
+}
+
+/// True if a `BM` prefix is followed by a known DIB header size, so text
+/// that happens to start with "BM" is not taken for a bitmap.
+fn has_bmp_header(prefix: &[u8]) -> bool {
+    prefix
+        .get(14..18)
+        .map(|h| u32::from_le_bytes([h[0], h[1], h[2], h[3]]))
+        .is_some_and(|size| matches!(size, 12 | 40 | 52 | 56 | 64 | 108 | 124))
+}
+
+/// UTF-8 without NUL or control characters other than common whitespace
+/// and escape. A multi-byte character cut off at the end of the prefix is
+/// allowed.
+fn is_text(prefix: &[u8]) -> bool {
+    if prefix.is_empty() {
+        return false;
+    }
+    let valid = match std::str::from_utf8(prefix) {
+        Ok(s) => s,
+        Err(e) if e.error_len().is_none() => {
+            // Only the truncated tail is invalid
+            std::str::from_utf8(&prefix[..e.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return false,
+    };
+    valid
+        .chars()
+        .all(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r' | '\x0c' | '\x1b'))
+}
@@ -0,0 +1,99 @@
+//! Archive-level de-duplication for
+//! [`Driver::dedupe_store`](crate::Driver::dedupe_store).
+//!
+//! A [`DedupeStore`] remembers the digests of archives that were already
+//! extracted, so a re-delivered archive is skipped instead of being
+//! extracted again. The crate only ships an in-memory store; anything
+//! shared between processes (a file, a database, redis) is a small
+//! implementation of the trait.
+
+use std::collections::HashSet;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+/// Digests of archives that have already been extracted.
+///
+/// Digests are lowercase hex strings from the algorithm chosen with
+/// [`Driver::hash_source`](crate::Driver::hash_source). Errors abort the
+/// extraction with [`Error::Io`](crate::Error::Io).
+///
+/// # Example
+///
+/// ```
+/// use safe_unzip::DedupeStore;
+/// use std::io;
+/// use std::path::PathBuf;
+///
+/// /// One empty marker file per extracted archive.
+/// struct MarkerDir(PathBuf);
+///
+/// impl DedupeStore for MarkerDir {
+///     fn contains(&self, digest: &str) -> io::Result<bool> {
+///         Ok(self.0.join(digest).exists())
+///     }
+///
+///     fn record(&self, digest: &str) -> io::Result<()> {
+///         std::fs::write(self.0.join(digest), b"")
+///     }
+/// }
+/// ```
+pub trait DedupeStore: Send + Sync {
+    /// Whether an archive with `digest` was already extracted.
+    fn contains(&self, digest: &str) -> io::Result<bool>;
+
+    /// Remember that the archive with `digest` has been extracted.
+    fn record(&self, digest: &str) -> io::Result<()>;
+}
+
+impl<S: DedupeStore + ?Sized> DedupeStore for Arc<S> {
+    fn contains(&self, digest: &str) -> io::Result<bool> {
+        (**self).contains(digest)
+    }
+
+    fn record(&self, digest: &str) -> io::Result<()> {
+        (**self).record(digest)
+    }
+}
+
+/// A [`DedupeStore`] that lives as long as the process.
+///
+/// Wrap it in an [`Arc`] to share it between drivers.
+#[derive(Debug, Default)]
+pub struct MemoryDedupeStore {
+    digests: Mutex<HashSet<String>>,
+}
+
+impl MemoryDedupeStore {
+    /// An empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of recorded archives.
+    pub fn len(&self) -> usize {
+        self.digests.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// True if nothing has been recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl DedupeStore for MemoryDedupeStore {
+    fn contains(&self, digest: &str) -> io::Result<bool> {
+        Ok(self
+            .digests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(digest))
+    }
+
+    fn record(&self, digest: &str) -> io::Result<()> {
+        self.digests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(digest.to_string());
+        Ok(())
+    }
+}
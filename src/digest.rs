@@ -0,0 +1,113 @@
+//! Archive digests for [`Driver::hash_source`](crate::Driver::hash_source).
+//!
+//! The hash is taken over the raw archive bytes as they are read for
+//! extraction, so provenance records don't need a second pass over a large
+//! archive.
+
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use sha2::{Digest, Sha256, Sha512};
+
+/// Hash function applied to the archive by
+/// [`Driver::hash_source`](crate::Driver::hash_source).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DigestAlgorithm {
+    /// SHA-256, reported as 64 lowercase hex characters.
+    Sha256,
+    /// SHA-512, reported as 128 lowercase hex characters.
+    Sha512,
+}
+
+#[derive(Clone)]
+enum Hasher {
+    Sha256(Sha256),
+    Sha512(Sha512),
+}
+
+impl Hasher {
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(h) => h.update(data),
+            Self::Sha512(h) => h.update(data),
+        }
+    }
+
+    fn hex(self) -> String {
+        let bytes = match self {
+            Self::Sha256(h) => h.finalize().to_vec(),
+            Self::Sha512(h) => h.finalize().to_vec(),
+        };
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+struct HashState {
+    hasher: Hasher,
+    /// Bytes hashed so far, i.e. the offset of the next unhashed byte.
+    len: u64,
+}
+
+/// A running digest of one archive.
+///
+/// Shared with the [`HashingReader`] handed to the adapter, so it can be
+/// finished once the adapter has been dropped.
+#[derive(Clone)]
+pub(crate) struct SourceDigest(Arc<Mutex<HashState>>);
+
+impl SourceDigest {
+    pub(crate) fn new(algorithm: DigestAlgorithm) -> Self {
+        let hasher = match algorithm {
+            DigestAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            DigestAlgorithm::Sha512 => Hasher::Sha512(Sha512::new()),
+        };
+        Self(Arc::new(Mutex::new(HashState { hasher, len: 0 })))
+    }
+
+    pub(crate) fn update(&self, data: &[u8]) {
+        let mut state = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        state.hasher.update(data);
+        state.len += data.len() as u64;
+    }
+
+    /// Wrap `inner` so everything read through it is hashed.
+    pub(crate) fn reader<R: Read>(&self, inner: R) -> HashingReader<R> {
+        HashingReader {
+            inner,
+            digest: self.clone(),
+        }
+    }
+
+    /// Hash whatever of `path` the extraction did not read (e.g. padding
+    /// after a TAR end marker) and return the hex digest of the whole file.
+    pub(crate) fn finish_file(&self, path: &Path) -> io::Result<String> {
+        let len = self.0.lock().unwrap_or_else(|e| e.into_inner()).len;
+        let mut file = fs::File::open(path)?;
+        file.seek(SeekFrom::Start(len))?;
+        io::copy(&mut self.reader(file), &mut io::sink())?;
+        Ok(self.finish())
+    }
+
+    /// Hex digest of the bytes hashed so far.
+    pub(crate) fn finish(&self) -> String {
+        let state = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        state.hasher.clone().hex()
+    }
+}
+
+/// Reader that feeds every byte read through it into a [`SourceDigest`].
+pub(crate) struct HashingReader<R> {
+    inner: R,
+    digest: SourceDigest,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.digest.update(&buf[..n]);
+        Ok(n)
+    }
+}
@@ -3,20 +3,40 @@
 //! The driver orchestrates extraction using adapters (format-specific) and
 //! policies (security checks).
 
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::io::{Read, Seek};
+use std::io::{self, BufReader, Cursor, Read, Seek, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "tar")]
 use crate::adapter::TarAdapter;
-use crate::adapter::ZipAdapter;
+use crate::adapter::{ArchiveReader, ZipAdapter};
+use crate::completion::CompletionMarker;
+use crate::content::{ContentCategory, SNIFF_LEN};
+use crate::dedupe::DedupeStore;
+#[cfg(feature = "tar")]
+use crate::digest::HashingReader;
+use crate::digest::{DigestAlgorithm, SourceDigest};
 use crate::entry::{EntryInfo, EntryKind};
 use crate::error::Error;
-use crate::limits::Limits;
+use crate::extractor::Progress;
+use crate::filesystem::{FileSystem, StdFileSystem};
+use crate::index::{IndexFormat, IndexWriter};
+use crate::interrupt::Interrupt;
+use crate::limits::{check_free_space, Limits};
+use crate::observer::{EntryAction, ExtractionObserver, SkipReason};
 use crate::policy::{
-    CountPolicy, DepthPolicy, ExtractionState, PathPolicy, PolicyChain, SizePolicy,
-    SymlinkBehavior, SymlinkPolicy,
+    absolute_root_len, case_collision, cur_dir_prefix_len, folded_name, normal_path, prefixed,
+    relative_dir, resolve_link_target, strip_components, AbsolutePathPolicy, CaseCollisionPolicy,
+    CountPolicy, DepthPolicy, DirCountPolicy, ExtractionState, NameMap, PathPolicy, PermissionMode,
+    PolicyChain, RenamedEntry, SizePolicy, SkippedSymlink, SkippedUnsupported, SymlinkBehavior,
+    SymlinkPolicy, SymlinkRewrite, UnknownSizePolicy, UnsupportedPolicy,
 };
+use crate::staging;
 
 /// What to do when a file already exists at the extraction path.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -28,6 +48,10 @@ pub enum OverwriteMode {
     Skip,
     /// Overwrite existing files. Symlinks are removed before overwriting.
     Overwrite,
+    /// Skip files whose existing contents are identical to the entry; fail
+    /// with [`Error::ContentConflict`] if they differ. Lets a redeploy re-run
+    /// over its own output without ever replacing a changed file.
+    ErrorIfDifferent,
 }
 
 /// Extraction mode determining validation strategy.
@@ -38,6 +62,42 @@ pub enum ValidationMode {
     Streaming,
     /// Validate all entries first, then extract. Slower but atomic for validation failures.
     ValidateFirst,
+    /// Like `ValidateFirst`, but also decompress every ZIP entry to check
+    /// its CRC before extracting, so corrupt data fails the archive before
+    /// anything is written. Each entry is decompressed twice. TAR has no
+    /// data checksums and 7z is decompressed up front under
+    /// `ValidateFirst` already, so both are treated as `ValidateFirst`.
+    ValidateFirstDeep,
+}
+
+/// Order in which entries are written to disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Order {
+    /// Process entries in the order they appear in the archive.
+    #[default]
+    Archive,
+    /// Process entries sorted by name (byte order), independent of archive
+    /// layout. Useful for reproducible output. Not available for streaming
+    /// TAR extraction, which cannot look ahead.
+    SortedByName,
+}
+
+/// How aggressively extracted data is pushed to stable storage.
+///
+/// Syncing trades throughput for crash consistency: with `None` a power loss
+/// shortly after extraction can leave truncated or missing files even though
+/// extraction reported success.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Durability {
+    /// Leave flushing to the OS. Fastest.
+    #[default]
+    None,
+    /// `sync_all` each file after its contents and permissions are written.
+    FsyncFiles,
+    /// Like `FsyncFiles`, and also fsync the parent directory so the new
+    /// directory entry itself survives a crash (Unix only; same as
+    /// `FsyncFiles` elsewhere).
+    FsyncDir,
 }
 
 /// Extraction report with statistics.
@@ -47,10 +107,391 @@ pub struct ExtractionReport {
     pub files_extracted: usize,
     /// Number of directories created.
     pub dirs_created: usize,
+    /// Empty directories removed afterwards (only with
+    /// [`Driver::prune_empty_dirs`]).
+    pub dirs_pruned: usize,
     /// Total bytes written.
     pub bytes_written: u64,
+    /// Bytes consumed from the archive source. Compare against the expected
+    /// archive size to detect truncated or partially consumed input.
+    pub bytes_read: u64,
     /// Number of entries skipped (symlinks, filtered, existing).
     pub entries_skipped: usize,
+    /// Number of symlinks created (only with `SymlinkBehavior::AllowContained`
+    /// or `ContainedOrSkip`).
+    pub symlinks_created: usize,
+    /// Symlinks dropped because their target escapes the destination (only
+    /// with `SymlinkBehavior::ContainedOrSkip`). Also counted in
+    /// `entries_skipped`.
+    pub skipped_symlinks: Vec<SkippedSymlink>,
+    /// Device nodes, fifos, and other unsupported entries dropped under
+    /// `UnsupportedPolicy::Skip`. Also counted in `entries_skipped`.
+    pub skipped_unsupported: Vec<SkippedUnsupported>,
+    /// Entries extracted under another name to avoid a case collision
+    /// (only with `CaseCollisionPolicy::SuffixSecond`).
+    pub renamed_entries: Vec<RenamedEntry>,
+    /// Lowercase hex digest of the archive file, with
+    /// [`Driver::hash_source`]. `None` for adapter-based extraction and for
+    /// the combined report of `extract_many`.
+    pub source_digest: Option<String>,
+    /// True if the archive was skipped because the
+    /// [`Driver::dedupe_store`] had already seen its digest. Nothing was
+    /// extracted. Always false for the combined report of `extract_many`.
+    pub skipped_duplicate_archive: bool,
+    /// True if the archive had no entries at all, as opposed to every entry
+    /// being filtered out or skipped.
+    pub was_empty: bool,
+    /// Paths of the files, directories, and symlinks created, in extraction
+    /// order. Only filled in with [`Driver::collect_paths`].
+    pub extracted_paths: Vec<PathBuf>,
+}
+
+impl ExtractionReport {
+    fn from_state(state: &ExtractionState, was_empty: bool, bytes_read: u64) -> Self {
+        Self {
+            files_extracted: state.files_extracted,
+            dirs_created: state.dirs_created,
+            dirs_pruned: 0,
+            bytes_written: state.bytes_written,
+            bytes_read,
+            entries_skipped: state.entries_skipped,
+            symlinks_created: state.symlinks_created,
+            skipped_symlinks: state.skipped_symlinks.clone(),
+            skipped_unsupported: state.skipped_unsupported.clone(),
+            renamed_entries: state.renamed_entries.clone(),
+            source_digest: None,
+            skipped_duplicate_archive: false,
+            was_empty,
+            extracted_paths: state.extracted_paths.clone(),
+        }
+    }
+}
+
+/// Validated entries pulled one at a time from an archive, without writing
+/// anything. Created by [`Driver::open`].
+///
+/// This is a lending iterator: each reader borrows the stream, so drop it
+/// before asking for the next entry.
+pub struct EntryStream<'a, A> {
+    driver: &'a Driver,
+    adapter: A,
+    policies: PolicyChain,
+    state: ExtractionState,
+    /// Entries read from the archive, allowed through or not.
+    entries_read: usize,
+}
+
+impl<A: ArchiveReader> EntryStream<'_, A> {
+    /// The next entry that passes the filter, observers, and policies,
+    /// with a reader over its content (empty for directories and
+    /// symlinks). `None` once the archive is exhausted.
+    ///
+    /// Content not read before the next call is skipped. Reading past the
+    /// single-file limit or the remaining total budget fails; `?` in a
+    /// function returning [`Error`] turns that back into
+    /// [`Error::FileTooLarge`] or [`Error::TotalSizeExceeded`].
+    #[allow(clippy::type_complexity)]
+    pub fn next_entry(&mut self) -> Option<Result<(EntryInfo, Box<dyn Read + '_>), Error>> {
+        let info = match self.advance() {
+            Ok(Some(info)) => info,
+            Ok(None) => return None,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let Self {
+            driver,
+            adapter,
+            state,
+            ..
+        } = self;
+        let inner = match adapter.entry_reader() {
+            Ok(inner) => inner,
+            Err(e) => return Some(Err(e)),
+        };
+        let reader = LimitedEntryReader {
+            inner,
+            entry: info.name.clone(),
+            read: 0,
+            limits: &driver.limits,
+            total: &mut state.bytes_written,
+            spent: &mut state.decompress_time,
+            interrupt: &driver.interrupt,
+        };
+        Some(Ok((info, Box::new(reader))))
+    }
+
+    /// Entries yielded, bytes read, and entries skipped so far.
+    pub fn state(&self) -> &ExtractionState {
+        &self.state
+    }
+
+    /// Pull entries until one is allowed through.
+    fn advance(&mut self) -> Result<Option<EntryInfo>, Error> {
+        let driver = self.driver;
+        while let Some(next) = self.adapter.next_entry() {
+            driver.interrupt.check()?;
+            self.entries_read += 1;
+            driver.limits.check_entry_count(self.entries_read)?;
+            let info = match next {
+                Err(Error::UnsupportedEntryType { entry, entry_type })
+                    if driver.unsupported == UnsupportedPolicy::Skip =>
+                {
+                    self.state.entries_skipped += 1;
+                    self.state.skipped_unsupported.push(SkippedUnsupported {
+                        name: entry,
+                        entry_type,
+                    });
+                    continue;
+                }
+                next => next?,
+            };
+            let Some(info) = driver.normalized(&info, &self.state) else {
+                continue;
+            };
+            driver.check_duplicate(&info, &mut self.state)?;
+            if driver.skip_entry(&info, &mut self.state) {
+                continue;
+            }
+            let Some(info) = driver.resolve_case(info, &mut self.state)? else {
+                continue;
+            };
+            let info = info.into_owned();
+
+            self.policies.check_all(&info, &self.state)?;
+
+            match &info.kind {
+                EntryKind::File => self.state.files_extracted += 1,
+                EntryKind::Directory => self.state.dirs_created += 1,
+                EntryKind::Symlink { target } => {
+                    if target.is_empty() && driver.creates_symlinks() {
+                        return Err(Error::InvalidFilename {
+                            entry: info.name.clone(),
+                            reason: "empty symlink target".to_string(),
+                        });
+                    }
+                    // Nothing is created, so only the lexical check applies
+                    let base = Path::new(&info.name).parent().unwrap_or(Path::new(""));
+                    let contained = !Path::new(target).is_absolute()
+                        && resolve_link_target(base, Path::new(target)).is_some();
+                    match driver.symlinks {
+                        SymlinkBehavior::AllowContained | SymlinkBehavior::ContainedOrSkip
+                            if contained =>
+                        {
+                            self.state.symlinks_created += 1;
+                        }
+                        SymlinkBehavior::AllowContained => {
+                            return Err(Error::PathEscape {
+                                entry: info.name.clone(),
+                                detail: format!("symlink target '{}' escapes destination", target),
+                            });
+                        }
+                        SymlinkBehavior::ContainedOrSkip => {
+                            self.state.skipped_symlinks.push(SkippedSymlink {
+                                name: info.name.clone(),
+                                target: target.clone(),
+                            });
+                            driver.skip(&info, SkipReason::SymlinkEscape, &mut self.state);
+                            continue;
+                        }
+                        _ => {
+                            driver.skip(&info, SkipReason::Symlink, &mut self.state);
+                            continue;
+                        }
+                    }
+                }
+            }
+            return Ok(Some(info));
+        }
+        Ok(None)
+    }
+}
+
+/// Content reader handed out by [`EntryStream`]. Fails instead of
+/// returning bytes past the single-file limit or the remaining total.
+struct LimitedEntryReader<'a> {
+    inner: Box<dyn Read + 'a>,
+    entry: String,
+    read: u64,
+    limits: &'a Limits,
+    total: &'a mut u64,
+    spent: &'a mut Duration,
+    interrupt: &'a Interrupt,
+}
+
+impl Read for LimitedEntryReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.interrupt.check().map_err(io::Error::other)?;
+
+        let allowed = (self.limits.max_single_file.saturating_sub(self.read))
+            .min(self.limits.max_total_bytes.saturating_sub(*self.total));
+        // One byte more than allowed tells "at the limit" from "over it"
+        let len =
+            usize::try_from(allowed.saturating_add(1)).map_or(buf.len(), |n| n.min(buf.len()));
+        let started = Instant::now();
+        let n = self.inner.read(&mut buf[..len])? as u64;
+        self.interrupt
+            .charge(self.spent, started.elapsed())
+            .map_err(io::Error::other)?;
+
+        if n > allowed {
+            let size = self.read + n;
+            return Err(io::Error::other(if size > self.limits.max_single_file {
+                Error::FileTooLarge {
+                    entry: self.entry.clone(),
+                    limit: self.limits.max_single_file,
+                    size,
+                }
+            } else {
+                Error::TotalSizeExceeded {
+                    limit: self.limits.max_total_bytes,
+                    would_be: *self.total + n,
+                }
+            }));
+        }
+
+        self.read += n;
+        *self.total += n;
+        Ok(n as usize)
+    }
+}
+
+/// An archive on disk for [`Driver::extract_many`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ArchiveSource {
+    /// A ZIP archive.
+    Zip(PathBuf),
+    /// An uncompressed TAR archive.
+    #[cfg(feature = "tar")]
+    Tar(PathBuf),
+    /// A gzip-compressed TAR archive (`.tar.gz`, `.tgz`).
+    #[cfg(feature = "tar")]
+    TarGz(PathBuf),
+    /// An xz-compressed TAR archive (`.tar.xz`, `.txz`).
+    #[cfg(feature = "xz")]
+    TarXz(PathBuf),
+    /// A single gzip-compressed file (`.gz`), see
+    /// [`Driver::extract_gz_file`].
+    #[cfg(feature = "tar")]
+    Gz(PathBuf),
+    /// A 7z archive.
+    #[cfg(feature = "sevenz")]
+    SevenZ(PathBuf),
+}
+
+impl ArchiveSource {
+    /// Path of the archive file.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Zip(path) => path,
+            #[cfg(feature = "tar")]
+            Self::Tar(path) | Self::TarGz(path) | Self::Gz(path) => path,
+            #[cfg(feature = "xz")]
+            Self::TarXz(path) => path,
+            #[cfg(feature = "sevenz")]
+            Self::SevenZ(path) => path,
+        }
+    }
+
+    /// True for formats the extractor must seek around in.
+    fn is_random_access(&self) -> bool {
+        match self {
+            Self::Zip(_) => true,
+            #[cfg(feature = "tar")]
+            Self::Tar(_) | Self::TarGz(_) | Self::Gz(_) => false,
+            #[cfg(feature = "xz")]
+            Self::TarXz(_) => false,
+            #[cfg(feature = "sevenz")]
+            Self::SevenZ(_) => true,
+        }
+    }
+}
+
+/// Output name for a `.gz` without a stored name: the archive's file name
+/// minus `.gz`.
+#[cfg(feature = "tar")]
+fn gz_output_name(archive: &Path) -> Option<String> {
+    let name = archive.file_name()?.to_str()?;
+    let stem = name
+        .strip_suffix(".gz")
+        .or_else(|| name.strip_suffix(".GZ"))?;
+    (!stem.is_empty() && stem != "." && stem != "..").then(|| stem.to_string())
+}
+
+/// Move entries the adapter skipped as unsupported into `state`. Returns
+/// how many there were.
+#[cfg(feature = "tar")]
+fn record_unsupported<R: Read>(adapter: &mut TarAdapter<R>, state: &mut ExtractionState) -> usize {
+    let skipped = adapter.take_skipped_unsupported();
+    state.entries_skipped += skipped.len();
+    let count = skipped.len();
+    state.skipped_unsupported.extend(skipped);
+    count
+}
+
+/// Remove the directories in `dirs` that are empty, deepest first so
+/// emptied parents go too. Returns how many were removed.
+fn prune_dirs(mut dirs: Vec<PathBuf>) -> Result<usize, Error> {
+    dirs.sort_by(|a, b| {
+        let depth = |dir: &PathBuf| dir.components().count();
+        depth(b).cmp(&depth(a)).then_with(|| a.cmp(b))
+    });
+    dirs.dedup();
+    let mut pruned = 0;
+    for dir in dirs {
+        // Anything the caller or a later entry put here keeps it alive
+        if fs::read_dir(&dir)?.next().is_none() {
+            fs::remove_dir(&dir)?;
+            pruned += 1;
+        }
+    }
+    Ok(pruned)
+}
+
+/// Remove what a failed extraction created, deepest first so directories
+/// are empty by the time they are reached. Best effort: whatever can't be
+/// removed, such as a directory something else wrote into, stays.
+pub(crate) fn remove_created(mut paths: Vec<PathBuf>) {
+    paths.sort_by(|a, b| {
+        let depth = |path: &PathBuf| path.components().count();
+        depth(b).cmp(&depth(a)).then_with(|| a.cmp(b))
+    });
+    paths.dedup();
+    for path in paths {
+        match fs::symlink_metadata(&path) {
+            Ok(meta) if meta.is_dir() => {
+                let _ = fs::remove_dir(&path);
+            }
+            Ok(_) => {
+                let _ = fs::remove_file(&path);
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+/// `name` with `~n` appended to its file stem, e.g. `docs/Notes~1.txt` for
+/// `docs/Notes.txt`.
+fn with_case_suffix(name: &str, n: usize) -> String {
+    let (dir, file) = name.split_at(name.rfind('/').map_or(0, |i| i + 1));
+    match file.rfind('.') {
+        Some(dot) if dot > 0 => format!("{}{}~{}{}", dir, &file[..dot], n, &file[dot..]),
+        _ => format!("{}{}~{}", dir, file, n),
+    }
+}
+
+/// Archive file name without its archive extension(s), e.g. `data` for
+/// `data.tar.gz`. `None` if nothing usable is left.
+fn archive_stem(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let lower = name.to_ascii_lowercase();
+    let stem = [
+        ".tar.gz", ".tgz", ".tar.xz", ".txz", ".tar", ".gz", ".zip", ".7z",
+    ]
+    .iter()
+    .find(|ext| lower.ends_with(*ext))
+    .map_or(name, |ext| &name[..name.len() - ext.len()]);
+    (!stem.is_empty() && stem != "." && stem != "..").then(|| stem.to_string())
 }
 
 /// Generic extraction driver that works with any archive format.
@@ -76,11 +517,92 @@ pub struct Driver {
     overwrite: OverwriteMode,
     /// What to do with symlinks.
     symlinks: SymlinkBehavior,
+    /// How created symlink targets are written.
+    symlink_rewrite: SymlinkRewrite,
     /// Validation strategy.
     validation: ValidationMode,
+    /// Whether written files are synced to disk.
+    durability: Durability,
+    /// Entry processing order.
+    order: Order,
+    /// Reject names with bidi controls or invisible characters.
+    reject_deceptive_unicode: bool,
+    /// Reject Windows device names.
+    windows_name_check: bool,
+    /// Check free space against the declared total before writing.
+    ensure_free_space: bool,
+    /// Create directories only, skipping file contents and symlinks.
+    structure_only: bool,
     /// Optional entry filter.
     #[allow(clippy::type_complexity)]
     filter: Option<Box<dyn Fn(&EntryInfo) -> bool + Send + Sync>>,
+    /// A builder option that could not be applied, as `(option, reason)`;
+    /// reported when extraction starts.
+    #[cfg(feature = "regex")]
+    invalid_option: Option<(String, String)>,
+    /// When to stop extraction early.
+    interrupt: Interrupt,
+    /// Optional check of the raw archive bytes before extraction.
+    #[allow(clippy::type_complexity)]
+    verifier: Option<Box<dyn Fn(&[u8]) -> Result<(), Error> + Send + Sync>>,
+    /// Optional per-entry permission override.
+    #[allow(clippy::type_complexity)]
+    permissions: Option<Box<dyn Fn(&EntryInfo, u32) -> u32 + Send + Sync>>,
+    /// Where applied modes come from.
+    permission_mode: PermissionMode,
+    /// Set TAR extended attributes on extracted files.
+    #[cfg(feature = "xattr")]
+    restore_xattrs: bool,
+    /// Per-entry hooks, called in registration order.
+    observers: Vec<Box<dyn ExtractionObserver>>,
+    /// Called before each entry.
+    #[allow(clippy::type_complexity)]
+    on_progress: Option<Box<dyn Fn(&Progress) + Send + Sync>>,
+    /// Called with the destination after a successful extraction.
+    #[allow(clippy::type_complexity)]
+    post_extract: Option<Box<dyn Fn(&Path) + Send + Sync>>,
+    /// Index of extracted files written after success.
+    index: Option<IndexWriter>,
+    /// Sentinel file written after success.
+    completion_marker: Option<CompletionMarker>,
+    /// Sniffed content types files may have; `None` allows any.
+    content_types: Option<Vec<ContentCategory>>,
+    /// Handling of files whose declared size is unreliable.
+    unknown_size: UnknownSizePolicy,
+    /// Handling of entry names rooted at `/` or a drive letter.
+    absolute_paths: AbsolutePathPolicy,
+    /// Directory, inside the destination, absolute entry names are mapped
+    /// under instead of having their root stripped.
+    absolute_root: Option<PathBuf>,
+    /// Handling of device nodes, fifos, and other unsupported entry types.
+    unsupported: UnsupportedPolicy,
+    /// Handling of names that differ only in case; `None` doesn't check.
+    case_collisions: Option<CaseCollisionPolicy>,
+    /// Fail on a file or symlink name that appears twice.
+    reject_duplicate_entries: bool,
+    /// Leading name components dropped from every entry.
+    strip_components: usize,
+    /// Rewrites entry names; `None` skips the entry.
+    map_name: Option<NameMap>,
+    /// Directory every entry is extracted under, relative to the destination.
+    prefix: Option<String>,
+    /// List created paths in the report.
+    collect_paths: bool,
+    /// Digest of the raw archive to put on the report.
+    hash_source: Option<DigestAlgorithm>,
+    /// Digests of archives already extracted, which are skipped.
+    dedupe: Option<Box<dyn DedupeStore>>,
+    /// Storage entries are written to; `None` for the local disk.
+    filesystem: Option<Box<dyn FileSystem>>,
+    /// Count the destination's own depth against `max_path_depth`.
+    depth_includes_destination: bool,
+    /// Remove directories an extraction created that are still empty.
+    prune_empty_dirs: bool,
+    /// Remove what a failed extraction created.
+    cleanup_on_error: bool,
+    /// Output files open at once across all extractions sharing this
+    /// driver; `None` is unbounded.
+    open_files: Option<FileSlots>,
 }
 
 impl Driver {
@@ -96,6 +618,31 @@ impl Driver {
         Self::new_impl(destination.as_ref(), true)
     }
 
+    /// Remove staging files and directories left in `destination` by an
+    /// extraction that was killed before it could clean up.
+    ///
+    /// Temporary output is named `.safe_unzip_staging_<pid>_<random>` and is
+    /// normally removed by the extraction itself, even on failure. Only a
+    /// crash or `SIGKILL` leaves it behind. Entries modified less than
+    /// `older_than` ago are kept, since they may belong to an extraction
+    /// still in progress. Returns the number of entries removed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    /// use std::time::Duration;
+    ///
+    /// let removed = Driver::cleanup_stale_staging("/srv/app", Duration::from_secs(3600))?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn cleanup_stale_staging<P: AsRef<Path>>(
+        destination: P,
+        older_than: Duration,
+    ) -> Result<usize, Error> {
+        staging::cleanup_stale(destination.as_ref(), older_than)
+    }
+
     fn new_impl(destination: &Path, create: bool) -> Result<Self, Error> {
         if !destination.exists() {
             if create {
@@ -112,8 +659,46 @@ impl Driver {
             limits: Limits::default(),
             overwrite: OverwriteMode::default(),
             symlinks: SymlinkBehavior::default(),
+            symlink_rewrite: SymlinkRewrite::default(),
             validation: ValidationMode::default(),
+            durability: Durability::default(),
+            order: Order::default(),
+            reject_deceptive_unicode: false,
+            windows_name_check: cfg!(windows),
+            ensure_free_space: false,
+            structure_only: false,
             filter: None,
+            #[cfg(feature = "regex")]
+            invalid_option: None,
+            interrupt: Interrupt::default(),
+            verifier: None,
+            permissions: None,
+            permission_mode: PermissionMode::FromArchive,
+            #[cfg(feature = "xattr")]
+            restore_xattrs: false,
+            observers: Vec::new(),
+            on_progress: None,
+            post_extract: None,
+            index: None,
+            completion_marker: None,
+            content_types: None,
+            unknown_size: UnknownSizePolicy::default(),
+            absolute_paths: AbsolutePathPolicy::default(),
+            absolute_root: None,
+            unsupported: UnsupportedPolicy::default(),
+            case_collisions: None,
+            reject_duplicate_entries: false,
+            strip_components: 0,
+            map_name: None,
+            prefix: None,
+            collect_paths: false,
+            hash_source: None,
+            dedupe: None,
+            filesystem: None,
+            depth_includes_destination: false,
+            prune_empty_dirs: false,
+            cleanup_on_error: false,
+            open_files: None,
         })
     }
 
@@ -123,6 +708,31 @@ impl Driver {
         self
     }
 
+    /// Count the destination's own directories against
+    /// [`Limits::max_path_depth`], making the limit absolute rather than
+    /// relative to the destination. Default: `false`.
+    ///
+    /// Useful near filesystem path limits, where a shallow entry under an
+    /// already deep destination can still fail. With this set, extracting
+    /// `a/b.txt` into `/srv/data/out` counts as depth 5 instead of 2, and
+    /// [`Error::PathTooDeep`] reports the combined depth.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, Limits};
+    ///
+    /// let report = Driver::new("/srv/data/out")?
+    ///     .limits(Limits { max_path_depth: 32, ..Default::default() })
+    ///     .depth_includes_destination(true)
+    ///     .extract_zip_file("bundle.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn depth_includes_destination(mut self, enabled: bool) -> Self {
+        self.depth_includes_destination = enabled;
+        self
+    }
+
     /// Set overwrite mode.
     pub fn overwrite(mut self, mode: OverwriteMode) -> Self {
         self.overwrite = mode;
@@ -135,265 +745,2292 @@ impl Driver {
         self
     }
 
+    /// Set how targets of created symlinks are written.
+    ///
+    /// Only meaningful with [`SymlinkBehavior::AllowContained`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, SymlinkBehavior, SymlinkRewrite};
+    ///
+    /// // `/srv/out/lib/libfoo.so -> /srv/out/lib/libfoo.so.1` becomes
+    /// // `libfoo.so -> libfoo.so.1`
+    /// let report = Driver::new("/srv/out")?
+    ///     .symlinks(SymlinkBehavior::AllowContained)
+    ///     .symlink_rewrite(SymlinkRewrite::RelativizeContained)
+    ///     .extract_zip_file("bundle.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn symlink_rewrite(mut self, rewrite: SymlinkRewrite) -> Self {
+        self.symlink_rewrite = rewrite;
+        self
+    }
+
     /// Set validation mode.
     pub fn validation(mut self, mode: ValidationMode) -> Self {
         self.validation = mode;
         self
     }
 
-    /// Set entry filter.
-    pub fn filter<F>(mut self, f: F) -> Self
-    where
-        F: Fn(&EntryInfo) -> bool + Send + Sync + 'static,
-    {
-        self.filter = Some(Box::new(f));
+    /// Set write durability.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, Durability};
+    ///
+    /// let report = Driver::new("/boot/staging")?
+    ///     .durability(Durability::FsyncDir)
+    ///     .extract_zip_file("image.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
         self
     }
 
-    /// Extract only specific files by exact name.
+    /// Fail with [`Error::DeadlineExceeded`] once `deadline` has passed.
     ///
-    /// Names are matched exactly (case-sensitive).
+    /// Checked before each entry and between chunks of file data, so one
+    /// huge entry cannot overrun the deadline by much. Files already
+    /// extracted are left in place.
     ///
     /// # Example
     ///
     /// ```no_run
     /// use safe_unzip::Driver;
+    /// use std::time::{Duration, Instant};
     ///
     /// let report = Driver::new("/tmp/out")?
-    ///     .only(&["README.md", "LICENSE"])
-    ///     .extract_zip_file("archive.zip")?;
+    ///     .deadline(Instant::now() + Duration::from_secs(30))
+    ///     .extract_zip_file("upload.zip")?;
     /// # Ok::<(), safe_unzip::Error>(())
     /// ```
-    pub fn only<S: AsRef<str>>(self, names: &[S]) -> Self {
-        let names: Vec<String> = names.iter().map(|s| s.as_ref().to_string()).collect();
-        self.filter(move |entry| names.iter().any(|n| n == &entry.name))
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.interrupt.deadline = Some(deadline);
+        self
     }
 
-    /// Include only files matching glob patterns.
+    /// Fail with [`Error::Cancelled`] once `flag` is set.
     ///
-    /// Patterns: `*` matches except `/`, `**` matches including `/`, `?` matches one char.
+    /// Checked wherever the [`deadline`](Self::deadline) is: before each
+    /// entry and between chunks of file data. Cancellation is best effort;
+    /// the entry being written when the flag is set may be partly on disk
+    /// unless [`cleanup_on_error`](Self::cleanup_on_error) is on.
+    pub fn cancel_on(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.interrupt.cancel = Some(flag);
+        self
+    }
+
+    /// Fail with [`Error::Timeout`] naming the entry if copying any single
+    /// entry's data takes longer than `timeout`.
+    ///
+    /// Complements [`deadline`](Self::deadline): a whole-archive budget
+    /// says nothing about which entry used it up, and lets one stalling
+    /// entry consume all of it. The clock starts when the entry's data
+    /// starts being copied.
+    ///
+    /// This is best effort. It is checked each time decompressed data is
+    /// written, so a read that blocks inside the decompressor, or a
+    /// CPU-bound stretch that produces no output, is only noticed once it
+    /// returns the next chunk.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, Error};
+    /// use std::time::Duration;
+    ///
+    /// match Driver::new("/tmp/out")?
+    ///     .per_entry_deadline(Duration::from_secs(5))
+    ///     .extract_zip_file("upload.zip")
+    /// {
+    ///     Err(Error::Timeout { entry }) => eprintln!("'{}' took too long", entry),
+    ///     other => {
+    ///         other?;
+    ///     }
+    /// }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn per_entry_deadline(mut self, timeout: Duration) -> Self {
+        self.interrupt.entry_timeout = Some(timeout);
+        self
+    }
+
+    /// Fail with [`Error::DecompressBudgetExceeded`] once decompressing
+    /// entry data has taken more than `budget` in total.
+    ///
+    /// Aimed at data that is cheap to store but expensive to inflate.
+    /// Unlike [`deadline`](Self::deadline), time spent writing output or
+    /// waiting on the caller of [`open`](Self::open) is not counted, so a
+    /// slow disk or consumer does not use up the budget. Reading the
+    /// compressed input happens inside the decompressor and is counted.
+    ///
+    /// The time is measured with [`Instant`] around each chunk the
+    /// decompressor produces and accumulates in
+    /// [`ExtractionState::decompress_time`]. 7z archives are decoded
+    /// before their entries are seen, so the budget does not cover them.
     ///
     /// # Example
     ///
     /// ```no_run
     /// use safe_unzip::Driver;
+    /// use std::time::Duration;
     ///
-    /// // Extract only Rust source files
     /// let report = Driver::new("/tmp/out")?
-    ///     .include_glob(&["**/*.rs"])
-    ///     .extract_zip_file("archive.zip")?;
+    ///     .max_decompress_cpu(Duration::from_secs(2))
+    ///     .extract_zip_file("upload.zip")?;
     /// # Ok::<(), safe_unzip::Error>(())
     /// ```
-    pub fn include_glob<S: AsRef<str>>(self, patterns: &[S]) -> Self {
-        let patterns: Vec<String> = patterns.iter().map(|s| s.as_ref().to_string()).collect();
-        self.filter(move |entry| {
-            patterns
-                .iter()
-                .any(|p| glob_match::glob_match(p, &entry.name))
-        })
+    pub fn max_decompress_cpu(mut self, budget: Duration) -> Self {
+        self.interrupt.decompress_budget = Some(budget);
+        self
     }
 
-    /// Exclude files matching glob patterns.
+    /// Set the order in which entries are extracted.
+    ///
+    /// [`Order::SortedByName`] works for ZIP, 7z, and TAR in
+    /// [`ValidationMode::ValidateFirst`]; streaming TAR extraction returns
+    /// [`Error::InvalidOption`].
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use safe_unzip::Driver;
+    /// use safe_unzip::{Driver, Order};
     ///
-    /// // Extract everything except tests
     /// let report = Driver::new("/tmp/out")?
-    ///     .exclude_glob(&["**/test_*", "**/tests/**"])
+    ///     .extract_order(Order::SortedByName)
     ///     .extract_zip_file("archive.zip")?;
     /// # Ok::<(), safe_unzip::Error>(())
     /// ```
-    pub fn exclude_glob<S: AsRef<str>>(self, patterns: &[S]) -> Self {
-        let patterns: Vec<String> = patterns.iter().map(|s| s.as_ref().to_string()).collect();
-        self.filter(move |entry| {
-            !patterns
-                .iter()
-                .any(|p| glob_match::glob_match(p, &entry.name))
-        })
+    pub fn extract_order(mut self, order: Order) -> Self {
+        self.order = order;
+        self
     }
 
-    /// Build the policy chain from current settings.
-    fn build_policies(&self) -> Result<PolicyChain, Error> {
-        Ok(PolicyChain::new()
-            .with(PathPolicy::new(&self.destination)?)
-            .with(SizePolicy::new(
-                self.limits.max_single_file,
-                self.limits.max_total_bytes,
-            ))
-            .with(CountPolicy::new(self.limits.max_file_count))
-            .with(DepthPolicy::new(self.limits.max_path_depth))
-            .with(SymlinkPolicy::new(self.symlinks)))
+    /// Reject entry names containing bidi control, zero-width, or other
+    /// invisible characters with [`Error::InvalidFilename`].
+    ///
+    /// Off by default, since zero-width joiners appear legitimately in some
+    /// scripts and emoji. Turn it on for untrusted uploads, where
+    /// `photo\u{202E}gpj.exe` would otherwise display as `photoexe.jpg`.
+    pub fn reject_deceptive_unicode(mut self, reject: bool) -> Self {
+        self.reject_deceptive_unicode = reject;
+        self
     }
 
-    /// Extract a ZIP archive.
-    pub fn extract_zip<R: Read + Seek>(
-        &self,
-        mut adapter: ZipAdapter<R>,
-    ) -> Result<ExtractionReport, Error> {
-        let policies = self.build_policies()?;
-
-        // ValidateFirst mode: check all entries before extracting
-        if self.validation == ValidationMode::ValidateFirst {
-            self.validate_all_zip(&mut adapter, &policies)?;
-        }
-
-        let mut state = ExtractionState::default();
-
-        for i in 0..adapter.len() {
-            self.extract_zip_entry(&mut adapter, i, &policies, &mut state)?;
-        }
-
-        Ok(ExtractionReport {
-            files_extracted: state.files_extracted,
-            dirs_created: state.dirs_created,
-            bytes_written: state.bytes_written,
-            entries_skipped: state.entries_skipped,
-        })
+    /// Reject entry names with a Windows device name component (`CON`,
+    /// `NUL`, `COM1`, `LPT1`, ..., with any extension) with
+    /// [`Error::InvalidFilename`].
+    ///
+    /// Defaults to on for Windows targets, where such a name opens the
+    /// device rather than creating a file, and off on other platforms,
+    /// where a project's `aux.py` should extract like any other file.
+    pub fn windows_name_check(mut self, check: bool) -> Self {
+        self.windows_name_check = check;
+        self
     }
 
-    /// Validate all entries without extracting.
-    fn validate_all_zip<R: Read + Seek>(
-        &self,
-        adapter: &mut ZipAdapter<R>,
-        policies: &PolicyChain,
-    ) -> Result<(), Error> {
-        let entries = adapter.entries_metadata()?;
-        let mut state = ExtractionState::default();
-
-        for info in entries {
-            policies.check_all(&info, &state)?;
+    /// Fail with [`Error::InsufficientSpace`] before writing anything if
+    /// the destination's filesystem has less free space than the files
+    /// that pass validation declare. Default: `false`.
+    ///
+    /// Needs [`ValidationMode::ValidateFirst`], which works out the total;
+    /// in `Streaming` mode extraction fails with [`Error::InvalidOption`].
+    /// The check is skipped with a custom [`filesystem`](Self::filesystem),
+    /// whose free space the driver can't see. An archive can declare less
+    /// than it inflates to, so the size limits still apply while writing.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, ValidationMode};
+    ///
+    /// let report = Driver::new("/mnt/scratch")?
+    ///     .validation(ValidationMode::ValidateFirst)
+    ///     .ensure_free_space(true)
+    ///     .extract_zip_file("dataset.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn ensure_free_space(mut self, ensure: bool) -> Self {
+        self.ensure_free_space = ensure;
+        self
+    }
 
-            // Update state for cumulative checks
-            if matches!(info.kind, EntryKind::File) {
-                state.bytes_written += info.size;
-                state.files_extracted += 1;
-            }
-        }
+    /// Set how files whose decompressed size is not known up front are
+    /// handled. Default: [`UnknownSizePolicy::Allow`].
+    ///
+    /// Streaming ZIPs and PAX TAR entries can declare 0 bytes and still
+    /// carry data, which size checks and
+    /// [`ValidationMode::ValidateFirst`] cannot reason about. The write-time
+    /// limits always apply; this decides whether such entries are also
+    /// rejected or budgeted at `max_single_file` before extraction.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, UnknownSizePolicy};
+    ///
+    /// let report = Driver::new("/tmp/out")?
+    ///     .unknown_size(UnknownSizePolicy::Reject)
+    ///     .extract_zip_file("upload.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn unknown_size(mut self, policy: UnknownSizePolicy) -> Self {
+        self.unknown_size = policy;
+        self
+    }
 
-        Ok(())
+    /// Set how entry names rooted at `/` or a drive letter (`C:`) are
+    /// handled. Default: [`AbsolutePathPolicy::Strip`], which extracts
+    /// `/etc/passwd` to `<dest>/etc/passwd`; [`AbsolutePathPolicy::Reject`]
+    /// fails with [`Error::AbsolutePath`] instead.
+    ///
+    /// Either way nothing is written outside the destination.
+    pub fn absolute_paths(mut self, policy: AbsolutePathPolicy) -> Self {
+        self.absolute_paths = policy;
+        self
     }
 
-    /// Extract a single ZIP entry.
+    /// Drop the first `count` components of every entry name, like
+    /// `tar --strip-components`: with 1, `project-1.2.3/src/main.rs` is
+    /// extracted to `src/main.rs`. Default: 0.
+    ///
+    /// Entries with nothing left, such as the `project-1.2.3/` directory
+    /// itself, are skipped and counted in `entries_skipped`. Only normal
+    /// components are stripped; filters, observers, and the path checks
+    /// all see the stripped name, and a `..` is never stripped, so
+    /// `a/../../x` fails with [`Error::PathEscape`] rather than becoming
+    /// `x`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// let report = Driver::new("/src/project")?
+    ///     .strip_components(1)
+    ///     .extract_zip_file("project-1.2.3.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn strip_components(mut self, count: usize) -> Self {
+        self.strip_components = count;
+        self
+    }
+
+    /// Rename entries with `f`, which gets each name and returns the name
+    /// to extract it under, or `None` to skip it (counted in
+    /// `entries_skipped`, reported to observers as
+    /// [`SkipReason::Filtered`]). Directory names keep their trailing `/`.
+    ///
+    /// Names are rewritten in this order: a leading `./` is dropped,
+    /// [`strip_components`](Self::strip_components) are removed, `f` runs,
+    /// and the [`prefix`](Self::prefix) is added. Filters, observers, and
+    /// every path check then see the new name, so a callback can't send an
+    /// entry out of the destination: a returned `../x` fails with
+    /// [`Error::PathEscape`] like any other. Two entries mapped to one name
+    /// are handled by the [`overwrite`](Self::overwrite) mode, which by
+    /// default fails with [`Error::AlreadyExists`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// let report = Driver::new("/srv/assets")?
+    ///     .map_name(|name| {
+    ///         // Skip macOS metadata, lowercase the rest
+    ///         (!name.starts_with("__MACOSX/")).then(|| name.to_lowercase())
+    ///     })
+    ///     .extract_zip_file("assets.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn map_name<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.map_name = Some(Box::new(f));
+        self
+    }
+
+    /// Extract every entry under `prefix`, a directory relative to the
+    /// destination: with `prefix("foo")`, `hello.txt` is written to
+    /// `<dest>/foo/hello.txt`. Useful for unpacking several archives side
+    /// by side in one tree. The prefix is added after
+    /// [`strip_components`](Self::strip_components) and
+    /// [`map_name`](Self::map_name), and filters, observers, and reports
+    /// see the prefixed name.
+    ///
+    /// Fails with [`Error::InvalidOption`] if `prefix` is absolute or
+    /// contains `..`. An entry that would climb out of the prefix, such as
+    /// `../x`, still fails with [`Error::PathEscape`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// for name in ["foo", "bar"] {
+    ///     Driver::new("/srv/unpacked")?
+    ///         .prefix(name)?
+    ///         .extract_zip_file(format!("{}.zip", name))?;
+    /// }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn prefix<P: AsRef<Path>>(mut self, prefix: P) -> Result<Self, Error> {
+        let prefix = prefix.as_ref();
+        self.prefix = relative_dir(prefix).map_err(|reason| Error::InvalidOption {
+            option: "prefix".to_string(),
+            reason: format!("'{}' {}", prefix.display(), reason),
+        })?;
+        Ok(self)
+    }
+
+    /// Extract absolute entry names under `root`, keeping their structure:
+    /// with `root` set to `sysroot`, `/etc/hosts` is written to
+    /// `<dest>/sysroot/etc/hosts`. Relative names still go to the
+    /// destination itself. This takes precedence over
+    /// [`absolute_paths`](Self::absolute_paths).
+    ///
+    /// `root` is relative to the destination, or an absolute path inside
+    /// it; anything else fails with [`Error::InvalidOption`] when
+    /// extraction starts. Names under the root must stay inside it:
+    /// `/../etc/passwd` or `/etc/../../x` fail with [`Error::PathEscape`]
+    /// rather than landing elsewhere in the destination. This applies to
+    /// relative names that start with the root too.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "tar")]
+    /// # {
+    /// use safe_unzip::Driver;
+    ///
+    /// // /usr/bin/env -> /extract/sysroot/usr/bin/env
+    /// let report = Driver::new("/extract")?
+    ///     .absolute_root_map("sysroot")
+    ///     .extract_tar_file("rootfs.tar")?;
+    /// # }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn absolute_root_map<P: AsRef<Path>>(mut self, root: P) -> Self {
+        self.absolute_root = Some(root.as_ref().to_path_buf());
+        self
+    }
+
+    /// The [`absolute_root_map`](Self::absolute_root_map) directory as a
+    /// `/`-separated name relative to the destination; `None` if unset or
+    /// the destination itself.
+    fn absolute_root(&self, destination: &Path) -> Result<Option<String>, Error> {
+        let Some(ref root) = self.absolute_root else {
+            return Ok(None);
+        };
+        let invalid = |reason: &str| Error::InvalidOption {
+            option: "absolute_root_map".to_string(),
+            reason: format!("'{}' {}", root.display(), reason),
+        };
+
+        let relative = if root.is_absolute() {
+            root.strip_prefix(destination)
+                .map_err(|_| invalid("is not inside the destination"))?
+        } else {
+            root.as_path()
+        };
+        relative_dir(relative).map_err(invalid)
+    }
+
+    /// Set what happens to TAR device nodes, fifos, and entries of unknown
+    /// type. Default: [`UnsupportedPolicy::Abort`], which fails the whole
+    /// extraction with [`Error::UnsupportedEntryType`].
+    ///
+    /// With [`UnsupportedPolicy::Skip`] such entries are never created;
+    /// they are counted in `entries_skipped` and listed with their type in
+    /// the report's `skipped_unsupported`, and the remaining entries are
+    /// extracted as usual.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "tar")]
+    /// # {
+    /// use safe_unzip::{Driver, UnsupportedPolicy};
+    ///
+    /// let report = Driver::new("/tmp/rootfs")?
+    ///     .unsupported_entries(UnsupportedPolicy::Skip)
+    ///     .extract_tar_file("rootfs.tar")?;
+    /// for entry in &report.skipped_unsupported {
+    ///     println!("skipped {} ({})", entry.name, entry.entry_type);
+    /// }
+    /// # }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn unsupported_entries(mut self, policy: UnsupportedPolicy) -> Self {
+        self.unsupported = policy;
+        self
+    }
+
+    /// Check for entries whose names differ only in case, such as `README`
+    /// and `readme`, and handle the later one with `policy`. Default: not
+    /// checked.
+    ///
+    /// Both extract fine on Linux, but on a case-insensitive filesystem
+    /// (the macOS and Windows defaults) the second overwrites the first,
+    /// or fails under [`OverwriteMode::Error`] with an error that blames
+    /// the destination. Files and symlinks are compared by their full
+    /// path, lowercased, after normalization; directories are not, since
+    /// two spellings of one directory just merge. Entries removed by the
+    /// filter or an observer don't count.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{CaseCollisionPolicy, Driver};
+    ///
+    /// let report = Driver::new("/Users/me/out")?
+    ///     .case_collisions(CaseCollisionPolicy::SuffixSecond)
+    ///     .extract_zip_file("linux-build.zip")?;
+    /// for entry in &report.renamed_entries {
+    ///     println!("{} extracted as {}", entry.name, entry.renamed_to);
+    /// }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn case_collisions(mut self, policy: CaseCollisionPolicy) -> Self {
+        self.case_collisions = Some(policy);
+        self
+    }
+
+    /// Fail with [`Error::CaseCollision`] on names that differ only in
+    /// case: shorthand for
+    /// [`case_collisions(CaseCollisionPolicy::Error)`](Self::case_collisions),
+    /// and `false` turns the check off again. Default: off.
+    ///
+    /// Under [`ValidationMode::ValidateFirst`] collisions are found while
+    /// validating, before anything is written. Entries the filter skips
+    /// don't count; entries an observer vetoes are only known to be
+    /// skipped during extraction, so validation counts them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, ValidationMode};
+    ///
+    /// // Built on Linux, deployed to macOS
+    /// let report = Driver::new("/tmp/artifacts")?
+    ///     .validation(ValidationMode::ValidateFirst)
+    ///     .reject_case_collisions(true)
+    ///     .extract_zip_file("build.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn reject_case_collisions(mut self, reject: bool) -> Self {
+        self.case_collisions = reject.then_some(CaseCollisionPolicy::Error);
+        self
+    }
+
+    /// Fail with [`Error::DuplicateEntry`] when a file or symlink name
+    /// appears a second time in the archive. Default: off.
+    ///
+    /// Without this the second entry overwrites the first, or fails under
+    /// [`OverwriteMode::Error`] with [`Error::AlreadyExists`], blaming the
+    /// destination for what is a property of the archive. Names are
+    /// compared after normalization, so `a.txt` and `./a.txt` are the same
+    /// entry. Every entry counts, including ones the filter skips, so
+    /// [`ValidationMode::ValidateFirst`] rejects the archive before
+    /// anything is written. Repeated directory entries are allowed.
+    ///
+    /// A ZIP read through [`ZipAdapter`] only shows the last of several
+    /// entries with exactly the same name; [`Driver::extract_zip`] fails
+    /// on those before extracting anything, in either mode.
+    pub fn reject_duplicate_entries(mut self, reject: bool) -> Self {
+        self.reject_duplicate_entries = reject;
+        self
+    }
+
+    /// Recreate only the directory tree, without writing file contents.
+    ///
+    /// Directory entries are created with their stored modes, as are the
+    /// parent directories of files. Files and symlinks are validated as
+    /// usual but counted in `entries_skipped` instead of being written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// let report = Driver::new("/tmp/scaffold")?
+    ///     .structure_only(true)
+    ///     .extract_zip_file("template.zip")?;
+    /// assert_eq!(report.files_extracted, 0);
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn structure_only(mut self, enabled: bool) -> Self {
+        self.structure_only = enabled;
+        self
+    }
+
+    /// Decide the mode applied to each entry (Unix only; no-op elsewhere).
+    ///
+    /// The callback receives the entry and its stored mode, before any
+    /// stripping, and returns the mode to apply. setuid, setgid, and sticky
+    /// bits are still stripped from the result, and the
+    /// [`permission_mode`](Self::permission_mode) applies to it afterwards.
+    /// Entries without a stored mode (e.g. 7z) are not passed to the
+    /// callback.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// // Only scripts under bin/ stay executable
+    /// let report = Driver::new("/tmp/out")?
+    ///     .permissions(|entry, mode| {
+    ///         if entry.name.starts_with("bin/") {
+    ///             mode
+    ///         } else {
+    ///             mode & !0o111
+    ///         }
+    ///     })
+    ///     .extract_zip_file("archive.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn permissions<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&EntryInfo, u32) -> u32 + Send + Sync + 'static,
+    {
+        self.permissions = Some(Box::new(f));
+        self
+    }
+
+    /// Choose where the modes of extracted files and directories come from
+    /// (Unix only). Default: [`PermissionMode::FromArchive`].
+    ///
+    /// [`PermissionMode::Mask`] ANDs each stored mode (or the result of a
+    /// [`permissions`](Self::permissions) callback) with a mask, and
+    /// [`PermissionMode::Fixed`] ignores the archive altogether, including
+    /// for formats like 7z that store no modes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, PermissionMode};
+    ///
+    /// // Uploaded files are only ever readable by the service
+    /// let report = Driver::new("/srv/uploads")?
+    ///     .permission_mode(PermissionMode::Fixed {
+    ///         file: 0o600,
+    ///         dir: 0o700,
+    ///     })
+    ///     .extract_zip_file("upload.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn permission_mode(mut self, mode: PermissionMode) -> Self {
+        self.permission_mode = mode;
+        self
+    }
+
+    /// Set the extended attributes TAR entries carry in `SCHILY.xattr.*`
+    /// PAX records on the files extracted from them (Unix only; no-op
+    /// elsewhere). Default: off.
+    ///
+    /// Attributes are applied as stored, in every namespace the process
+    /// may write. Only enable this for archives you would also trust to
+    /// run as the extracting user: `security.capability` grants file
+    /// capabilities much like a setuid bit, which this crate otherwise
+    /// always strips, and `security.selinux` relabels files. Filesystems
+    /// without xattr support are skipped silently; any other failure,
+    /// such as lacking the privilege for a `trusted.*` or `security.*`
+    /// attribute, aborts extraction.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// // Unpack a root filesystem image with its SELinux labels
+    /// let report = Driver::new("/srv/rootfs")?
+    ///     .restore_xattrs(true)
+    ///     .extract_tar_file("rootfs.tar")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    #[cfg(feature = "xattr")]
+    pub fn restore_xattrs(mut self, enabled: bool) -> Self {
+        self.restore_xattrs = enabled;
+        self
+    }
+
+    /// Register an observer for per-entry hooks.
+    ///
+    /// May be called more than once; observers run in registration order.
+    /// Any observer returning [`EntryAction::Skip`] from `before_entry`
+    /// skips the entry. See [`ExtractionObserver`] for an example.
+    pub fn observe<O: ExtractionObserver + 'static>(mut self, observer: O) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    /// Check the raw archive bytes before anything is extracted.
+    ///
+    /// `f` receives the complete archive and returns `Ok(())` to proceed.
+    /// Any error it returns aborts extraction as
+    /// [`Error::SignatureInvalid`]. The crate does no cryptography itself;
+    /// plug in whichever verifier signs your bundles.
+    ///
+    /// Only sources the driver reads itself can be verified:
+    /// `extract_*_file` and `extract_7z_bytes`. The archive is read into
+    /// memory once and the verifier and extraction see the same bytes, so
+    /// the file cannot be swapped in between. The adapter-based methods
+    /// (`extract_zip`, `extract_tar`, `extract_7z`) fail with
+    /// [`Error::InvalidOption`] while a verifier is set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, Error};
+    ///
+    /// # fn verify_detached(data: &[u8], sig: &[u8]) -> bool { true }
+    /// let signature = std::fs::read("bundle.zip.sig")?;
+    /// let report = Driver::new("/srv/app")?
+    ///     .verify_signature(move |data| {
+    ///         if verify_detached(data, &signature) {
+    ///             Ok(())
+    ///         } else {
+    ///             Err(Error::SignatureInvalid {
+    ///                 reason: "signature does not match".into(),
+    ///             })
+    ///         }
+    ///     })
+    ///     .extract_zip_file("bundle.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn verify_signature<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&[u8]) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        self.verifier = Some(Box::new(f));
+        self
+    }
+
+    /// Hash the whole archive file while extracting it and report the
+    /// digest as [`ExtractionReport::source_digest`].
+    ///
+    /// TAR, `.tar.gz`, and `.gz` files are hashed as they stream through
+    /// the extractor; any bytes it never reads, such as padding after the
+    /// end-of-archive marker, are hashed afterwards so the digest always
+    /// covers the complete file. ZIP and 7z need random access, so they are
+    /// read into memory once through the hasher and extracted from that
+    /// buffer, which also guarantees the digest matches the bytes that
+    /// were extracted. The `max_compressed_bytes` limit bounds that buffer.
+    ///
+    /// Only the path-based methods (`extract_zip_file`, `extract_tar_file`,
+    /// `extract_file`, ...) hash their input; adapters are opened by the
+    /// caller, so the driver never sees their raw bytes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{DigestAlgorithm, Driver};
+    ///
+    /// let report = Driver::new("/srv/app")?
+    ///     .hash_source(DigestAlgorithm::Sha256)
+    ///     .extract_zip_file("bundle.zip")?;
+    /// println!("extracted bundle.zip sha256:{}", report.source_digest.unwrap());
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn hash_source(mut self, algorithm: DigestAlgorithm) -> Self {
+        self.hash_source = Some(algorithm);
+        self
+    }
+
+    /// Skip archives whose digest `store` already holds, and record the
+    /// digest of every archive extracted successfully.
+    ///
+    /// The digest is the one from [`hash_source`](Self::hash_source),
+    /// which this turns on with SHA-256 unless an algorithm was already
+    /// chosen. A skipped archive returns a report with
+    /// [`skipped_duplicate_archive`](ExtractionReport::skipped_duplicate_archive)
+    /// set and nothing written. TAR and `.gz` files are hashed once up
+    /// front to decide, then again as they are extracted; the digest
+    /// recorded is the one of the bytes actually extracted.
+    ///
+    /// Like `hash_source`, this only applies to the path-based methods.
+    /// Use an `Arc` around the store to share it between drivers.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, MemoryDedupeStore};
+    /// use std::sync::Arc;
+    ///
+    /// let seen = Arc::new(MemoryDedupeStore::new());
+    /// for delivery in ["batch.zip", "batch.zip"] {
+    ///     let report = Driver::new("/srv/inbox")?
+    ///         .overwrite(safe_unzip::OverwriteMode::Overwrite)
+    ///         .dedupe_store(Arc::clone(&seen))
+    ///         .extract_zip_file(delivery)?;
+    ///     if report.skipped_duplicate_archive {
+    ///         println!("{} already processed", delivery);
+    ///     }
+    /// }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn dedupe_store<S: DedupeStore + 'static>(mut self, store: S) -> Self {
+        self.hash_source.get_or_insert(DigestAlgorithm::Sha256);
+        self.dedupe = Some(Box::new(store));
+        self
+    }
+
+    /// Write entries to `filesystem` instead of the local disk.
+    ///
+    /// Entries go through the same policies and limits; only the writes
+    /// change. The destination must still exist locally, since entry
+    /// names are checked against it, and the backend is handed paths
+    /// under it. Options that have to look at or flush what was written
+    /// fail with [`Error::InvalidOption`] when a backend is set:
+    /// [`durability`](Self::durability) other than `None`,
+    /// [`OverwriteMode::ErrorIfDifferent`],
+    /// [`prune_empty_dirs`](Self::prune_empty_dirs), restoring xattrs, and
+    /// creating symlinks, whose containment is checked by resolving them
+    /// on disk.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, StdFileSystem};
+    ///
+    /// // Same as the default, but any FileSystem can take its place
+    /// let report = Driver::new("/srv/app")?
+    ///     .filesystem(StdFileSystem)
+    ///     .extract_zip_file("bundle.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn filesystem<F: FileSystem + 'static>(mut self, filesystem: F) -> Self {
+        self.filesystem = Some(Box::new(filesystem));
+        self
+    }
+
+    /// The storage entries are written to.
+    fn fs(&self) -> &dyn FileSystem {
+        self.filesystem.as_deref().unwrap_or(&StdFileSystem)
+    }
+
+    /// Reject options a custom [`filesystem`](Self::filesystem) can't
+    /// honour.
+    fn check_filesystem(&self) -> Result<(), Error> {
+        if self.filesystem.is_none() {
+            return Ok(());
+        }
+        let local_only = if self.durability != Durability::None {
+            Some("durability")
+        } else if self.overwrite == OverwriteMode::ErrorIfDifferent {
+            Some("OverwriteMode::ErrorIfDifferent")
+        } else if self.prune_empty_dirs {
+            Some("prune_empty_dirs")
+        } else if self.cleanup_on_error {
+            Some("cleanup_on_error")
+        } else if self.completion_marker.is_some() {
+            Some("completion_marker")
+        } else if self.creates_symlinks() {
+            Some("symlinks")
+        } else {
+            None
+        };
+        #[cfg(feature = "xattr")]
+        let local_only = local_only.or(self.restore_xattrs.then_some("restore_xattrs"));
+        match local_only {
+            Some(option) => Err(Error::InvalidOption {
+                option: option.to_string(),
+                reason: "requires the local filesystem, but a custom filesystem is set".to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Only extract files whose content matches one of `allowed`.
+    ///
+    /// Each file's type is sniffed from the magic bytes at the start of its
+    /// decompressed data (see [`ContentCategory::sniff`]), so a renamed
+    /// executable cannot slip past as `photo.png`. Files that don't match,
+    /// including empty and unrecognised binary files, fail with
+    /// [`Error::DisallowedContent`] before anything is written for them.
+    /// Directories and symlinks are not affected.
+    ///
+    /// Cost: ZIP and streaming TAR decode only the first kilobyte of each
+    /// file for the check. 7z must decode each solid block in full before
+    /// any of its files can be checked, so a disallowed file late in a
+    /// large block is only rejected after the whole block is decoded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{ContentCategory, Driver};
+    ///
+    /// let report = Driver::new("/srv/uploads")?
+    ///     .allow_content_types(&[ContentCategory::Image])
+    ///     .extract_zip_file("photos.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn allow_content_types(mut self, allowed: &[ContentCategory]) -> Self {
+        self.content_types = Some(allowed.to_vec());
+        self
+    }
+
+    /// Write an index of extracted files to `path` once extraction succeeds.
+    ///
+    /// Each file is listed with its archive path, size, mode, and SHA-256
+    /// digest, in the given format ([`JsonIndex`](crate::JsonIndex),
+    /// [`CsvIndex`](crate::CsvIndex), or your own [`IndexFormat`]). Nothing
+    /// is written if extraction fails. With [`Self::extract_many`], one index
+    /// covers all archives. Each extraction writes its own index, so
+    /// extractions running at once on a shared driver replace each
+    /// other's.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{CsvIndex, Driver};
+    ///
+    /// let report = Driver::new("/srv/app")?
+    ///     .write_index("/srv/app.index.csv", CsvIndex)
+    ///     .extract_zip_file("release.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn write_index<P, F>(mut self, path: P, format: F) -> Self
+    where
+        P: AsRef<Path>,
+        F: IndexFormat + 'static,
+    {
+        self.index = Some(IndexWriter::new(
+            path.as_ref().to_path_buf(),
+            Box::new(format),
+        ));
+        self
+    }
+
+    /// Create a marker file at `path` once extraction has fully succeeded,
+    /// so consumers polling the destination know the tree is complete.
+    ///
+    /// The marker contains the [`ExtractionReport`] as JSON. Any marker
+    /// already at `path` is removed before the extraction writes anything,
+    /// and the new one is renamed into place after everything else,
+    /// including the index and `post_extract`, so it never exists while
+    /// extraction is in progress, not even partially written. An archive
+    /// entry that would land on `path` fails with
+    /// [`Error::InvalidFilename`]. Nothing is written if extraction fails.
+    ///
+    /// Written by the `extract_*` methods; [`open`](Self::open) and
+    /// [`extract_to_cas`](Self::extract_to_cas) ignore it. Requires the
+    /// local filesystem.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// let report = Driver::new("/srv/app")?
+    ///     .completion_marker("/srv/app/.extraction-complete")
+    ///     .extract_zip_file("release.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn completion_marker<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.completion_marker = Some(CompletionMarker::new(path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// After a successful extraction, remove directories it created that
+    /// are still empty, e.g. directory entries whose files were all
+    /// filtered out. Default: `false`.
+    ///
+    /// Only directories that did not exist beforehand are candidates, so
+    /// empty directories already in the destination are left alone.
+    /// Removal runs deepest first, so a tree that is hollow all the way
+    /// down disappears entirely. The count is reported as
+    /// [`ExtractionReport::dirs_pruned`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, EntryKind};
+    ///
+    /// let report = Driver::new("/srv/docs")?
+    ///     .filter(|entry| entry.kind != EntryKind::File || entry.name.ends_with(".md"))
+    ///     .prune_empty_dirs(true)
+    ///     .extract_zip_file("repo.zip")?;
+    /// println!("pruned {} empty directories", report.dirs_pruned);
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn prune_empty_dirs(mut self, prune: bool) -> Self {
+        self.prune_empty_dirs = prune;
+        self
+    }
+
+    /// Remove what a failed extraction created before returning its error.
+    /// Default: `false`.
+    ///
+    /// Every file, directory, and symlink the extraction creates is
+    /// recorded, and if any entry fails (a limit, a policy, or a corrupt
+    /// entry found only while decompressing) they are removed, deepest
+    /// first. Anything that existed beforehand is left alone, including
+    /// files an [`OverwriteMode`] replaced, whose old contents are gone. A
+    /// directory that something else wrote into meanwhile is kept.
+    ///
+    /// Removal is best effort; the extraction's error is returned either
+    /// way. Fails with [`Error::InvalidOption`] with a custom
+    /// [`filesystem`](Self::filesystem), which cannot remove anything.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// // A corrupt entry leaves the destination as it was
+    /// let result = Driver::new("/srv/app")?
+    ///     .cleanup_on_error(true)
+    ///     .extract_zip_file("release.zip");
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn cleanup_on_error(mut self, enabled: bool) -> Self {
+        self.cleanup_on_error = enabled;
+        self
+    }
+
+    /// Record the path of every file, directory, and symlink written in
+    /// [`ExtractionReport::extracted_paths`], in extraction order. Entries
+    /// that are skipped for any reason, including files left in place
+    /// because they are identical under
+    /// [`OverwriteMode::ErrorIfDifferent`], are not listed. Default:
+    /// `false`, so large archives don't pay for the list unless it is
+    /// wanted.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// let report = Driver::new("/srv/uploads")?
+    ///     .collect_paths(true)
+    ///     .extract_zip_file("upload.zip")?;
+    /// // Scan exactly what this upload added
+    /// let new_files: Vec<_> = report
+    ///     .extracted_paths
+    ///     .iter()
+    ///     .filter(|path| path.is_file())
+    ///     .collect();
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn collect_paths(mut self, collect: bool) -> Self {
+        self.collect_paths = collect;
+        self
+    }
+
+    /// Bound how many output files are open at the same time.
+    ///
+    /// A single extraction writes one entry at a time, so it never holds
+    /// more than one output file and this changes nothing on its own. The
+    /// bound applies across threads sharing one driver (it is `Sync`):
+    /// each thread waits for a free slot before opening an output file and
+    /// releases it once the entry is written, so at most `max` threads are
+    /// writing at once however many are extracting. There is no built-in
+    /// parallel mode; size the thread pool as you like and let this cap
+    /// the file descriptors it uses. Covers
+    /// [`extract_to_cas`](Self::extract_to_cas) as well. What an
+    /// extraction tracks for [`cleanup_on_error`](Self::cleanup_on_error),
+    /// [`prune_empty_dirs`](Self::prune_empty_dirs), and
+    /// [`write_index`](Self::write_index) is kept per call, so those
+    /// options work on a shared driver too.
+    ///
+    /// Zero is rejected with [`Error::InvalidOption`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, ZipAdapter};
+    /// use std::sync::Arc;
+    /// use std::thread;
+    ///
+    /// let driver = Arc::new(Driver::new("/srv/uploads")?.max_open_files(4));
+    /// let workers: Vec<_> = ["a.zip", "b.zip", "c.zip", "d.zip", "e.zip", "f.zip"]
+    ///     .into_iter()
+    ///     .map(|name| {
+    ///         let driver = Arc::clone(&driver);
+    ///         thread::spawn(move || driver.extract_zip(ZipAdapter::open(name)?))
+    ///     })
+    ///     .collect();
+    /// for worker in workers {
+    ///     worker.join().unwrap()?;
+    /// }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn max_open_files(mut self, max: usize) -> Self {
+        self.open_files = Some(FileSlots::new(max));
+        self
+    }
+
+    /// Call `callback` before each entry is processed, filtered or not.
+    ///
+    /// `total_entries` is the number of entries in the archive when it is
+    /// known up front: always for ZIP and 7z, and for TAR in
+    /// [`ValidationMode::ValidateFirst`], which reads the whole archive
+    /// first. Streaming TAR extraction cannot know it and reports `0`.
+    /// `bytes_written` and `files_extracted` are cumulative, across
+    /// archives too with [`extract_many`](Self::extract_many).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "tar")]
+    /// # {
+    /// use safe_unzip::Driver;
+    ///
+    /// let report = Driver::new("/tmp/out")?
+    ///     .on_progress(|p| {
+    ///         println!("[{}] {} ({} bytes so far)", p.entry_index + 1, p.entry_name, p.bytes_written);
+    ///     })
+    ///     .extract_tar_gz_file("backup.tar.gz")?;
+    /// # }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&Progress) + Send + Sync + 'static,
+    {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Run `f` with the destination path once extraction has succeeded.
+    ///
+    /// Not called if extraction fails. Useful for sealing the result:
+    /// making the tree read-only, setting immutable flags, or taking a
+    /// snapshot.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    /// use std::process::Command;
+    ///
+    /// let report = Driver::new("/srv/releases/v42")?
+    ///     .post_extract(|dest| {
+    ///         let _ = Command::new("chattr").arg("-R").arg("+i").arg(dest).status();
+    ///     })
+    ///     .extract_zip_file("release.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn post_extract<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Path) + Send + Sync + 'static,
+    {
+        self.post_extract = Some(Box::new(f));
+        self
+    }
+
+    /// Set entry filter.
+    ///
+    /// Filters compose: calling this again, or adding names, globs, or
+    /// regexes, narrows the selection, and an entry is extracted only if
+    /// every filter accepts it.
+    pub fn filter<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&EntryInfo) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(match self.filter.take() {
+            Some(previous) => Box::new(move |entry| previous(entry) && f(entry)),
+            None => Box::new(f),
+        });
+        self
+    }
+
+    /// Extract only specific files by exact name.
+    ///
+    /// Names are matched exactly (case-sensitive).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// let report = Driver::new("/tmp/out")?
+    ///     .only(&["README.md", "LICENSE"])
+    ///     .extract_zip_file("archive.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn only<S: AsRef<str>>(self, names: &[S]) -> Self {
+        let names: Vec<String> = names.iter().map(|s| s.as_ref().to_string()).collect();
+        self.filter(move |entry| names.iter().any(|n| n == &entry.name))
+    }
+
+    /// Include only files matching glob patterns.
+    ///
+    /// Patterns: `*` matches except `/`, `**` matches including `/`, `?` matches one char.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// // Extract only Rust source files
+    /// let report = Driver::new("/tmp/out")?
+    ///     .include_glob(&["**/*.rs"])
+    ///     .extract_zip_file("archive.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn include_glob<S: AsRef<str>>(self, patterns: &[S]) -> Self {
+        let patterns: Vec<String> = patterns.iter().map(|s| s.as_ref().to_string()).collect();
+        self.filter(move |entry| {
+            patterns
+                .iter()
+                .any(|p| glob_match::glob_match(p, &entry.name))
+        })
+    }
+
+    /// Exclude files matching glob patterns.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// // Extract everything except tests
+    /// let report = Driver::new("/tmp/out")?
+    ///     .exclude_glob(&["**/test_*", "**/tests/**"])
+    ///     .extract_zip_file("archive.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn exclude_glob<S: AsRef<str>>(self, patterns: &[S]) -> Self {
+        let patterns: Vec<String> = patterns.iter().map(|s| s.as_ref().to_string()).collect();
+        self.filter(move |entry| {
+            !patterns
+                .iter()
+                .any(|p| glob_match::glob_match(p, &entry.name))
+        })
+    }
+
+    /// Include only files whose path matches one of the regular
+    /// expressions.
+    ///
+    /// For selections globs can't express. Patterns use the
+    /// [`regex`](https://docs.rs/regex) syntax and match anywhere in the
+    /// path unless anchored with `^` and `$`. An invalid pattern fails the
+    /// extraction with [`Error::InvalidOption`] before anything is read.
+    ///
+    /// Requires the `regex` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// // Extract only test modules
+    /// let report = Driver::new("/tmp/out")?
+    ///     .include_regex(&[r"^src/.*_test\.rs$"])
+    ///     .extract_zip_file("archive.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn include_regex<S: AsRef<str>>(self, patterns: &[S]) -> Self {
+        self.regex_filter("include_regex", patterns, true)
+    }
+
+    /// Exclude files whose path matches any of the regular expressions.
+    ///
+    /// Same syntax and error handling as
+    /// [`include_regex`](Self::include_regex). Requires the `regex` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// // Skip generated files and editor backups
+    /// let report = Driver::new("/tmp/out")?
+    ///     .exclude_regex(&[r"\.generated\.", r"~$"])
+    ///     .extract_zip_file("archive.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn exclude_regex<S: AsRef<str>>(self, patterns: &[S]) -> Self {
+        self.regex_filter("exclude_regex", patterns, false)
+    }
+
+    /// Add a filter passing entries whose match against `patterns` equals
+    /// `include`, or remember why the patterns don't compile.
+    #[cfg(feature = "regex")]
+    fn regex_filter<S: AsRef<str>>(mut self, option: &str, patterns: &[S], include: bool) -> Self {
+        match regex::RegexSet::new(patterns.iter().map(|p| p.as_ref())) {
+            Ok(set) => self.filter(move |entry| set.is_match(&entry.name) == include),
+            Err(e) => {
+                self.invalid_option
+                    .get_or_insert_with(|| (option.to_string(), e.to_string()));
+                self
+            }
+        }
+    }
+
+    fn size_policy(&self) -> SizePolicy {
+        SizePolicy::new(self.limits.max_single_file, self.limits.max_total_bytes)
+            .unknown_size(self.unknown_size)
+            .max_compression_ratio(self.limits.max_compression_ratio)
+    }
+
+    fn depth_policy(&self, destination: &Path) -> Result<DepthPolicy, Error> {
+        let policy = DepthPolicy::new(self.limits.max_path_depth);
+        if self.depth_includes_destination {
+            policy.include_destination(destination)
+        } else {
+            Ok(policy)
+        }
+    }
+
+    /// Build the policy chain from current settings, for entries written
+    /// under `destination`.
+    fn build_policies(&self, destination: &Path) -> Result<PolicyChain, Error> {
+        #[cfg(feature = "regex")]
+        if let Some((ref option, ref reason)) = self.invalid_option {
+            return Err(Error::InvalidOption {
+                option: option.clone(),
+                reason: reason.clone(),
+            });
+        }
+        if self
+            .open_files
+            .as_ref()
+            .is_some_and(|slots| slots.limit == 0)
+        {
+            return Err(Error::InvalidOption {
+                option: "max_open_files".to_string(),
+                reason: "at least one file must be allowed open".to_string(),
+            });
+        }
+        self.check_filesystem()?;
+        let mut path_policy = PathPolicy::new(destination)?
+            .max_absolute_path(self.limits.max_absolute_path)
+            .reject_deceptive_unicode(self.reject_deceptive_unicode)
+            .windows_name_check(self.windows_name_check)
+            .absolute_paths(self.absolute_paths);
+        if let Some(root) = self.absolute_root(destination)? {
+            path_policy = path_policy.absolute_root(root);
+        }
+        let mut policies = PolicyChain::new()
+            .with(path_policy)
+            .with(self.size_policy())
+            .with(CountPolicy::new(self.limits.max_file_count))
+            .with(DirCountPolicy::new(self.limits.max_dir_count))
+            .with(self.depth_policy(destination)?)
+            .with(SymlinkPolicy::new(self.symlinks));
+        if let Some(marker) = self.completion_marker.as_ref() {
+            if let Some(policy) = marker.policy(destination) {
+                policies = policies.with(policy);
+            }
+        }
+        Ok(policies)
+    }
+
+    /// Where this run writes entries: `state`'s destination if it has one,
+    /// the driver's otherwise.
+    fn destination<'a>(&'a self, state: &'a ExtractionState) -> &'a Path {
+        state.destination.as_deref().unwrap_or(&self.destination)
+    }
+
+    /// Policies for an extraction about to write to the destination. A
+    /// completion marker from an earlier run is removed first.
+    fn begin_extraction(&self, state: &ExtractionState) -> Result<PolicyChain, Error> {
+        let policies = self.build_policies(self.destination(state))?;
+        if self.ensure_free_space && self.validation == ValidationMode::Streaming {
+            return Err(Error::InvalidOption {
+                option: "ensure_free_space".to_string(),
+                reason: "needs ValidationMode::ValidateFirst to know the total size before \
+                         writing"
+                    .to_string(),
+            });
+        }
+        if let Some(ref marker) = self.completion_marker {
+            marker.clear()?;
+        }
+        Ok(policies)
+    }
+
+    /// Extract a ZIP archive.
+    ///
+    /// Fails with [`Error::InvalidOption`] if
+    /// [`verify_signature`](Self::verify_signature) is set, since the raw
+    /// archive bytes are not available from an adapter.
+    pub fn extract_zip<R: Read + Seek>(
+        &self,
+        adapter: ZipAdapter<R>,
+    ) -> Result<ExtractionReport, Error> {
+        self.reject_unverifiable("extract_zip")?;
+        let mut state = ExtractionState::default();
+        let result = self.extract_zip_adapter(adapter, &mut state);
+        self.finish(result, state)
+    }
+
+    /// Extract a ZIP archive, continuing from `state`.
+    fn extract_zip_adapter<R: Read + Seek>(
+        &self,
+        mut adapter: ZipAdapter<R>,
+        state: &mut ExtractionState,
+    ) -> Result<ExtractionReport, Error> {
+        self.limits.check_compressed(adapter.archive_len())?;
+        self.limits.check_entry_count(adapter.len())?;
+        if let Some(entry) = adapter
+            .repeated_name()
+            .filter(|_| self.reject_duplicate_entries)
+        {
+            return Err(Error::DuplicateEntry {
+                entry: entry.to_string(),
+            });
+        }
+        let policies = self.begin_extraction(state)?;
+
+        // ValidateFirst mode: check all entries before extracting
+        if self.validation != ValidationMode::Streaming {
+            self.validate_all_zip(&mut adapter, &policies, state)?;
+        }
+        if self.validation == ValidationMode::ValidateFirstDeep {
+            self.verify_all_zip(&mut adapter)?;
+        }
+
+        let mut indices: Vec<usize> = (0..adapter.len()).collect();
+        if self.order == Order::SortedByName {
+            indices.sort_by(|&a, &b| adapter.entry_name(a).cmp(&adapter.entry_name(b)));
+        }
+
+        let total = adapter.len();
+        for (position, i) in indices.into_iter().enumerate() {
+            self.interrupt.check()?;
+            if self.on_progress.is_some() {
+                self.progress(&adapter.entry_info(i)?, position, total, state);
+            }
+            self.extract_zip_entry(&mut adapter, i, &policies, state)?;
+        }
+        Ok(ExtractionReport::from_state(
+            state,
+            adapter.is_empty(),
+            adapter.bytes_read(),
+        ))
+    }
+
+    /// Check entry metadata against the configured limits and policies
+    /// without extracting anything.
+    pub(crate) fn validate_entries(&self, entries: &[EntryInfo]) -> Result<(), Error> {
+        self.limits.check_entry_count(entries.len())?;
+        let policies = self.build_policies(&self.destination)?;
+        let mut state = ExtractionState::default();
+
+        for info in entries {
+            let Some(info) = self.normalized(info, &state) else {
+                continue;
+            };
+            self.check_duplicate(&info, &mut state)?;
+            self.check_case_ahead(&info, &mut state)?;
+            policies.check_all(&info, &state)?;
+            match info.kind {
+                EntryKind::File => {
+                    state.bytes_written += self.size_policy().accounted_size(&info);
+                    state.files_extracted += 1;
+                }
+                EntryKind::Directory => state.dirs_created += 1,
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate all entries without extracting.
+    fn validate_all_zip<R: Read + Seek>(
+        &self,
+        adapter: &mut ZipAdapter<R>,
+        policies: &PolicyChain,
+        state: &ExtractionState,
+    ) -> Result<(), Error> {
+        let entries = adapter.entries_metadata()?;
+        let mut state = state.clone();
+        let before = state.bytes_written;
+
+        for info in &entries {
+            let Some(info) = self.normalized(info, &state) else {
+                continue;
+            };
+            self.check_duplicate(&info, &mut state)?;
+            self.check_case_ahead(&info, &mut state)?;
+            policies.check_all(&info, &state)?;
+
+            // Update state for cumulative checks
+            match info.kind {
+                EntryKind::File => {
+                    state.bytes_written += self.size_policy().accounted_size(&info);
+                    state.files_extracted += 1;
+                }
+                EntryKind::Directory => state.dirs_created += 1,
+                _ => {}
+            }
+        }
+
+        self.check_space(state.bytes_written - before)
+    }
+
+    /// Decompress every entry into a sink, failing on the first bad CRC
+    /// or size.
+    fn verify_all_zip<R: Read + Seek>(&self, adapter: &mut ZipAdapter<R>) -> Result<(), Error> {
+        let mut decompress_time = Duration::ZERO;
+        for i in 0..adapter.len() {
+            self.interrupt.check()?;
+            let name = adapter.entry_name(i).unwrap_or_default().to_string();
+            adapter
+                .extract_to(
+                    i,
+                    &mut self
+                        .interrupt
+                        .writer(&mut std::io::sink(), &name, &mut decompress_time),
+                    self.limits.max_single_file,
+                )
+                .map_err(|e| self.interrupt.recover(e))?;
+        }
+        Ok(())
+    }
+
+    /// Extract a single ZIP entry.
     fn extract_zip_entry<R: Read + Seek>(
         &self,
-        adapter: &mut ZipAdapter<R>,
-        index: usize,
-        policies: &PolicyChain,
+        adapter: &mut ZipAdapter<R>,
+        index: usize,
+        policies: &PolicyChain,
+        state: &mut ExtractionState,
+    ) -> Result<(), Error> {
+        let info = adapter.entry_info(index)?;
+        let Some(info) = self.extracted_name(&info, state) else {
+            return Ok(());
+        };
+        self.check_duplicate(&info, state)?;
+
+        // Apply filter and observer veto
+        if self.skip_entry(&info, state) {
+            return Ok(());
+        }
+        let Some(info) = self.resolve_case(info, state)? else {
+            return Ok(());
+        };
+
+        // Check policies
+        policies.check_all(&info, state)?;
+
+        // Handle symlinks (skip by default, policy may error)
+        if matches!(info.kind, EntryKind::Symlink { .. }) {
+            if self.creates_symlinks() && !self.structure_only {
+                // The target is stored as the entry's content
+                let (link, _) = adapter.extract_to(index, &mut std::io::sink(), 0)?;
+                if let EntryKind::Symlink { target } = &link.kind {
+                    return self.create_symlink(&info, target, state);
+                }
+            }
+            self.skip(&info, SkipReason::Symlink, state);
+            return Ok(());
+        }
+
+        let safe_path = self.destination(state).join(&info.name);
+
+        // Extract based on entry type
+        match info.kind {
+            EntryKind::Directory => self.create_dir(&info, &safe_path, state)?,
+            EntryKind::File => {
+                if let Some(parent) = safe_path.parent() {
+                    self.make_dirs(parent, state)?;
+                }
+
+                if self.structure_only {
+                    self.skip(&info, SkipReason::StructureOnly, state);
+                    return Ok(());
+                }
+
+                if self.content_types.is_some() {
+                    let prefix = adapter.peek_entry(index, SNIFF_LEN)?;
+                    self.check_content(&info, &prefix)?;
+                }
+
+                let _slot = self.file_slot();
+                // Atomic file creation based on overwrite mode
+                let Some(mut output) = self.open_for_write(&info, &safe_path, state)? else {
+                    return Ok(()); // Skipped
+                };
+
+                let limit = self.limits.max_single_file.min(
+                    self.limits
+                        .max_total_bytes
+                        .saturating_sub(state.bytes_written),
+                );
+
+                let (_, written) = adapter
+                    .extract_to(
+                        index,
+                        &mut self.interrupt.writer(
+                            &mut output,
+                            &info.name,
+                            &mut state.decompress_time,
+                        ),
+                        limit,
+                    )
+                    .map_err(|e| self.interrupt.recover(e))?;
+
+                let Some(outfile) = self.written_file(&info, &safe_path, output, state)? else {
+                    return Ok(()); // Identical to the existing file
+                };
+
+                // Set permissions on Unix
+                self.apply_permissions(&info, &safe_path)?;
+
+                self.sync_written(&outfile, &safe_path)?;
+
+                state.bytes_written += written;
+                state.files_extracted += 1;
+                self.notify_extracted(&info, &safe_path, state);
+            }
+            EntryKind::Symlink { .. } => {
+                // Already handled above (skipped or errored by policy)
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create a symlink after checking that its target stays inside the
+    /// destination.
+    ///
+    /// The target is resolved from the link's real (canonical) parent
+    /// directory, so symlinks created by earlier entries cannot be used to
+    /// make an escaping target look contained.
+    fn create_symlink(
+        &self,
+        info: &EntryInfo,
+        target: &str,
+        state: &mut ExtractionState,
+    ) -> Result<(), Error> {
+        let name = info.name.as_str();
+        if target.is_empty() {
+            return Err(Error::InvalidFilename {
+                entry: name.to_string(),
+                reason: "empty symlink target".to_string(),
+            });
+        }
+
+        let destination = self.destination(state).to_path_buf();
+        let safe_path = destination.join(name);
+        let parent = safe_path.parent().unwrap_or(&destination);
+        self.make_dirs(parent, state)?;
+        self.track_new(&safe_path, state);
+
+        let root = fs::canonicalize(&destination)?;
+        let link_dir = fs::canonicalize(parent)?;
+
+        // Absolute targets may name the destination as configured rather
+        // than its canonical form (e.g. through a symlinked /tmp)
+        let target_path = match Path::new(target).strip_prefix(&destination) {
+            Ok(rest) if destination.is_absolute() => root.join(rest),
+            _ => PathBuf::from(target),
+        };
+
+        let resolved = resolve_link_target(&link_dir, &target_path)
+            .filter(|resolved| resolved.starts_with(&root));
+        let Some(resolved) = resolved else {
+            if self.symlinks == SymlinkBehavior::ContainedOrSkip {
+                state.skipped_symlinks.push(SkippedSymlink {
+                    name: name.to_string(),
+                    target: target.to_string(),
+                });
+                self.skip(info, SkipReason::SymlinkEscape, state);
+                return Ok(());
+            }
+            return Err(Error::PathEscape {
+                entry: name.to_string(),
+                detail: format!("symlink target '{}' escapes destination", target),
+            });
+        };
+
+        let link_target = if target_path.is_absolute()
+            && self.symlink_rewrite == SymlinkRewrite::RelativizeContained
+        {
+            relative_to(&link_dir, &resolved)
+        } else {
+            PathBuf::from(target)
+        };
+
+        // Same overwrite semantics as files; never follow what is there
+        if let Ok(existing) = fs::symlink_metadata(&safe_path) {
+            match self.overwrite {
+                OverwriteMode::Skip => {
+                    self.skip(info, SkipReason::AlreadyExists, state);
+                    return Ok(());
+                }
+                OverwriteMode::Overwrite if !existing.is_dir() => {
+                    fs::remove_file(&safe_path)?;
+                }
+                OverwriteMode::ErrorIfDifferent => {
+                    if existing.file_type().is_symlink()
+                        && fs::read_link(&safe_path)? == link_target
+                    {
+                        self.skip(info, SkipReason::AlreadyExists, state);
+                        return Ok(());
+                    }
+                    return Err(Error::ContentConflict {
+                        entry: safe_path.display().to_string(),
+                    });
+                }
+                _ => {
+                    return Err(Error::AlreadyExists {
+                        entry: safe_path.display().to_string(),
+                    });
+                }
+            }
+        }
+
+        #[cfg(unix)]
+        {
+            self.fs().symlink(&link_target, &safe_path)?;
+            state.symlinks_created += 1;
+            self.notify_extracted(info, &safe_path, state);
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = link_target;
+            self.skip(info, SkipReason::Symlink, state);
+        }
+
+        Ok(())
+    }
+
+    /// True if symlink entries are created rather than skipped.
+    fn creates_symlinks(&self) -> bool {
+        matches!(
+            self.symlinks,
+            SymlinkBehavior::AllowContained | SymlinkBehavior::ContainedOrSkip
+        )
+    }
+
+    /// Read an archive into memory and run the signature verifier on it.
+    fn read_verified(&self, path: &Path) -> Result<Vec<u8>, Error> {
+        let data = fs::read(path)?;
+        self.check_signature(&data)?;
+        Ok(data)
+    }
+
+    /// Run the signature verifier, if any, on the raw archive bytes.
+    fn check_signature(&self, data: &[u8]) -> Result<(), Error> {
+        let Some(ref verify) = self.verifier else {
+            return Ok(());
+        };
+        verify(data).map_err(|e| match e {
+            Error::SignatureInvalid { .. } => e,
+            other => Error::SignatureInvalid {
+                reason: other.to_string(),
+            },
+        })
+    }
+
+    /// Refuse adapter-based extraction when a signature must be verified.
+    fn reject_unverifiable(&self, method: &str) -> Result<(), Error> {
+        if self.verifier.is_some() {
+            return Err(Error::InvalidOption {
+                option: "verify_signature".to_string(),
+                reason: format!(
+                    "{} cannot see the raw archive bytes; use the _file or _bytes variant",
+                    method
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Complete an extraction: prune empty directories, write the index,
+    /// and run the `post_extract` hook, if configured, when it succeeded,
+    /// or remove what it created when it failed and cleanup is on.
+    fn finish(
+        &self,
+        result: Result<ExtractionReport, Error>,
+        state: ExtractionState,
+    ) -> Result<ExtractionReport, Error> {
+        let mut report = match result {
+            Ok(report) => report,
+            Err(e) => {
+                if self.cleanup_on_error {
+                    remove_created(state.created);
+                }
+                return Err(e);
+            }
+        };
+        if self.prune_empty_dirs {
+            report.dirs_pruned = prune_dirs(state.new_dirs)?;
+        }
+        if let Some(ref index) = self.index {
+            index.write(state.indexed)?;
+        }
+        if let Some(ref post_extract) = self.post_extract {
+            post_extract(&self.destination);
+        }
+        if let Some(ref marker) = self.completion_marker {
+            marker.write(&report)?;
+        }
+        Ok(report)
+    }
+
+    /// Create a directory entry (idempotent), remembering the directories
+    /// that are new if empty ones are to be pruned.
+    fn create_dir(
+        &self,
+        info: &EntryInfo,
+        path: &Path,
         state: &mut ExtractionState,
     ) -> Result<(), Error> {
-        let info = adapter.entry_info(index)?;
+        if self.prune_empty_dirs {
+            let destination = self.destination(state).to_path_buf();
+            let new = path
+                .ancestors()
+                .take_while(|dir| *dir != destination && fs::symlink_metadata(dir).is_err());
+            state.new_dirs.extend(new.map(Path::to_path_buf));
+        }
+        self.make_dirs(path, state)?;
+        self.apply_permissions(info, path)?;
+        state.dirs_created += 1;
+        self.notify_extracted(info, path, state);
+        Ok(())
+    }
 
-        // Apply filter
-        if let Some(ref filter) = self.filter {
-            if !filter(&info) {
-                state.entries_skipped += 1;
-                return Ok(());
+    /// Fail if `ensure_free_space` is set and the destination can't hold
+    /// `required` more bytes.
+    fn check_space(&self, required: u64) -> Result<(), Error> {
+        if !self.ensure_free_space || self.filesystem.is_some() {
+            return Ok(());
+        }
+        check_free_space(&self.destination, required)
+    }
+
+    /// Create `dir` and any missing parents.
+    fn make_dirs(&self, dir: &Path, state: &mut ExtractionState) -> Result<(), Error> {
+        self.track_new(dir, state);
+        self.fs().create_dir(dir)?;
+        Ok(())
+    }
+
+    /// Record `path` and its ancestors below the destination that don't
+    /// exist yet, if a failed extraction is to be cleaned up.
+    fn track_new(&self, path: &Path, state: &mut ExtractionState) {
+        if self.cleanup_on_error {
+            let destination = self.destination(state).to_path_buf();
+            let new = path
+                .ancestors()
+                .take_while(|p| *p != destination && fs::symlink_metadata(p).is_err());
+            state.created.extend(new.map(Path::to_path_buf));
+        }
+    }
+
+    /// The limit error for a file found to exceed `size` bytes while
+    /// writing.
+    #[cfg(feature = "tar")]
+    fn over_limit(&self, info: &EntryInfo, size: u64, state: &ExtractionState) -> Error {
+        if size > self.limits.max_single_file {
+            Error::FileTooLarge {
+                entry: info.name.clone(),
+                limit: self.limits.max_single_file,
+                size,
+            }
+        } else {
+            Error::TotalSizeExceeded {
+                limit: self.limits.max_total_bytes,
+                would_be: state.bytes_written + size,
             }
         }
+    }
 
-        // Check policies
-        policies.check_all(&info, state)?;
+    /// The entry as it will be extracted: with
+    /// [`AbsolutePathPolicy::Strip`], the root of an absolute name is
+    /// removed (and with [`absolute_root_map`](Self::absolute_root_map)
+    /// replaced by the mapped directory), a leading `./` is always
+    /// dropped, then [`strip_components`](Self::strip_components) are,
+    /// [`map_name`](Self::map_name) runs, and last the
+    /// [`prefix`](Self::prefix) is added, so filters, observers, and
+    /// policies all see the name that is actually written and depth is not
+    /// inflated by `.` components. `None` for a bare `./` directory (as in
+    /// `tar -cf x.tar .`), which is the destination itself and needs no
+    /// extraction, and for entries stripped to nothing or dropped by
+    /// `map_name`.
+    ///
+    /// This is the only place entry names are rewritten. Callers must run
+    /// the result through the policy chain before joining it onto the
+    /// destination: a rewrite can expose `..` (stripping `/` from
+    /// `/../x` leaves `../x`, and `map_name` can return anything), so
+    /// validating the original name proves nothing about the rewritten one.
+    fn normalized<'a>(
+        &self,
+        info: &'a EntryInfo,
+        state: &ExtractionState,
+    ) -> Option<Cow<'a, EntryInfo>> {
+        self.rewritten(info, state).ok()
+    }
 
-        // Handle symlinks (skip by default, policy may error)
-        if matches!(info.kind, EntryKind::Symlink { .. }) {
-            state.entries_skipped += 1;
-            return Ok(());
+    /// [`normalized`](Self::normalized), with why an entry has no name:
+    /// `Err(None)` for the destination itself, otherwise the reason it is
+    /// skipped.
+    fn rewritten<'a>(
+        &self,
+        info: &'a EntryInfo,
+        state: &ExtractionState,
+    ) -> Result<Cow<'a, EntryInfo>, Option<SkipReason>> {
+        let mut info = Cow::Borrowed(info);
+
+        if let Some(root) = absolute_root_len(&info.name) {
+            // An invalid mapping has already failed when the policies
+            // were built
+            if let Ok(Some(mapped)) = self.absolute_root(self.destination(state)) {
+                let name = format!("{}/{}", mapped, &info.name[root..]);
+                info.to_mut().name = name;
+            } else if self.absolute_paths == AbsolutePathPolicy::Strip {
+                info.to_mut().name.drain(..root);
+            }
+        }
+
+        let dots = cur_dir_prefix_len(&info.name);
+        if dots > 0 {
+            if dots == info.name.len() && info.kind == EntryKind::Directory {
+                return Err(None);
+            }
+            info.to_mut().name.drain(..dots);
         }
 
-        let safe_path = self.destination.join(&info.name);
+        if self.strip_components > 0 {
+            let stripped = strip_components(&info.name, self.strip_components)
+                .ok_or(Some(SkipReason::Stripped))?;
+            let removed = info.name.len() - stripped.len();
+            info.to_mut().name.drain(..removed);
+        }
 
-        // Extract based on entry type
-        match info.kind {
-            EntryKind::Directory => {
-                // For directories, just create (idempotent)
-                fs::create_dir_all(&safe_path)?;
-                state.dirs_created += 1;
+        if let Some(ref map) = self.map_name {
+            let name = map(&info.name).ok_or(Some(SkipReason::Filtered))?;
+            info.to_mut().name = name;
+        }
+
+        if let Some(ref prefix) = self.prefix {
+            if let Some(name) = prefixed(prefix, &info.name) {
+                info.to_mut().name = name;
             }
-            EntryKind::File => {
-                if let Some(parent) = safe_path.parent() {
-                    fs::create_dir_all(parent)?;
+        }
+        Ok(info)
+    }
+
+    /// [`normalized`](Self::normalized) for extraction: an entry stripped
+    /// to nothing or dropped by [`map_name`](Self::map_name) is reported
+    /// as skipped.
+    fn extracted_name<'a>(
+        &self,
+        info: &'a EntryInfo,
+        state: &mut ExtractionState,
+    ) -> Option<Cow<'a, EntryInfo>> {
+        match self.rewritten(info, state) {
+            Ok(info) => Some(info),
+            Err(reason) => {
+                if let Some(reason) = reason {
+                    self.skip(info, reason, state);
                 }
+                None
+            }
+        }
+    }
 
-                // Atomic file creation based on overwrite mode
-                let outfile = match self.overwrite {
-                    OverwriteMode::Error => {
-                        // create_new(true) is atomic: fails if file exists (no TOCTOU)
-                        match fs::OpenOptions::new()
-                            .write(true)
-                            .create_new(true)
-                            .open(&safe_path)
-                        {
-                            Ok(f) => f,
-                            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
-                                return Err(Error::AlreadyExists {
-                                    entry: safe_path.display().to_string(),
-                                });
-                            }
-                            Err(e) => return Err(e.into()),
-                        }
-                    }
-                    OverwriteMode::Skip => {
-                        // Try atomic create, skip on exists
-                        match fs::OpenOptions::new()
-                            .write(true)
-                            .create_new(true)
-                            .open(&safe_path)
-                        {
-                            Ok(f) => f,
-                            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
-                                state.entries_skipped += 1;
-                                return Ok(());
-                            }
-                            Err(e) => return Err(e.into()),
-                        }
-                    }
-                    OverwriteMode::Overwrite => {
-                        // SECURITY: Remove any existing symlink first to prevent following
-                        if let Ok(m) = fs::symlink_metadata(&safe_path) {
-                            if m.file_type().is_symlink() {
-                                let _ = fs::remove_file(&safe_path);
-                            }
-                        }
-                        // Now create/truncate
-                        fs::File::create(&safe_path)?
+    /// Fail if duplicates are rejected and a file or symlink with this
+    /// name has already been seen.
+    fn check_duplicate(&self, info: &EntryInfo, state: &mut ExtractionState) -> Result<(), Error> {
+        if !self.reject_duplicate_entries || info.kind == EntryKind::Directory {
+            return Ok(());
+        }
+        if !state.seen_names.insert(normal_path(&info.name)) {
+            return Err(Error::DuplicateEntry {
+                entry: info.name.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// While validating, fail on a case collision that
+    /// [`CaseCollisionPolicy::Error`] would fail on during extraction.
+    /// Observers aren't asked, so they see each entry only once.
+    fn check_case_ahead(&self, info: &EntryInfo, state: &mut ExtractionState) -> Result<(), Error> {
+        if self.case_collisions != Some(CaseCollisionPolicy::Error)
+            || info.kind == EntryKind::Directory
+            || self.filter.as_ref().is_some_and(|filter| !filter(info))
+        {
+            return Ok(());
+        }
+        match case_collision(&mut state.folded_names, &info.name) {
+            Some(existing) => Err(Error::CaseCollision {
+                existing,
+                conflicting: info.name.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Apply the filter and observer veto. Returns true if the entry is
+    /// skipped.
+    fn skip_entry(&self, info: &EntryInfo, state: &mut ExtractionState) -> bool {
+        if let Some(ref filter) = self.filter {
+            if !filter(info) {
+                self.skip(info, SkipReason::Filtered, state);
+                return true;
+            }
+        }
+
+        let vetoed = self
+            .observers
+            .iter()
+            .any(|observer| observer.before_entry(info) == EntryAction::Skip);
+        if vetoed {
+            self.skip(info, SkipReason::Vetoed, state);
+        }
+        vetoed
+    }
+
+    /// Apply the case collision policy to an entry that passed the filter.
+    /// Returns the entry to extract, renamed if necessary, or `None` if it
+    /// is skipped.
+    fn resolve_case<'a>(
+        &self,
+        info: Cow<'a, EntryInfo>,
+        state: &mut ExtractionState,
+    ) -> Result<Option<Cow<'a, EntryInfo>>, Error> {
+        let Some(policy) = self.case_collisions else {
+            return Ok(Some(info));
+        };
+        if info.kind == EntryKind::Directory {
+            return Ok(Some(info));
+        }
+
+        let Some(existing) = case_collision(&mut state.folded_names, &info.name) else {
+            return Ok(Some(info));
+        };
+
+        match policy {
+            CaseCollisionPolicy::Error => Err(Error::CaseCollision {
+                existing,
+                conflicting: info.name.clone(),
+            }),
+            CaseCollisionPolicy::KeepFirst => {
+                self.skip(&info, SkipReason::CaseCollision, state);
+                Ok(None)
+            }
+            CaseCollisionPolicy::SuffixSecond => {
+                let mut n = 1;
+                let renamed = loop {
+                    let candidate = with_case_suffix(&info.name, n);
+                    if !state.folded_names.contains_key(&folded_name(&candidate)) {
+                        break candidate;
                     }
+                    n += 1;
                 };
+                state
+                    .folded_names
+                    .insert(folded_name(&renamed), renamed.clone());
+                state.renamed_entries.push(RenamedEntry {
+                    name: info.name.clone(),
+                    renamed_to: renamed.clone(),
+                });
+                let mut info = info;
+                info.to_mut().name = renamed;
+                Ok(Some(info))
+            }
+        }
+    }
 
-                let mut outfile = outfile;
-                let limit = self.limits.max_single_file.min(
-                    self.limits
-                        .max_total_bytes
-                        .saturating_sub(state.bytes_written),
-                );
+    /// Report entry `index` of `total` to the progress callback.
+    fn progress(&self, info: &EntryInfo, index: usize, total: usize, state: &ExtractionState) {
+        if let Some(ref callback) = self.on_progress {
+            callback(&Progress {
+                entry_name: info.name.clone(),
+                entry_size: info.size,
+                entry_index: index,
+                total_entries: total,
+                bytes_written: state.bytes_written,
+                files_extracted: state.files_extracted,
+            });
+        }
+    }
 
-                let (_, written) = adapter.extract_to(index, &mut outfile, limit)?;
+    /// Count an entry as skipped and tell observers why.
+    fn skip(&self, info: &EntryInfo, reason: SkipReason, state: &mut ExtractionState) {
+        state.entries_skipped += 1;
+        for observer in &self.observers {
+            observer.on_skip(info, reason);
+        }
+    }
 
-                // Set permissions on Unix
-                #[cfg(unix)]
-                if let Some(mode) = info.mode {
-                    use std::os::unix::fs::PermissionsExt;
-                    let safe_mode = mode & 0o0777;
-                    fs::set_permissions(&safe_path, fs::Permissions::from_mode(safe_mode))?;
-                }
+    /// Tell observers an entry was written to `path`, and collect the path
+    /// if asked to.
+    fn notify_extracted(&self, info: &EntryInfo, path: &Path, state: &mut ExtractionState) {
+        if self.collect_paths {
+            state.extracted_paths.push(path.to_path_buf());
+        }
+        if self.index.is_some() && matches!(info.kind, EntryKind::File) {
+            state.indexed.push((info.name.clone(), path.to_path_buf()));
+        }
+        for observer in &self.observers {
+            observer.after_entry(info, path);
+        }
+    }
 
-                state.bytes_written += written;
-                state.files_extracted += 1;
+    /// Reject a file whose sniffed content type is not in the allowlist.
+    fn check_content(&self, info: &EntryInfo, prefix: &[u8]) -> Result<(), Error> {
+        let Some(ref allowed) = self.content_types else {
+            return Ok(());
+        };
+        match ContentCategory::sniff(prefix) {
+            Some(detected) if allowed.contains(&detected) => Ok(()),
+            detected => Err(Error::DisallowedContent {
+                entry: info.name.clone(),
+                detected: detected.map_or("unknown", |c| c.as_str()).to_string(),
+            }),
+        }
+    }
+
+    /// Apply an entry's stored Unix mode, after the `permissions` callback.
+    ///
+    /// Special bits are always stripped. Directories keep owner `rwx` so
+    /// later entries can still be written into them.
+    fn apply_permissions(&self, info: &EntryInfo, path: &Path) -> Result<(), Error> {
+        #[cfg(unix)]
+        {
+            let stored = info.mode.map(|stored| match self.permissions {
+                Some(ref permissions) => permissions(info, stored),
+                None => stored,
+            });
+            let is_dir = matches!(info.kind, EntryKind::Directory);
+            if let Some(mode) = self.permission_mode.resolve(stored, is_dir) {
+                self.fs().set_permissions(path, mode)?;
             }
-            EntryKind::Symlink { .. } => {
-                // Already handled above (skipped or errored by policy)
+        }
+        #[cfg(not(unix))]
+        let _ = (info, path);
+
+        Ok(())
+    }
+
+    /// Set an entry's extended attributes if `restore_xattrs` is on.
+    #[cfg(feature = "tar")]
+    fn apply_xattrs(&self, info: &EntryInfo, path: &Path) -> Result<(), Error> {
+        #[cfg(all(unix, feature = "xattr"))]
+        if self.restore_xattrs {
+            for (name, value) in &info.xattrs {
+                match xattr::set(path, name, value) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == std::io::ErrorKind::Unsupported => return Ok(()),
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+        #[cfg(not(all(unix, feature = "xattr")))]
+        let _ = (info, path);
+
+        Ok(())
+    }
+
+    /// Flush a freshly written file according to the durability setting.
+    fn sync_written(&self, output: &Output, path: &Path) -> Result<(), Error> {
+        // Durability is rejected with a custom filesystem
+        let Output::File(file) = output else {
+            return Ok(());
+        };
+        if self.durability == Durability::None {
+            return Ok(());
+        }
+
+        file.sync_all()?;
+
+        // Directory handles can only be opened for syncing on Unix
+        #[cfg(unix)]
+        if self.durability == Durability::FsyncDir {
+            if let Some(parent) = path.parent() {
+                fs::File::open(parent)?.sync_all()?;
             }
         }
+        #[cfg(not(unix))]
+        let _ = path;
 
         Ok(())
     }
 
     /// Convenience: extract ZIP from a file path.
     pub fn extract_zip_file<P: AsRef<Path>>(&self, path: P) -> Result<ExtractionReport, Error> {
-        let adapter = ZipAdapter::open(path)?;
-        self.extract_zip(adapter)
+        let source = ArchiveSource::Zip(path.as_ref().to_path_buf());
+        let mut state = ExtractionState::default();
+        let result = self.extract_source(&source, &mut state);
+        self.finish(result, state)
+    }
+
+    // =========================================================================
+    // Entry Streaming
+    // =========================================================================
+
+    /// Pull validated entries from `adapter` one at a time, without
+    /// writing anything to disk.
+    ///
+    /// Limits, path and symlink policies, the filter, and observers'
+    /// `before_entry` apply as in extraction; the file count and total
+    /// size are enforced as the stream is consumed. Options that concern
+    /// writing (overwrite mode, permissions, durability, index, content
+    /// types) and [`ValidationMode`]/[`Order`] do not apply: entries come
+    /// in archive order and are checked one at a time.
+    ///
+    /// Symlinks are yielded only under `SymlinkBehavior::AllowContained` or
+    /// `ContainedOrSkip`, and only if their target stays inside the
+    /// archive root. Skipping a reader, or not reading it to the end,
+    /// simply advances past the rest of its bytes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, ZipAdapter};
+    /// use std::io::Read;
+    ///
+    /// let driver = Driver::new("/tmp/unused")?;
+    /// let mut stream = driver.open(ZipAdapter::open("archive.zip")?)?;
+    /// while let Some(entry) = stream.next_entry() {
+    ///     let (info, mut reader) = entry?;
+    ///     let mut content = Vec::new();
+    ///     reader.read_to_end(&mut content)?;
+    ///     println!("{}: {} bytes", info.name, content.len());
+    /// }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn open<A: ArchiveReader>(&self, adapter: A) -> Result<EntryStream<'_, A>, Error> {
+        Ok(EntryStream {
+            driver: self,
+            adapter,
+            policies: self.build_policies(&self.destination)?,
+            state: ExtractionState::default(),
+            entries_read: 0,
+        })
+    }
+
+    /// Extract the files of `adapter` into the destination as a
+    /// content-addressed store, and return a manifest mapping each file's
+    /// archive path to its SHA-256.
+    ///
+    /// Each file is hashed as it is written to a staging file, which is
+    /// then renamed to `<destination>/<sha256>`. If that address already
+    /// exists, from this archive or an earlier one, the staging file is
+    /// discarded instead, so identical files are stored once. Directories
+    /// and symlinks have no content and are left out of the manifest.
+    ///
+    /// Entries are pulled through [`open`](Self::open), so the same
+    /// policies apply and limits bound the decompressed bytes. Of the
+    /// options that concern writing, only durability and
+    /// [`filesystem`](Self::filesystem) are honoured. A custom filesystem
+    /// keeps the staging file of an entry that failed part way.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Driver, ZipAdapter};
+    ///
+    /// let manifest = Driver::new("/var/cache/artifacts")?
+    ///     .extract_to_cas(ZipAdapter::open("build.zip")?)?;
+    /// for (path, hash) in &manifest {
+    ///     println!("{} -> /var/cache/artifacts/{}", path, hash);
+    /// }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn extract_to_cas<A: ArchiveReader>(
+        &self,
+        adapter: A,
+    ) -> Result<BTreeMap<String, String>, Error> {
+        let mut stream = self.open(adapter)?;
+        let mut manifest = BTreeMap::new();
+
+        while let Some(entry) = stream.next_entry() {
+            let (info, reader) = entry?;
+            if info.kind != EntryKind::File {
+                continue;
+            }
+
+            let _slot = self.file_slot();
+            let staging = staging::Staging::in_dir(&self.destination);
+            let mut output = match self.filesystem {
+                Some(ref filesystem) => {
+                    Output::Backend(filesystem.open_write(staging.path(), true)?)
+                }
+                None => Output::File(fs::File::create(staging.path())?),
+            };
+            let digest = SourceDigest::new(DigestAlgorithm::Sha256);
+            std::io::copy(&mut digest.reader(reader), &mut output)?;
+            let hash = digest.finish();
+
+            // A backend can't be asked whether the address exists, but
+            // replacing it with the same content is harmless
+            let address = self.destination.join(&hash);
+            if self.filesystem.is_some() || fs::symlink_metadata(&address).is_err() {
+                self.sync_written(&output, &address)?;
+                drop(output);
+                self.fs().rename(staging.path(), &address)?;
+                staging.persist();
+            }
+            manifest.insert(info.name, hash);
+        }
+
+        Ok(manifest)
+    }
+
+    /// Read the files of `adapter` into memory and return a map from entry
+    /// name to contents, writing nothing to the destination.
+    ///
+    /// Entries are pulled through [`open`](Self::open), so the same
+    /// policies apply and names are still checked against the destination.
+    /// The total size limit bounds the memory held by the map. Directories
+    /// and symlinks have no contents and are left out. A name that appears
+    /// twice is resolved by the [`OverwriteMode`] as if the first copy were
+    /// on disk; none of the other options that concern writing apply.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "tar")]
+    /// # {
+    /// use safe_unzip::{Driver, TarAdapter};
+    ///
+    /// let files = Driver::new("/tmp")?.extract_to_memory(TarAdapter::open("config.tar")?)?;
+    /// for (name, content) in &files {
+    ///     println!("{}: {} bytes", name, content.len());
+    /// }
+    /// # }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn extract_to_memory<A: ArchiveReader>(
+        &self,
+        adapter: A,
+    ) -> Result<HashMap<String, Vec<u8>>, Error> {
+        let mut stream = self.open(adapter)?;
+        let mut files = HashMap::new();
+
+        while let Some(entry) = stream.next_entry() {
+            let (info, mut reader) = entry?;
+            if info.kind != EntryKind::File {
+                continue;
+            }
+
+            let mut content = Vec::new();
+            reader.read_to_end(&mut content)?;
+            if let Some(first) = files.get(&info.name) {
+                match self.overwrite {
+                    OverwriteMode::Error => {
+                        return Err(Error::AlreadyExists { entry: info.name });
+                    }
+                    OverwriteMode::Skip => continue,
+                    OverwriteMode::ErrorIfDifferent if *first != content => {
+                        return Err(Error::ContentConflict { entry: info.name });
+                    }
+                    _ => {}
+                }
+            }
+            files.insert(info.name, content);
+        }
+
+        Ok(files)
     }
 
     // =========================================================================
@@ -403,57 +3040,121 @@ impl Driver {
     /// Extract a TAR archive.
     ///
     /// For `.tar.gz` files, use [`Self::extract_tar_gz_file`] or wrap the reader
-    /// in `flate2::read::GzDecoder`.
+    /// in `flate2::read::GzDecoder`; for `.tar.xz` files (with the `xz`
+    /// feature), [`Self::extract_tar_xz_file`]. Only the `_file` variants
+    /// see the compressed bytes; here
+    /// [`max_compressed_bytes`](Limits::max_compressed_bytes) counts what
+    /// the adapter reads, which is the decompressed stream if its reader
+    /// decompresses.
+    ///
+    /// Fails with [`Error::InvalidOption`] if
+    /// [`verify_signature`](Self::verify_signature) is set.
+    #[cfg(feature = "tar")]
+    pub fn extract_tar<R: Read>(&self, adapter: TarAdapter<R>) -> Result<ExtractionReport, Error> {
+        self.reject_unverifiable("extract_tar")?;
+        let mut state = ExtractionState::default();
+        let result = self.extract_tar_adapter(adapter, &mut state);
+        self.finish(result, state)
+    }
+
+    /// Extract a TAR archive, continuing from `state`.
     #[cfg(feature = "tar")]
-    pub fn extract_tar<R: Read>(
+    fn extract_tar_adapter<R: Read>(
         &self,
-        mut adapter: TarAdapter<R>,
+        adapter: TarAdapter<R>,
+        state: &mut ExtractionState,
     ) -> Result<ExtractionReport, Error> {
-        let policies = self.build_policies()?;
+        if self.order == Order::SortedByName && self.validation == ValidationMode::Streaming {
+            return Err(Error::InvalidOption {
+                option: "extract_order".to_string(),
+                reason: "streaming TAR extraction cannot reorder entries; \
+                         use ValidationMode::ValidateFirst"
+                    .to_string(),
+            });
+        }
+
+        let policies = self.begin_extraction(state)?;
+        let mut adapter = adapter.unsupported_entries(self.unsupported);
+        let input = adapter.input_counter();
 
         // ValidateFirst mode: cache all entries, validate, then extract
-        if self.validation == ValidationMode::ValidateFirst {
-            let entries = adapter.cache_all()?;
-            let mut state = ExtractionState::default();
+        if self.validation != ValidationMode::Streaming {
+            // Limits on the input are checked as each entry is cached,
+            // so a long archive is not buffered only to be rejected
+            let entries = adapter.cache_all_checked(|count| {
+                self.limits
+                    .check_compressed(input.load(std::sync::atomic::Ordering::Relaxed))?;
+                self.limits.check_entry_count(count)
+            })?;
+            self.limits
+                .check_compressed(input.load(std::sync::atomic::Ordering::Relaxed))?;
+            let skipped = record_unsupported(&mut adapter, state);
+            let mut planned = state.clone();
 
             // Validate all entries
             for info in &entries {
-                policies.check_all(info, &state)?;
-                if matches!(info.kind, EntryKind::File) {
-                    state.bytes_written += info.size;
-                    state.files_extracted += 1;
+                let Some(info) = self.normalized(info, &planned) else {
+                    continue;
+                };
+                self.check_duplicate(&info, &mut planned)?;
+                self.check_case_ahead(&info, &mut planned)?;
+                policies.check_all(&info, &planned)?;
+                match info.kind {
+                    EntryKind::File => {
+                        planned.bytes_written += self.size_policy().accounted_size(&info);
+                        planned.files_extracted += 1;
+                    }
+                    EntryKind::Directory => planned.dirs_created += 1,
+                    _ => {}
                 }
             }
 
+            self.check_space(planned.bytes_written - state.bytes_written)?;
+
+            if self.order == Order::SortedByName {
+                adapter.sort_cached_by_name();
+            }
+
             // Extract from cache
-            let mut state = ExtractionState::default();
+            let mut index = 0;
             adapter.extract_cached(|info, data| {
-                self.extract_tar_entry_data(&info, data, &policies, &mut state)?;
+                self.interrupt.check()?;
+                self.progress(&info, index, entries.len(), state);
+                index += 1;
+                self.extract_tar_entry_data(&info, data, &policies, state)?;
                 Ok(true)
             })?;
-
-            return Ok(ExtractionReport {
-                files_extracted: state.files_extracted,
-                dirs_created: state.dirs_created,
-                bytes_written: state.bytes_written,
-                entries_skipped: state.entries_skipped,
-            });
+            return Ok(ExtractionReport::from_state(
+                state,
+                entries.is_empty() && skipped == 0,
+                adapter.bytes_read(),
+            ));
         }
 
-        // Streaming mode: extract as we read
-        let mut state = ExtractionState::default();
+        // Streaming mode: extract as we read. The input size is unknown up
+        // front, so it is checked as each entry's header is reached.
+        let mut was_empty = true;
 
+        let mut index = 0;
         adapter.for_each(|info, reader| {
-            self.extract_tar_entry(&info, reader, &policies, &mut state)?;
+            was_empty = false;
+            self.interrupt.check()?;
+            self.limits
+                .check_compressed(input.load(std::sync::atomic::Ordering::Relaxed))?;
+            self.limits.check_entry_count(index + 1)?;
+            self.progress(&info, index, 0, state);
+            index += 1;
+            self.extract_tar_entry(&info, reader, &policies, state)?;
             Ok(true)
         })?;
-
-        Ok(ExtractionReport {
-            files_extracted: state.files_extracted,
-            dirs_created: state.dirs_created,
-            bytes_written: state.bytes_written,
-            entries_skipped: state.entries_skipped,
-        })
+        self.limits
+            .check_compressed(input.load(std::sync::atomic::Ordering::Relaxed))?;
+        let skipped = record_unsupported(&mut adapter, state);
+        Ok(ExtractionReport::from_state(
+            state,
+            was_empty && skipped == 0,
+            adapter.bytes_read(),
+        ))
     }
 
     /// Extract a single TAR entry (streaming mode).
@@ -461,62 +3162,100 @@ impl Driver {
     fn extract_tar_entry(
         &self,
         info: &EntryInfo,
-        reader: Option<&mut dyn Read>,
+        mut reader: Option<&mut dyn Read>,
         policies: &PolicyChain,
         state: &mut ExtractionState,
     ) -> Result<(), Error> {
-        // Apply filter
-        if let Some(ref filter) = self.filter {
-            if !filter(info) {
-                state.entries_skipped += 1;
-                return Ok(());
-            }
+        let Some(normalized) = self.extracted_name(info, state) else {
+            return Ok(());
+        };
+        self.check_duplicate(&normalized, state)?;
+
+        // Apply filter and observer veto
+        if self.skip_entry(&normalized, state) {
+            return Ok(());
         }
+        let Some(normalized) = self.resolve_case(normalized, state)? else {
+            return Ok(());
+        };
+        let info = &*normalized;
 
         // Check policies
         policies.check_all(info, state)?;
 
         // Handle symlinks
-        if matches!(info.kind, EntryKind::Symlink { .. }) {
-            state.entries_skipped += 1;
+        if let EntryKind::Symlink { target } = &info.kind {
+            if self.creates_symlinks() && !self.structure_only {
+                return self.create_symlink(info, target, state);
+            }
+            self.skip(info, SkipReason::Symlink, state);
             return Ok(());
         }
 
-        let safe_path = self.destination.join(&info.name);
+        let safe_path = self.destination(state).join(&info.name);
 
         match info.kind {
-            EntryKind::Directory => {
-                fs::create_dir_all(&safe_path)?;
-                state.dirs_created += 1;
-            }
+            EntryKind::Directory => self.create_dir(info, &safe_path, state)?,
             EntryKind::File => {
                 if let Some(parent) = safe_path.parent() {
-                    fs::create_dir_all(parent)?;
+                    self.make_dirs(parent, state)?;
+                }
+
+                if self.structure_only {
+                    self.skip(info, SkipReason::StructureOnly, state);
+                    return Ok(());
+                }
+
+                // Sniff the content type before anything is written
+                let mut prefix = Vec::new();
+                if let (Some(_), Some(reader)) = (&self.content_types, reader.as_mut()) {
+                    reader.take(SNIFF_LEN as u64).read_to_end(&mut prefix)?;
                 }
+                self.check_content(info, &prefix)?;
 
-                let outfile = self.open_for_write(&safe_path, state)?;
-                let Some(mut outfile) = outfile else {
+                let _slot = self.file_slot();
+                let Some(mut output) = self.open_for_write(info, &safe_path, state)? else {
                     return Ok(()); // Skipped
                 };
 
+                let mut written = 0;
                 if let Some(reader) = reader {
+                    let mut reader = prefix.as_slice().chain(reader);
                     let limit = self.limits.max_single_file.min(
                         self.limits
                             .max_total_bytes
                             .saturating_sub(state.bytes_written),
                     );
-                    let written = crate::adapter::copy_limited(reader, &mut outfile, limit)?;
-                    state.bytes_written += written;
-                }
+                    written = crate::adapter::copy_limited(
+                        &mut reader,
+                        &mut self.interrupt.writer(
+                            &mut output,
+                            &info.name,
+                            &mut state.decompress_time,
+                        ),
+                        limit,
+                    )
+                    .map_err(|e| self.interrupt.recover(e))?;
 
-                #[cfg(unix)]
-                if let Some(mode) = info.mode {
-                    use std::os::unix::fs::PermissionsExt;
-                    let safe_mode = mode & 0o0777;
-                    fs::set_permissions(&safe_path, fs::Permissions::from_mode(safe_mode))?;
+                    // A declared size was checked up front; an unknown one is
+                    // only bounded here, so more data means the limit was hit
+                    if !info.size_known && written == limit && reader.read(&mut [0u8])? > 0 {
+                        return Err(self.over_limit(info, written + 1, state));
+                    }
                 }
 
+                let Some(outfile) = self.written_file(info, &safe_path, output, state)? else {
+                    return Ok(()); // Identical to the existing file
+                };
+
+                self.apply_permissions(info, &safe_path)?;
+                self.apply_xattrs(info, &safe_path)?;
+
+                self.sync_written(&outfile, &safe_path)?;
+
+                state.bytes_written += written;
                 state.files_extracted += 1;
+                self.notify_extracted(info, &safe_path, state);
             }
             EntryKind::Symlink { .. } => {
                 // Already handled
@@ -535,54 +3274,68 @@ impl Driver {
         policies: &PolicyChain,
         state: &mut ExtractionState,
     ) -> Result<(), Error> {
-        // Apply filter
-        if let Some(ref filter) = self.filter {
-            if !filter(info) {
-                state.entries_skipped += 1;
-                return Ok(());
-            }
+        let Some(normalized) = self.extracted_name(info, state) else {
+            return Ok(());
+        };
+        self.check_duplicate(&normalized, state)?;
+
+        // Apply filter and observer veto
+        if self.skip_entry(&normalized, state) {
+            return Ok(());
         }
+        let Some(normalized) = self.resolve_case(normalized, state)? else {
+            return Ok(());
+        };
+        let info = &*normalized;
 
         // Check policies (already validated, but need for state updates)
         policies.check_all(info, state)?;
 
         // Handle symlinks
-        if matches!(info.kind, EntryKind::Symlink { .. }) {
-            state.entries_skipped += 1;
+        if let EntryKind::Symlink { target } = &info.kind {
+            if self.creates_symlinks() && !self.structure_only {
+                return self.create_symlink(info, target, state);
+            }
+            self.skip(info, SkipReason::Symlink, state);
             return Ok(());
         }
 
-        let safe_path = self.destination.join(&info.name);
+        let safe_path = self.destination(state).join(&info.name);
 
         match info.kind {
-            EntryKind::Directory => {
-                fs::create_dir_all(&safe_path)?;
-                state.dirs_created += 1;
-            }
+            EntryKind::Directory => self.create_dir(info, &safe_path, state)?,
             EntryKind::File => {
                 if let Some(parent) = safe_path.parent() {
-                    fs::create_dir_all(parent)?;
+                    self.make_dirs(parent, state)?;
+                }
+
+                if self.structure_only {
+                    self.skip(info, SkipReason::StructureOnly, state);
+                    return Ok(());
                 }
 
-                let outfile = self.open_for_write(&safe_path, state)?;
-                let Some(mut outfile) = outfile else {
+                let data = data.unwrap_or_default();
+                self.check_content(info, &data[..data.len().min(SNIFF_LEN)])?;
+
+                let _slot = self.file_slot();
+                let Some(mut output) = self.open_for_write(info, &safe_path, state)? else {
                     return Ok(()); // Skipped
                 };
 
-                if let Some(data) = data {
-                    use std::io::Write;
-                    outfile.write_all(data)?;
-                    state.bytes_written += data.len() as u64;
-                }
+                output.write_all(data)?;
 
-                #[cfg(unix)]
-                if let Some(mode) = info.mode {
-                    use std::os::unix::fs::PermissionsExt;
-                    let safe_mode = mode & 0o0777;
-                    fs::set_permissions(&safe_path, fs::Permissions::from_mode(safe_mode))?;
-                }
+                let Some(outfile) = self.written_file(info, &safe_path, output, state)? else {
+                    return Ok(()); // Identical to the existing file
+                };
 
+                self.apply_permissions(info, &safe_path)?;
+                self.apply_xattrs(info, &safe_path)?;
+
+                self.sync_written(&outfile, &safe_path)?;
+
+                state.bytes_written += data.len() as u64;
                 state.files_extracted += 1;
+                self.notify_extracted(info, &safe_path, state);
             }
             EntryKind::Symlink { .. } => {
                 // Already handled
@@ -592,22 +3345,48 @@ impl Driver {
         Ok(())
     }
 
+    /// Wait for an output file slot if [`max_open_files`](Self::max_open_files)
+    /// is set. The slot is released when the guard is dropped.
+    fn file_slot(&self) -> Option<FileSlot<'_>> {
+        self.open_files.as_ref().map(FileSlots::acquire)
+    }
+
     /// Open a file for writing based on overwrite policy.
     /// Returns None if the file should be skipped.
-    #[cfg(feature = "tar")]
     fn open_for_write(
         &self,
+        info: &EntryInfo,
         path: &Path,
         state: &mut ExtractionState,
-    ) -> Result<Option<fs::File>, Error> {
+    ) -> Result<Option<Output>, Error> {
+        self.track_new(path, state);
+        if let Some(ref filesystem) = self.filesystem {
+            // ErrorIfDifferent was rejected up front
+            let create_new = self.overwrite != OverwriteMode::Overwrite;
+            return match filesystem.open_write(path, create_new) {
+                Ok(writer) => Ok(Some(Output::Backend(writer))),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if self.overwrite == OverwriteMode::Skip {
+                        self.skip(info, SkipReason::AlreadyExists, state);
+                        return Ok(None);
+                    }
+                    Err(Error::AlreadyExists {
+                        entry: path.display().to_string(),
+                    })
+                }
+                Err(e) => Err(e.into()),
+            };
+        }
+
         match self.overwrite {
             OverwriteMode::Error => {
+                // create_new(true) is atomic: fails if file exists (no TOCTOU)
                 match fs::OpenOptions::new()
                     .write(true)
                     .create_new(true)
                     .open(path)
                 {
-                    Ok(f) => Ok(Some(f)),
+                    Ok(f) => Ok(Some(Output::File(f))),
                     Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
                         Err(Error::AlreadyExists {
                             entry: path.display().to_string(),
@@ -622,21 +3401,71 @@ impl Driver {
                     .create_new(true)
                     .open(path)
                 {
-                    Ok(f) => Ok(Some(f)),
+                    Ok(f) => Ok(Some(Output::File(f))),
                     Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
-                        state.entries_skipped += 1;
+                        self.skip(info, SkipReason::AlreadyExists, state);
                         Ok(None)
                     }
                     Err(e) => Err(e.into()),
                 }
             }
             OverwriteMode::Overwrite => {
+                // SECURITY: Remove any existing symlink first to prevent following
                 if let Ok(m) = fs::symlink_metadata(path) {
                     if m.file_type().is_symlink() {
                         let _ = fs::remove_file(path);
                     }
                 }
-                Ok(Some(fs::File::create(path)?))
+                Ok(Some(Output::File(fs::File::create(path)?)))
+            }
+            OverwriteMode::ErrorIfDifferent => {
+                match fs::OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(path)
+                {
+                    Ok(f) => Ok(Some(Output::File(f))),
+                    Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                        let conflict = || Error::ContentConflict {
+                            entry: path.display().to_string(),
+                        };
+                        // Only a regular file of the same size can match; never
+                        // follow a symlink to compare against its target
+                        let existing = fs::symlink_metadata(path)?;
+                        if !existing.is_file() || existing.len() != info.size {
+                            return Err(conflict());
+                        }
+                        Ok(Some(Output::Compare(ContentMatch::new(fs::File::open(
+                            path,
+                        )?))))
+                    }
+                    Err(e) => Err(e.into()),
+                }
+            }
+        }
+    }
+
+    /// Resolve an entry's output once its contents have been copied.
+    ///
+    /// Returns the new file, or None if the entry matched an existing file
+    /// (`ErrorIfDifferent`) and was skipped.
+    fn written_file(
+        &self,
+        info: &EntryInfo,
+        path: &Path,
+        output: Output,
+        state: &mut ExtractionState,
+    ) -> Result<Option<Output>, Error> {
+        match output {
+            Output::File(_) | Output::Backend(_) => Ok(Some(output)),
+            Output::Compare(existing) => {
+                if !existing.finish()? {
+                    return Err(Error::ContentConflict {
+                        entry: path.display().to_string(),
+                    });
+                }
+                self.skip(info, SkipReason::AlreadyExists, state);
+                Ok(None)
             }
         }
     }
@@ -644,20 +3473,124 @@ impl Driver {
     /// Convenience: extract TAR from a file path.
     #[cfg(feature = "tar")]
     pub fn extract_tar_file<P: AsRef<Path>>(&self, path: P) -> Result<ExtractionReport, Error> {
-        let adapter = TarAdapter::open(path)?;
-        self.extract_tar(adapter)
+        let source = ArchiveSource::Tar(path.as_ref().to_path_buf());
+        let mut state = ExtractionState::default();
+        let result = self.extract_source(&source, &mut state);
+        self.finish(result, state)
     }
 
     /// Convenience: extract gzip-compressed TAR (.tar.gz, .tgz) from a file path.
     #[cfg(feature = "tar")]
     pub fn extract_tar_gz_file<P: AsRef<Path>>(&self, path: P) -> Result<ExtractionReport, Error> {
-        let adapter = TarAdapter::open_gz(path)?;
-        self.extract_tar(adapter)
+        let source = ArchiveSource::TarGz(path.as_ref().to_path_buf());
+        let mut state = ExtractionState::default();
+        let result = self.extract_source(&source, &mut state);
+        self.finish(result, state)
+    }
+
+    /// Convenience: extract xz-compressed TAR (.tar.xz, .txz) from a file path.
+    #[cfg(feature = "xz")]
+    pub fn extract_tar_xz_file<P: AsRef<Path>>(&self, path: P) -> Result<ExtractionReport, Error> {
+        let source = ArchiveSource::TarXz(path.as_ref().to_path_buf());
+        let mut state = ExtractionState::default();
+        let result = self.extract_source(&source, &mut state);
+        self.finish(result, state)
+    }
+
+    /// Decompress a single gzip-compressed file (not a tarball) into the
+    /// destination, like `gunzip`.
+    ///
+    /// The output is named from the file name stored in the gzip header,
+    /// reduced to its last component, or else the archive name without
+    /// `.gz`. It goes through the same policies as any archive entry, and
+    /// its size is only known once decompressed, so `max_single_file` is
+    /// enforced while writing (see [`UnknownSizePolicy`]).
+    ///
+    /// If the decompressed content turns out to be a TAR archive, it is
+    /// extracted as one, as with
+    /// [`extract_tar_gz_file`](Self::extract_tar_gz_file).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Driver;
+    ///
+    /// // Writes /tmp/out/access.log
+    /// let report = Driver::new("/tmp/out")?.extract_gz_file("access.log.gz")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    #[cfg(feature = "tar")]
+    pub fn extract_gz_file<P: AsRef<Path>>(&self, path: P) -> Result<ExtractionReport, Error> {
+        let source = ArchiveSource::Gz(path.as_ref().to_path_buf());
+        let mut state = ExtractionState::default();
+        let result = self.extract_source(&source, &mut state);
+        self.finish(result, state)
+    }
+
+    /// Decompress a single-file gzip stream, continuing from `state`.
+    #[cfg(feature = "tar")]
+    fn extract_gz_reader<R: Read>(
+        &self,
+        reader: R,
+        archive: &Path,
+        state: &mut ExtractionState,
+    ) -> Result<ExtractionReport, Error> {
+        let counting = crate::adapter::CountingReader::new(reader);
+        let read = counting.counter();
+        let mut decoder = flate2::read::MultiGzDecoder::new(BufReader::new(counting));
+
+        // Reading the first block also parses the header and its file name
+        let mut prefix = Vec::new();
+        (&mut decoder).take(512).read_to_end(&mut prefix)?;
+        if prefix.get(257..262) == Some(b"ustar") {
+            let adapter = TarAdapter::new(prefix.chain(decoder)).count_input(read);
+            return self.extract_tar_adapter(adapter, state);
+        }
+
+        let name = decoder
+            .header()
+            .and_then(|header| header.filename())
+            .map(|stored| {
+                let stored = String::from_utf8_lossy(stored);
+                let base = stored.rsplit(['/', '\\']).next().unwrap_or_default();
+                base.to_string()
+            })
+            .filter(|base| !base.is_empty() && base != "." && base != "..")
+            .or_else(|| gz_output_name(archive))
+            .ok_or_else(|| Error::InvalidFilename {
+                entry: archive.display().to_string(),
+                reason: "cannot derive an output name".to_string(),
+            })?;
+
+        let info = EntryInfo {
+            name,
+            size: 0,
+            compressed_size: None,
+            kind: EntryKind::File,
+            mode: None,
+            uid: None,
+            gid: None,
+            mtime: None,
+            size_known: false,
+            xattrs: Vec::new(),
+        };
+        let policies = self.begin_extraction(state)?;
+        let mut content = prefix.as_slice().chain(decoder);
+        self.extract_tar_entry(&info, Some(&mut content), &policies, state)?;
+
+        let bytes_read = read.load(std::sync::atomic::Ordering::Relaxed);
+        self.limits.check_compressed(bytes_read)?;
+        Ok(ExtractionReport::from_state(state, false, bytes_read))
     }
 
     /// Extract a 7z archive.
     ///
-    /// Requires the `sevenz` feature to be enabled.
+    /// Requires the `sevenz` feature to be enabled. Entries are written as
+    /// they are decoded, except with [`Order::SortedByName`] or
+    /// [`ValidationMode::ValidateFirst`], where the archive is decompressed
+    /// into memory first. The driver's `max_single_file` and
+    /// `max_total_bytes` then also cap how much that is; see
+    /// [`SevenZAdapter::limits`](crate::SevenZAdapter::limits).
     ///
     /// # Example
     ///
@@ -667,75 +3600,151 @@ impl Driver {
     /// let adapter = SevenZAdapter::open("archive.7z")?;
     /// let report = Driver::new("/tmp/out")?.extract_7z(adapter)?;
     /// ```
+    ///
+    /// Fails with [`Error::InvalidOption`] if
+    /// [`verify_signature`](Self::verify_signature) is set.
     #[cfg(feature = "sevenz")]
     pub fn extract_7z(
         &self,
         adapter: crate::adapter::SevenZAdapter,
     ) -> Result<ExtractionReport, Error> {
-        let policies = self.build_policies()?;
+        self.reject_unverifiable("extract_7z")?;
         let mut state = ExtractionState::default();
+        let result = self.extract_7z_adapter(adapter, &mut state);
+        self.finish(result, state)
+    }
 
-        adapter.for_each(|info, data| {
-            self.extract_7z_entry(info, data, &policies, &mut state)?;
-            Ok(true)
-        })?;
+    /// Extract a 7z archive, continuing from `state`.
+    #[cfg(feature = "sevenz")]
+    fn extract_7z_adapter(
+        &self,
+        mut adapter: crate::adapter::SevenZAdapter,
+        state: &mut ExtractionState,
+    ) -> Result<ExtractionReport, Error> {
+        self.limits.check_compressed(adapter.archive_len())?;
+        self.limits.check_entry_count(adapter.len())?;
+        adapter.cap_sizes(&self.limits);
+        if self.order == Order::SortedByName {
+            adapter.sort_by_name()?;
+        }
 
-        Ok(ExtractionReport {
-            files_extracted: state.files_extracted,
-            dirs_created: state.dirs_created,
-            bytes_written: state.bytes_written,
-            entries_skipped: state.entries_skipped,
-        })
+        let policies = self.begin_extraction(state)?;
+        if self.validation != ValidationMode::Streaming {
+            let required = adapter
+                .entries_metadata()?
+                .iter()
+                .filter(|info| matches!(info.kind, EntryKind::File))
+                .fold(0u64, |total, info| total.saturating_add(info.size));
+            self.check_space(required)?;
+        }
+
+        let total = adapter.len();
+        let mut index = 0;
+        if self.order == Order::SortedByName || self.validation != ValidationMode::Streaming {
+            adapter.for_each(|info, data| {
+                self.interrupt.check()?;
+                self.progress(info, index, total, state);
+                index += 1;
+                let mut data = data.unwrap_or_default();
+                self.extract_7z_entry(info, &mut data, &policies, state)?;
+                Ok(true)
+            })?;
+        } else {
+            // Archive order needs nothing cached: write each entry as it
+            // is decoded
+            adapter.extract_each(|info, reader| {
+                self.interrupt.check()?;
+                self.progress(info, index, total, state);
+                index += 1;
+                self.extract_7z_entry(info, reader, &policies, state)
+            })?;
+        }
+        Ok(ExtractionReport::from_state(
+            state,
+            adapter.is_empty(),
+            adapter.bytes_read(),
+        ))
     }
 
-    /// Extract a single 7z entry.
+    /// Extract a single 7z entry, reading its content from `reader`.
     #[cfg(feature = "sevenz")]
     fn extract_7z_entry(
         &self,
         info: &EntryInfo,
-        data: Option<&[u8]>,
+        reader: &mut dyn Read,
         policies: &PolicyChain,
         state: &mut ExtractionState,
     ) -> Result<(), Error> {
-        // Apply filter
-        if let Some(ref filter) = self.filter {
-            if !filter(info) {
-                state.entries_skipped += 1;
-                return Ok(());
-            }
+        let Some(normalized) = self.extracted_name(info, state) else {
+            return Ok(());
+        };
+        self.check_duplicate(&normalized, state)?;
+
+        // Apply filter and observer veto
+        if self.skip_entry(&normalized, state) {
+            return Ok(());
         }
+        let Some(normalized) = self.resolve_case(normalized, state)? else {
+            return Ok(());
+        };
+        let info = &*normalized;
 
         // Validate with policies
         policies.check_all(info, state)?;
 
-        let safe_path = self.destination.join(&info.name);
+        let safe_path = self.destination(state).join(&info.name);
 
         match info.kind {
-            EntryKind::Directory => {
-                fs::create_dir_all(&safe_path)?;
-                state.dirs_created += 1;
-            }
+            EntryKind::Directory => self.create_dir(info, &safe_path, state)?,
             EntryKind::File => {
                 if let Some(parent) = safe_path.parent() {
-                    fs::create_dir_all(parent)?;
+                    self.make_dirs(parent, state)?;
+                }
+
+                if self.structure_only {
+                    self.skip(info, SkipReason::StructureOnly, state);
+                    return Ok(());
                 }
 
-                let outfile = self.open_for_write(&safe_path, state)?;
-                let Some(mut outfile) = outfile else {
+                // Sniff the content type before anything is written
+                let mut prefix = Vec::new();
+                if self.content_types.is_some() {
+                    reader.take(SNIFF_LEN as u64).read_to_end(&mut prefix)?;
+                }
+                self.check_content(info, &prefix)?;
+
+                let _slot = self.file_slot();
+                let Some(mut output) = self.open_for_write(info, &safe_path, state)? else {
                     return Ok(()); // Skipped
                 };
 
-                if let Some(bytes) = data {
-                    use std::io::Write;
-                    outfile.write_all(bytes)?;
-                    state.bytes_written += bytes.len() as u64;
-                }
+                let limit = self.limits.max_single_file.min(
+                    self.limits
+                        .max_total_bytes
+                        .saturating_sub(state.bytes_written),
+                );
+                let written = std::io::copy(
+                    &mut prefix.as_slice().chain(reader).take(limit),
+                    &mut self
+                        .interrupt
+                        .writer(&mut output, &info.name, &mut state.decompress_time),
+                )
+                .map_err(|e| self.interrupt.recover(e.into()))?;
 
+                let Some(outfile) = self.written_file(info, &safe_path, output, state)? else {
+                    return Ok(()); // Identical to the existing file
+                };
+
+                self.apply_permissions(info, &safe_path)?;
+                self.sync_written(&outfile, &safe_path)?;
+
+                state.bytes_written += written;
                 state.files_extracted += 1;
+                self.notify_extracted(info, &safe_path, state);
             }
             EntryKind::Symlink { .. } => {
                 // Skip symlinks for 7z (same policy as TAR)
-                state.entries_skipped += 1;
+                self.skip(info, SkipReason::Symlink, state);
             }
         }
 
@@ -745,14 +3754,466 @@ impl Driver {
     /// Convenience: extract 7z from a file path.
     #[cfg(feature = "sevenz")]
     pub fn extract_7z_file<P: AsRef<Path>>(&self, path: P) -> Result<ExtractionReport, Error> {
-        let adapter = crate::adapter::SevenZAdapter::open(path)?;
-        self.extract_7z(adapter)
+        let source = ArchiveSource::SevenZ(path.as_ref().to_path_buf());
+        let mut state = ExtractionState::default();
+        let result = self.extract_source(&source, &mut state);
+        self.finish(result, state)
     }
 
     /// Convenience: extract 7z from bytes.
     #[cfg(feature = "sevenz")]
     pub fn extract_7z_bytes(&self, data: &[u8]) -> Result<ExtractionReport, Error> {
+        self.limits.check_compressed(data.len() as u64)?;
+        self.check_signature(data)?;
         let adapter = crate::adapter::SevenZAdapter::from_bytes(data)?;
-        self.extract_7z(adapter)
+        let mut state = ExtractionState::default();
+        let result = self.extract_7z_adapter(adapter, &mut state);
+        self.finish(result, state)
+    }
+
+    // =========================================================================
+    // Batch Extraction
+    // =========================================================================
+
+    /// Extract several archives into the destination as one operation.
+    ///
+    /// Limits apply to the combined output: `max_total_bytes` and
+    /// `max_file_count` bound all archives together, not each one. The
+    /// overwrite mode applies across archives too, so with the default
+    /// [`OverwriteMode::Error`] a later archive cannot replace a file an
+    /// earlier one wrote. Archives are extracted in order and the first
+    /// error stops the batch; earlier archives stay extracted.
+    ///
+    /// The returned report merges all archives. `post_extract` runs once,
+    /// after the last archive.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{ArchiveSource, Driver, Limits};
+    ///
+    /// let report = Driver::new("/srv/import")?
+    ///     .limits(Limits {
+    ///         max_total_bytes: 2 * 1024 * 1024 * 1024,
+    ///         ..Limits::default()
+    ///     })
+    ///     .extract_many(&[
+    ///         ArchiveSource::Zip("part1.zip".into()),
+    ///         ArchiveSource::Zip("part2.zip".into()),
+    ///     ])?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn extract_many(&self, sources: &[ArchiveSource]) -> Result<ExtractionReport, Error> {
+        let mut state = ExtractionState::default();
+        let result = self.extract_sources(sources, &mut state);
+        self.finish(result, state)
+    }
+
+    fn extract_sources(
+        &self,
+        sources: &[ArchiveSource],
+        state: &mut ExtractionState,
+    ) -> Result<ExtractionReport, Error> {
+        let mut was_empty = true;
+        let mut bytes_read = 0;
+
+        for source in sources {
+            let report = self.extract_source(source, state)?;
+            was_empty &= report.was_empty;
+            bytes_read += report.bytes_read;
+        }
+
+        Ok(ExtractionReport::from_state(state, was_empty, bytes_read))
+    }
+
+    /// Extract several archives, each into its own subdirectory of the
+    /// destination named after the archive's stem (`logs-2024.tar.gz` goes
+    /// to `logs-2024/`).
+    ///
+    /// Like [`extract_many`](Self::extract_many), limits bound all archives
+    /// together and the first error stops the batch. Entries are confined to
+    /// their archive's subdirectory, so archives cannot overwrite each
+    /// other's files. Two archives with the same stem fail with
+    /// [`Error::InvalidOption`] before anything is extracted.
+    ///
+    /// Returns a report per archive, keyed by subdirectory name.
+    /// `post_extract` runs once with the top-level destination.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{ArchiveSource, Driver};
+    ///
+    /// let reports = Driver::new("/srv/import")?.extract_many_into_subdirs(&[
+    ///     ArchiveSource::Zip("customer-a.zip".into()),
+    ///     ArchiveSource::Zip("customer-b.zip".into()),
+    /// ])?;
+    /// for (dir, report) in &reports {
+    ///     println!("{}: {} files", dir, report.files_extracted);
+    /// }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn extract_many_into_subdirs(
+        &self,
+        sources: &[ArchiveSource],
+    ) -> Result<BTreeMap<String, ExtractionReport>, Error> {
+        let mut subdirs = Vec::with_capacity(sources.len());
+        for source in sources {
+            let stem = archive_stem(source.path()).ok_or_else(|| Error::InvalidOption {
+                option: "extract_many_into_subdirs".to_string(),
+                reason: format!(
+                    "cannot name a directory after '{}'",
+                    source.path().display()
+                ),
+            })?;
+            if subdirs.contains(&stem) {
+                return Err(Error::InvalidOption {
+                    option: "extract_many_into_subdirs".to_string(),
+                    reason: format!("more than one archive would extract into '{}'", stem),
+                });
+            }
+            subdirs.push(stem);
+        }
+
+        let mut reports = BTreeMap::new();
+        let mut state = ExtractionState::default();
+        let result = self.extract_sources_into(sources, &subdirs, &mut reports, &mut state);
+        self.finish(result, state)?;
+        Ok(reports)
+    }
+
+    /// Extract each archive into its subdirectory, pointing `state` at it
+    /// for the archive's entries.
+    fn extract_sources_into(
+        &self,
+        sources: &[ArchiveSource],
+        subdirs: &[String],
+        reports: &mut BTreeMap<String, ExtractionReport>,
+        state: &mut ExtractionState,
+    ) -> Result<ExtractionReport, Error> {
+        let mut was_empty = true;
+        let mut bytes_read = 0;
+
+        for (source, subdir) in sources.iter().zip(subdirs) {
+            let destination = self.destination.join(subdir);
+            if self.cleanup_on_error && fs::symlink_metadata(&destination).is_err() {
+                state.created.push(destination.clone());
+            }
+            fs::create_dir_all(&destination)?;
+            state.destination = Some(destination);
+
+            // Entry names are relative to each archive's own subdirectory
+            state.folded_names.clear();
+            state.seen_names.clear();
+            let before = state.clone();
+            let report = self.extract_source(source, state)?;
+            was_empty &= report.was_empty;
+            bytes_read += report.bytes_read;
+
+            let this = ExtractionState {
+                files_extracted: state.files_extracted - before.files_extracted,
+                dirs_created: state.dirs_created - before.dirs_created,
+                bytes_written: state.bytes_written - before.bytes_written,
+                entries_skipped: state.entries_skipped - before.entries_skipped,
+                symlinks_created: state.symlinks_created - before.symlinks_created,
+                skipped_symlinks: state.skipped_symlinks[before.skipped_symlinks.len()..].to_vec(),
+                skipped_unsupported: state.skipped_unsupported[before.skipped_unsupported.len()..]
+                    .to_vec(),
+                decompress_time: state.decompress_time - before.decompress_time,
+                renamed_entries: state.renamed_entries[before.renamed_entries.len()..].to_vec(),
+                folded_names: Default::default(),
+                seen_names: Default::default(),
+                extracted_paths: state.extracted_paths[before.extracted_paths.len()..].to_vec(),
+                created: Default::default(),
+                new_dirs: Default::default(),
+                indexed: Default::default(),
+                destination: Default::default(),
+            };
+            let mut this = ExtractionReport::from_state(&this, report.was_empty, report.bytes_read);
+            this.source_digest = report.source_digest;
+            this.skipped_duplicate_archive = report.skipped_duplicate_archive;
+            reports.insert(subdir.clone(), this);
+        }
+
+        Ok(ExtractionReport::from_state(state, was_empty, bytes_read))
+    }
+
+    /// Open `source` (verifying its signature if configured) and extract
+    /// it, continuing from `state`.
+    fn extract_source(
+        &self,
+        source: &ArchiveSource,
+        state: &mut ExtractionState,
+    ) -> Result<ExtractionReport, Error> {
+        // Bound the input before reading or decompressing any of it
+        self.limits
+            .check_compressed(fs::metadata(source.path())?.len())?;
+
+        let digest = self.hash_source.map(SourceDigest::new);
+
+        // With a verifier, extract from the exact bytes that were verified.
+        // Random-access formats are buffered for hashing so the file is
+        // read only once.
+        let data = match (&self.verifier, &digest) {
+            (Some(_), _) => Some(self.read_verified(source.path())?),
+            (None, Some(_)) if source.is_random_access() => Some(fs::read(source.path())?),
+            (None, _) => None,
+        };
+        if let (Some(digest), Some(data)) = (&digest, &data) {
+            digest.update(data);
+        }
+
+        if let (Some(store), Some(algorithm)) = (&self.dedupe, self.hash_source) {
+            let seen = match (&digest, &data) {
+                (Some(digest), Some(_)) => digest.finish(),
+                _ => SourceDigest::new(algorithm).finish_file(source.path())?,
+            };
+            if store.contains(&seen)? {
+                let mut report =
+                    ExtractionReport::from_state(&ExtractionState::default(), false, 0);
+                report.source_digest = Some(seen);
+                report.skipped_duplicate_archive = true;
+                return Ok(report);
+            }
+        }
+
+        #[cfg(feature = "tar")]
+        let open = |path: &Path| -> Result<_, Error> {
+            let file = fs::File::open(path)?;
+            Ok(match digest {
+                Some(ref digest) => HashedFile::Hashed(digest.reader(file)),
+                None => HashedFile::Plain(file),
+            })
+        };
+
+        let mut report = match (source, data) {
+            (ArchiveSource::Zip(_), Some(data)) => {
+                self.extract_zip_adapter(ZipAdapter::new(Cursor::new(data))?, state)
+            }
+            (ArchiveSource::Zip(path), None) => {
+                self.extract_zip_adapter(ZipAdapter::open(path)?, state)
+            }
+            #[cfg(feature = "tar")]
+            (ArchiveSource::Tar(_), Some(data)) => {
+                self.extract_tar_adapter(TarAdapter::new(Cursor::new(data)), state)
+            }
+            #[cfg(feature = "tar")]
+            (ArchiveSource::Tar(path), None) => {
+                self.extract_tar_adapter(TarAdapter::new(BufReader::new(open(path)?)), state)
+            }
+            #[cfg(feature = "tar")]
+            (ArchiveSource::TarGz(_), Some(data)) => {
+                let adapter = counted_tar(Cursor::new(data), flate2::read::GzDecoder::new);
+                self.extract_tar_adapter(adapter, state)
+            }
+            #[cfg(feature = "tar")]
+            (ArchiveSource::TarGz(path), None) => {
+                let adapter =
+                    counted_tar(BufReader::new(open(path)?), flate2::read::GzDecoder::new);
+                self.extract_tar_adapter(adapter, state)
+            }
+            #[cfg(feature = "xz")]
+            (ArchiveSource::TarXz(_), Some(data)) => {
+                let adapter = counted_tar(Cursor::new(data), xz2::read::XzDecoder::new);
+                self.extract_tar_adapter(adapter, state)
+            }
+            #[cfg(feature = "xz")]
+            (ArchiveSource::TarXz(path), None) => {
+                let adapter = counted_tar(BufReader::new(open(path)?), xz2::read::XzDecoder::new);
+                self.extract_tar_adapter(adapter, state)
+            }
+            #[cfg(feature = "tar")]
+            (ArchiveSource::Gz(path), Some(data)) => {
+                self.extract_gz_reader(Cursor::new(data), path, state)
+            }
+            #[cfg(feature = "tar")]
+            (ArchiveSource::Gz(path), None) => self.extract_gz_reader(open(path)?, path, state),
+            #[cfg(feature = "sevenz")]
+            (ArchiveSource::SevenZ(_), Some(data)) => {
+                let adapter = crate::adapter::SevenZAdapter::from_bytes(&data)?;
+                self.extract_7z_adapter(adapter, state)
+            }
+            #[cfg(feature = "sevenz")]
+            (ArchiveSource::SevenZ(path), None) => {
+                let adapter = crate::adapter::SevenZAdapter::open(path)?;
+                self.extract_7z_adapter(adapter, state)
+            }
+        }?;
+
+        if let Some(digest) = digest {
+            let hex = digest.finish_file(source.path())?;
+            if let Some(ref store) = self.dedupe {
+                store.record(&hex)?;
+            }
+            report.source_digest = Some(hex);
+        }
+        Ok(report)
+    }
+}
+
+/// A TAR adapter over `decoder(input)`, counting the compressed bytes
+/// read from `input` against `max_compressed_bytes`.
+#[cfg(feature = "tar")]
+fn counted_tar<R: Read, D: Read>(
+    input: R,
+    decoder: impl FnOnce(crate::adapter::CountingReader<R>) -> D,
+) -> TarAdapter<D> {
+    let input = crate::adapter::CountingReader::new(input);
+    let read = input.counter();
+    TarAdapter::new(decoder(input)).count_input(read)
+}
+
+/// An archive file, optionally hashed as it is read.
+#[cfg(feature = "tar")]
+enum HashedFile {
+    Plain(fs::File),
+    Hashed(HashingReader<fs::File>),
+}
+
+#[cfg(feature = "tar")]
+impl Read for HashedFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(file) => file.read(buf),
+            Self::Hashed(reader) => reader.read(buf),
+        }
+    }
+}
+
+/// Counting semaphore behind [`Driver::max_open_files`].
+struct FileSlots {
+    limit: usize,
+    open: Mutex<usize>,
+    freed: Condvar,
+}
+
+impl FileSlots {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            open: Mutex::new(0),
+            freed: Condvar::new(),
+        }
+    }
+
+    /// Block until fewer than `limit` slots are taken, then take one.
+    fn acquire(&self) -> FileSlot<'_> {
+        let mut open = self.lock();
+        while *open >= self.limit {
+            open = self.freed.wait(open).unwrap_or_else(|e| e.into_inner());
+        }
+        *open += 1;
+        FileSlot(self)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, usize> {
+        self.open.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A slot taken from [`FileSlots`], given back on drop.
+struct FileSlot<'a>(&'a FileSlots);
+
+impl Drop for FileSlot<'_> {
+    fn drop(&mut self) {
+        *self.0.lock() -= 1;
+        self.0.freed.notify_one();
+    }
+}
+
+/// Where an entry's contents are copied.
+enum Output {
+    /// A newly created (or truncated) file.
+    File(fs::File),
+    /// An existing file the contents must match (`ErrorIfDifferent`).
+    Compare(ContentMatch),
+    /// A file opened through a custom [`FileSystem`].
+    Backend(Box<dyn Write + Send>),
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::File(file) => file.write(buf),
+            Self::Compare(existing) => existing.write(buf),
+            Self::Backend(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::File(file) => file.flush(),
+            Self::Compare(_) => Ok(()),
+            Self::Backend(writer) => writer.flush(),
+        }
+    }
+}
+
+/// Compares bytes written to it against an existing file, without
+/// modifying the file.
+///
+/// A mismatch does not fail the write: the rest of the entry is still
+/// consumed so streaming formats stay in sync, and [`ContentMatch::finish`]
+/// reports the result.
+struct ContentMatch {
+    existing: BufReader<fs::File>,
+    scratch: Vec<u8>,
+    identical: bool,
+}
+
+impl ContentMatch {
+    fn new(existing: fs::File) -> Self {
+        Self {
+            existing: BufReader::new(existing),
+            scratch: Vec::new(),
+            identical: true,
+        }
+    }
+
+    /// True if everything written matched and the existing file has no
+    /// bytes left over.
+    fn finish(mut self) -> std::io::Result<bool> {
+        if !self.identical {
+            return Ok(false);
+        }
+        let mut probe = [0u8; 1];
+        Ok(self.existing.read(&mut probe)? == 0)
+    }
+}
+
+impl Write for ContentMatch {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.identical {
+            self.scratch.resize(buf.len(), 0);
+            match self.existing.read_exact(&mut self.scratch) {
+                Ok(()) => self.identical = self.scratch == buf,
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => self.identical = false,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Path from directory `from` to `to`; both must be absolute and normalized.
+fn relative_to(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<_> = from.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &to[common..] {
+        relative.push(component);
+    }
+    if relative.as_os_str().is_empty() {
+        relative.push(".");
     }
+    relative
 }
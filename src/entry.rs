@@ -4,7 +4,9 @@
 //! produce, enabling format-agnostic security policies.
 
 use std::io::Read;
-use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::policy::normal_depth;
 
 /// The type of entry in an archive.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -30,6 +32,12 @@ pub struct Entry<'a> {
     pub kind: EntryKind,
     /// Unix permissions (if available).
     pub mode: Option<u32>,
+    /// Owner user id recorded in the archive (if available).
+    pub uid: Option<u32>,
+    /// Owner group id recorded in the archive (if available).
+    pub gid: Option<u32>,
+    /// Whether `size` is reliable before decompression.
+    pub size_known: bool,
     /// A reader to access the entry's content.
     pub reader: Box<dyn Read + 'a>,
 }
@@ -58,9 +66,10 @@ impl<'a> Entry<'a> {
         }
     }
 
-    /// Returns the depth of the entry path (number of components).
+    /// Returns the depth of the entry path: the number of normal
+    /// components, as counted by the depth limit.
     pub fn depth(&self) -> usize {
-        Path::new(&self.name).components().count()
+        normal_depth(&self.name)
     }
 }
 
@@ -73,10 +82,46 @@ pub struct EntryInfo {
     pub name: String,
     /// The uncompressed size in bytes.
     pub size: u64,
+    /// The compressed size in bytes, for formats that compress each entry
+    /// on its own (ZIP). `None` for TAR, which compresses the archive as a
+    /// whole if at all, and 7z, whose entries share compressed blocks.
+    pub compressed_size: Option<u64>,
     /// The type of entry.
     pub kind: EntryKind,
     /// Unix permissions (if available).
     pub mode: Option<u32>,
+    /// Owner user id recorded in the archive (if available). Reported
+    /// only; extraction never changes file ownership.
+    pub uid: Option<u32>,
+    /// Owner group id recorded in the archive (if available). Reported
+    /// only, like `uid`.
+    pub gid: Option<u32>,
+    /// Last modification time recorded in the archive (if available).
+    /// Read from the NTFS or Unix timestamp extra fields for ZIP (the DOS
+    /// time every ZIP entry has carries no time zone, so it is not used),
+    /// the header or PAX `mtime` record for TAR, and the entry for 7z.
+    /// Reported only; extracted files get the current time.
+    pub mtime: Option<SystemTime>,
+    /// Whether `size` can be trusted before decompression. False for
+    /// entries whose real size is only discovered while streaming, such as
+    /// ZIP files declaring 0 bytes but carrying compressed data, or TAR
+    /// entries whose ustar size field disagrees with a PAX `size` record.
+    /// See [`UnknownSizePolicy`](crate::UnknownSizePolicy).
+    pub size_known: bool,
+    /// Extended attributes as `(name, value)` pairs, from TAR
+    /// `SCHILY.xattr.*` PAX records; empty for other formats. Only
+    /// restored with `Driver::restore_xattrs`.
+    pub xattrs: Vec<(String, Vec<u8>)>,
+}
+
+impl EntryInfo {
+    /// The target of a symlink entry; `None` for files and directories.
+    pub fn symlink_target(&self) -> Option<&str> {
+        match self.kind {
+            EntryKind::Symlink { ref target } => Some(target),
+            _ => None,
+        }
+    }
 }
 
 impl<'a> From<&Entry<'a>> for EntryInfo {
@@ -84,8 +129,36 @@ impl<'a> From<&Entry<'a>> for EntryInfo {
         Self {
             name: entry.name.clone(),
             size: entry.size,
+            compressed_size: None,
             kind: entry.kind.clone(),
             mode: entry.mode,
+            uid: entry.uid,
+            gid: entry.gid,
+            mtime: None,
+            size_known: entry.size_known,
+            xattrs: Vec::new(),
         }
     }
 }
+
+/// A time given as seconds and nanoseconds from the Unix epoch, which may
+/// be negative; `None` if it is out of `SystemTime`'s range.
+pub(crate) fn unix_time(secs: i64, nanos: u32) -> Option<SystemTime> {
+    let since_epoch = Duration::new(secs.unsigned_abs(), 0);
+    if secs >= 0 {
+        SystemTime::UNIX_EPOCH.checked_add(since_epoch + Duration::from_nanos(nanos.into()))
+    } else {
+        SystemTime::UNIX_EPOCH
+            .checked_sub(since_epoch)?
+            .checked_add(Duration::from_nanos(nanos.into()))
+    }
+}
+
+/// A Windows FILETIME (100 ns ticks since 1601-01-01 UTC), as used by ZIP
+/// NTFS extra fields and 7z.
+pub(crate) fn filetime(ticks: u64) -> Option<SystemTime> {
+    // Seconds from 1601-01-01 to 1970-01-01
+    const EPOCH_OFFSET: i64 = 11_644_473_600;
+    let secs = (ticks / 10_000_000) as i64 - EPOCH_OFFSET;
+    unix_time(secs, (ticks % 10_000_000) as u32 * 100)
+}
@@ -17,9 +17,18 @@ pub enum Error {
     /// Exceeded maximum total bytes.
     TotalSizeExceeded { limit: u64, would_be: u64 },
 
+    /// Archive (compressed input) is larger than allowed.
+    CompressedSizeExceeded { limit: u64, size: u64 },
+
     /// Exceeded maximum file count.
     FileCountExceeded { limit: usize, attempted: usize },
 
+    /// Exceeded maximum directory count.
+    DirCountExceeded { limit: usize, attempted: usize },
+
+    /// Exceeded maximum number of archive entries of any kind.
+    EntryCountExceeded { limit: usize, attempted: usize },
+
     /// Single file exceeds size limit.
     FileTooLarge {
         entry: String,
@@ -27,13 +36,31 @@ pub enum Error {
         size: u64,
     },
 
-    /// Actual decompressed size exceeds declared size (potential zip bomb).
+    /// Entry declares an uncompressed size more than
+    /// [`Limits::max_compression_ratio`](crate::Limits::max_compression_ratio)
+    /// times its compressed size.
+    CompressionRatioExceeded {
+        entry: String,
+        ratio: u64,
+        limit: u64,
+    },
+
+    /// Actual decompressed size differs from the declared size (potential
+    /// zip bomb, or a truncated entry). Reading stops one byte past the
+    /// declared size, so `actual` is at most `declared + 1`.
     SizeMismatch {
         entry: String,
         declared: u64,
         actual: u64,
     },
 
+    /// File's decompressed size is not known up front and
+    /// `UnknownSizePolicy::Reject` is set.
+    UnknownEntrySize { entry: String },
+
+    /// Entry name is absolute and `AbsolutePathPolicy::Reject` is set.
+    AbsolutePath { entry: String },
+
     /// Path exceeds depth limit.
     PathTooDeep {
         entry: String,
@@ -44,18 +71,98 @@ pub enum Error {
     /// File already exists and policy is Error.
     AlreadyExists { entry: String },
 
+    /// File already exists with different contents and policy is
+    /// ErrorIfDifferent.
+    ContentConflict { entry: String },
+
     /// Destination directory does not exist or is invalid.
     DestinationNotFound { path: String },
 
+    /// The destination's filesystem has less free space than the archive
+    /// declares it needs.
+    InsufficientSpace { required: u64, available: u64 },
+
     /// Filename contains invalid characters or reserved names.
     InvalidFilename { entry: String, reason: String },
 
+    /// Two entries differ only in case, so one would overwrite the other
+    /// on a case-insensitive filesystem.
+    CaseCollision {
+        existing: String,
+        conflicting: String,
+    },
+
+    /// The archive has more than one file or symlink with this name, so
+    /// the later one would overwrite (or collide with) the earlier.
+    DuplicateEntry { entry: String },
+
     /// Archive entry is encrypted (not supported).
+    ///
+    /// No longer returned for ZIP entries, which report
+    /// [`UnsupportedEncryption`](Self::UnsupportedEncryption) instead.
     EncryptedEntry { entry: String },
 
+    /// Archive entry is encrypted with a scheme this crate can't decrypt.
+    ///
+    /// `scheme` is `"ZipCrypto"` for traditional PKWARE encryption, or
+    /// `"AES-128"`/`"AES-192"`/`"AES-256"` (`"AES"` if the key size is
+    /// unknown) for WinZip AES.
+    UnsupportedEncryption { entry: String, scheme: String },
+
     /// Archive contains unsupported entry type (device file, fifo, etc.).
     UnsupportedEntryType { entry: String, entry_type: String },
 
+    /// File content (sniffed from its magic bytes) is not an allowed type.
+    DisallowedContent { entry: String, detected: String },
+
+    /// Entry uses a compression method outside the allowed set.
+    DisallowedCompression { entry: String, method: String },
+
+    /// ZIP end-of-central-directory record declares a different number of
+    /// entries than the central directory actually holds, which can hide
+    /// entries from parsers that trust either one.
+    EntryCountMismatch { declared: u64, actual: u64 },
+
+    /// Archive contents differ from the expected manifest.
+    ManifestMismatch {
+        /// Listed in the manifest but absent from the archive.
+        missing: Vec<String>,
+        /// In the archive but not listed (or listed once, present twice).
+        extra: Vec<String>,
+        /// Present in both with different sizes.
+        size_mismatches: Vec<crate::manifest::ManifestSizeMismatch>,
+    },
+
+    /// Extraction ran past the configured deadline.
+    DeadlineExceeded,
+
+    /// The flag passed to `cancel_on` was set.
+    Cancelled,
+
+    /// Copying a single entry's data took longer than the per-entry
+    /// deadline.
+    Timeout { entry: String },
+
+    /// Decompressing entry data used more time than the budget set with
+    /// [`Driver::max_decompress_cpu`](crate::Driver::max_decompress_cpu).
+    DecompressBudgetExceeded { limit: std::time::Duration },
+
+    /// The signature verifier rejected the archive.
+    SignatureInvalid { reason: String },
+
+    /// Archive headers declare a decoder window larger than allowed.
+    DecodeMemoryExceeded { limit: u64, required: u64 },
+
+    /// No entry has the name asked for.
+    EntryNotFound { name: String },
+
+    /// The entry asked for is a `"directory"` or `"symlink"`, not a file
+    /// with contents to extract.
+    NotAFile { entry: String, kind: String },
+
+    /// Builder options were combined in a way extraction cannot honour.
+    InvalidOption { option: String, reason: String },
+
     /// Zip format error.
     Zip(zip::result::ZipError),
 
@@ -83,6 +190,41 @@ fn format_bytes(bytes: u64) -> String {
     }
 }
 
+impl Error {
+    /// True if the archive itself broke a security rule or limit, as
+    /// opposed to an I/O failure or unreadable archive.
+    pub(crate) fn is_violation(&self) -> bool {
+        matches!(
+            self,
+            Self::PathEscape { .. }
+                | Self::AbsolutePath { .. }
+                | Self::SymlinkNotAllowed { .. }
+                | Self::TotalSizeExceeded { .. }
+                | Self::CompressedSizeExceeded { .. }
+                | Self::FileCountExceeded { .. }
+                | Self::DirCountExceeded { .. }
+                | Self::EntryCountExceeded { .. }
+                | Self::FileTooLarge { .. }
+                | Self::CompressionRatioExceeded { .. }
+                | Self::SizeMismatch { .. }
+                | Self::UnknownEntrySize { .. }
+                | Self::PathTooDeep { .. }
+                | Self::InvalidFilename { .. }
+                | Self::CaseCollision { .. }
+                | Self::DuplicateEntry { .. }
+                | Self::EncryptedEntry { .. }
+                | Self::UnsupportedEncryption { .. }
+                | Self::UnsupportedEntryType { .. }
+                | Self::DecodeMemoryExceeded { .. }
+                | Self::DisallowedContent { .. }
+                | Self::DisallowedCompression { .. }
+                | Self::EntryCountMismatch { .. }
+                | Self::ManifestMismatch { .. }
+                | Self::Jail(_)
+        )
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -112,6 +254,14 @@ impl fmt::Display for Error {
                     format_bytes(*limit)
                 )
             }
+            Self::CompressedSizeExceeded { limit, size } => {
+                write!(
+                    f,
+                    "archive is at least {}, exceeding the {} compressed input limit",
+                    format_bytes(*size),
+                    format_bytes(*limit)
+                )
+            }
             Self::FileCountExceeded { limit, attempted } => {
                 write!(
                     f,
@@ -119,6 +269,20 @@ impl fmt::Display for Error {
                     attempted, limit
                 )
             }
+            Self::DirCountExceeded { limit, attempted } => {
+                write!(
+                    f,
+                    "extraction stopped at directory {}: would exceed {} directory limit",
+                    attempted, limit
+                )
+            }
+            Self::EntryCountExceeded { limit, attempted } => {
+                write!(
+                    f,
+                    "archive has at least {} entries, exceeding the {} entry limit",
+                    attempted, limit
+                )
+            }
             Self::FileTooLarge { entry, limit, size } => {
                 write!(
                     f,
@@ -128,6 +292,17 @@ impl fmt::Display for Error {
                     format_bytes(*limit)
                 )
             }
+            Self::CompressionRatioExceeded {
+                entry,
+                ratio,
+                limit,
+            } => {
+                write!(
+                    f,
+                    "file '{}' expands {}x from its compressed size (limit: {}x)",
+                    entry, ratio, limit
+                )
+            }
             Self::SizeMismatch {
                 entry,
                 declared,
@@ -141,6 +316,16 @@ impl fmt::Display for Error {
                     format_bytes(*declared)
                 )
             }
+            Self::UnknownEntrySize { entry } => {
+                write!(
+                    f,
+                    "file '{}' does not declare a reliable size (rejected by unknown-size policy)",
+                    entry
+                )
+            }
+            Self::AbsolutePath { entry } => {
+                write!(f, "entry '{}' has an absolute path", entry)
+            }
             Self::PathTooDeep {
                 entry,
                 depth,
@@ -155,12 +340,37 @@ impl fmt::Display for Error {
             Self::AlreadyExists { entry } => {
                 write!(f, "file '{}' already exists", entry)
             }
+            Self::ContentConflict { entry } => {
+                write!(f, "file '{}' already exists with different contents", entry)
+            }
             Self::DestinationNotFound { path } => {
                 write!(f, "destination directory '{}' does not exist", path)
             }
+            Self::InsufficientSpace {
+                required,
+                available,
+            } => {
+                write!(
+                    f,
+                    "archive needs {} but only {} is free at the destination",
+                    format_bytes(*required),
+                    format_bytes(*available)
+                )
+            }
             Self::InvalidFilename { entry, reason } => {
                 write!(f, "invalid filename '{}': {}", entry, reason)
             }
+            Self::CaseCollision {
+                existing,
+                conflicting,
+            } => write!(
+                f,
+                "entry '{}' differs from '{}' only in case",
+                conflicting, existing
+            ),
+            Self::DuplicateEntry { entry } => {
+                write!(f, "entry '{}' appears more than once in the archive", entry)
+            }
             Self::EncryptedEntry { entry } => {
                 write!(
                     f,
@@ -168,6 +378,13 @@ impl fmt::Display for Error {
                     entry
                 )
             }
+            Self::UnsupportedEncryption { entry, scheme } => {
+                write!(
+                    f,
+                    "entry '{}' is encrypted with {} (encrypted archives not supported)",
+                    entry, scheme
+                )
+            }
             Self::UnsupportedEntryType { entry, entry_type } => {
                 write!(
                     f,
@@ -175,6 +392,78 @@ impl fmt::Display for Error {
                     entry, entry_type
                 )
             }
+            Self::DisallowedContent { entry, detected } => {
+                write!(
+                    f,
+                    "entry '{}' has disallowed content type '{}'",
+                    entry, detected
+                )
+            }
+            Self::DisallowedCompression { entry, method } => {
+                write!(
+                    f,
+                    "entry '{}' uses disallowed compression method '{}'",
+                    entry, method
+                )
+            }
+            Self::EntryCountMismatch { declared, actual } => {
+                write!(
+                    f,
+                    "archive declares {} entries but its central directory holds {}",
+                    declared, actual
+                )
+            }
+            Self::ManifestMismatch {
+                missing,
+                extra,
+                size_mismatches,
+            } => {
+                write!(f, "archive does not match manifest:")?;
+                let mut sep = " ";
+                if !missing.is_empty() {
+                    write!(f, "{}missing {}", sep, missing.join(", "))?;
+                    sep = "; ";
+                }
+                if !extra.is_empty() {
+                    write!(f, "{}unexpected {}", sep, extra.join(", "))?;
+                    sep = "; ";
+                }
+                for m in size_mismatches {
+                    write!(
+                        f,
+                        "{}'{}' is {} bytes, expected {}",
+                        sep, m.name, m.actual, m.expected
+                    )?;
+                    sep = "; ";
+                }
+                Ok(())
+            }
+            Self::DeadlineExceeded => write!(f, "extraction deadline exceeded"),
+            Self::Cancelled => write!(f, "extraction cancelled"),
+            Self::Timeout { entry } => {
+                write!(f, "entry '{}' exceeded the per-entry deadline", entry)
+            }
+            Self::DecompressBudgetExceeded { limit } => {
+                write!(f, "decompression exceeded its {:?} time budget", limit)
+            }
+            Self::SignatureInvalid { reason } => {
+                write!(f, "archive signature verification failed: {}", reason)
+            }
+            Self::DecodeMemoryExceeded { limit, required } => {
+                write!(
+                    f,
+                    "decoding requires a {} dictionary (limit: {})",
+                    format_bytes(*required),
+                    format_bytes(*limit)
+                )
+            }
+            Self::EntryNotFound { name } => write!(f, "no entry named '{}'", name),
+            Self::NotAFile { entry, kind } => {
+                write!(f, "entry '{}' is a {}, not a file", entry, kind)
+            }
+            Self::InvalidOption { option, reason } => {
+                write!(f, "invalid option '{}': {}", option, reason)
+            }
             Self::Zip(e) => write!(f, "zip format error: {}", e),
             Self::Io(e) => write!(f, "I/O error: {}", e),
             Self::Jail(e) => write!(f, "path validation error: {}", e),
@@ -201,6 +490,14 @@ impl From<zip::result::ZipError> for Error {
 }
 impl From<std::io::Error> for Error {
     fn from(e: std::io::Error) -> Self {
+        // Limit errors raised inside a `Read` impl (`EntryStream` readers)
+        // have to travel as io::Error; unwrap them back out
+        if e.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            if let Some(Ok(inner)) = e.into_inner().map(|inner| inner.downcast::<Error>()) {
+                return *inner;
+            }
+            unreachable!("checked by get_ref");
+        }
         Self::Io(e)
     }
 }
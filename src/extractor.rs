@@ -1,9 +1,25 @@
+use crate::adapter::{
+    check_central_directory, check_declared_size, raw_entry_info, reject_encrypted, CountingReader,
+};
+use crate::driver::remove_created;
+use crate::entry::EntryKind;
 use crate::error::Error;
-use crate::limits::Limits;
+use crate::interrupt::Interrupt;
+use crate::limits::{check_compression_ratio, check_free_space, Limits};
+use crate::policy::{
+    case_collision, has_windows_reserved_name, is_deceptive_char, normal_depth, normal_path,
+    prefixed, relative_dir, resolve_link_target, strip_components, NameMap, PermissionMode,
+};
+use crate::text::{TextConvert, TextWriter};
 use path_jail::Jail;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::{Read, Seek};
-use std::path::{Component, Path};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
 /// What to do when a file already exists at the extraction path.
 ///
@@ -40,6 +56,15 @@ pub enum SymlinkPolicy {
     Skip,
     /// Fail extraction if archive contains any symlinks.
     Error,
+    /// Create symlinks whose relative target resolves inside the
+    /// destination. The target is resolved from the link's real parent
+    /// directory, so links created earlier cannot be chained to escape.
+    /// Absolute and escaping targets fail with [`Error::PathEscape`] and
+    /// are never created.
+    ///
+    /// Symlinks are only created on Unix; elsewhere they are skipped and
+    /// counted in `entries_skipped`.
+    Allow,
 }
 
 /// Extraction strategy.
@@ -50,10 +75,11 @@ pub enum SymlinkPolicy {
 /// |------|-------|------------|----------|
 /// | `Streaming` | Fast (1 pass) | Partial files remain on disk | Speed matters; you'll clean up on error |
 /// | `ValidateFirst` | Slower (2 passes) | No files written if validation fails | Can't tolerate partial state |
+/// | `ValidateFirstDeep` | Slowest (decompresses twice) | No files written if validation or a CRC check fails | Can't tolerate partial state from corrupt data |
 ///
 /// ## Important Limitations
 ///
-/// **Neither mode is truly atomic.** If extraction fails mid-write (e.g., disk full),
+/// **No mode is truly atomic.** If extraction fails mid-write (e.g., disk full),
 /// partial files will remain regardless of mode. `ValidateFirst` only prevents writes
 /// when *validation* fails (bad paths, exceeded limits, etc.), not when I/O fails.
 ///
@@ -76,6 +102,93 @@ pub enum ExtractionMode {
     /// against all entries, which is conservative—validation may reject archives that
     /// would succeed with filtering.
     ValidateFirst,
+
+    /// Like `ValidateFirst`, and also decompress every entry to check its
+    /// CRC before anything is written.
+    ///
+    /// **Tradeoff:** `ValidateFirst` only reads metadata, so an entry whose
+    /// compressed data is corrupt still fails halfway through extraction.
+    /// This mode catches it up front, at the cost of decompressing the
+    /// whole archive twice. [`Extractor::verify_then_extract`] avoids the
+    /// second pass for archives small enough to keep in memory.
+    ValidateFirstDeep,
+}
+
+/// ZIP compression method, as restricted by
+/// [`Extractor::allowed_compression_methods`].
+///
+/// Parses from and displays as the lowercase name (`"deflate"`,
+/// `"stored"`, ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CompressionMethod {
+    /// No compression (method 0).
+    Stored,
+    /// Deflate (method 8).
+    Deflate,
+    /// Deflate64 (method 9).
+    Deflate64,
+    /// BZIP2 (method 12).
+    Bzip2,
+    /// LZMA (method 14).
+    Lzma,
+    /// Zstandard (method 93).
+    Zstd,
+    /// XZ (method 95).
+    Xz,
+    /// Any other method, by its numeric id.
+    Other(u16),
+}
+
+impl CompressionMethod {
+    fn from_zip(method: zip::CompressionMethod) -> Self {
+        #[allow(deprecated)]
+        match method.to_u16() {
+            0 => Self::Stored,
+            8 => Self::Deflate,
+            9 => Self::Deflate64,
+            12 => Self::Bzip2,
+            14 => Self::Lzma,
+            93 => Self::Zstd,
+            95 => Self::Xz,
+            id => Self::Other(id),
+        }
+    }
+}
+
+impl std::fmt::Display for CompressionMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stored => f.write_str("stored"),
+            Self::Deflate => f.write_str("deflate"),
+            Self::Deflate64 => f.write_str("deflate64"),
+            Self::Bzip2 => f.write_str("bzip2"),
+            Self::Lzma => f.write_str("lzma"),
+            Self::Zstd => f.write_str("zstd"),
+            Self::Xz => f.write_str("xz"),
+            Self::Other(id) => write!(f, "method {}", id),
+        }
+    }
+}
+
+impl std::str::FromStr for CompressionMethod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "stored" | "store" => Ok(Self::Stored),
+            "deflate" | "deflated" => Ok(Self::Deflate),
+            "deflate64" => Ok(Self::Deflate64),
+            "bzip2" => Ok(Self::Bzip2),
+            "lzma" => Ok(Self::Lzma),
+            "zstd" => Ok(Self::Zstd),
+            "xz" => Ok(Self::Xz),
+            _ => Err(Error::InvalidOption {
+                option: "compression method".to_string(),
+                reason: format!("unknown method '{}'", s),
+            }),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -83,7 +196,39 @@ pub struct Report {
     pub files_extracted: usize,
     pub dirs_created: usize,
     pub bytes_written: u64,
+    /// Bytes consumed from the source reader (central directory, local
+    /// headers, and compressed entry data).
+    pub bytes_read: u64,
     pub entries_skipped: usize,
+    /// Symlinks created under [`SymlinkPolicy::Allow`].
+    pub symlinks_created: usize,
+    /// True if the archive had no entries at all, as opposed to every entry
+    /// being filtered out or skipped.
+    pub was_empty: bool,
+    /// Paths of the files, directories, and symlinks created, in extraction
+    /// order. Only filled in with [`Extractor::collect_paths`].
+    pub extracted_paths: Vec<PathBuf>,
+}
+
+/// What [`Extractor::plan`] found extraction would do, without doing it.
+///
+/// The counts follow the same rules as [`Report`]: entries the filter
+/// rejects are not in `entries`, and symlinks are only included when
+/// [`SymlinkPolicy::Allow`] would create them.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionPlan {
+    /// Files that would be written.
+    pub total_files: usize,
+    /// Declared size of those files.
+    pub total_bytes: u64,
+    /// Directory entries that would be created.
+    pub total_dirs: usize,
+    /// Symlinks that would be created.
+    pub symlink_count: usize,
+    /// Entries the filter rejects.
+    pub skipped_by_filter: usize,
+    /// The entries that would be extracted, in archive order.
+    pub entries: Vec<crate::entry::EntryInfo>,
 }
 
 /// Report returned by `verify()`.
@@ -103,7 +248,8 @@ pub struct EntryInfo<'a> {
     pub is_symlink: bool,
 }
 
-/// Progress information passed to callbacks.
+/// Progress information passed to [`Extractor::on_progress`] and
+/// [`Driver::on_progress`](crate::Driver::on_progress) callbacks.
 #[derive(Debug, Clone)]
 pub struct Progress {
     /// Name of the current entry being processed.
@@ -112,7 +258,8 @@ pub struct Progress {
     pub entry_size: u64,
     /// Index of the current entry (0-based).
     pub entry_index: usize,
-    /// Total number of entries in the archive.
+    /// Total number of entries in the archive, or 0 if it is not known
+    /// before the end (streaming TAR extraction).
     pub total_entries: usize,
     /// Bytes written so far (cumulative).
     pub bytes_written: u64,
@@ -120,6 +267,9 @@ pub struct Progress {
     pub files_extracted: usize,
 }
 
+/// Default for [`Extractor::verify_buffer_limit`].
+const DEFAULT_VERIFY_BUFFER_LIMIT: u64 = 64 * 1024 * 1024;
+
 pub struct Extractor {
     root: std::path::PathBuf,
     jail: Jail,
@@ -133,6 +283,44 @@ pub struct Extractor {
     // Progress callback
     #[allow(clippy::type_complexity)]
     on_progress: Option<Box<dyn Fn(&Progress) + Send + Sync>>,
+    // Early-stop conditions (deadline)
+    interrupt: Interrupt,
+    // Reject bidi/zero-width characters in names
+    reject_deceptive_unicode: bool,
+    // Reject CON, NUL, COM1, ... as name components
+    windows_name_check: bool,
+    // Fail on a file or symlink name that appears twice
+    reject_duplicate_entries: bool,
+    // Fail on file or symlink names that differ only in case
+    reject_case_collisions: bool,
+    // Leading name components dropped from every entry
+    strip_components: usize,
+    // Rewrites entry names; None skips the entry
+    map_name: Option<NameMap>,
+    // Directory every entry is extracted under
+    prefix: Option<String>,
+    // List created paths in the report
+    collect_paths: bool,
+    // Where applied modes come from
+    permission_mode: PermissionMode,
+    // Clear execute bits on extracted files
+    strip_exec: bool,
+    // Copy entry modification times onto extracted files
+    preserve_mtime: bool,
+    // Used when an entry's timestamp is missing or unrepresentable
+    default_mtime: Option<SystemTime>,
+    // Compression methods entries may use; None allows any supported one
+    allowed_methods: Option<Vec<CompressionMethod>>,
+    // Leave holes for zero blocks instead of writing them (Unix)
+    sparse: bool,
+    // Line ending conversion for text entries
+    text_convert: Option<TextConvert>,
+    // Remove what a failed extraction created
+    cleanup_on_error: bool,
+    // Declared bytes verify_then_extract may hold in memory
+    verify_buffer_limit: u64,
+    // Check free space against the declared total before writing
+    ensure_free_space: bool,
 }
 
 impl Extractor {
@@ -196,6 +384,25 @@ impl Extractor {
             mode: ExtractionMode::default(),
             filter: None,
             on_progress: None,
+            interrupt: Interrupt::default(),
+            reject_deceptive_unicode: false,
+            windows_name_check: cfg!(windows),
+            reject_duplicate_entries: false,
+            reject_case_collisions: false,
+            strip_components: 0,
+            map_name: None,
+            prefix: None,
+            collect_paths: false,
+            permission_mode: PermissionMode::FromArchive,
+            strip_exec: false,
+            preserve_mtime: false,
+            default_mtime: None,
+            sparse: false,
+            text_convert: None,
+            allowed_methods: None,
+            cleanup_on_error: false,
+            verify_buffer_limit: DEFAULT_VERIFY_BUFFER_LIMIT,
+            ensure_free_space: false,
         })
     }
 
@@ -219,6 +426,303 @@ impl Extractor {
         self
     }
 
+    /// Reject filenames containing bidi control, zero-width, or other
+    /// invisible characters.
+    ///
+    /// Such names can disguise a file's real extension: `photo\u{202E}gpj.exe`
+    /// displays as `photoexe.jpg`. Off by default because zero-width joiners
+    /// are legitimate in some scripts; enable it for untrusted archives.
+    /// Rejected entries fail with [`Error::InvalidFilename`].
+    pub fn reject_deceptive_unicode(mut self, reject: bool) -> Self {
+        self.reject_deceptive_unicode = reject;
+        self
+    }
+
+    /// Reject names with a component Windows treats as a device, such as
+    /// `CON`, `NUL`, `COM1`, or `aux.py`, with [`Error::InvalidFilename`].
+    ///
+    /// On by default when built for Windows, where writing one opens the
+    /// device instead of creating a file, and off elsewhere, where
+    /// `aux.py` is a perfectly good name. Turn it on to keep archives
+    /// portable to Windows, or off to accept such names anyway.
+    pub fn windows_name_check(mut self, check: bool) -> Self {
+        self.windows_name_check = check;
+        self
+    }
+
+    /// Fail with [`Error::DuplicateEntry`] when a file or symlink name
+    /// appears a second time in the archive, instead of letting the
+    /// overwrite policy decide. Off by default.
+    ///
+    /// `a.txt` and `./a.txt` count as the same name. Entries the filter
+    /// skips still count; directories may repeat. Exact repeats are found
+    /// in the central directory before anything is extracted.
+    pub fn reject_duplicate_entries(mut self, reject: bool) -> Self {
+        self.reject_duplicate_entries = reject;
+        self
+    }
+
+    /// Fail with [`Error::CaseCollision`] when two file or symlink names
+    /// differ only in case, like `README` and `readme`. Off by default.
+    ///
+    /// Both extract fine on Linux, but on a case-insensitive filesystem
+    /// (the macOS and Windows defaults) one clobbers the other. Entries the
+    /// filter skips don't count, and directories are not compared. With
+    /// [`ExtractionMode::ValidateFirst`] a collision fails the archive
+    /// before anything is written.
+    pub fn reject_case_collisions(mut self, reject: bool) -> Self {
+        self.reject_case_collisions = reject;
+        self
+    }
+
+    /// Drop the first `count` components of every entry name, as
+    /// `tar --strip-components` does. Default: 0.
+    ///
+    /// With 1, `project-1.2.3/src/main.rs` is extracted to `src/main.rs`,
+    /// and `project-1.2.3/` itself, with nothing left, is skipped and
+    /// counted in `entries_skipped`. Stripping happens before any check,
+    /// so the filter and the path jail see the stripped name. A `..` is
+    /// never stripped: `a/../../x` still fails with [`Error::PathEscape`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    ///
+    /// let report = Extractor::new("/src/project")?
+    ///     .strip_components(1)
+    ///     .extract_file("project-1.2.3.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn strip_components(mut self, count: usize) -> Self {
+        self.strip_components = count;
+        self
+    }
+
+    /// Rename entries with `f`, which gets each name and returns the name
+    /// to extract it under, or `None` to skip the entry. Directory names
+    /// keep their trailing `/`.
+    ///
+    /// `f` runs after [`strip_components`](Self::strip_components) and
+    /// before the [`prefix`](Self::prefix) is added; the filter, the
+    /// filename checks, and the path jail all come after, so whatever `f`
+    /// returns is validated like a name read from the archive. When two
+    /// entries map to the same name the [`OverwritePolicy`] decides, and
+    /// the default fails with [`Error::AlreadyExists`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    ///
+    /// // "My Photos/IMG 001.JPG" -> "My_Photos/IMG_001.JPG"
+    /// let report = Extractor::new("/srv/photos")?
+    ///     .map_name(|name| Some(name.replace(' ', "_")))
+    ///     .extract_file("photos.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn map_name<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.map_name = Some(Box::new(f));
+        self
+    }
+
+    /// Extract every entry under `prefix`, a directory relative to the
+    /// destination, so `foo.zip` can be unpacked into `<dest>/foo/`
+    /// alongside other archives. The prefix is added after
+    /// [`strip_components`](Self::strip_components) and
+    /// [`map_name`](Self::map_name), and is part of the
+    /// name the filter and the report see.
+    ///
+    /// Fails with [`Error::InvalidOption`] if `prefix` is absolute or
+    /// contains `..`. Entries can't use `..` to leave the prefix: `../x`
+    /// fails with [`Error::PathEscape`] as it would without one.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    ///
+    /// // hello.txt -> /srv/unpacked/foo/hello.txt
+    /// let report = Extractor::new("/srv/unpacked")?
+    ///     .prefix("foo")?
+    ///     .extract_file("foo.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn prefix<P: AsRef<Path>>(mut self, prefix: P) -> Result<Self, Error> {
+        let prefix = prefix.as_ref();
+        self.prefix = relative_dir(prefix).map_err(|reason| Error::InvalidOption {
+            option: "prefix".to_string(),
+            reason: format!("'{}' {}", prefix.display(), reason),
+        })?;
+        Ok(self)
+    }
+
+    /// Clear the execute bits of every extracted file, so nothing from the
+    /// archive can be run directly. Unix only; off by default.
+    pub fn strip_exec(mut self, strip: bool) -> Self {
+        self.strip_exec = strip;
+        self
+    }
+
+    /// Choose where the modes of extracted files and directories come from
+    /// (Unix only). Default: [`PermissionMode::FromArchive`], the modes
+    /// stored in the archive. [`strip_exec`](Self::strip_exec) still
+    /// applies on top of whatever this gives.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{Extractor, PermissionMode};
+    ///
+    /// // Never group or world writable, whatever the archive says
+    /// let report = Extractor::new("/srv/uploads")?
+    ///     .permission_mode(PermissionMode::Mask(0o755))
+    ///     .extract_file("upload.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn permission_mode(mut self, mode: PermissionMode) -> Self {
+        self.permission_mode = mode;
+        self
+    }
+
+    /// Apply every hardening option at once, for archives from untrusted
+    /// sources. This sets:
+    ///
+    /// - [`Limits::strict`]
+    /// - [`OverwritePolicy::Error`]
+    /// - [`SymlinkPolicy::Error`], so archives with symlinks are rejected
+    /// - [`ExtractionMode::ValidateFirstDeep`], so nothing is written
+    ///   unless every entry passes, CRC checks included
+    /// - [`Self::reject_deceptive_unicode`]
+    /// - [`Self::windows_name_check`]
+    /// - [`Self::strip_exec`]
+    ///
+    /// Path traversal and absolute paths are rejected regardless, and files
+    /// are never written through a symlink that leads outside the
+    /// destination. Options set after this call override the hardened
+    /// values.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    ///
+    /// let report = Extractor::new("/var/uploads")?
+    ///     .hardened()
+    ///     .extract_file("upload.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn hardened(self) -> Self {
+        self.limits(Limits::strict())
+            .overwrite(OverwritePolicy::Error)
+            .symlinks(SymlinkPolicy::Error)
+            .mode(ExtractionMode::ValidateFirstDeep)
+            .reject_deceptive_unicode(true)
+            .windows_name_check(true)
+            .strip_exec(true)
+    }
+
+    /// Set each extracted file's modification time from the archive entry.
+    ///
+    /// Off by default: files get the extraction time. ZIP stores MS-DOS
+    /// timestamps, which are read as UTC with two-second precision.
+    ///
+    /// Directory entries get their time too. It is applied after every
+    /// entry has been written, deepest directory first, so files extracted
+    /// into a directory do not overwrite its timestamp.
+    ///
+    /// Entries whose timestamp is zero, invalid, or the 1980-01-01 00:00:00
+    /// minimum (which tools write for pre-1980 or unknown dates) are treated
+    /// as having no timestamp. Those files keep the extraction time, or get
+    /// [`Self::default_mtime`] if set.
+    pub fn preserve_mtime(mut self, preserve: bool) -> Self {
+        self.preserve_mtime = preserve;
+        self
+    }
+
+    /// Modification time for entries without a usable timestamp when
+    /// [`Self::preserve_mtime`] is on.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    /// use std::time::SystemTime;
+    ///
+    /// // Undated entries sort as oldest rather than as "just extracted"
+    /// let report = Extractor::new("/tmp/out")?
+    ///     .preserve_mtime(true)
+    ///     .default_mtime(SystemTime::UNIX_EPOCH)
+    ///     .extract_file("archive.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn default_mtime(mut self, mtime: SystemTime) -> Self {
+        self.default_mtime = Some(mtime);
+        self
+    }
+
+    /// Write extracted files as sparse files: blocks of zero bytes are
+    /// skipped with a seek instead of being written, so the filesystem
+    /// leaves holes there. A mostly empty disk image or database dump then
+    /// takes up only the space of its data. File sizes and contents read
+    /// back are unchanged.
+    ///
+    /// Off by default. Unix only; elsewhere files are written in full.
+    /// Whether holes actually save space depends on the filesystem.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    ///
+    /// let report = Extractor::new("/var/lib/images")?
+    ///     .sparse(true)
+    ///     .extract_file("vm.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn sparse(mut self, sparse: bool) -> Self {
+        self.sparse = sparse;
+        self
+    }
+
+    /// Convert line endings of entries with the given extensions while
+    /// writing them, e.g. CRLF to LF for Windows sources extracted on Unix.
+    ///
+    /// Off by default. An entry whose first 8000 bytes contain a NUL is
+    /// treated as binary and written unchanged, whatever its name. Size
+    /// limits apply to the converted output, which can be larger than the
+    /// entry when converting to CRLF; `bytes_written` reports it too.
+    pub fn text_convert(mut self, convert: TextConvert) -> Self {
+        self.text_convert = Some(convert);
+        self
+    }
+
+    /// Only accept entries compressed with one of `methods`, shrinking the
+    /// decompressor code that untrusted input can reach. Anything else
+    /// fails with [`Error::DisallowedCompression`] before its data is
+    /// decompressed. In `ValidateFirst` mode the whole archive is checked
+    /// before anything is written.
+    ///
+    /// Default: every method this build can decompress.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{CompressionMethod, Extractor};
+    ///
+    /// let report = Extractor::new("/tmp/out")?
+    ///     .allowed_compression_methods(&[CompressionMethod::Deflate, CompressionMethod::Stored])
+    ///     .extract_file("upload.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn allowed_compression_methods(mut self, methods: &[CompressionMethod]) -> Self {
+        self.allowed_methods = Some(methods.to_vec());
+        self
+    }
+
     pub fn filter<F>(mut self, f: F) -> Self
     where
         F: Fn(&EntryInfo) -> bool + Send + Sync + 'static,
@@ -335,270 +839,754 @@ impl Extractor {
         self
     }
 
+    /// Give up with [`Error::DeadlineExceeded`] once `deadline` has passed.
+    ///
+    /// Checked before each entry and between chunks of file data. Files
+    /// already extracted are left in place.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let report = Extractor::new("/tmp/out")?
+    ///     .deadline(Instant::now() + Duration::from_secs(30))
+    ///     .extract_file("upload.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.interrupt.deadline = Some(deadline);
+        self
+    }
+
+    /// Stop with [`Error::Cancelled`] once `flag` is set, e.g. from a
+    /// cancel button on another thread.
+    ///
+    /// Best effort: the flag is checked before each entry and between
+    /// chunks of file data, so extraction stops soon after, not at once.
+    /// Files already extracted are left in place unless
+    /// [`cleanup_on_error`](Self::cleanup_on_error) is on.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let cancel = Arc::new(AtomicBool::new(false));
+    /// let extractor = Extractor::new("/tmp/out")?
+    ///     .cancel_on(Arc::clone(&cancel))
+    ///     .cleanup_on_error(true);
+    /// // Elsewhere: cancel.store(true, Ordering::Relaxed);
+    /// let result = extractor.extract_file("large.zip");
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn cancel_on(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.interrupt.cancel = Some(flag);
+        self
+    }
+
+    /// Remove the files, directories, and symlinks an extraction created if
+    /// it fails, before returning the error. Off by default.
+    ///
+    /// Useful in [`ExtractionMode::Streaming`], and in `ValidateFirst` for
+    /// what validation cannot see, such as a CRC mismatch found while
+    /// decompressing. Anything that existed before the call stays,
+    /// including directories the archive wrote into and files
+    /// [`OverwritePolicy::Overwrite`] replaced.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    ///
+    /// let result = Extractor::new("/srv/app")?
+    ///     .cleanup_on_error(true)
+    ///     .extract_file("release.zip");
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn cleanup_on_error(mut self, enabled: bool) -> Self {
+        self.cleanup_on_error = enabled;
+        self
+    }
+
+    /// List every file, directory, and symlink created in
+    /// [`Report::extracted_paths`], in the order they were extracted, so
+    /// post-processing can touch exactly the new files without rescanning
+    /// the destination. Skipped and filtered entries are left out. Off by
+    /// default, since the list grows with the archive.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    ///
+    /// let report = Extractor::new("/srv/uploads")?
+    ///     .collect_paths(true)
+    ///     .extract_file("upload.zip")?;
+    /// for path in &report.extracted_paths {
+    ///     println!("new: {}", path.display());
+    /// }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn collect_paths(mut self, collect: bool) -> Self {
+        self.collect_paths = collect;
+        self
+    }
+
+    /// Before writing anything, check that the destination's filesystem
+    /// has room for the declared size of every file, and fail with
+    /// [`Error::InsufficientSpace`] if not, instead of running out of space
+    /// halfway through.
+    ///
+    /// Off by default. The total is worked out by the `ValidateFirst` pass,
+    /// so extracting in [`ExtractionMode::Streaming`] fails with
+    /// [`Error::InvalidOption`]. Declared sizes come from the archive and
+    /// can understate what an entry inflates to; the size limits are still
+    /// enforced on what is actually written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::{ExtractionMode, Extractor};
+    ///
+    /// let report = Extractor::new("/mnt/scratch")?
+    ///     .mode(ExtractionMode::ValidateFirst)
+    ///     .ensure_free_space(true)
+    ///     .extract_file("dataset.zip")?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn ensure_free_space(mut self, ensure: bool) -> Self {
+        self.ensure_free_space = ensure;
+        self
+    }
+
+    /// Most declared bytes [`verify_then_extract`](Self::verify_then_extract)
+    /// keeps in memory to avoid decompressing the archive twice. Defaults
+    /// to 64 MiB.
+    pub fn verify_buffer_limit(mut self, bytes: u64) -> Self {
+        self.verify_buffer_limit = bytes;
+        self
+    }
+
     pub fn extract<R: Read + Seek>(&self, reader: R) -> Result<Report, Error> {
+        let mut walk = Walk::new();
+        let result = self.extract_entries(reader, false, &mut walk);
+        self.finish(result, walk)
+    }
+
+    /// Extract only after every entry's CRC has been checked, so a corrupt
+    /// archive fails before anything is written.
+    ///
+    /// [`extract`](Self::extract) finds a bad CRC only when it reaches the
+    /// end of that entry, after earlier entries, and the bad one itself,
+    /// are already on disk. Here the limits, paths and filter are checked
+    /// from the metadata, as [`ExtractionMode::ValidateFirst`] does, then
+    /// every entry to be extracted is decompressed and checked. If the declared sizes of all entries add up to no more than
+    /// [`verify_buffer_limit`](Self::verify_buffer_limit), the checked data
+    /// is kept in memory and written from there, decompressing each entry
+    /// once. Larger archives are decompressed twice: once to check, as
+    /// [`verify`](Self::verify) does, then again to extract.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    /// use std::fs::File;
+    ///
+    /// let report = Extractor::new("/srv/uploads")?
+    ///     .verify_buffer_limit(16 * 1024 * 1024)
+    ///     .verify_then_extract(File::open("upload.zip")?)?;
+    /// println!("{} files", report.files_extracted);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn verify_then_extract<R: Read + Seek>(&self, reader: R) -> Result<Report, Error> {
+        let mut walk = Walk::new();
+        let result = self.extract_entries(reader, true, &mut walk);
+        self.finish(result, walk)
+    }
+
+    /// Extract a ZIP read front to back from a reader that can't seek,
+    /// such as a socket or an HTTP response body, without buffering it.
+    ///
+    /// Entries are taken from their local headers as they arrive and go
+    /// through the same checks as [`extract`](Self::extract). Sizes are
+    /// checked against the limits before each entry is written, and the
+    /// copy stops with [`Error::SizeMismatch`] as soon as an entry
+    /// decompresses to more than its header declared. Compared to
+    /// `extract`:
+    ///
+    /// - [`ExtractionMode::ValidateFirst`] is unavailable, since the input
+    ///   can't be read twice, and fails with [`Error::InvalidOption`].
+    /// - Local headers don't record file types or modes, so a symlink
+    ///   entry is written as a regular file holding its target, and
+    ///   permissions are left at their defaults.
+    /// - Entries whose sizes only follow in a data descriptor, and
+    ///   encrypted entries, can't be streamed and fail with [`Error::Zip`].
+    /// - The central directory is never read. Entries it leaves out, which
+    ///   other tools would not show, are extracted too.
+    /// - [`Progress::total_entries`] is 0, since the count isn't known.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    /// use std::net::TcpStream;
+    ///
+    /// let upload = TcpStream::connect("10.0.0.5:9000")?;
+    /// let report = Extractor::new("/srv/uploads")?.extract_stream(upload)?;
+    /// println!("{} files", report.files_extracted);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn extract_stream<R: Read>(&self, reader: R) -> Result<Report, Error> {
+        let mut walk = Walk::new();
+        let result = self.extract_stream_entries(reader, &mut walk);
+        self.finish(result, walk)
+    }
+
+    /// Undo what a failed extraction created, if cleanup is on.
+    fn finish(&self, result: Result<Report, Error>, walk: Walk) -> Result<Report, Error> {
+        if self.cleanup_on_error && result.is_err() {
+            remove_created(walk.created);
+        }
+        result
+    }
+
+    /// Record `path` and its ancestors below the destination that don't
+    /// exist yet, if a failed extraction is to be cleaned up.
+    fn track_new(&self, walk: &mut Walk, path: &Path) {
+        if self.cleanup_on_error {
+            let new = path
+                .ancestors()
+                .take_while(|p| *p != self.root && fs::symlink_metadata(p).is_err());
+            walk.created.extend(new.map(Path::to_path_buf));
+        }
+    }
+
+    fn extract_entries<R: Read + Seek>(
+        &self,
+        mut reader: R,
+        verify_first: bool,
+        walk: &mut Walk,
+    ) -> Result<Report, Error> {
+        if self.ensure_free_space && matches!(self.mode, ExtractionMode::Streaming) {
+            return Err(Error::InvalidOption {
+                option: "ensure_free_space".to_string(),
+                reason: "needs ValidateFirst to know the total size before writing".to_string(),
+            });
+        }
+
+        // Bound the input before anything is decompressed
+        let start = reader.stream_position()?;
+        let archive_len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+        self.limits.check_compressed(archive_len)?;
+        let repeated = check_central_directory(&mut reader)?;
+        if let Some(entry) = repeated.filter(|_| self.reject_duplicate_entries) {
+            return Err(Error::DuplicateEntry { entry });
+        }
+
+        let reader = CountingReader::new(reader);
+        let bytes_read = reader.counter();
         let mut archive = zip::ZipArchive::new(reader)?;
+        self.limits.check_entry_count(archive.len())?;
 
-        // If ValidateFirst mode, do a dry run first
-        if matches!(self.mode, ExtractionMode::ValidateFirst) {
+        // If ValidateFirst mode, do a dry run first. Verifying decompresses
+        // everything, so it needs the limits checked beforehand too.
+        if verify_first || !matches!(self.mode, ExtractionMode::Streaming) {
             self.validate_all(&mut archive)?;
         }
+        let verified = if verify_first {
+            self.verify_entries(&mut archive, self.verify_buffer_limit)?
+        } else {
+            if matches!(self.mode, ExtractionMode::ValidateFirstDeep) {
+                self.verify_entries(&mut archive, 0)?;
+            }
+            None
+        };
 
-        let mut report = Report::default();
-        let mut total_bytes_written: u64 = 0;
+        walk.report.was_empty = archive.is_empty();
         let total_entries = archive.len();
 
         for i in 0..total_entries {
-            let mut entry = archive.by_index(i)?;
-            let name = entry.name().to_string();
+            self.interrupt.check()?;
 
-            // Call progress callback if set
-            if let Some(ref callback) = self.on_progress {
-                callback(&Progress {
-                    entry_name: name.clone(),
-                    entry_size: entry.size(),
-                    entry_index: i,
-                    total_entries,
-                    bytes_written: total_bytes_written,
-                    files_extracted: report.files_extracted,
-                });
+            // Already checked, and decompressed, by verify_entries
+            if let Some(ref data) = verified {
+                let mut entry = archive.by_index_raw(i)?;
+                self.extract_entry(&mut entry, Some(&data[i]), i, total_entries, walk)?;
+                continue;
             }
 
-            // 0. SECURITY: Filename Sanitization
-            if let Err(reason) = self.validate_filename(&name) {
-                return Err(Error::InvalidFilename {
-                    entry: name,
-                    reason: reason.to_string(),
-                });
+            // Look at the raw entry before a decompressor is set up for it
+            {
+                let raw = archive.by_index_raw(i)?;
+                reject_encrypted(&raw)?;
+                self.check_compression(raw.name(), raw.compression())?;
             }
 
-            // 1. SECURITY: Path Validation (Path Jail)
-            // We check this FIRST to ensure the path is safe (doesn't escape root).
-            // NOTE: We discard the returned path because Jail might resolve symlinks (e.g. on overwrite),
-            // which causes us to overwrite the target instead of the symlink.
-            // We construct the path manually relative to root, which is safe because Jail verified it.
-            let _ = self.jail.join(&name).map_err(|e| Error::PathEscape {
-                entry: name.clone(),
-                detail: e.to_string(),
-            })?;
+            let mut entry = archive.by_index(i)?;
+            self.extract_entry(&mut entry, None, i, total_entries, walk)?;
+        }
 
-            // Construct safe_path manually to preserve symlinks in the path
-            let safe_path = self.root.join(&name);
+        walk.finish(bytes_read.load(Ordering::Relaxed))
+    }
 
-            // 2. CHECK: Symlinks
-            if entry.is_symlink() {
-                match self.symlinks {
-                    SymlinkPolicy::Error => {
-                        return Err(Error::SymlinkNotAllowed {
-                            entry: name,
-                            target: String::new(), // ZIP symlink targets require reading content
-                        });
-                    }
-                    SymlinkPolicy::Skip => {
-                        report.entries_skipped += 1;
-                        continue;
-                    }
-                }
-            }
+    fn extract_stream_entries<R: Read>(&self, reader: R, walk: &mut Walk) -> Result<Report, Error> {
+        const EOCD_SIG: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
 
-            // 3. CHECK: Limits (Depth)
-            // Count normal components to check depth
-            let depth = Path::new(&name)
-                .components()
-                .filter(|c| matches!(c, Component::Normal(_)))
-                .count();
-            if depth > self.limits.max_path_depth {
-                return Err(Error::PathTooDeep {
-                    entry: name,
-                    depth,
-                    limit: self.limits.max_path_depth,
-                });
-            }
+        if !matches!(self.mode, ExtractionMode::Streaming) {
+            return Err(Error::InvalidOption {
+                option: "mode".to_string(),
+                reason: "ValidateFirst needs a seekable reader; use extract".to_string(),
+            });
+        }
 
-            // 4. CHECK: Filter (User Logic)
-            let info = EntryInfo {
-                name: &name,
-                size: entry.size(),
-                compressed_size: entry.compressed_size(),
-                is_dir: entry.is_dir(),
-                is_symlink: entry.is_symlink(),
+        let mut reader = CountingReader::new(reader);
+        let bytes_read = reader.counter();
+
+        // An empty archive is only its end record, which the zip crate's
+        // stream reader takes for a truncated local header
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic == EOCD_SIG {
+            walk.report.was_empty = true;
+            return walk.finish(bytes_read.load(Ordering::Relaxed));
+        }
+        let stopped = Cell::new(false);
+        let mut reader = StopReader {
+            inner: (&magic[..]).chain(reader),
+            stopped: &stopped,
+        };
+
+        let mut index = 0;
+        loop {
+            self.interrupt.check()?;
+            let Some(mut entry) = zip::read::read_zipfile_from_stream(&mut reader)? else {
+                break;
             };
+            let result = (|| {
+                // The input's size isn't known up front, so it is checked
+                // as each entry's header is reached
+                self.limits
+                    .check_compressed(bytes_read.load(Ordering::Relaxed))?;
+                self.limits.check_entry_count(index + 1)?;
+                reject_encrypted(&entry)?;
+                self.check_compression(entry.name(), entry.compression())?;
+                self.extract_entry(&mut entry, None, index, 0, walk)
+            })();
+            if result.is_err() {
+                // Dropping the entry would otherwise read the rest of it,
+                // however long that takes
+                stopped.set(true);
+            }
+            result?;
+            index += 1;
+        }
+
+        walk.finish(bytes_read.load(Ordering::Relaxed))
+    }
+
+    /// Check and extract one entry, from either walk. With `verified`, the
+    /// entry's data is taken from there instead of being decompressed.
+    fn extract_entry(
+        &self,
+        entry: &mut zip::read::ZipFile<'_>,
+        verified: Option<&[u8]>,
+        index: usize,
+        total_entries: usize,
+        walk: &mut Walk,
+    ) -> Result<(), Error> {
+        // Call progress callback if set
+        if let Some(ref callback) = self.on_progress {
+            callback(&Progress {
+                entry_name: entry.name().to_string(),
+                entry_size: entry.size(),
+                entry_index: index,
+                total_entries,
+                bytes_written: walk.report.bytes_written,
+                files_extracted: walk.report.files_extracted,
+            });
+        }
+
+        let Some(name) = self.extracted_name(entry.name()) else {
+            walk.report.entries_skipped += 1;
+            return Ok(());
+        };
+
+        // 0. SECURITY: Filename Sanitization
+        if let Err(reason) = self.validate_filename(&name) {
+            return Err(Error::InvalidFilename {
+                entry: name,
+                reason: reason.to_string(),
+            });
+        }
 
-            if let Some(ref filter) = self.filter {
-                if !filter(&info) {
-                    report.entries_skipped += 1;
-                    continue;
+        // 1. SECURITY: Path Validation (Path Jail)
+        // We check this FIRST to ensure the path is safe (doesn't escape root).
+        // NOTE: We discard the returned path because Jail might resolve symlinks (e.g. on overwrite),
+        // which causes us to overwrite the target instead of the symlink.
+        // We construct the path manually relative to root, which is safe because Jail verified it.
+        let _ = self.jail.join(&name).map_err(|e| Error::PathEscape {
+            entry: name.clone(),
+            detail: e.to_string(),
+        })?;
+
+        // Construct safe_path manually to preserve symlinks in the path
+        let safe_path = self.root.join(&name);
+
+        // 2. CHECK: Symlinks
+        if entry.is_symlink() {
+            match self.symlinks {
+                SymlinkPolicy::Error => {
+                    return Err(Error::SymlinkNotAllowed {
+                        entry: name,
+                        target: String::new(), // ZIP symlink targets require reading content
+                    });
+                }
+                SymlinkPolicy::Skip => {
+                    walk.report.entries_skipped += 1;
+                    return Ok(());
+                }
+                SymlinkPolicy::Allow if cfg!(not(unix)) => {
+                    walk.report.entries_skipped += 1;
+                    return Ok(());
                 }
+                SymlinkPolicy::Allow => {}
             }
+        }
 
-            // 5. CHECK: Limits (Count & Lookahead Total)
-            // Check file count
-            if report.files_extracted >= self.limits.max_file_count {
-                return Err(Error::FileCountExceeded {
-                    limit: self.limits.max_file_count,
-                    attempted: report.files_extracted + 1,
-                });
+        // 3. CHECK: Limits (Depth)
+        let depth = normal_depth(&name);
+        if depth > self.limits.max_path_depth {
+            return Err(Error::PathTooDeep {
+                entry: name,
+                depth,
+                limit: self.limits.max_path_depth,
+            });
+        }
+        self.check_duplicate(&name, entry.is_dir(), &mut walk.seen)?;
+
+        // 4. CHECK: Filter (User Logic)
+        let info = EntryInfo {
+            name: &name,
+            size: entry.size(),
+            compressed_size: entry.compressed_size(),
+            is_dir: entry.is_dir(),
+            is_symlink: entry.is_symlink(),
+        };
+
+        if let Some(ref filter) = self.filter {
+            if !filter(&info) {
+                walk.report.entries_skipped += 1;
+                return Ok(());
             }
+        }
+        self.check_case(&name, entry.is_dir(), &mut walk.folded)?;
 
-            // Check single file size (declared)
-            if !entry.is_dir() && entry.size() > self.limits.max_single_file {
-                return Err(Error::FileTooLarge {
-                    entry: name,
-                    limit: self.limits.max_single_file,
-                    size: entry.size(),
-                });
-            }
+        // 5. CHECK: Limits (Count & Lookahead Total)
+        // Check file count
+        if walk.report.files_extracted >= self.limits.max_file_count {
+            return Err(Error::FileCountExceeded {
+                limit: self.limits.max_file_count,
+                attempted: walk.report.files_extracted + 1,
+            });
+        }
+        if entry.is_dir() && walk.report.dirs_created >= self.limits.max_dir_count {
+            return Err(Error::DirCountExceeded {
+                limit: self.limits.max_dir_count,
+                attempted: walk.report.dirs_created + 1,
+            });
+        }
 
-            // Check total size (Lookahead declared)
-            // Note: We ALSO check this during streaming to prevent zip bombs that lie about size
-            if total_bytes_written + entry.size() > self.limits.max_total_bytes {
-                return Err(Error::TotalSizeExceeded {
-                    limit: self.limits.max_total_bytes,
-                    would_be: total_bytes_written + entry.size(),
-                });
-            }
+        // Check single file size (declared)
+        if !entry.is_dir() && entry.size() > self.limits.max_single_file {
+            return Err(Error::FileTooLarge {
+                entry: name,
+                limit: self.limits.max_single_file,
+                size: entry.size(),
+            });
+        }
 
-            // 7. EXECUTION
-            if entry.is_dir() {
-                fs::create_dir_all(&safe_path)?;
-                report.dirs_created += 1;
-            } else {
-                if let Some(parent) = safe_path.parent() {
-                    fs::create_dir_all(parent)?;
+        check_compression_ratio(
+            &name,
+            entry.size(),
+            entry.compressed_size(),
+            self.limits.max_compression_ratio,
+        )?;
+
+        // Check total size (Lookahead declared)
+        // Note: We ALSO check this during streaming to prevent zip bombs that lie about size
+        if walk.report.bytes_written + entry.size() > self.limits.max_total_bytes {
+            return Err(Error::TotalSizeExceeded {
+                limit: self.limits.max_total_bytes,
+                would_be: walk.report.bytes_written + entry.size(),
+            });
+        }
+
+        // 7. EXECUTION
+        if entry.is_dir() {
+            self.track_new(walk, &safe_path);
+            fs::create_dir_all(&safe_path)?;
+            walk.report.dirs_created += 1;
+            self.collect_path(walk, &safe_path);
+
+            // Apply the stored directory mode, keeping owner rwx so the
+            // directory's own entries can still be extracted into it
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                if let Some(mode) = self.permission_mode.resolve(entry.unix_mode(), true) {
+                    fs::set_permissions(&safe_path, fs::Permissions::from_mode(mode))?;
                 }
+            }
 
-                // SECURITY: Atomic file creation based on overwrite policy
-                // Using create_new(true) eliminates TOCTOU race conditions
-                let outfile = match self.overwrite {
-                    OverwritePolicy::Error => {
-                        // create_new(true) is atomic: fails if file exists (no TOCTOU)
-                        match fs::OpenOptions::new()
-                            .write(true)
-                            .create_new(true)
-                            .open(&safe_path)
-                        {
-                            Ok(f) => f,
-                            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
-                                return Err(Error::AlreadyExists {
-                                    entry: safe_path.display().to_string(),
-                                });
-                            }
-                            Err(e) => return Err(Error::Io(e)),
+            if self.preserve_mtime {
+                if let Some(mtime) = zip_mtime(entry.last_modified()).or(self.default_mtime) {
+                    walk.dir_mtimes.push((safe_path, mtime));
+                }
+            }
+        } else if entry.is_symlink() {
+            // Only `Allow` gets here, and only on Unix
+            let target = match verified {
+                Some(mut data) => self.read_link_target(&name, &mut data)?,
+                None => self.read_link_target(&name, entry)?,
+            };
+            if self.create_symlink(&name, &target, &safe_path, walk)? {
+                walk.report.symlinks_created += 1;
+                self.collect_path(walk, &safe_path);
+            } else {
+                walk.report.entries_skipped += 1;
+            }
+        } else {
+            if let Some(parent) = safe_path.parent() {
+                self.track_new(walk, parent);
+                fs::create_dir_all(parent)?;
+            }
+            self.track_new(walk, &safe_path);
+
+            // SECURITY: Atomic file creation based on overwrite policy
+            // Using create_new(true) eliminates TOCTOU race conditions
+            let outfile = match self.overwrite {
+                OverwritePolicy::Error => {
+                    // create_new(true) is atomic: fails if file exists (no TOCTOU)
+                    match fs::OpenOptions::new()
+                        .write(true)
+                        .create_new(true)
+                        .open(&safe_path)
+                    {
+                        Ok(f) => f,
+                        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                            return Err(Error::AlreadyExists {
+                                entry: safe_path.display().to_string(),
+                            });
                         }
+                        Err(e) => return Err(Error::Io(e)),
                     }
-                    OverwritePolicy::Skip => {
-                        // Try atomic create, skip on exists
-                        match fs::OpenOptions::new()
-                            .write(true)
-                            .create_new(true)
-                            .open(&safe_path)
-                        {
-                            Ok(f) => f,
-                            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
-                                report.entries_skipped += 1;
-                                continue;
-                            }
-                            Err(e) => return Err(Error::Io(e)),
+                }
+                OverwritePolicy::Skip => {
+                    // Try atomic create, skip on exists
+                    match fs::OpenOptions::new()
+                        .write(true)
+                        .create_new(true)
+                        .open(&safe_path)
+                    {
+                        Ok(f) => f,
+                        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                            walk.report.entries_skipped += 1;
+                            return Ok(());
                         }
+                        Err(e) => return Err(Error::Io(e)),
                     }
-                    OverwritePolicy::Overwrite => {
-                        // SECURITY: Remove any existing symlink first to prevent following
-                        if let Ok(m) = fs::symlink_metadata(&safe_path) {
-                            if m.file_type().is_symlink() {
-                                let _ = fs::remove_file(&safe_path);
-                            }
+                }
+                OverwritePolicy::Overwrite => {
+                    // SECURITY: Remove any existing symlink first to prevent following
+                    if let Ok(m) = fs::symlink_metadata(&safe_path) {
+                        if m.file_type().is_symlink() {
+                            let _ = fs::remove_file(&safe_path);
                         }
-                        // Now create/truncate
-                        fs::File::create(&safe_path)?
-                    }
-                };
-
-                // SECURITY: LimitReader
-                // Enforce:
-                // 1. entry.size() (Declared size) - catch bombs that lie
-                // 2. limits.max_single_file - catch bombs exceeding limit
-                // 3. limits.max_total_bytes - catch global limit violation
-
-                let limit_single = self.limits.max_single_file.min(entry.size());
-                let remaining_global = self
-                    .limits
-                    .max_total_bytes
-                    .saturating_sub(total_bytes_written);
-                let hard_limit = limit_single.min(remaining_global);
-
-                let mut limiter = LimitReader::new(&mut entry, hard_limit);
-                let mut outfile = outfile;
-
-                // We use io::copy, which loops until EOF or error.
-                // LimitReader returns EOF at limit.
-                // BUT we need to distinguish EOF at limit vs natural EOF.
-                // If EOF at limit AND entry has more data -> Error.
-
-                let written = std::io::copy(&mut limiter, &mut outfile)?;
-
-                // Check if we hit the limit strictly
-                if limiter.hit_limit {
-                    // If we hit the limit, we must check if there was MORE data expected.
-                    // If declared size > written, we stopped early -> OK?
-                    // No, if we stopped because of max_single_file, it's an error if the file was larger.
-                    // If we stopped because of max_total_bytes, it's an error.
-                    // If we stopped because of entry.size(), it's fine (just consumed declared).
-
-                    // Actually, if we hit the hard_limit, we should check WHY.
-                    if written >= self.limits.max_single_file
-                        && entry.size() > self.limits.max_single_file
-                    {
-                        return Err(Error::FileTooLarge {
-                            entry: name,
-                            limit: self.limits.max_single_file,
-                            size: written + 1, // At least this much
-                        });
                     }
+                    // Now create/truncate
+                    fs::File::create(&safe_path)?
+                }
+            };
 
-                    if remaining_global <= written && written < entry.size() {
-                        return Err(Error::TotalSizeExceeded {
-                            limit: self.limits.max_total_bytes,
-                            would_be: total_bytes_written + written + 1,
-                        });
-                    }
+            // SECURITY: LimitReader
+            // The declared size already passed the single-file and total
+            // limits above, so reading one byte past it is enough to
+            // catch a bomb that lies about its size without filling the
+            // disk first.
+            let declared = entry.size();
+            let remaining_global = self
+                .limits
+                .max_total_bytes
+                .saturating_sub(walk.report.bytes_written);
+
+            let mut from_memory;
+            let mut source: &mut dyn Read = match verified {
+                Some(data) => {
+                    from_memory = data;
+                    &mut from_memory
+                }
+                None => &mut *entry,
+            };
+            let mut limiter = LimitReader::new(&mut source, declared.saturating_add(1));
+            let mut outfile = outfile;
+
+            // Extractor has no decompression budget to charge
+            let mut decompress_time = Duration::ZERO;
+            let mut sparse = SparseWriter::new(&mut outfile, self.sparse && cfg!(unix));
+            let convert_to = self
+                .text_convert
+                .as_ref()
+                .filter(|convert| convert.matches(&name))
+                .map(|convert| convert.to);
+            let output_limit = self.limits.max_single_file.min(remaining_global);
+            let mut output = TextWriter::new(&mut sparse, convert_to, output_limit);
+            let copied = std::io::copy(
+                &mut limiter,
+                &mut self
+                    .interrupt
+                    .writer(&mut output, &name, &mut decompress_time),
+            )
+            .and_then(|written| Ok((written, output.finish()?)));
+            if output.over_limit() {
+                return Err(self.output_too_large(name, walk.report.bytes_written));
+            }
+            let (written, output_len) = copied.map_err(|e| self.interrupt.recover(e.into()))?;
+            sparse.finish()?;
+
+            // SECURITY: Detect zip bombs that lie about declared size,
+            // and truncated entries. Nothing of the entry is kept.
+            if let Err(e) = check_declared_size(&name, declared, written) {
+                drop(outfile);
+                let _ = fs::remove_file(&safe_path);
+                return Err(e);
+            }
 
-                    // Specific check: if written == entry.size(), we are good.
-                    // If written < entry.size() but we hit limit, it means limit < entry.size().
-                    // Which implies one of the above errors triggered.
+            if self.preserve_mtime {
+                if let Some(mtime) = zip_mtime(entry.last_modified()).or(self.default_mtime) {
+                    outfile.set_modified(mtime)?;
                 }
+            }
 
-                // SECURITY: Detect zip bombs that lie about declared size.
-                // If we wrote exactly the declared size, check if there's more data.
-                // If so, the file is larger than declared (potential zip bomb).
-                if written == entry.size() {
-                    let mut buf = [0u8; 1];
-                    if entry.read(&mut buf)? > 0 {
-                        return Err(Error::SizeMismatch {
-                            entry: name.clone(),
-                            declared: entry.size(),
-                            actual: entry.size() + 1, // At least this much more
-                        });
-                    }
+            walk.report.bytes_written += output_len;
+            walk.report.files_extracted += 1;
+            self.collect_path(walk, &safe_path);
+
+            // Handle permissions on Unix
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                // Special bits are already gone; strip_exec drops the x bits too
+                if let Some(mode) = self.permission_mode.resolve(entry.unix_mode(), false) {
+                    let mask = if self.strip_exec { 0o0666 } else { 0o0777 };
+                    let safe_mode = mode & mask;
+                    fs::set_permissions(&safe_path, fs::Permissions::from_mode(safe_mode))?;
                 }
+            }
+        }
+        Ok(())
+    }
 
-                total_bytes_written += written;
-                report.bytes_written += written;
-                report.files_extracted += 1;
-
-                // Handle permissions on Unix
-                #[cfg(unix)]
-                {
-                    use std::os::unix::fs::PermissionsExt;
-                    if let Some(mode) = entry.unix_mode() {
-                        // Strip setuid (0o4000), setgid (0o2000), sticky (0o1000) bits
-                        // 0o0777 mask keeps only owner/group/other rwx flags
-                        let safe_mode = mode & 0o0777;
-                        fs::set_permissions(&safe_path, fs::Permissions::from_mode(safe_mode))?;
-                    }
+    /// Read a ZIP symlink's target, which is stored as the entry contents.
+    fn read_link_target<R: Read>(&self, name: &str, entry: &mut R) -> Result<String, Error> {
+        let max = self.limits.max_absolute_path;
+        let mut target = String::new();
+        entry.take(max as u64 + 1).read_to_string(&mut target)?;
+        if target.len() > max {
+            return Err(Error::InvalidFilename {
+                entry: name.to_string(),
+                reason: format!("symlink target longer than {} bytes", max),
+            });
+        }
+        Ok(target)
+    }
+
+    /// Resolve a symlink target from `link_dir`, failing unless it is
+    /// relative and stays inside `root`.
+    fn check_link_target(
+        &self,
+        name: &str,
+        target: &str,
+        link_dir: &Path,
+        root: &Path,
+    ) -> Result<PathBuf, Error> {
+        if target.is_empty() {
+            return Err(Error::InvalidFilename {
+                entry: name.to_string(),
+                reason: "empty symlink target".to_string(),
+            });
+        }
+        let target_path = Path::new(target);
+        if target_path.has_root()
+            || matches!(target_path.components().next(), Some(Component::Prefix(_)))
+        {
+            return Err(Error::PathEscape {
+                entry: name.to_string(),
+                detail: format!("symlink target '{}' is absolute", target),
+            });
+        }
+        resolve_link_target(link_dir, target_path)
+            .filter(|resolved| resolved.starts_with(root))
+            .ok_or_else(|| Error::PathEscape {
+                entry: name.to_string(),
+                detail: format!("symlink target '{}' escapes destination", target),
+            })
+    }
+
+    /// Create the symlink at `safe_path` under [`SymlinkPolicy::Allow`].
+    /// Returns false if it was skipped because something already exists
+    /// there.
+    fn create_symlink(
+        &self,
+        name: &str,
+        target: &str,
+        safe_path: &Path,
+        walk: &mut Walk,
+    ) -> Result<bool, Error> {
+        let parent = safe_path.parent().unwrap_or(&self.root);
+        self.track_new(walk, parent);
+        fs::create_dir_all(parent)?;
+        self.track_new(walk, safe_path);
+        let root = fs::canonicalize(&self.root)?;
+        let link_dir = fs::canonicalize(parent)?;
+        let resolved = self.check_link_target(name, target, &link_dir, &root)?;
+
+        // Let the jail follow whatever already exists along the target
+        if let Ok(relative) = resolved.strip_prefix(&root) {
+            if !relative.as_os_str().is_empty() {
+                self.jail.join(relative).map_err(|e| Error::PathEscape {
+                    entry: name.to_string(),
+                    detail: e.to_string(),
+                })?;
+            }
+        }
+
+        // Never follow or replace a directory that is in the way
+        if let Ok(existing) = fs::symlink_metadata(safe_path) {
+            match self.overwrite {
+                OverwritePolicy::Skip => return Ok(false),
+                OverwritePolicy::Overwrite if !existing.is_dir() => fs::remove_file(safe_path)?,
+                _ => {
+                    return Err(Error::AlreadyExists {
+                        entry: safe_path.display().to_string(),
+                    })
                 }
             }
         }
 
-        Ok(report)
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(target, safe_path)?;
+            Ok(true)
+        }
+        #[cfg(not(unix))]
+        {
+            Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into())
+        }
     }
 
     /// Validate all entries without extracting (fast dry run).
@@ -614,11 +1602,16 @@ impl Extractor {
     fn validate_all<R: Read + Seek>(&self, archive: &mut zip::ZipArchive<R>) -> Result<(), Error> {
         let mut total_size: u64 = 0;
         let mut file_count: usize = 0;
+        let mut dir_count: usize = 0;
+        let mut seen = HashSet::new();
+        let mut folded = HashMap::new();
 
         for i in 0..archive.len() {
             // by_index_raw reads metadata WITHOUT decompressing
             let entry = archive.by_index_raw(i)?;
-            let name = entry.name().to_string();
+            let Some(name) = self.extracted_name(entry.name()) else {
+                continue;
+            };
 
             // 0. Filename sanitization
             if let Err(reason) = self.validate_filename(&name) {
@@ -643,10 +1636,7 @@ impl Extractor {
             }
 
             // 3. Path depth check
-            let depth = Path::new(&name)
-                .components()
-                .filter(|c| matches!(c, Component::Normal(_)))
-                .count();
+            let depth = normal_depth(&name);
             if depth > self.limits.max_path_depth {
                 return Err(Error::PathTooDeep {
                     entry: name,
@@ -654,8 +1644,25 @@ impl Extractor {
                     limit: self.limits.max_path_depth,
                 });
             }
+            self.check_duplicate(&name, entry.is_dir(), &mut seen)?;
+            if self.reject_case_collisions {
+                let info = EntryInfo {
+                    name: &name,
+                    size: entry.size(),
+                    compressed_size: entry.compressed_size(),
+                    is_dir: entry.is_dir(),
+                    is_symlink: entry.is_symlink(),
+                };
+                if self.filter.as_ref().is_none_or(|filter| filter(&info)) {
+                    self.check_case(&name, entry.is_dir(), &mut folded)?;
+                }
+            }
+
+            // 4. Encryption and compression method check
+            reject_encrypted(&entry)?;
+            self.check_compression(&name, entry.compression())?;
 
-            // 4. Single file size check
+            // 5. Single file size check
             if !entry.is_dir() && entry.size() > self.limits.max_single_file {
                 return Err(Error::FileTooLarge {
                     entry: name,
@@ -663,15 +1670,41 @@ impl Extractor {
                     size: entry.size(),
                 });
             }
-
-            // Accumulate totals (skip symlinks and dirs)
-            if !entry.is_dir() && !entry.is_symlink() {
+            check_compression_ratio(
+                &name,
+                entry.size(),
+                entry.compressed_size(),
+                self.limits.max_compression_ratio,
+            )?;
+
+            // Accumulate totals (skip symlinks)
+            if entry.is_dir() {
+                dir_count += 1;
+            } else if !entry.is_symlink() {
                 total_size += entry.size();
                 file_count += 1;
             }
         }
 
-        // 5. Check accumulated totals
+        // 5b. Symlink targets are entry contents, so checking them means
+        // decompressing. Lexical only: the links they would pass through
+        // don't exist yet, and creation checks again against the real tree.
+        if matches!(self.symlinks, SymlinkPolicy::Allow) {
+            for i in 0..archive.len() {
+                let mut entry = archive.by_index(i)?;
+                if entry.is_symlink() {
+                    let Some(name) = self.extracted_name(entry.name()) else {
+                        continue;
+                    };
+                    let target = self.read_link_target(&name, &mut entry)?;
+                    let link_dir = self.root.join(&name);
+                    let link_dir = link_dir.parent().unwrap_or(&self.root);
+                    self.check_link_target(&name, &target, link_dir, &self.root)?;
+                }
+            }
+        }
+
+        // 6. Check accumulated totals
         if total_size > self.limits.max_total_bytes {
             return Err(Error::TotalSizeExceeded {
                 limit: self.limits.max_total_bytes,
@@ -685,6 +1718,15 @@ impl Extractor {
                 attempted: file_count,
             });
         }
+        if dir_count > self.limits.max_dir_count {
+            return Err(Error::DirCountExceeded {
+                limit: self.limits.max_dir_count,
+                attempted: dir_count,
+            });
+        }
+        if self.ensure_free_space {
+            check_free_space(&self.root, total_size)?;
+        }
 
         Ok(())
     }
@@ -696,6 +1738,438 @@ impl Extractor {
         self.extract(reader)
     }
 
+    /// Run the checks of [`extract`](Self::extract) against the archive's
+    /// metadata and return what extraction would do, without writing
+    /// anything.
+    ///
+    /// The filter, limits, and symlink policy are applied as in extraction,
+    /// and in [`ExtractionMode::ValidateFirst`] the whole archive is
+    /// validated first, so the plan fails whenever extraction would fail
+    /// before decompressing. Sizes are the declared ones, which extraction
+    /// still checks against the data. Allowed symlink targets are read and
+    /// checked lexically.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    /// use std::fs::File;
+    ///
+    /// let extractor = Extractor::new("/srv/data")?;
+    /// let plan = extractor.plan(File::open("dataset.zip")?)?;
+    /// println!("about to write {} files ({} bytes)", plan.total_files, plan.total_bytes);
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn plan<R: Read + Seek>(&self, mut reader: R) -> Result<ExtractionPlan, Error> {
+        let start = reader.stream_position()?;
+        let archive_len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+        self.limits.check_compressed(archive_len)?;
+        let repeated = check_central_directory(&mut reader)?;
+        if let Some(entry) = repeated.filter(|_| self.reject_duplicate_entries) {
+            return Err(Error::DuplicateEntry { entry });
+        }
+        let mut archive = zip::ZipArchive::new(reader)?;
+        self.limits.check_entry_count(archive.len())?;
+
+        if !matches!(self.mode, ExtractionMode::Streaming) {
+            self.validate_all(&mut archive)?;
+        }
+
+        let mut plan = ExtractionPlan::default();
+        let mut seen = HashSet::new();
+        let mut folded = HashMap::new();
+        for i in 0..archive.len() {
+            let mut info = {
+                let raw = archive.by_index_raw(i)?;
+                reject_encrypted(&raw)?;
+                self.check_compression(raw.name(), raw.compression())?;
+                raw_entry_info(&raw)
+            };
+            let Some(name) = self.extracted_name(&info.name) else {
+                continue;
+            };
+            info.name = name;
+            let name = &info.name;
+            let is_dir = info.kind == EntryKind::Directory;
+            let is_symlink = matches!(info.kind, EntryKind::Symlink { .. });
+
+            if let Err(reason) = self.validate_filename(name) {
+                return Err(Error::InvalidFilename {
+                    entry: info.name,
+                    reason: reason.to_string(),
+                });
+            }
+            self.jail.join(name).map_err(|e| Error::PathEscape {
+                entry: name.clone(),
+                detail: e.to_string(),
+            })?;
+
+            if is_symlink {
+                match self.symlinks {
+                    SymlinkPolicy::Error => {
+                        return Err(Error::SymlinkNotAllowed {
+                            entry: info.name,
+                            target: String::new(),
+                        });
+                    }
+                    SymlinkPolicy::Allow if cfg!(unix) => {}
+                    _ => continue,
+                }
+            }
+
+            let depth = normal_depth(name);
+            if depth > self.limits.max_path_depth {
+                return Err(Error::PathTooDeep {
+                    entry: info.name,
+                    depth,
+                    limit: self.limits.max_path_depth,
+                });
+            }
+            self.check_duplicate(name, is_dir, &mut seen)?;
+
+            let compressed_size = info.compressed_size.unwrap_or_default();
+            let filter_info = EntryInfo {
+                name,
+                size: info.size,
+                compressed_size,
+                is_dir,
+                is_symlink,
+            };
+            if self
+                .filter
+                .as_ref()
+                .is_some_and(|filter| !filter(&filter_info))
+            {
+                plan.skipped_by_filter += 1;
+                continue;
+            }
+            self.check_case(name, is_dir, &mut folded)?;
+
+            if plan.total_files >= self.limits.max_file_count {
+                return Err(Error::FileCountExceeded {
+                    limit: self.limits.max_file_count,
+                    attempted: plan.total_files + 1,
+                });
+            }
+            if is_dir && plan.total_dirs >= self.limits.max_dir_count {
+                return Err(Error::DirCountExceeded {
+                    limit: self.limits.max_dir_count,
+                    attempted: plan.total_dirs + 1,
+                });
+            }
+            if !is_dir && info.size > self.limits.max_single_file {
+                return Err(Error::FileTooLarge {
+                    entry: info.name,
+                    limit: self.limits.max_single_file,
+                    size: info.size,
+                });
+            }
+            check_compression_ratio(
+                name,
+                info.size,
+                compressed_size,
+                self.limits.max_compression_ratio,
+            )?;
+            if plan.total_bytes + info.size > self.limits.max_total_bytes {
+                return Err(Error::TotalSizeExceeded {
+                    limit: self.limits.max_total_bytes,
+                    would_be: plan.total_bytes + info.size,
+                });
+            }
+
+            if is_dir {
+                plan.total_dirs += 1;
+            } else if is_symlink {
+                let mut entry = archive.by_index(i)?;
+                let target = self.read_link_target(name, &mut entry)?;
+                let link_dir = self.root.join(name);
+                let link_dir = link_dir.parent().unwrap_or(&self.root);
+                self.check_link_target(name, &target, link_dir, &self.root)?;
+                plan.symlink_count += 1;
+                info.kind = EntryKind::Symlink { target };
+            } else {
+                plan.total_files += 1;
+                plan.total_bytes += info.size;
+            }
+            plan.entries.push(info);
+        }
+
+        Ok(plan)
+    }
+
+    /// Extract every file into memory instead of the destination, returning
+    /// a map from entry name to contents.
+    ///
+    /// The same checks as [`extract`](Self::extract) apply, including the
+    /// filter and the [`ExtractionMode`]; names are still checked against
+    /// the destination, but nothing is written there. `max_total_bytes`
+    /// bounds the memory held by the map, and each file is decompressed
+    /// into a buffer no larger than its declared size plus one byte.
+    ///
+    /// Directories are omitted; a file's directories are implied by its
+    /// name. Symlinks are never materialised: with [`SymlinkPolicy::Error`]
+    /// they fail as usual, otherwise they are skipped. A name that appears
+    /// twice is handled by the [`OverwritePolicy`] as if the first copy
+    /// were on disk. Options that only concern files on disk (permissions,
+    /// modification times, sparse files, text conversion) have no effect.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    /// use std::fs::File;
+    ///
+    /// let files = Extractor::new("/tmp")?.extract_to_memory(File::open("bundle.zip")?)?;
+    /// if let Some(manifest) = files.get("manifest.json") {
+    ///     println!("manifest is {} bytes", manifest.len());
+    /// }
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn extract_to_memory<R: Read + Seek>(
+        &self,
+        mut reader: R,
+    ) -> Result<HashMap<String, Vec<u8>>, Error> {
+        let start = reader.stream_position()?;
+        let archive_len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+        self.limits.check_compressed(archive_len)?;
+        let repeated = check_central_directory(&mut reader)?;
+        if let Some(entry) = repeated.filter(|_| self.reject_duplicate_entries) {
+            return Err(Error::DuplicateEntry { entry });
+        }
+        let mut archive = zip::ZipArchive::new(reader)?;
+        self.limits.check_entry_count(archive.len())?;
+
+        if !matches!(self.mode, ExtractionMode::Streaming) {
+            self.validate_all(&mut archive)?;
+        }
+
+        let mut files = HashMap::new();
+        let mut seen = HashSet::new();
+        let mut total_bytes: u64 = 0;
+        for i in 0..archive.len() {
+            self.interrupt.check()?;
+            {
+                let raw = archive.by_index_raw(i)?;
+                reject_encrypted(&raw)?;
+                self.check_compression(raw.name(), raw.compression())?;
+            }
+            let mut entry = archive.by_index(i)?;
+            let Some(name) = self.extracted_name(entry.name()) else {
+                continue;
+            };
+
+            if let Err(reason) = self.validate_filename(&name) {
+                return Err(Error::InvalidFilename {
+                    entry: name,
+                    reason: reason.to_string(),
+                });
+            }
+            self.jail.join(&name).map_err(|e| Error::PathEscape {
+                entry: name.clone(),
+                detail: e.to_string(),
+            })?;
+
+            if entry.is_symlink() {
+                if matches!(self.symlinks, SymlinkPolicy::Error) {
+                    return Err(Error::SymlinkNotAllowed {
+                        entry: name,
+                        target: String::new(),
+                    });
+                }
+                continue;
+            }
+
+            let depth = normal_depth(&name);
+            if depth > self.limits.max_path_depth {
+                return Err(Error::PathTooDeep {
+                    entry: name,
+                    depth,
+                    limit: self.limits.max_path_depth,
+                });
+            }
+            self.check_duplicate(&name, entry.is_dir(), &mut seen)?;
+
+            let info = EntryInfo {
+                name: &name,
+                size: entry.size(),
+                compressed_size: entry.compressed_size(),
+                is_dir: entry.is_dir(),
+                is_symlink: false,
+            };
+            if entry.is_dir() || self.filter.as_ref().is_some_and(|filter| !filter(&info)) {
+                continue;
+            }
+
+            if files.len() >= self.limits.max_file_count {
+                return Err(Error::FileCountExceeded {
+                    limit: self.limits.max_file_count,
+                    attempted: files.len() + 1,
+                });
+            }
+            let declared = entry.size();
+            if declared > self.limits.max_single_file {
+                return Err(Error::FileTooLarge {
+                    entry: name,
+                    limit: self.limits.max_single_file,
+                    size: declared,
+                });
+            }
+            check_compression_ratio(
+                &name,
+                declared,
+                entry.compressed_size(),
+                self.limits.max_compression_ratio,
+            )?;
+            if total_bytes + declared > self.limits.max_total_bytes {
+                return Err(Error::TotalSizeExceeded {
+                    limit: self.limits.max_total_bytes,
+                    would_be: total_bytes + declared,
+                });
+            }
+
+            if files.contains_key(&name) {
+                match self.overwrite {
+                    OverwritePolicy::Error => return Err(Error::AlreadyExists { entry: name }),
+                    OverwritePolicy::Skip => continue,
+                    OverwritePolicy::Overwrite => {}
+                }
+            }
+
+            // The declared size passed the limits, and reading stops one
+            // byte past it
+            let mut content = Vec::with_capacity(declared as usize);
+            let mut limiter = LimitReader::new(&mut entry, declared.saturating_add(1));
+            let mut decompress_time = Duration::ZERO;
+            std::io::copy(
+                &mut limiter,
+                &mut self
+                    .interrupt
+                    .writer(&mut content, &name, &mut decompress_time),
+            )
+            .map_err(|e| self.interrupt.recover(e.into()))?;
+            check_declared_size(&name, declared, content.len() as u64)?;
+
+            total_bytes += declared;
+            if let Some(replaced) = files.insert(name, content) {
+                total_bytes -= replaced.len() as u64;
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Decompress the single entry `name` into `out` without touching the
+    /// disk, returning the number of bytes written.
+    ///
+    /// The name must match the archive exactly. The entry passes the same
+    /// checks as during [`extract`](Self::extract): filename rules, the
+    /// path jail, depth, `max_single_file`, the compression ratio, and
+    /// encryption and compression method. Filters are not applied, and no
+    /// other entry is decompressed.
+    ///
+    /// Fails with [`Error::EntryNotFound`] if there is no such entry and
+    /// [`Error::NotAFile`] for directories and symlinks. If the entry's
+    /// data turns out not to match its declared size, part of it may
+    /// already have been written to `out`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use safe_unzip::Extractor;
+    /// use std::fs::File;
+    ///
+    /// let mut config = Vec::new();
+    /// Extractor::new("/tmp")?.extract_entry_to(
+    ///     File::open("upload.zip")?,
+    ///     "app/config.toml",
+    ///     &mut config,
+    /// )?;
+    /// # Ok::<(), safe_unzip::Error>(())
+    /// ```
+    pub fn extract_entry_to<R: Read + Seek, W: Write>(
+        &self,
+        mut reader: R,
+        name: &str,
+        out: &mut W,
+    ) -> Result<u64, Error> {
+        check_central_directory(&mut reader)?;
+        let mut archive = zip::ZipArchive::new(reader)?;
+        let index = archive
+            .index_for_name(name)
+            .ok_or_else(|| Error::EntryNotFound {
+                name: name.to_string(),
+            })?;
+
+        {
+            let raw = archive.by_index_raw(index)?;
+            reject_encrypted(&raw)?;
+            self.check_compression(name, raw.compression())?;
+        }
+        let mut entry = archive.by_index(index)?;
+
+        if let Err(reason) = self.validate_filename(name) {
+            return Err(Error::InvalidFilename {
+                entry: name.to_string(),
+                reason: reason.to_string(),
+            });
+        }
+        self.jail.join(name).map_err(|e| Error::PathEscape {
+            entry: name.to_string(),
+            detail: e.to_string(),
+        })?;
+
+        let kind = if entry.is_dir() {
+            Some("directory")
+        } else if entry.is_symlink() {
+            Some("symlink")
+        } else {
+            None
+        };
+        if let Some(kind) = kind {
+            return Err(Error::NotAFile {
+                entry: name.to_string(),
+                kind: kind.to_string(),
+            });
+        }
+
+        let depth = normal_depth(name);
+        if depth > self.limits.max_path_depth {
+            return Err(Error::PathTooDeep {
+                entry: name.to_string(),
+                depth,
+                limit: self.limits.max_path_depth,
+            });
+        }
+
+        let declared = entry.size();
+        if declared > self.limits.max_single_file {
+            return Err(Error::FileTooLarge {
+                entry: name.to_string(),
+                limit: self.limits.max_single_file,
+                size: declared,
+            });
+        }
+        check_compression_ratio(
+            name,
+            declared,
+            entry.compressed_size(),
+            self.limits.max_compression_ratio,
+        )?;
+
+        // One byte past the declared size is enough to catch a lie
+        let mut limiter = LimitReader::new(&mut entry, declared.saturating_add(1));
+        let mut decompress_time = Duration::ZERO;
+        let written = std::io::copy(
+            &mut limiter,
+            &mut self.interrupt.writer(out, name, &mut decompress_time),
+        )
+        .map_err(|e| self.interrupt.recover(e.into()))?;
+        check_declared_size(name, declared, written)?;
+        Ok(written)
+    }
+
     /// Verify archive integrity by reading all entries and checking CRC32.
     ///
     /// This method reads and decompresses all file entries (triggering CRC validation)
@@ -710,7 +2184,7 @@ impl Extractor {
     ///
     /// Returns an error if:
     /// - Any entry fails CRC32 validation
-    /// - Any entry is encrypted
+    /// - Any entry is encrypted ([`Error::UnsupportedEncryption`])
     /// - The archive is corrupted
     ///
     /// # Example
@@ -723,20 +2197,22 @@ impl Extractor {
     /// println!("Verified {} entries, {} bytes", report.entries_verified, report.bytes_verified);
     /// # Ok::<(), safe_unzip::Error>(())
     /// ```
-    pub fn verify<R: Read + Seek>(&self, reader: R) -> Result<VerifyReport, Error> {
+    pub fn verify<R: Read + Seek>(&self, mut reader: R) -> Result<VerifyReport, Error> {
+        check_central_directory(&mut reader)?;
         let mut archive = zip::ZipArchive::new(reader)?;
         let mut entries_verified = 0usize;
         let mut bytes_verified = 0u64;
 
         for i in 0..archive.len() {
+            {
+                let raw = archive.by_index_raw(i)?;
+                reject_encrypted(&raw)?;
+                self.check_compression(raw.name(), raw.compression())?;
+            }
+
             let mut entry = archive.by_index(i)?;
             let name = entry.name().to_string();
 
-            // Check for encrypted entries
-            if entry.encrypted() {
-                return Err(Error::EncryptedEntry { entry: name });
-            }
-
             // Skip directories and symlinks
             if entry.is_dir() || entry.is_symlink() {
                 continue;
@@ -768,6 +2244,73 @@ impl Extractor {
         })
     }
 
+    /// Decompress every entry of `archive` that would be extracted to
+    /// check its CRC and size, before
+    /// [`verify_then_extract`](Self::verify_then_extract), or
+    /// [`ExtractionMode::ValidateFirstDeep`], writes anything. Expects
+    /// [`validate_all`](Self::validate_all) to have passed. Returns each
+    /// entry's data if they fit within `buffer_limit` and the total limit
+    /// together, or `None` if they are to be decompressed again.
+    fn verify_entries<R: Read + Seek>(
+        &self,
+        archive: &mut zip::ZipArchive<R>,
+        buffer_limit: u64,
+    ) -> Result<Option<Vec<Vec<u8>>>, Error> {
+        let mut declared_total = 0u64;
+        let mut wanted = Vec::with_capacity(archive.len());
+        for i in 0..archive.len() {
+            let raw = archive.by_index_raw(i)?;
+            reject_encrypted(&raw)?;
+            self.check_compression(raw.name(), raw.compression())?;
+            // Entries extract_entry skips are never decompressed
+            let want = !raw.is_dir()
+                && self.extracted_name(raw.name()).is_some_and(|name| {
+                    let info = EntryInfo {
+                        name: &name,
+                        size: raw.size(),
+                        compressed_size: raw.compressed_size(),
+                        is_dir: false,
+                        is_symlink: raw.is_symlink(),
+                    };
+                    self.filter.as_ref().is_none_or(|filter| filter(&info))
+                });
+            if want {
+                declared_total = declared_total.saturating_add(raw.size());
+            }
+            wanted.push(want);
+        }
+        let keep = declared_total <= buffer_limit.min(self.limits.max_total_bytes);
+
+        let mut verified = Vec::with_capacity(if keep { archive.len() } else { 0 });
+        for (i, want) in wanted.into_iter().enumerate() {
+            self.interrupt.check()?;
+            let mut data = Vec::new();
+            if want {
+                let mut entry = archive.by_index(i)?;
+                let name = entry.name().to_string();
+                let declared = entry.size();
+                // One byte past the declared size is enough to catch a lie
+                let mut limited = (&mut entry).take(declared.saturating_add(1));
+                let read = if keep {
+                    limited.read_to_end(&mut data).map(|n| n as u64)
+                } else {
+                    std::io::copy(&mut limited, &mut std::io::sink())
+                };
+                let read = read.map_err(|e| {
+                    Error::Io(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("CRC check failed for '{}': {}", name, e),
+                    ))
+                })?;
+                check_declared_size(&name, declared, read)?;
+            }
+            if keep {
+                verified.push(data);
+            }
+        }
+        Ok(keep.then_some(verified))
+    }
+
     /// Verify archive integrity from a file path.
     pub fn verify_file<P: AsRef<Path>>(&self, path: P) -> Result<VerifyReport, Error> {
         let file = fs::File::open(path)?;
@@ -780,6 +2323,98 @@ impl Extractor {
         self.verify(std::io::Cursor::new(data))
     }
 
+    /// Fail if `method` is outside `allowed_compression_methods`.
+    fn check_compression(&self, name: &str, method: zip::CompressionMethod) -> Result<(), Error> {
+        let Some(ref allowed) = self.allowed_methods else {
+            return Ok(());
+        };
+        let method = CompressionMethod::from_zip(method);
+        if allowed.contains(&method) {
+            Ok(())
+        } else {
+            Err(Error::DisallowedCompression {
+                entry: name.to_string(),
+                method: method.to_string(),
+            })
+        }
+    }
+
+    /// Error for converted output of `entry` that outgrew the single-file
+    /// limit or what was left of the total after `total_before` bytes.
+    fn output_too_large(&self, entry: String, total_before: u64) -> Error {
+        let remaining = self.limits.max_total_bytes.saturating_sub(total_before);
+        if self.limits.max_single_file <= remaining {
+            Error::FileTooLarge {
+                entry,
+                limit: self.limits.max_single_file,
+                size: self.limits.max_single_file + 1, // At least this much
+            }
+        } else {
+            Error::TotalSizeExceeded {
+                limit: self.limits.max_total_bytes,
+                would_be: self.limits.max_total_bytes + 1,
+            }
+        }
+    }
+
+    /// Fail if duplicates are rejected and `name` was already seen as a
+    /// file or symlink.
+    fn check_duplicate(
+        &self,
+        name: &str,
+        is_dir: bool,
+        seen: &mut HashSet<PathBuf>,
+    ) -> Result<(), Error> {
+        if self.reject_duplicate_entries && !is_dir && !seen.insert(normal_path(name)) {
+            return Err(Error::DuplicateEntry {
+                entry: name.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// `name` with [`strip_components`](Self::strip_components),
+    /// [`map_name`](Self::map_name), and the [`prefix`](Self::prefix)
+    /// applied, or `None` if the entry is not extracted.
+    fn extracted_name(&self, name: &str) -> Option<String> {
+        let name = strip_components(name, self.strip_components)?;
+        let name = match self.map_name {
+            Some(ref map) => map(name)?,
+            None => name.to_string(),
+        };
+        match self.prefix {
+            Some(ref prefix) => Some(prefixed(prefix, &name).unwrap_or(name)),
+            None => Some(name),
+        }
+    }
+
+    /// Add `path` to the report if paths are collected.
+    fn collect_path(&self, walk: &mut Walk, path: &Path) {
+        if self.collect_paths {
+            walk.report.extracted_paths.push(path.to_path_buf());
+        }
+    }
+
+    /// Fail if case collisions are rejected and `name` differs only in case
+    /// from a file or symlink already seen.
+    fn check_case(
+        &self,
+        name: &str,
+        is_dir: bool,
+        folded: &mut HashMap<String, String>,
+    ) -> Result<(), Error> {
+        if !self.reject_case_collisions || is_dir {
+            return Ok(());
+        }
+        match case_collision(folded, name) {
+            Some(existing) => Err(Error::CaseCollision {
+                existing,
+                conflicting: name.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
     /// Validate filename. Returns Ok(()) if valid, Err(reason) if invalid.
     fn validate_filename(&self, name: &str) -> Result<(), &'static str> {
         // Reject empty names
@@ -797,6 +2432,10 @@ impl Extractor {
             return Err("contains backslash");
         }
 
+        if self.reject_deceptive_unicode && name.chars().any(is_deceptive_char) {
+            return Err("deceptive unicode");
+        }
+
         // Reject extremely long filenames (filesystem limits)
         if name.len() > 1024 {
             return Err("path too long (>1024 bytes)");
@@ -806,36 +2445,190 @@ impl Extractor {
             return Err("path component too long (>255 bytes)");
         }
 
-        // Check path components for reserved names
-        let path = Path::new(name);
-        for component in path.components() {
-            if let Component::Normal(os_str) = component {
-                if let Some(s) = os_str.to_str() {
-                    // Windows reserved names
-                    let s_upper = s.to_ascii_uppercase();
-                    let file_stem = s_upper.split('.').next().unwrap_or(&s_upper);
-
-                    match file_stem {
-                        "CON" | "PRN" | "AUX" | "NUL" | "COM1" | "COM2" | "COM3" | "COM4"
-                        | "COM5" | "COM6" | "COM7" | "COM8" | "COM9" | "LPT1" | "LPT2" | "LPT3"
-                        | "LPT4" | "LPT5" | "LPT6" | "LPT7" | "LPT8" | "LPT9" => {
-                            return Err("Windows reserved name");
-                        }
-                        _ => {}
-                    }
-                }
-            }
+        // The OS limit applies to the joined path, not just the entry name
+        if self.root.as_os_str().len() + 1 + name.len() >= self.limits.max_absolute_path {
+            return Err("absolute path too long");
+        }
+
+        if self.windows_name_check && has_windows_reserved_name(name) {
+            return Err("Windows reserved name");
         }
         Ok(())
     }
 }
 
+/// Convert a ZIP entry's MS-DOS timestamp (read as UTC) to a `SystemTime`.
+///
+/// Returns None for missing or invalid timestamps and for the 1980-01-01
+/// 00:00:00 minimum, which archivers write when the real date is unknown or
+/// predates 1980.
+fn zip_mtime(dt: Option<zip::DateTime>) -> Option<SystemTime> {
+    let dt = dt.filter(|dt| dt.is_valid() && *dt != zip::DateTime::default())?;
+
+    // Days since 1970-01-01 (Howard Hinnant's days_from_civil)
+    let (month, day) = (i64::from(dt.month()), i64::from(dt.day()));
+    let year = i64::from(dt.year()) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let secs = days * 86_400
+        + i64::from(dt.hour()) * 3_600
+        + i64::from(dt.minute()) * 60
+        + i64::from(dt.second());
+    // DOS dates start in 1980, so this is always after the epoch
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs as u64))
+}
+
+/// Running state of an extraction, shared by [`Extractor::extract`] and
+/// [`Extractor::extract_stream`].
+struct Walk {
+    report: Report,
+    /// Directory times are applied last, since writing into a directory
+    /// bumps its mtime.
+    dir_mtimes: Vec<(PathBuf, SystemTime)>,
+    /// File and symlink names seen, for duplicate detection.
+    seen: HashSet<PathBuf>,
+    /// Lowercased names seen, for case collision detection.
+    folded: HashMap<String, String>,
+    /// Files, directories, and symlinks created, to remove if the
+    /// extraction fails. Only filled in when cleanup is on.
+    created: Vec<PathBuf>,
+}
+
+impl Walk {
+    fn new() -> Self {
+        Self {
+            report: Report::default(),
+            dir_mtimes: Vec::new(),
+            seen: HashSet::new(),
+            folded: HashMap::new(),
+            created: Vec::new(),
+        }
+    }
+
+    /// Apply the deferred directory times and return the report.
+    fn finish(&mut self, bytes_read: u64) -> Result<Report, Error> {
+        // Deepest first, so setting a child's time cannot disturb its parent
+        let mut dir_mtimes = std::mem::take(&mut self.dir_mtimes);
+        dir_mtimes.sort_by_key(|(path, _)| std::cmp::Reverse(path.components().count()));
+        for (path, mtime) in dir_mtimes {
+            set_dir_mtime(&path, mtime)?;
+        }
+        self.report.bytes_read = bytes_read;
+        Ok(std::mem::take(&mut self.report))
+    }
+}
+
+/// Set a directory's modification time.
+fn set_dir_mtime(path: &Path, mtime: SystemTime) -> std::io::Result<()> {
+    // Windows can only open a directory handle with backup semantics
+    #[cfg(windows)]
+    let dir = {
+        use std::os::windows::fs::OpenOptionsExt;
+        const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+        fs::OpenOptions::new()
+            .write(true)
+            .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+            .open(path)?
+    };
+    #[cfg(not(windows))]
+    let dir = fs::File::open(path)?;
+    dir.set_modified(mtime)
+}
+
+/// Block size zero runs are detected in; the usual filesystem block.
+const SPARSE_BLOCK: u64 = 4096;
+
+// Helper struct to leave holes for zero blocks in the output file
+struct SparseWriter<'a> {
+    file: &'a mut fs::File,
+    enabled: bool,
+    /// Bytes passed in so far, i.e. the logical file size.
+    pos: u64,
+    /// Zero bytes at the end of `pos` not yet skipped over.
+    hole: u64,
+}
+
+impl<'a> SparseWriter<'a> {
+    fn new(file: &'a mut fs::File, enabled: bool) -> Self {
+        Self {
+            file,
+            enabled,
+            pos: 0,
+            hole: 0,
+        }
+    }
+
+    /// Seek past the pending hole.
+    fn skip_hole(&mut self) -> std::io::Result<()> {
+        if self.hole > 0 {
+            self.file.seek(SeekFrom::Current(self.hole as i64))?;
+            self.hole = 0;
+        }
+        Ok(())
+    }
+
+    /// Extend the file over a trailing hole, which no write follows.
+    fn finish(mut self) -> std::io::Result<()> {
+        if self.hole > 0 {
+            self.skip_hole()?;
+            self.file.set_len(self.pos)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for SparseWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.enabled {
+            return self.file.write(buf);
+        }
+        // Stop at block boundaries so each chunk checked is one whole
+        // block of the file
+        let to_boundary = SPARSE_BLOCK - self.pos % SPARSE_BLOCK;
+        let len = buf.len().min(to_boundary as usize);
+        let chunk = &buf[..len];
+
+        let n = if chunk.iter().all(|&b| b == 0) {
+            self.hole += len as u64;
+            len
+        } else {
+            self.skip_hole()?;
+            self.file.write(chunk)?
+        };
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Reader that reports end of input once `stopped` is set, so the zip
+/// crate can't drain a streamed entry after extraction has failed.
+struct StopReader<'a, R> {
+    inner: R,
+    stopped: &'a Cell<bool>,
+}
+
+impl<R: Read> Read for StopReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.stopped.get() {
+            return Ok(0);
+        }
+        self.inner.read(buf)
+    }
+}
+
 // Helper struct to enforce read limits
 struct LimitReader<'a, R> {
     inner: &'a mut R,
     limit: u64,
     bytes_read: u64,
-    hit_limit: bool,
 }
 
 impl<'a, R: Read> LimitReader<'a, R> {
@@ -844,7 +2637,6 @@ impl<'a, R: Read> LimitReader<'a, R> {
             inner,
             limit,
             bytes_read: 0,
-            hit_limit: false,
         }
     }
 }
@@ -852,7 +2644,6 @@ impl<'a, R: Read> LimitReader<'a, R> {
 impl<'a, R: Read> Read for LimitReader<'a, R> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         if self.bytes_read >= self.limit {
-            self.hit_limit = true;
             return Ok(0);
         }
 
@@ -862,11 +2653,6 @@ impl<'a, R: Read> Read for LimitReader<'a, R> {
 
         let n = self.inner.read(&mut buf[0..len])?;
         self.bytes_read += n as u64;
-
-        if self.bytes_read >= self.limit {
-            self.hit_limit = true;
-        }
-
         Ok(n)
     }
 }
@@ -0,0 +1,180 @@
+//! Pluggable storage for [`Driver::filesystem`](crate::Driver::filesystem).
+//!
+//! By default the driver writes to the local disk. A [`FileSystem`]
+//! receives the writes instead, after every entry has passed the same
+//! policy chain, so extraction can target memory, object storage, or
+//! anything else that can hold files.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Where extracted entries are written.
+///
+/// Paths are the destination joined with the checked entry name. Names
+/// are validated against the destination before any method is called,
+/// but an implementation that follows symlinks of its own is responsible
+/// for keeping them inside itself.
+///
+/// # Example
+///
+/// ```
+/// use safe_unzip::FileSystem;
+/// use std::collections::BTreeMap;
+/// use std::io::{self, Write};
+/// use std::path::{Path, PathBuf};
+/// use std::sync::{Arc, Mutex};
+///
+/// /// Keeps file contents in a map; everything else is a no-op.
+/// #[derive(Default)]
+/// struct MemoryFs(Arc<Mutex<BTreeMap<PathBuf, Vec<u8>>>>);
+///
+/// struct MemoryFile(Arc<Mutex<BTreeMap<PathBuf, Vec<u8>>>>, PathBuf);
+///
+/// impl Write for MemoryFile {
+///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+///         let mut files = self.0.lock().unwrap();
+///         files.entry(self.1.clone()).or_default().extend_from_slice(buf);
+///         Ok(buf.len())
+///     }
+///
+///     fn flush(&mut self) -> io::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// impl FileSystem for MemoryFs {
+///     fn create_dir(&self, _path: &Path) -> io::Result<()> {
+///         Ok(())
+///     }
+///
+///     fn open_write(&self, path: &Path, create_new: bool) -> io::Result<Box<dyn Write + Send>> {
+///         let mut files = self.0.lock().unwrap();
+///         if create_new && files.contains_key(path) {
+///             return Err(io::ErrorKind::AlreadyExists.into());
+///         }
+///         files.insert(path.to_path_buf(), Vec::new());
+///         Ok(Box::new(MemoryFile(Arc::clone(&self.0), path.to_path_buf())))
+///     }
+///
+///     fn set_permissions(&self, _path: &Path, _mode: u32) -> io::Result<()> {
+///         Ok(())
+///     }
+///
+///     fn symlink(&self, _target: &Path, _link: &Path) -> io::Result<()> {
+///         Err(io::ErrorKind::Unsupported.into())
+///     }
+///
+///     fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+///         let mut files = self.0.lock().unwrap();
+///         let data = files.remove(from).ok_or(io::ErrorKind::NotFound)?;
+///         files.insert(to.to_path_buf(), data);
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait FileSystem: Send + Sync {
+    /// Create the directory `path` and any missing parents. An existing
+    /// directory is not an error.
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// Open `path` for writing its full contents.
+    ///
+    /// With `create_new`, fail with [`io::ErrorKind::AlreadyExists`] if
+    /// anything is at `path`; otherwise replace it, without writing
+    /// through a symlink that is there.
+    fn open_write(&self, path: &Path, create_new: bool) -> io::Result<Box<dyn Write + Send>>;
+
+    /// Set the Unix permission bits of `path`. Special bits are already
+    /// stripped from `mode`.
+    fn set_permissions(&self, path: &Path, mode: u32) -> io::Result<()>;
+
+    /// Create a symlink at `link` pointing to `target`.
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()>;
+
+    /// Move `from` to `to`, replacing whatever is at `to`.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+}
+
+impl<S: FileSystem + ?Sized> FileSystem for Arc<S> {
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        (**self).create_dir(path)
+    }
+
+    fn open_write(&self, path: &Path, create_new: bool) -> io::Result<Box<dyn Write + Send>> {
+        (**self).open_write(path, create_new)
+    }
+
+    fn set_permissions(&self, path: &Path, mode: u32) -> io::Result<()> {
+        (**self).set_permissions(path, mode)
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        (**self).symlink(target, link)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        (**self).rename(from, to)
+    }
+}
+
+/// The local disk through `std::fs`, which the driver uses unless
+/// another [`FileSystem`] is set. Useful as the inner layer of a wrapper
+/// that records or filters writes.
+///
+/// Permissions and symlinks are Unix only; elsewhere `set_permissions`
+/// does nothing and `symlink` fails with [`io::ErrorKind::Unsupported`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdFileSystem;
+
+impl FileSystem for StdFileSystem {
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn open_write(&self, path: &Path, create_new: bool) -> io::Result<Box<dyn Write + Send>> {
+        if !create_new {
+            // Never write through a symlink left at the path
+            if fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink()) {
+                fs::remove_file(path)?;
+            }
+        }
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .create_new(create_new)
+            .open(path)?;
+        Ok(Box::new(file))
+    }
+
+    fn set_permissions(&self, path: &Path, mode: u32) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (path, mode);
+            Ok(())
+        }
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(target, link)
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (target, link);
+            Err(io::ErrorKind::Unsupported.into())
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+}
@@ -0,0 +1,203 @@
+//! Checksum index of extracted files.
+//!
+//! [`Driver::write_index`](crate::Driver::write_index) records every file an
+//! extraction writes and, once extraction succeeds, saves a machine-readable
+//! index next to the tree: archive path, size, mode, and SHA-256 digest per
+//! file. Compliance scanners and SBOM tooling can consume it without walking
+//! and hashing the tree themselves.
+//!
+//! The format is pluggable through [`IndexFormat`]; [`JsonIndex`] and
+//! [`CsvIndex`] are provided.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use safe_unzip::{Driver, JsonIndex};
+//!
+//! let report = Driver::new("/srv/app")?
+//!     .write_index("/srv/app.index.json", JsonIndex)
+//!     .extract_zip_file("release.zip")?;
+//! # Ok::<(), safe_unzip::Error>(())
+//! ```
+
+use std::fs;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::error::Error;
+use crate::staging::Staging;
+
+/// One extracted file in the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IndexEntry {
+    /// Path of the entry inside the archive.
+    pub path: String,
+    /// Size of the extracted file in bytes.
+    pub size: u64,
+    /// Permission bits of the extracted file (Unix only).
+    pub mode: Option<u32>,
+    /// Lowercase hex SHA-256 of the extracted file.
+    pub sha256: String,
+}
+
+/// Serializes the index. Implement this to emit a custom format.
+pub trait IndexFormat: Send + Sync {
+    /// Write `entries`, sorted by path, to `out`.
+    fn write(&self, entries: &[IndexEntry], out: &mut dyn Write) -> io::Result<()>;
+}
+
+/// JSON index: `{"files": [{"path", "size", "mode", "sha256"}, ...]}`.
+///
+/// `mode` is an octal string such as `"0644"`, or `null` when unknown.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonIndex;
+
+impl IndexFormat for JsonIndex {
+    fn write(&self, entries: &[IndexEntry], out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{{")?;
+        writeln!(out, "  \"files\": [")?;
+        for (i, entry) in entries.iter().enumerate() {
+            let mode = match entry.mode {
+                Some(mode) => format!("\"{:04o}\"", mode),
+                None => "null".to_string(),
+            };
+            let separator = if i + 1 < entries.len() { "," } else { "" };
+            writeln!(
+                out,
+                "    {{\"path\": \"{}\", \"size\": {}, \"mode\": {}, \"sha256\": \"{}\"}}{}",
+                json_escape(&entry.path),
+                entry.size,
+                mode,
+                entry.sha256,
+                separator
+            )?;
+        }
+        writeln!(out, "  ]")?;
+        writeln!(out, "}}")
+    }
+}
+
+/// CSV index with a `path,size,mode,sha256` header row (RFC 4180 quoting).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsvIndex;
+
+impl IndexFormat for CsvIndex {
+    fn write(&self, entries: &[IndexEntry], out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "path,size,mode,sha256")?;
+        for entry in entries {
+            let mode = entry.mode.map(|m| format!("{:04o}", m)).unwrap_or_default();
+            writeln!(
+                out,
+                "{},{},{},{}",
+                csv_field(&entry.path),
+                entry.size,
+                mode,
+                entry.sha256
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Lowercase hex SHA-256 of a file's contents.
+pub(crate) fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Writes the index of an extraction's files once it has succeeded.
+pub(crate) struct IndexWriter {
+    path: PathBuf,
+    format: Box<dyn IndexFormat>,
+}
+
+impl IndexWriter {
+    pub(crate) fn new(path: PathBuf, format: Box<dyn IndexFormat>) -> Self {
+        Self { path, format }
+    }
+
+    /// Hash `files`, given as `(archive name, extracted path)`, and write
+    /// the index.
+    ///
+    /// Digests are taken from the files on disk after extraction, so they
+    /// describe what was actually written. The index is written to a
+    /// temporary file and renamed into place, which replaces (rather than
+    /// follows) anything already at the index path.
+    pub(crate) fn write(&self, files: Vec<(String, PathBuf)>) -> Result<(), Error> {
+        let mut entries = Vec::with_capacity(files.len());
+        for (name, path) in files {
+            let metadata = fs::symlink_metadata(&path)?;
+            #[cfg(unix)]
+            let mode = {
+                use std::os::unix::fs::PermissionsExt;
+                Some(metadata.permissions().mode() & 0o7777)
+            };
+            #[cfg(not(unix))]
+            let mode = None;
+
+            entries.push(IndexEntry {
+                path: name,
+                size: metadata.len(),
+                mode,
+                sha256: sha256_file(&path)?,
+            });
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let parent = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let tmp = Staging::in_dir(parent);
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(tmp.path())?;
+        let mut out = BufWriter::new(file);
+        self.format.write(&entries, &mut out)?;
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(tmp.path(), &self.path)?;
+        tmp.persist();
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+//! Cooperative interruption of an extraction in progress.
+//!
+//! Checked before each entry and on every write, so a single huge entry
+//! cannot run far past the point where extraction should have stopped.
+//! The same writes delimit the time spent decompressing: whatever happens
+//! between two writes is the decompressor producing the next chunk.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+
+/// Conditions under which an extraction stops early.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Interrupt {
+    /// Wall-clock time after which extraction fails.
+    pub(crate) deadline: Option<Instant>,
+    /// Set by the caller to stop extraction.
+    pub(crate) cancel: Option<Arc<AtomicBool>>,
+    /// Longest any one entry's data may take to copy.
+    pub(crate) entry_timeout: Option<Duration>,
+    /// Total time decompression may take across the extraction.
+    pub(crate) decompress_budget: Option<Duration>,
+}
+
+impl Interrupt {
+    /// Fail if extraction should stop now.
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if self
+            .cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+        {
+            return Err(Error::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(Error::DeadlineExceeded);
+        }
+        Ok(())
+    }
+
+    /// Add `elapsed` decompression time to `spent`, failing once the
+    /// budget is used up.
+    pub(crate) fn charge(&self, spent: &mut Duration, elapsed: Duration) -> Result<(), Error> {
+        *spent += elapsed;
+        match self.decompress_budget {
+            Some(limit) if *spent > limit => Err(Error::DecompressBudgetExceeded { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Wrap `inner`, which receives the data of `entry`, so every write
+    /// checks for interruption first. The entry's own timeout starts now,
+    /// and time between writes is added to `spent`.
+    pub(crate) fn writer<'a, W: Write>(
+        &'a self,
+        inner: W,
+        entry: &'a str,
+        spent: &'a mut Duration,
+    ) -> InterruptWriter<'a, W> {
+        let started = Instant::now();
+        InterruptWriter {
+            inner,
+            interrupt: self,
+            entry,
+            started,
+            spent,
+            idle_since: started,
+        }
+    }
+
+    /// Turn an I/O error raised by [`InterruptWriter`] back into the
+    /// interruption error; other errors pass through unchanged.
+    pub(crate) fn recover(&self, err: Error) -> Error {
+        match err {
+            Error::Io(ref e) if e.get_ref().is_some_and(|inner| inner.is::<Interrupted>()) => {
+                self.check().err().unwrap_or(err)
+            }
+            _ => err,
+        }
+    }
+}
+
+/// Marker carried inside the `io::Error` returned by [`InterruptWriter`].
+#[derive(Debug)]
+struct Interrupted;
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "extraction interrupted")
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
+/// Writer that refuses further writes once extraction is interrupted.
+pub(crate) struct InterruptWriter<'a, W> {
+    inner: W,
+    interrupt: &'a Interrupt,
+    entry: &'a str,
+    started: Instant,
+    spent: &'a mut Duration,
+    /// When the previous write returned.
+    idle_since: Instant,
+}
+
+impl<W: Write> Write for InterruptWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.interrupt.check().is_err() {
+            return Err(io::Error::other(Interrupted));
+        }
+        if self
+            .interrupt
+            .entry_timeout
+            .is_some_and(|timeout| self.started.elapsed() >= timeout)
+        {
+            // Converted straight back by `From<io::Error> for Error`
+            return Err(io::Error::other(Error::Timeout {
+                entry: self.entry.to_string(),
+            }));
+        }
+        self.interrupt
+            .charge(self.spent, self.idle_since.elapsed())
+            .map_err(io::Error::other)?;
+        let written = self.inner.write(buf);
+        // Time spent writing is I/O, not decompression
+        self.idle_since = Instant::now();
+        written
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
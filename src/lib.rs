@@ -1,12 +1,23 @@
 mod error;
 mod extractor;
+mod interrupt;
 mod limits;
 
 // New architecture modules (v0.2)
 pub mod adapter;
+mod completion;
+pub mod content;
+mod dedupe;
+mod digest;
 mod driver;
 pub mod entry;
+mod filesystem;
+pub mod index;
+mod manifest;
+pub mod observer;
 pub mod policy;
+mod staging;
+mod text;
 
 // Async API (requires `async` feature)
 #[cfg(feature = "async")]
@@ -48,7 +59,8 @@ pub mod r#async {
 
 pub use error::Error;
 pub use extractor::{
-    ExtractionMode, Extractor, OverwritePolicy, Progress, Report, SymlinkPolicy, VerifyReport,
+    CompressionMethod, ExtractionMode, ExtractionPlan, Extractor, OverwritePolicy, Progress,
+    Report, SymlinkPolicy, VerifyReport,
 };
 pub use limits::Limits;
 
@@ -57,10 +69,25 @@ pub use limits::Limits;
 pub use adapter::SevenZAdapter;
 #[cfg(feature = "tar")]
 pub use adapter::TarAdapter;
-pub use adapter::ZipAdapter;
-pub use driver::{Driver, ExtractionReport, OverwriteMode, ValidationMode};
+pub use adapter::{ArchiveReader, ZipAdapter};
+pub use content::ContentCategory;
+pub use dedupe::{DedupeStore, MemoryDedupeStore};
+pub use digest::DigestAlgorithm;
+pub use driver::{
+    ArchiveSource, Driver, Durability, EntryStream, ExtractionReport, Order, OverwriteMode,
+    ValidationMode,
+};
 pub use entry::{Entry, EntryInfo, EntryKind};
-pub use policy::{Policy, PolicyChain, PolicyConfig, SymlinkBehavior};
+pub use filesystem::{FileSystem, StdFileSystem};
+pub use index::{CsvIndex, IndexEntry, IndexFormat, JsonIndex};
+pub use manifest::{Manifest, ManifestSizeMismatch};
+pub use observer::{EntryAction, ExtractionObserver, SkipReason};
+pub use policy::{
+    AbsolutePathPolicy, CaseCollisionPolicy, DirCountPolicy, PermissionMode, Policy, PolicyChain,
+    PolicyConfig, RenamedEntry, SkippedSymlink, SkippedUnsupported, SymlinkBehavior,
+    SymlinkRewrite, UnknownSizePolicy, UnsupportedPolicy,
+};
+pub use text::{LineEnding, TextConvert};
 
 /// Extract from a reader with default settings.
 ///
@@ -152,10 +179,147 @@ pub fn verify_bytes(data: &[u8]) -> Result<VerifyReport, Error> {
     Extractor::new(&temp)?.verify_bytes(data)
 }
 
+/// Check whether an archive would extract cleanly, without extracting it.
+///
+/// Runs the same path, size, count, depth, and symlink checks as
+/// extraction against the archive's metadata only; no entry content is
+/// decompressed or written. Returns `Ok(false)` when the archive breaks a
+/// rule or limit, and `Err` only for I/O errors or archives that cannot be
+/// read at all.
+///
+/// `.tar`, `.tar.gz`, and `.tgz` files are checked as TAR (with the `tar`
+/// feature), as are `.tar.xz` and `.txz` with the `xz` feature; everything
+/// else is read as ZIP. Declared sizes are trusted
+/// here, so extraction still enforces limits against the actual data.
+///
+/// # Example
+///
+/// ```no_run
+/// use safe_unzip::{is_safe_file, Limits, SymlinkBehavior};
+///
+/// if !is_safe_file("upload.zip", &Limits::default(), SymlinkBehavior::Error)? {
+///     println!("rejected");
+/// }
+/// # Ok::<(), safe_unzip::Error>(())
+/// ```
+pub fn is_safe_file<P: AsRef<std::path::Path>>(
+    path: P,
+    limits: &Limits,
+    symlinks: SymlinkBehavior,
+) -> Result<bool, Error> {
+    let path = path.as_ref();
+    let checked = limits
+        .check_compressed(std::fs::metadata(path)?.len())
+        .and_then(|()| list_for_check(path))
+        .and_then(|entries| {
+            // Validation needs a real directory to anchor paths; nothing is written
+            Driver::new(std::env::temp_dir())?
+                .limits(*limits)
+                .symlinks(symlinks)
+                .validate_entries(&entries)
+        });
+
+    match checked {
+        Ok(()) => Ok(true),
+        Err(e) if e.is_violation() => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Check that an archive contains exactly the files in `manifest`, with
+/// the listed sizes, without extracting it.
+///
+/// Only archive metadata is read. Directory entries are ignored, since
+/// they carry no content; every other entry (files and symlinks) must be
+/// listed, and every listed name must be present exactly once. Any
+/// difference fails with [`Error::ManifestMismatch`], which lists all of
+/// them rather than just the first.
+///
+/// The format is picked by extension as for [`is_safe_file`]. Sizes are
+/// the ones the archive declares; extraction still checks them against
+/// the actual data.
+///
+/// # Example
+///
+/// ```no_run
+/// use safe_unzip::{validate_against_manifest, Manifest};
+///
+/// let manifest = Manifest::new()
+///     .entry("bin/tool", 48_120)
+///     .entry("README.md", 912);
+/// validate_against_manifest("bundle.zip", &manifest)?;
+/// # Ok::<(), safe_unzip::Error>(())
+/// ```
+pub fn validate_against_manifest<P: AsRef<std::path::Path>>(
+    path: P,
+    manifest: &Manifest,
+) -> Result<(), Error> {
+    let mut seen = std::collections::BTreeSet::new();
+    let mut extra = Vec::new();
+    let mut size_mismatches = Vec::new();
+
+    for entry in list_for_check(path.as_ref())? {
+        if entry.kind == EntryKind::Directory {
+            continue;
+        }
+        match manifest.get(&entry.name) {
+            Some(expected) if seen.insert(entry.name.clone()) => {
+                if entry.size != expected {
+                    size_mismatches.push(ManifestSizeMismatch {
+                        name: entry.name,
+                        expected,
+                        actual: entry.size,
+                    });
+                }
+            }
+            _ => extra.push(entry.name),
+        }
+    }
+
+    let missing: Vec<String> = manifest
+        .iter()
+        .filter(|(name, _)| !seen.contains(*name))
+        .map(|(name, _)| name.to_string())
+        .collect();
+
+    if missing.is_empty() && extra.is_empty() && size_mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::ManifestMismatch {
+            missing,
+            extra,
+            size_mismatches,
+        })
+    }
+}
+
+/// List entries for [`is_safe_file`], picking the format by extension.
+fn list_for_check(path: &std::path::Path) -> Result<Vec<entry::EntryInfo>, Error> {
+    #[cfg(feature = "tar")]
+    {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            return list_tar_gz_entries(path);
+        }
+        #[cfg(feature = "xz")]
+        if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+            return list_tar_xz_entries(path);
+        }
+        if name.ends_with(".tar") {
+            return list_tar_entries(path);
+        }
+    }
+    list_zip_entries(path)
+}
+
 /// List entries in a ZIP archive without extracting.
 ///
-/// Returns metadata for all entries including name, size, and type.
-/// No files are written to disk.
+/// Returns metadata for all entries including name, size, and type, the
+/// Unix mode when the archive records one, and symlink targets, so they
+/// can be audited before extracting. No files are written to disk.
 ///
 /// # Example
 ///
@@ -165,6 +329,12 @@ pub fn verify_bytes(data: &[u8]) -> Result<VerifyReport, Error> {
 /// let entries = list_zip_entries("archive.zip")?;
 /// for entry in entries {
 ///     println!("{}: {} bytes, {:?}", entry.name, entry.size, entry.kind);
+///     if let Some(mode) = entry.mode.filter(|mode| mode & 0o4000 != 0) {
+///         println!("  setuid ({:o})", mode);
+///     }
+///     if let Some(target) = entry.symlink_target() {
+///         println!("  -> {}", target);
+///     }
 /// }
 /// # Ok::<(), safe_unzip::Error>(())
 /// ```
@@ -172,7 +342,7 @@ pub fn list_zip_entries<P: AsRef<std::path::Path>>(
     path: P,
 ) -> Result<Vec<entry::EntryInfo>, Error> {
     let mut adapter = ZipAdapter::open(path)?;
-    adapter.entries_metadata()
+    adapter.entries_with_targets()
 }
 
 /// List entries in a ZIP archive from a reader.
@@ -180,10 +350,43 @@ pub fn list_zip<R: std::io::Read + std::io::Seek>(
     reader: R,
 ) -> Result<Vec<entry::EntryInfo>, Error> {
     let mut adapter = ZipAdapter::new(reader)?;
-    adapter.entries_metadata()
+    adapter.entries_with_targets()
+}
+
+/// Total uncompressed size a ZIP archive declares for its entries, summed
+/// from the central directory without decompressing anything.
+///
+/// Useful for capacity planning and for rejecting an archive early, e.g.
+/// when it would not fit in the free disk space. The sizes come from the
+/// archive, so an attacker controls them: a bomb can declare a few bytes
+/// and inflate to gigabytes. Use the sum to reject archives early, never
+/// as the only defense; extraction still enforces
+/// [`Limits::max_total_bytes`] on the bytes actually written. Saturates
+/// at `u64::MAX`.
+///
+/// # Example
+///
+/// ```no_run
+/// use safe_unzip::zip_total_uncompressed;
+///
+/// let needed = zip_total_uncompressed("archive.zip")?;
+/// if needed > 10 * 1024 * 1024 * 1024 {
+///     eprintln!("archive declares {} bytes, refusing", needed);
+/// }
+/// # Ok::<(), safe_unzip::Error>(())
+/// ```
+pub fn zip_total_uncompressed<P: AsRef<std::path::Path>>(path: P) -> Result<u64, Error> {
+    ZipAdapter::open(path)?.total_uncompressed()
+}
+
+/// Total uncompressed size a ZIP archive in memory declares. See
+/// [`zip_total_uncompressed`].
+pub fn zip_total_uncompressed_bytes(data: &[u8]) -> Result<u64, Error> {
+    ZipAdapter::new(std::io::Cursor::new(data))?.total_uncompressed()
 }
 
-/// List entries in a TAR archive without extracting.
+/// List entries in a TAR archive without extracting, with each entry's
+/// mode and, for links, its target.
 ///
 /// Note: TAR is a sequential format, so listing requires reading
 /// through the entire archive (but not decompressing file content).
@@ -219,6 +422,17 @@ pub fn list_tar_gz_entries<P: AsRef<std::path::Path>>(
     list_tar(decoder)
 }
 
+/// List entries in an xz-compressed TAR archive.
+#[cfg(feature = "xz")]
+pub fn list_tar_xz_entries<P: AsRef<std::path::Path>>(
+    path: P,
+) -> Result<Vec<entry::EntryInfo>, Error> {
+    let file = std::fs::File::open(path)?;
+    let reader = std::io::BufReader::new(file);
+    let decoder = xz2::read::XzDecoder::new(reader);
+    list_tar(decoder)
+}
+
 /// List entries in a TAR archive from a reader.
 #[cfg(feature = "tar")]
 pub fn list_tar<R: std::io::Read>(reader: R) -> Result<Vec<entry::EntryInfo>, Error> {
@@ -226,7 +440,8 @@ pub fn list_tar<R: std::io::Read>(reader: R) -> Result<Vec<entry::EntryInfo>, Er
     let mut archive = tar::Archive::new(reader);
 
     for entry_result in archive.entries()? {
-        let entry = entry_result?;
+        let mut entry = entry_result?;
+        let pax = adapter::read_pax(&mut entry)?;
         let header = entry.header();
         let name = entry.path()?.to_string_lossy().into_owned();
 
@@ -252,10 +467,125 @@ pub fn list_tar<R: std::io::Read>(reader: R) -> Result<Vec<entry::EntryInfo>, Er
         entries.push(entry::EntryInfo {
             name,
             size: header.size()?,
+            compressed_size: None,
             kind,
             mode: header.mode().ok(),
+            uid: header.uid().ok().and_then(|id| u32::try_from(id).ok()),
+            gid: header.gid().ok().and_then(|id| u32::try_from(id).ok()),
+            // A PAX `size` record overrides the ustar field (often 0)
+            mtime: pax.mtime.or_else(|| adapter::header_mtime(header)),
+            size_known: header.size()? == entry.size(),
+            xattrs: pax.xattrs,
         });
     }
 
     Ok(entries)
 }
+
+/// List entries in a 7z archive without extracting.
+///
+/// Only the archive headers are read, so nothing is decompressed and
+/// memory use grows with the number of entries, not their size. Sizes are
+/// the ones the headers declare. Extracting is different: a 7z archive is
+/// decompressed into memory as a whole, see [`SevenZAdapter`].
+///
+/// # Example
+///
+/// ```no_run
+/// let entries = safe_unzip::list_7z_entries("archive.7z")?;
+/// let total: u64 = entries.iter().map(|e| e.size).sum();
+/// println!("{} entries, {} bytes unpacked", entries.len(), total);
+/// # Ok::<(), safe_unzip::Error>(())
+/// ```
+#[cfg(feature = "sevenz")]
+pub fn list_7z_entries<P: AsRef<std::path::Path>>(path: P) -> Result<Vec<entry::EntryInfo>, Error> {
+    SevenZAdapter::open(path)?.header_entries()
+}
+
+/// List entries in a 7z archive held in memory.
+///
+/// The 7z reader needs a file, so `data` is first copied to a temporary
+/// file; see [`list_7z_entries`].
+#[cfg(feature = "sevenz")]
+pub fn list_7z_bytes(data: &[u8]) -> Result<Vec<entry::EntryInfo>, Error> {
+    SevenZAdapter::from_bytes(data)?.header_entries()
+}
+
+/// Verify a TAR archive by reading every header and every entry's content.
+///
+/// TAR has no per-entry CRC. Each header's checksum is validated instead,
+/// and each entry must supply as many bytes as its header declares, so a
+/// truncated or damaged archive fails with [`Error::Io`].
+///
+/// # Example
+///
+/// ```no_run
+/// let report = safe_unzip::verify_tar_file("backup.tar")?;
+/// println!("Verified {} entries", report.entries_verified);
+/// # Ok::<(), safe_unzip::Error>(())
+/// ```
+#[cfg(feature = "tar")]
+pub fn verify_tar_file<P: AsRef<std::path::Path>>(path: P) -> Result<VerifyReport, Error> {
+    let file = std::fs::File::open(path)?;
+    verify_tar(std::io::BufReader::new(file))
+}
+
+/// Verify a gzip-compressed TAR archive. Besides the checks of
+/// [`verify_tar_file`], the whole gzip stream is decompressed so its
+/// trailing CRC32 and length are validated.
+#[cfg(feature = "tar")]
+pub fn verify_tar_gz_file<P: AsRef<std::path::Path>>(path: P) -> Result<VerifyReport, Error> {
+    let file = std::fs::File::open(path)?;
+    let decoder = flate2::read::GzDecoder::new(std::io::BufReader::new(file));
+    verify_tar(decoder)
+}
+
+/// Verify an xz-compressed TAR archive. Like [`verify_tar_gz_file`], the
+/// whole stream is decompressed, so the xz block and stream checks are
+/// validated too.
+#[cfg(feature = "xz")]
+pub fn verify_tar_xz_file<P: AsRef<std::path::Path>>(path: P) -> Result<VerifyReport, Error> {
+    let file = std::fs::File::open(path)?;
+    let decoder = xz2::read::XzDecoder::new(std::io::BufReader::new(file));
+    verify_tar(decoder)
+}
+
+/// Verify a TAR archive from a reader. See [`verify_tar_file`].
+///
+/// The reader is drained to its end, past the TAR end-of-archive marker,
+/// so a compression layer underneath gets to check its trailer.
+#[cfg(feature = "tar")]
+pub fn verify_tar<R: std::io::Read>(reader: R) -> Result<VerifyReport, Error> {
+    let mut archive = tar::Archive::new(reader);
+    let mut entries_verified = 0usize;
+    let mut bytes_verified = 0u64;
+
+    // The tar crate rejects a header whose checksum does not match
+    for entry_result in archive.entries()? {
+        let mut entry = entry_result?;
+        let declared = entry.size();
+        let read = std::io::copy(&mut entry, &mut std::io::sink())?;
+        if read != declared {
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!(
+                    "entry '{}' is truncated: {} of {} bytes",
+                    entry.path()?.display(),
+                    read,
+                    declared
+                ),
+            )));
+        }
+        if entry.header().entry_type().is_file() {
+            entries_verified += 1;
+            bytes_verified += read;
+        }
+    }
+
+    std::io::copy(&mut archive.into_inner(), &mut std::io::sink())?;
+
+    Ok(VerifyReport {
+        entries_verified,
+        bytes_verified,
+    })
+}
@@ -1,3 +1,7 @@
+use std::path::Path;
+
+use crate::error::Error;
+
 #[derive(Debug, Clone, Copy)]
 pub struct Limits {
     /// Maximum total bytes to extract. Default: 1 GB.
@@ -6,20 +10,142 @@ pub struct Limits {
     /// Maximum number of files to extract. Default: 10,000.
     pub max_file_count: usize,
 
+    /// Maximum number of directory entries to extract, which
+    /// `max_file_count` doesn't cover. Default: 100,000.
+    pub max_dir_count: usize,
+
+    /// Maximum number of entries in the archive, counting files,
+    /// directories, symlinks and everything else alike. ZIP and 7z archives
+    /// are checked against the count in their index before anything is
+    /// extracted, TAR archives as each entry is reached. Default:
+    /// `usize::MAX` (unchecked).
+    pub max_entries: usize,
+
     /// Maximum size of a single file. Default: 100 MB.
     pub max_single_file: u64,
 
-    /// Maximum directory depth. Default: 50.
+    /// Maximum directory depth of an entry name, counted from the
+    /// destination. Default: 50. See
+    /// [`Driver::depth_includes_destination`](crate::Driver::depth_includes_destination)
+    /// to count the destination's own depth as well.
     pub max_path_depth: usize,
+
+    /// Maximum length in bytes of the final path (destination, separator and
+    /// entry name). Default: the platform `PATH_MAX`.
+    pub max_absolute_path: usize,
+
+    /// Maximum size of the archive itself, i.e. the compressed input read
+    /// from the source. Checked before decompression where the size is
+    /// known up front; for `.tar.gz` and `.tar.xz` the bytes fed to the
+    /// decoder are counted. Default: unlimited; [`Limits::strict`] uses
+    /// 100 MB.
+    pub max_compressed_bytes: u64,
+
+    /// Maximum ratio of an entry's uncompressed size to its compressed
+    /// size, checked against the sizes the entry declares. Catches bombs
+    /// that stay under the absolute limits on a single entry but inflate
+    /// far more than real data does. Only formats that record a compressed
+    /// size per entry (ZIP) are checked. Deflate tops out near 1,032:1, so
+    /// a limit around 1,000 only rejects entries that are almost entirely
+    /// one repeated byte, which includes legitimately zero-filled files.
+    /// Default: `None` (unchecked); [`Limits::strict`] uses 1,000.
+    pub max_compression_ratio: Option<u64>,
 }
 
+/// Platform path length limit, including the terminating NUL.
+#[cfg(windows)]
+const DEFAULT_MAX_ABSOLUTE_PATH: usize = 260;
+#[cfg(target_os = "macos")]
+const DEFAULT_MAX_ABSOLUTE_PATH: usize = 1024;
+#[cfg(not(any(windows, target_os = "macos")))]
+const DEFAULT_MAX_ABSOLUTE_PATH: usize = 4096;
+
 impl Default for Limits {
     fn default() -> Self {
         Self {
             max_total_bytes: 1024 * 1024 * 1024, // 1 GB
             max_file_count: 10_000,
+            max_dir_count: 100_000,
+            max_entries: usize::MAX,
             max_single_file: 100 * 1024 * 1024, // 100 MB
             max_path_depth: 50,
+            max_absolute_path: DEFAULT_MAX_ABSOLUTE_PATH,
+            max_compressed_bytes: u64::MAX,
+            max_compression_ratio: None,
+        }
+    }
+}
+
+impl Limits {
+    /// Tighter limits for untrusted uploads: 100 MB total, 1,000 files,
+    /// 10 MB per file, depth 10, a 100 MB archive, and a compression ratio
+    /// of 1,000.
+    pub fn strict() -> Self {
+        Self {
+            max_total_bytes: 100 * 1024 * 1024,
+            max_file_count: 1_000,
+            max_single_file: 10 * 1024 * 1024,
+            max_path_depth: 10,
+            max_compressed_bytes: 100 * 1024 * 1024,
+            max_compression_ratio: Some(1000),
+            ..Self::default()
         }
     }
+
+    /// Fail if `size` bytes of archive input exceed `max_compressed_bytes`.
+    pub(crate) fn check_compressed(&self, size: u64) -> Result<(), Error> {
+        if size > self.max_compressed_bytes {
+            return Err(Error::CompressedSizeExceeded {
+                limit: self.max_compressed_bytes,
+                size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Fail if an archive of `count` entries exceeds `max_entries`.
+    pub(crate) fn check_entry_count(&self, count: usize) -> Result<(), Error> {
+        if count > self.max_entries {
+            return Err(Error::EntryCountExceeded {
+                limit: self.max_entries,
+                attempted: count,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Fail if `entry` declares more than `limit` times its compressed size.
+/// An entry with nothing compressed counts as ratio 0.
+pub(crate) fn check_compression_ratio(
+    entry: &str,
+    size: u64,
+    compressed_size: u64,
+    limit: Option<u64>,
+) -> Result<(), Error> {
+    let Some(limit) = limit else {
+        return Ok(());
+    };
+    let ratio = size.checked_div(compressed_size).unwrap_or(0);
+    if ratio > limit {
+        return Err(Error::CompressionRatioExceeded {
+            entry: entry.to_string(),
+            ratio,
+            limit,
+        });
+    }
+    Ok(())
+}
+
+/// Fail if the filesystem holding `destination` has less than `required`
+/// bytes available to this process.
+pub(crate) fn check_free_space(destination: &Path, required: u64) -> Result<(), Error> {
+    let available = fs2::available_space(destination)?;
+    if required > available {
+        return Err(Error::InsufficientSpace {
+            required,
+            available,
+        });
+    }
+    Ok(())
 }
@@ -0,0 +1,83 @@
+//! Expected archive contents for
+//! [`validate_against_manifest`](crate::validate_against_manifest).
+//!
+//! A manifest is the list of file names and sizes a trusted bundle is
+//! supposed to contain. Comparing an archive against it before extraction
+//! catches added, removed, or resized entries without touching disk.
+
+use std::collections::BTreeMap;
+
+/// Entry names and sizes an archive must contain, no more and no less.
+///
+/// # Example
+///
+/// ```
+/// use safe_unzip::Manifest;
+///
+/// let manifest = Manifest::new()
+///     .entry("bin/tool", 48_120)
+///     .entry("README.md", 912);
+/// assert_eq!(manifest.len(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    entries: BTreeMap<String, u64>,
+}
+
+impl Manifest {
+    /// An empty manifest; only an archive without files matches it.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect a file `name` of exactly `size` bytes. A repeated name
+    /// replaces the earlier size.
+    pub fn entry(mut self, name: impl Into<String>, size: u64) -> Self {
+        self.entries.insert(name.into(), size);
+        self
+    }
+
+    /// Expected size of `name`, if listed.
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.entries.get(name).copied()
+    }
+
+    /// Number of listed files.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// True if no files are listed.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Listed `(name, size)` pairs in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.entries
+            .iter()
+            .map(|(name, size)| (name.as_str(), *size))
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, u64)> for Manifest {
+    fn from_iter<I: IntoIterator<Item = (S, u64)>>(iter: I) -> Self {
+        Self {
+            entries: iter
+                .into_iter()
+                .map(|(name, size)| (name.into(), size))
+                .collect(),
+        }
+    }
+}
+
+/// A file whose size in the archive differs from the manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestSizeMismatch {
+    /// Entry name.
+    pub name: String,
+    /// Size listed in the manifest.
+    pub expected: u64,
+    /// Size declared in the archive.
+    pub actual: u64,
+}
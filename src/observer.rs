@@ -0,0 +1,120 @@
+//! Per-entry extraction hooks.
+//!
+//! An [`ExtractionObserver`] sees every entry the [`Driver`](crate::Driver)
+//! processes: it can veto an entry before anything is written, learn where
+//! each extracted entry landed, and find out why an entry was skipped.
+//! Progress reporting, logging, and audit trails are all observers; the
+//! CLI's `--verbose` output for TAR archives is a small one that prints each
+//! entry as it is written.
+//!
+//! Observers never weaken security checks. Vetoing an entry only ever skips
+//! it, and entries that fail a policy are rejected before the observer
+//! would see them extracted.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use safe_unzip::{Driver, EntryAction, EntryInfo, ExtractionObserver, SkipReason};
+//! use std::path::Path;
+//!
+//! struct Audit;
+//!
+//! impl ExtractionObserver for Audit {
+//!     fn before_entry(&self, entry: &EntryInfo) -> EntryAction {
+//!         if entry.name.ends_with(".exe") {
+//!             EntryAction::Skip
+//!         } else {
+//!             EntryAction::Extract
+//!         }
+//!     }
+//!
+//!     fn after_entry(&self, entry: &EntryInfo, path: &Path) {
+//!         println!("{} -> {}", entry.name, path.display());
+//!     }
+//!
+//!     fn on_skip(&self, entry: &EntryInfo, reason: SkipReason) {
+//!         println!("skipped {} ({:?})", entry.name, reason);
+//!     }
+//! }
+//!
+//! let report = Driver::new("/tmp/out")?
+//!     .observe(Audit)
+//!     .extract_zip_file("archive.zip")?;
+//! # Ok::<(), safe_unzip::Error>(())
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::entry::EntryInfo;
+
+/// What to do with an entry, as decided by [`ExtractionObserver::before_entry`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EntryAction {
+    /// Extract the entry normally.
+    #[default]
+    Extract,
+    /// Skip the entry; reported as [`SkipReason::Vetoed`].
+    Skip,
+}
+
+/// Why an entry was not extracted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// Rejected by the entry filter (`filter`, `only`, globs), or dropped by
+    /// `map_name`.
+    Filtered,
+    /// Vetoed by an observer's `before_entry`.
+    Vetoed,
+    /// Symlinks are not being created.
+    Symlink,
+    /// The symlink's target escapes the destination
+    /// (`SymlinkBehavior::ContainedOrSkip`).
+    SymlinkEscape,
+    /// The target already exists and `OverwriteMode::Skip` is set, or it
+    /// is identical to the entry under `OverwriteMode::ErrorIfDifferent`.
+    AlreadyExists,
+    /// File contents are not written in `structure_only` mode.
+    StructureOnly,
+    /// The name differs from an earlier entry's only in case
+    /// (`CaseCollisionPolicy::KeepFirst`).
+    CaseCollision,
+    /// Nothing is left of the name after `strip_components`.
+    Stripped,
+}
+
+/// Hooks called for each entry during extraction.
+///
+/// All methods have no-op defaults, so implementations override only what
+/// they need. Methods take `&self`; use interior mutability (atomics, a
+/// `Mutex`) to accumulate state.
+pub trait ExtractionObserver: Send + Sync {
+    /// Called for each entry that passes the filter, before policies are
+    /// checked. Returning [`EntryAction::Skip`] skips the entry.
+    fn before_entry(&self, _entry: &EntryInfo) -> EntryAction {
+        EntryAction::Extract
+    }
+
+    /// Called after a file, directory, or symlink has been written to `path`.
+    fn after_entry(&self, _entry: &EntryInfo, _path: &Path) {}
+
+    /// Called when an entry is skipped rather than extracted.
+    fn on_skip(&self, _entry: &EntryInfo, _reason: SkipReason) {}
+}
+
+/// Lets callers keep a handle to an observer and read its state after
+/// extraction.
+impl<T: ExtractionObserver + ?Sized> ExtractionObserver for Arc<T> {
+    fn before_entry(&self, entry: &EntryInfo) -> EntryAction {
+        (**self).before_entry(entry)
+    }
+
+    fn after_entry(&self, entry: &EntryInfo, path: &Path) {
+        (**self).after_entry(entry, path)
+    }
+
+    fn on_skip(&self, entry: &EntryInfo, reason: SkipReason) {
+        (**self).on_skip(entry, reason)
+    }
+}
@@ -3,12 +3,16 @@
 //! Policies validate entries before they are extracted, providing
 //! protection against various archive-based attacks.
 
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
 
 use path_jail::Jail;
 
 use crate::entry::{EntryInfo, EntryKind};
 use crate::error::Error;
+use crate::limits::{check_compression_ratio, Limits};
 
 /// State tracked during extraction for cumulative limit checks.
 #[derive(Debug, Clone, Default)]
@@ -21,6 +25,144 @@ pub struct ExtractionState {
     pub bytes_written: u64,
     /// Entries skipped (symlinks, filtered, etc.).
     pub entries_skipped: usize,
+    /// Symlinks created (only with [`SymlinkBehavior::AllowContained`]).
+    pub symlinks_created: usize,
+    /// Escaping symlinks skipped under [`SymlinkBehavior::ContainedOrSkip`].
+    pub skipped_symlinks: Vec<SkippedSymlink>,
+    /// Device, fifo, and other unsupported entries skipped under
+    /// [`UnsupportedPolicy::Skip`].
+    pub skipped_unsupported: Vec<SkippedUnsupported>,
+    /// Time spent decompressing entry data, as charged against
+    /// [`Driver::max_decompress_cpu`](crate::Driver::max_decompress_cpu).
+    pub decompress_time: Duration,
+    /// Entries written under another name by
+    /// [`CaseCollisionPolicy::SuffixSecond`].
+    pub renamed_entries: Vec<RenamedEntry>,
+    /// Lowercased names seen so far, mapped to the name as extracted.
+    /// Only filled in when case collisions are checked.
+    pub(crate) folded_names: HashMap<String, String>,
+    /// Names of the files and symlinks seen so far, as [`normal_path`]s.
+    /// Only filled in when duplicate entries are rejected.
+    pub(crate) seen_names: HashSet<PathBuf>,
+    /// Paths created so far. Only filled in when paths are collected.
+    pub(crate) extracted_paths: Vec<PathBuf>,
+    /// Files, directories, and symlinks this run created, to remove if it
+    /// fails. Only filled in when cleanup on error is on.
+    pub(crate) created: Vec<PathBuf>,
+    /// Directories this run created, to remove afterwards if still empty.
+    /// Only filled in when empty directories are pruned.
+    pub(crate) new_dirs: Vec<PathBuf>,
+    /// `(archive name, extracted path)` of each file written, for the
+    /// index. Only filled in when an index is written.
+    pub(crate) indexed: Vec<(String, PathBuf)>,
+    /// Where entries are written instead of the driver's destination, as
+    /// for each archive of `extract_many_into_subdirs`.
+    pub(crate) destination: Option<PathBuf>,
+}
+
+/// A symlink that was not created because its target escapes the
+/// destination.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedSymlink {
+    /// Path of the symlink entry inside the archive.
+    pub name: String,
+    /// Target as stored in the archive.
+    pub target: String,
+}
+
+/// An entry that was not extracted because its type (device node, fifo,
+/// ...) is not supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedUnsupported {
+    /// Path of the entry inside the archive.
+    pub name: String,
+    /// Entry type as reported by the archive, e.g. `"character device"`.
+    pub entry_type: String,
+}
+
+/// An entry extracted under a different name because it collided with an
+/// earlier one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenamedEntry {
+    /// Path of the entry inside the archive.
+    pub name: String,
+    /// Path it was extracted to, relative to the destination.
+    pub renamed_to: String,
+}
+
+/// What to do with an entry whose name matches an earlier entry's except
+/// for case, e.g. `readme` after `README`. On a case-insensitive filesystem
+/// (the macOS and Windows defaults) the second would overwrite the first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseCollisionPolicy {
+    /// Fail with [`Error::CaseCollision`].
+    Error,
+    /// Extract the second entry with `~1` (or the next free number)
+    /// appended to its file stem, so `readme` becomes `readme~1` and
+    /// `Notes.txt` becomes `Notes~1.txt`. Recorded as a [`RenamedEntry`].
+    SuffixSecond,
+    /// Skip the second entry and keep extracting.
+    KeepFirst,
+}
+
+/// What to do with entries of a type that cannot be extracted safely, such
+/// as TAR device nodes and fifos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnsupportedPolicy {
+    /// Fail with [`Error::UnsupportedEntryType`].
+    #[default]
+    Abort,
+    /// Skip the entry, record it as a [`SkippedUnsupported`], and keep
+    /// extracting.
+    Skip,
+}
+
+/// Which Unix permissions extracted files and directories get.
+///
+/// setuid, setgid, and sticky bits are always cleared, and directories
+/// always keep owner `rwx` so their contents can still be extracted into
+/// them. Ignored on other platforms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PermissionMode {
+    /// Apply the mode stored in the archive. Entries without one keep
+    /// whatever the system gives new files.
+    #[default]
+    FromArchive,
+    /// AND the stored mode with this mask, so `Mask(0o755)` keeps an entry
+    /// from being group or world writable. Entries without a stored mode
+    /// are treated as `0o644` (files) or `0o755` (directories).
+    Mask(u32),
+    /// Ignore the archive and give every file and directory these modes.
+    Fixed {
+        /// Mode of extracted files.
+        file: u32,
+        /// Mode of directory entries.
+        dir: u32,
+    },
+}
+
+impl PermissionMode {
+    /// Mode to set on an entry stored with `stored`, or `None` to leave it
+    /// as created.
+    #[cfg(unix)]
+    pub(crate) fn resolve(self, stored: Option<u32>, is_dir: bool) -> Option<u32> {
+        let mode = match self {
+            Self::FromArchive => stored?,
+            Self::Mask(mask) => stored.unwrap_or(if is_dir { 0o755 } else { 0o644 }) & mask,
+            Self::Fixed { file, dir } => {
+                if is_dir {
+                    dir
+                } else {
+                    file
+                }
+            }
+        };
+        Some(if is_dir {
+            (mode & 0o777) | 0o700
+        } else {
+            mode & 0o777
+        })
+    }
 }
 
 /// A security policy that validates entries before extraction.
@@ -72,6 +214,160 @@ impl Default for PolicyChain {
 /// Policy that prevents path traversal attacks (Zip Slip).
 pub struct PathPolicy {
     jail: Jail,
+    destination_len: usize,
+    reject_deceptive_unicode: bool,
+    max_absolute_path: usize,
+    absolute_paths: AbsolutePathPolicy,
+    absolute_root: Option<String>,
+    windows_name_check: bool,
+}
+
+/// What to do with entry names rooted at `/` or a drive letter (`C:`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AbsolutePathPolicy {
+    /// Strip the root and extract relative to the destination, so
+    /// `/etc/passwd` is written to `<dest>/etc/passwd`.
+    #[default]
+    Strip,
+    /// Fail with [`Error::AbsolutePath`].
+    Reject,
+}
+
+/// Length of the root prefix of an absolute entry name: leading slashes,
+/// or a drive letter and the slashes after it. `None` for relative names.
+pub(crate) fn absolute_root_len(name: &str) -> Option<usize> {
+    let bytes = name.as_bytes();
+    let drive = if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        2
+    } else {
+        0
+    };
+    let slashes = bytes[drive..].iter().take_while(|&&b| b == b'/').count();
+    (drive + slashes > 0).then_some(drive + slashes)
+}
+
+/// Length of the `./` prefix of an entry name, including repeats and the
+/// slashes after them (`././/a` gives 5). A name that is nothing but such
+/// a prefix (`.`, `./`) is entirely prefix.
+pub(crate) fn cur_dir_prefix_len(name: &str) -> usize {
+    let mut rest = name;
+    loop {
+        if rest == "." {
+            return name.len();
+        }
+        match rest.strip_prefix("./") {
+            Some(after) => rest = after.trim_start_matches('/'),
+            None => return name.len() - rest.len(),
+        }
+    }
+}
+
+/// Depth of an entry name: the number of its normal components, so
+/// `./a/b.txt`, `a//b.txt`, and `/a/b.txt` are all depth 2 and a trailing
+/// slash adds nothing. Every depth check uses this, whichever API runs it.
+pub(crate) fn normal_depth(name: &str) -> usize {
+    normal_components(name).count()
+}
+
+/// An entry name reduced to its normal components, so names that extract
+/// to the same place, like `a/b.txt` and `./a//b.txt`, compare equal.
+pub(crate) fn normal_path(name: &str) -> PathBuf {
+    normal_components(name).collect()
+}
+
+/// Key under which entry names that differ only in case compare equal,
+/// e.g. `README` and `./readme`.
+pub(crate) fn folded_name(name: &str) -> String {
+    normal_path(name).to_string_lossy().to_lowercase()
+}
+
+/// Record `name` in `folded_names`, the names seen so far by
+/// [`folded_name`]. Returns the earlier name it differs from only in
+/// case, if any; the same name twice is a duplicate, not a collision.
+pub(crate) fn case_collision(
+    folded_names: &mut HashMap<String, String>,
+    name: &str,
+) -> Option<String> {
+    let folded = folded_name(name);
+    match folded_names.get(&folded) {
+        Some(existing) if existing != name => Some(existing.clone()),
+        _ => {
+            folded_names.insert(folded, name.to_string());
+            None
+        }
+    }
+}
+
+/// What is left of `name` once its first `count` normal components are
+/// removed, or `None` if nothing would be. An absolute root or a `..` met
+/// on the way ends the stripping early and is kept, so the result still
+/// fails the path checks: `a/../../x` with one component stripped is
+/// `../../x`, never `x`.
+pub(crate) fn strip_components(name: &str, count: usize) -> Option<&str> {
+    if count == 0 {
+        return Some(name);
+    }
+    let mut rest = name;
+    let mut stripped = 0;
+    while stripped < count {
+        let (component, tail) = rest.split_once('/').unwrap_or((rest, ""));
+        match component {
+            "" if rest.is_empty() => return None,
+            "" | ".." => break,
+            "." => {}
+            _ => stripped += 1,
+        }
+        rest = tail.trim_start_matches('/');
+    }
+    Path::new(rest)
+        .components()
+        .any(|c| c != Component::CurDir)
+        .then_some(rest)
+}
+
+/// Callback set with `map_name`: the new name for an entry, or `None` to
+/// skip it.
+pub(crate) type NameMap = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// `name` under the directory `prefix`, or `None` if `name` is absolute
+/// or its `..` components climb above where it starts. Those names are
+/// left alone, so they fail the path checks instead of being let out of
+/// the prefix into the rest of the destination.
+pub(crate) fn prefixed(prefix: &str, name: &str) -> Option<String> {
+    if absolute_root_len(name).is_some() {
+        return None;
+    }
+    let mut depth = 0usize;
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => depth = depth.checked_sub(1)?,
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(format!("{}/{}", prefix, name))
+}
+
+/// A relative directory as a `/`-separated name, or `None` for the
+/// destination itself. The error is the reason it can't be used.
+pub(crate) fn relative_dir(path: &Path) -> Result<Option<String>, &'static str> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str().ok_or("is not valid UTF-8")?),
+            Component::CurDir => {}
+            Component::ParentDir => return Err("must not contain '..'"),
+            Component::RootDir | Component::Prefix(_) => return Err("must be relative"),
+        }
+    }
+    Ok((!parts.is_empty()).then(|| parts.join("/")))
+}
+
+fn normal_components(name: &str) -> impl Iterator<Item = Component<'_>> {
+    Path::new(name)
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
 }
 
 impl PathPolicy {
@@ -81,7 +377,53 @@ impl PathPolicy {
             entry: destination.display().to_string(),
             detail: e.to_string(),
         })?;
-        Ok(Self { jail })
+        Ok(Self {
+            jail,
+            destination_len: destination.as_os_str().len(),
+            reject_deceptive_unicode: false,
+            max_absolute_path: Limits::default().max_absolute_path,
+            absolute_paths: AbsolutePathPolicy::default(),
+            absolute_root: None,
+            windows_name_check: cfg!(windows),
+        })
+    }
+
+    /// Set how absolute entry names are handled.
+    ///
+    /// With [`AbsolutePathPolicy::Strip`] the caller is expected to strip
+    /// the root before writing; the policy checks the stripped name.
+    pub fn absolute_paths(mut self, policy: AbsolutePathPolicy) -> Self {
+        self.absolute_paths = policy;
+        self
+    }
+
+    /// Confine names under `root/` (a relative directory of the
+    /// destination) to that directory, for absolute names the caller maps
+    /// there. `..` that would climb out of it, or that follows a normal
+    /// component, fails with [`Error::PathEscape`].
+    pub fn absolute_root(mut self, root: impl Into<String>) -> Self {
+        self.absolute_root = Some(root.into());
+        self
+    }
+
+    /// Set the maximum length of the final path (destination + `/` + name).
+    pub fn max_absolute_path(mut self, max: usize) -> Self {
+        self.max_absolute_path = max;
+        self
+    }
+
+    /// Reject names containing bidi controls or invisible characters
+    /// (see [`is_deceptive_char`]).
+    pub fn reject_deceptive_unicode(mut self, reject: bool) -> Self {
+        self.reject_deceptive_unicode = reject;
+        self
+    }
+
+    /// Reject names with a Windows device name component, such as
+    /// `CON.txt` or `aux/x`. Default: on for Windows targets only.
+    pub fn windows_name_check(mut self, check: bool) -> Self {
+        self.windows_name_check = check;
+        self
     }
 
     /// Validate a filename for security issues.
@@ -110,41 +452,68 @@ impl PathPolicy {
             return Err("path component too long (>255 bytes)");
         }
 
-        // Reject Windows reserved names
-        for component in Path::new(name).components() {
-            if let Component::Normal(s) = component {
-                if let Some(s) = s.to_str() {
-                    let s_upper = s.to_ascii_uppercase();
-                    let file_stem = s_upper.split('.').next().unwrap_or(&s_upper);
-
-                    match file_stem {
-                        "CON" | "PRN" | "AUX" | "NUL" | "COM1" | "COM2" | "COM3" | "COM4"
-                        | "COM5" | "COM6" | "COM7" | "COM8" | "COM9" | "LPT1" | "LPT2" | "LPT3"
-                        | "LPT4" | "LPT5" | "LPT6" | "LPT7" | "LPT8" | "LPT9" => {
-                            return Err("Windows reserved name");
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
-
         Ok(())
     }
 }
 
 impl Policy for PathPolicy {
     fn check(&self, entry: &EntryInfo, _state: &ExtractionState) -> Result<(), Error> {
+        let name = match absolute_root_len(&entry.name) {
+            Some(_) if self.absolute_paths == AbsolutePathPolicy::Reject => {
+                return Err(Error::AbsolutePath {
+                    entry: entry.name.clone(),
+                });
+            }
+            Some(root) => &entry.name[root..],
+            None => entry.name.as_str(),
+        };
+
         // Validate filename syntax
-        if let Err(reason) = Self::validate_filename(&entry.name) {
+        if let Err(reason) = Self::validate_filename(name) {
             return Err(Error::InvalidFilename {
                 entry: entry.name.clone(),
                 reason: reason.to_string(),
             });
         }
 
+        if self.windows_name_check && has_windows_reserved_name(name) {
+            return Err(Error::InvalidFilename {
+                entry: entry.name.clone(),
+                reason: "Windows reserved name".to_string(),
+            });
+        }
+
+        // The OS limit applies to the joined path, not just the entry name
+        if self.destination_len + 1 + name.len() >= self.max_absolute_path {
+            return Err(Error::InvalidFilename {
+                entry: entry.name.clone(),
+                reason: "absolute path too long".to_string(),
+            });
+        }
+
+        if self.reject_deceptive_unicode && entry.name.chars().any(is_deceptive_char) {
+            return Err(Error::InvalidFilename {
+                entry: entry.name.clone(),
+                reason: "deceptive unicode".to_string(),
+            });
+        }
+
+        if let Some(ref root) = self.absolute_root {
+            let under_root = name
+                .strip_prefix(root.as_str())
+                .and_then(|rest| rest.strip_prefix('/'));
+            if let Some(rest) = under_root {
+                if resolve_link_target(Path::new(""), Path::new(rest)).is_none() {
+                    return Err(Error::PathEscape {
+                        entry: entry.name.clone(),
+                        detail: format!("escapes absolute root '{}'", root),
+                    });
+                }
+            }
+        }
+
         // Check path jail (prevents traversal)
-        self.jail.join(&entry.name).map_err(|e| Error::PathEscape {
+        self.jail.join(name).map_err(|e| Error::PathEscape {
             entry: entry.name.clone(),
             detail: e.to_string(),
         })?;
@@ -153,6 +522,57 @@ impl Policy for PathPolicy {
     }
 }
 
+const WINDOWS_DEVICE_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// True if a component of `name` is a DOS device name (`CON`, `NUL`,
+/// `COM1`, ...), with or without an extension. Windows opens the device
+/// instead of creating such a file; anywhere else they are ordinary names.
+pub(crate) fn has_windows_reserved_name(name: &str) -> bool {
+    Path::new(name).components().any(|component| {
+        let Component::Normal(part) = component else {
+            return false;
+        };
+        let Some(part) = part.to_str() else {
+            return false;
+        };
+        let stem = part.split('.').next().unwrap_or(part).to_ascii_uppercase();
+        WINDOWS_DEVICE_NAMES.contains(&stem.as_str())
+    })
+}
+
+/// Characters that change how a name is displayed without being visible.
+///
+/// Bidi overrides let `invoice\u{202E}fdp.exe` render as `invoiceexe.pdf`;
+/// zero-width and filler characters make two different names look
+/// identical. None of these are C0/C1 controls, so `char::is_control`
+/// misses them.
+pub(crate) fn is_deceptive_char(c: char) -> bool {
+    matches!(
+        c,
+        // Bidi marks, embeddings, overrides, and isolates
+        '\u{061C}'
+            | '\u{200E}'
+            | '\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2066}'..='\u{2069}'
+            // Zero-width space/joiners, word joiner, invisible operators, BOM
+            | '\u{200B}'..='\u{200D}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{FEFF}'
+            // Invisible or blank-rendering characters
+            | '\u{00AD}'
+            | '\u{034F}'
+            | '\u{115F}'
+            | '\u{1160}'
+            | '\u{180E}'
+            | '\u{3164}'
+            | '\u{FFA0}'
+    )
+}
+
 // ============================================================================
 // Size Limits Policy
 // ============================================================================
@@ -163,6 +583,30 @@ pub struct SizePolicy {
     pub max_single_file: u64,
     /// Maximum total bytes across all files.
     pub max_total: u64,
+    /// How to account for files whose declared size is unreliable.
+    pub unknown_size: UnknownSizePolicy,
+    /// Maximum ratio of declared to compressed size; `None` doesn't check.
+    pub max_compression_ratio: Option<u64>,
+}
+
+/// What to do with files whose decompressed size is not reliably known
+/// before extraction (see [`EntryInfo::size_known`]).
+///
+/// Preflight checks such as [`SizePolicy`] and
+/// [`ValidationMode::ValidateFirst`](crate::ValidationMode::ValidateFirst)
+/// reason about declared sizes; these entries slip past them and are only
+/// caught by the limits enforced while bytes are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownSizePolicy {
+    /// Check the declared size as usual and rely on the limits enforced
+    /// while writing.
+    #[default]
+    Allow,
+    /// Fail with [`Error::UnknownEntrySize`].
+    Reject,
+    /// Count the entry as `max_single_file` bytes in preflight checks, so
+    /// the total budget reserves room for the worst case.
+    AssumeMax,
 }
 
 impl SizePolicy {
@@ -171,12 +615,45 @@ impl SizePolicy {
         Self {
             max_single_file,
             max_total,
+            unknown_size: UnknownSizePolicy::default(),
+            max_compression_ratio: None,
+        }
+    }
+
+    /// Reject entries declaring more than `ratio` times their compressed
+    /// size. Entries without a compressed size are not checked.
+    pub fn max_compression_ratio(mut self, ratio: Option<u64>) -> Self {
+        self.max_compression_ratio = ratio;
+        self
+    }
+
+    /// Set how files with unreliable declared sizes are handled.
+    pub fn unknown_size(mut self, policy: UnknownSizePolicy) -> Self {
+        self.unknown_size = policy;
+        self
+    }
+
+    /// Size to account for `entry` in preflight checks.
+    pub fn accounted_size(&self, entry: &EntryInfo) -> u64 {
+        if !entry.size_known && self.unknown_size == UnknownSizePolicy::AssumeMax {
+            entry.size.max(self.max_single_file)
+        } else {
+            entry.size
         }
     }
 }
 
 impl Policy for SizePolicy {
     fn check(&self, entry: &EntryInfo, state: &ExtractionState) -> Result<(), Error> {
+        if !entry.size_known
+            && matches!(entry.kind, EntryKind::File)
+            && self.unknown_size == UnknownSizePolicy::Reject
+        {
+            return Err(Error::UnknownEntrySize {
+                entry: entry.name.clone(),
+            });
+        }
+
         // Check single file limit
         if entry.size > self.max_single_file {
             return Err(Error::FileTooLarge {
@@ -186,11 +663,21 @@ impl Policy for SizePolicy {
             });
         }
 
+        if let Some(compressed_size) = entry.compressed_size {
+            check_compression_ratio(
+                &entry.name,
+                entry.size,
+                compressed_size,
+                self.max_compression_ratio,
+            )?;
+        }
+
         // Check total size limit
-        if state.bytes_written + entry.size > self.max_total {
+        let size = self.accounted_size(entry);
+        if state.bytes_written + size > self.max_total {
             return Err(Error::TotalSizeExceeded {
                 limit: self.max_total,
-                would_be: state.bytes_written + entry.size,
+                would_be: state.bytes_written + size,
             });
         }
 
@@ -217,10 +704,39 @@ impl CountPolicy {
 
 impl Policy for CountPolicy {
     fn check(&self, _entry: &EntryInfo, state: &ExtractionState) -> Result<(), Error> {
-        if state.files_extracted >= self.max_files {
+        // Symlinks are inodes too; don't let them bypass the limit
+        let created = state.files_extracted + state.symlinks_created;
+        if created >= self.max_files {
             return Err(Error::FileCountExceeded {
                 limit: self.max_files,
-                attempted: state.files_extracted + 1,
+                attempted: created + 1,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Policy that enforces a maximum number of directory entries, which
+/// [`CountPolicy`] leaves uncounted. Directories created implicitly as the
+/// parents of other entries don't count.
+pub struct DirCountPolicy {
+    /// Maximum number of directories.
+    pub max_dirs: usize,
+}
+
+impl DirCountPolicy {
+    /// Create a new directory count policy.
+    pub fn new(max_dirs: usize) -> Self {
+        Self { max_dirs }
+    }
+}
+
+impl Policy for DirCountPolicy {
+    fn check(&self, entry: &EntryInfo, state: &ExtractionState) -> Result<(), Error> {
+        if matches!(entry.kind, EntryKind::Directory) && state.dirs_created >= self.max_dirs {
+            return Err(Error::DirCountExceeded {
+                limit: self.max_dirs,
+                attempted: state.dirs_created + 1,
             });
         }
         Ok(())
@@ -232,21 +748,43 @@ impl Policy for CountPolicy {
 // ============================================================================
 
 /// Policy that enforces a maximum path depth.
+///
+/// By default the limit is relative: only the entry name's own components
+/// are counted, however deep the destination is. With
+/// [`include_destination`](Self::include_destination) it becomes absolute,
+/// counting the destination's components too, so it tracks the real
+/// on-disk nesting.
 pub struct DepthPolicy {
     /// Maximum directory depth.
     pub max_depth: usize,
+    /// Components already used by the destination; 0 for a relative limit.
+    pub base_depth: usize,
 }
 
 impl DepthPolicy {
     /// Create a new depth policy.
     pub fn new(max_depth: usize) -> Self {
-        Self { max_depth }
+        Self {
+            max_depth,
+            base_depth: 0,
+        }
+    }
+
+    /// Count `destination`'s own directories towards the limit. The path is
+    /// canonicalized first, so `.`, `..`, and symlinks in it don't skew the
+    /// count; the root and any drive prefix are not counted.
+    pub fn include_destination(mut self, destination: &Path) -> Result<Self, Error> {
+        self.base_depth = fs::canonicalize(destination)?
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .count();
+        Ok(self)
     }
 }
 
 impl Policy for DepthPolicy {
     fn check(&self, entry: &EntryInfo, _state: &ExtractionState) -> Result<(), Error> {
-        let depth = Path::new(&entry.name).components().count();
+        let depth = self.base_depth + normal_depth(&entry.name);
         if depth > self.max_depth {
             return Err(Error::PathTooDeep {
                 entry: entry.name.clone(),
@@ -270,6 +808,31 @@ pub enum SymlinkBehavior {
     Skip,
     /// Return an error if a symlink is encountered.
     Error,
+    /// Create symlinks whose target resolves inside the destination.
+    ///
+    /// Targets that escape the destination (absolute paths elsewhere, or too
+    /// many `..`) fail with [`Error::PathEscape`]. Symlinks are only created
+    /// on Unix; on other platforms they are skipped.
+    AllowContained,
+    /// Like [`AllowContained`](Self::AllowContained), but escaping symlinks
+    /// are skipped instead of failing extraction. Each one is listed in the
+    /// report's `skipped_symlinks` for auditing.
+    ///
+    /// A pragmatic choice for semi-trusted source trees, where a stray link
+    /// to `/usr/include` should not abort the whole extraction.
+    ContainedOrSkip,
+}
+
+/// How to write the target of a symlink created under
+/// [`SymlinkBehavior::AllowContained`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkRewrite {
+    /// Write the target exactly as stored in the archive.
+    #[default]
+    Preserve,
+    /// Rewrite absolute targets that point inside the destination into a
+    /// path relative to the link, so the extracted tree can be moved.
+    RelativizeContained,
 }
 
 /// Policy that handles symlinks in archives.
@@ -289,7 +852,7 @@ impl Policy for SymlinkPolicy {
     fn check(&self, entry: &EntryInfo, _state: &ExtractionState) -> Result<(), Error> {
         if let EntryKind::Symlink { target } = &entry.kind {
             match self.behavior {
-                SymlinkBehavior::Skip => {
+                SymlinkBehavior::Skip | SymlinkBehavior::ContainedOrSkip => {
                     // This will be handled by the extractor by skipping
                     // We don't error here, just let the extractor know to skip
                 }
@@ -299,12 +862,61 @@ impl Policy for SymlinkPolicy {
                         target: target.clone(),
                     });
                 }
+                SymlinkBehavior::AllowContained => {
+                    // Absolute targets and targets not known until the entry
+                    // is read (ZIP) are checked against the real destination
+                    // when the link is created.
+                    let base = Path::new(&entry.name).parent().unwrap_or(Path::new(""));
+                    let target_path = Path::new(target);
+                    if !target.is_empty()
+                        && !target_path.is_absolute()
+                        && resolve_link_target(base, target_path).is_none()
+                    {
+                        return Err(Error::PathEscape {
+                            entry: entry.name.clone(),
+                            detail: format!("symlink target '{}' escapes destination", target),
+                        });
+                    }
+                }
             }
         }
         Ok(())
     }
 }
 
+/// Lexically resolve a symlink `target` against `base`, the directory that
+/// holds the link.
+///
+/// Returns `None` if the target climbs above `base`'s root, or if a `..`
+/// follows a normal component (`a/../b`): a later symlink at `a` could
+/// redirect such a path after this check.
+pub(crate) fn resolve_link_target(base: &Path, target: &Path) -> Option<PathBuf> {
+    let mut resolved = if target.is_absolute() {
+        PathBuf::new()
+    } else {
+        base.to_path_buf()
+    };
+    let mut seen_normal = false;
+
+    for component in target.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => resolved.push(component),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if seen_normal || !resolved.pop() {
+                    return None;
+                }
+            }
+            Component::Normal(part) => {
+                seen_normal = true;
+                resolved.push(part);
+            }
+        }
+    }
+
+    Some(resolved)
+}
+
 // ============================================================================
 // Default Policy Chain Builder
 // ============================================================================
@@ -316,17 +928,21 @@ pub struct PolicyConfig {
     pub max_single_file: u64,
     pub max_total: u64,
     pub max_files: usize,
+    pub max_dirs: usize,
     pub max_depth: usize,
     pub symlink_behavior: SymlinkBehavior,
+    /// Reject Windows device names; `cfg!(windows)` matches the default.
+    pub windows_name_check: bool,
 }
 
 impl PolicyConfig {
     /// Build a policy chain from this configuration.
     pub fn build(&self) -> Result<PolicyChain, Error> {
         Ok(PolicyChain::new()
-            .with(PathPolicy::new(&self.destination)?)
+            .with(PathPolicy::new(&self.destination)?.windows_name_check(self.windows_name_check))
             .with(SizePolicy::new(self.max_single_file, self.max_total))
             .with(CountPolicy::new(self.max_files))
+            .with(DirCountPolicy::new(self.max_dirs))
             .with(DepthPolicy::new(self.max_depth))
             .with(SymlinkPolicy::new(self.symlink_behavior)))
     }
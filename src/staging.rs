@@ -0,0 +1,112 @@
+//! Temporary staging paths.
+//!
+//! Anything safe_unzip writes before renaming it into place gets a name of
+//! the form `.safe_unzip_staging_<pid>_<random>`, so leftovers from a killed
+//! process are easy to recognise. A [`Staging`] handle removes its path when
+//! dropped unless it was persisted; [`cleanup_stale`] reclaims whatever a
+//! crash left behind.
+
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::error::Error;
+
+/// Prefix shared by every staging file and directory.
+pub(crate) const STAGING_PREFIX: &str = ".safe_unzip_staging_";
+
+/// A fresh staging name: the prefix, the process id, and a random suffix.
+pub(crate) fn staging_name() -> String {
+    // RandomState is randomly seeded per instance, which is enough to keep
+    // concurrent extractions in one process apart
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    format!(
+        "{}{}_{:016x}",
+        STAGING_PREFIX,
+        std::process::id(),
+        hasher.finish()
+    )
+}
+
+/// A staging path inside `parent`, removed on drop unless persisted.
+#[derive(Debug)]
+pub(crate) struct Staging {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl Staging {
+    /// Reserve a staging path in `parent` without creating anything.
+    pub(crate) fn in_dir(parent: &Path) -> Self {
+        Self {
+            path: parent.join(staging_name()),
+            persisted: false,
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Keep the path on drop, e.g. once it has been renamed into place.
+    pub(crate) fn persist(mut self) {
+        self.persisted = true;
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = remove(&self.path);
+        }
+    }
+}
+
+/// Remove a staging file or directory without following symlinks.
+fn remove(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Remove staging entries directly inside `dir` last modified more than
+/// `older_than` ago. Returns how many were removed.
+pub(crate) fn cleanup_stale(dir: &Path, older_than: Duration) -> Result<usize, Error> {
+    let now = SystemTime::now();
+    let mut removed = 0;
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(STAGING_PREFIX)
+        {
+            continue;
+        }
+        let modified = entry.metadata()?.modified()?;
+        // A modification time in the future counts as fresh
+        if now.duration_since(modified).unwrap_or_default() < older_than {
+            continue;
+        }
+        match remove(&entry.path()) {
+            Ok(()) => removed += 1,
+            // Another process got there first
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(removed)
+}
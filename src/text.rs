@@ -0,0 +1,179 @@
+//! Line ending conversion for [`Extractor::text_convert`](crate::Extractor::text_convert).
+//!
+//! Matching entries are streamed through [`TextWriter`], which rewrites
+//! line endings on the way to the output file. The first few kilobytes are
+//! held back until they show whether the entry is text at all: a NUL byte
+//! marks it as binary, and binary entries are written unchanged.
+
+use std::io::{self, Write};
+
+/// Bytes inspected for a NUL before converting, the same heuristic git
+/// uses to tell text from binary.
+const BINARY_SNIFF_LEN: usize = 8000;
+
+/// Line ending written by [`TextConvert`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`. Each `\r\n` becomes `\n`; a lone `\r` is kept.
+    Lf,
+    /// `\r\n`. Each `\n` not already preceded by `\r` gets one.
+    CrLf,
+}
+
+/// Which entries get their line endings converted, and to what.
+///
+/// # Example
+///
+/// ```no_run
+/// use safe_unzip::{Extractor, LineEnding, TextConvert};
+///
+/// let report = Extractor::new("/src/project")?
+///     .text_convert(TextConvert {
+///         extensions: vec!["rs".into(), "toml".into(), "md".into()],
+///         to: LineEnding::Lf,
+///     })
+///     .extract_file("windows-sources.zip")?;
+/// # Ok::<(), safe_unzip::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextConvert {
+    /// File extensions to convert, with or without the leading dot,
+    /// compared case-insensitively.
+    pub extensions: Vec<String>,
+    /// Line ending to write.
+    pub to: LineEnding,
+}
+
+impl TextConvert {
+    /// Whether the entry `name` has one of the configured extensions.
+    pub(crate) fn matches(&self, name: &str) -> bool {
+        let file = name.rsplit('/').next().unwrap_or(name);
+        let Some((stem, ext)) = file.rsplit_once('.') else {
+            return false;
+        };
+        !stem.is_empty()
+            && self
+                .extensions
+                .iter()
+                .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(ext))
+    }
+}
+
+/// Writer converting line endings (or passing data through unchanged)
+/// while counting and bounding the bytes that reach `inner`.
+pub(crate) struct TextWriter<W> {
+    inner: W,
+    /// Target line ending; `None` once the data is known to be binary, or
+    /// if the entry isn't converted at all.
+    to: Option<LineEnding>,
+    /// Start of the data, held back while deciding whether it is text.
+    prefix: Vec<u8>,
+    sniffing: bool,
+    /// The last byte seen was a `\r`. Converting to LF, it has not been
+    /// written yet.
+    after_cr: bool,
+    limit: u64,
+    written: u64,
+    over_limit: bool,
+}
+
+impl<W: Write> TextWriter<W> {
+    /// Convert to `to`, or pass through with `None`, failing once more
+    /// than `limit` bytes would be written.
+    pub(crate) fn new(inner: W, to: Option<LineEnding>, limit: u64) -> Self {
+        Self {
+            inner,
+            sniffing: to.is_some(),
+            to,
+            prefix: Vec::new(),
+            after_cr: false,
+            limit,
+            written: 0,
+            over_limit: false,
+        }
+    }
+
+    /// True if writing failed because the output would exceed the limit.
+    pub(crate) fn over_limit(&self) -> bool {
+        self.over_limit
+    }
+
+    /// Write whatever is still held back. Returns the output size.
+    pub(crate) fn finish(&mut self) -> io::Result<u64> {
+        if self.sniffing {
+            self.end_sniff()?;
+        }
+        if self.after_cr && self.to == Some(LineEnding::Lf) {
+            self.after_cr = false;
+            self.emit(b"\r")?;
+        }
+        Ok(self.written)
+    }
+
+    /// Decide text or binary from the prefix, then write it.
+    fn end_sniff(&mut self) -> io::Result<()> {
+        self.sniffing = false;
+        if self.prefix.contains(&0) {
+            self.to = None;
+        }
+        let prefix = std::mem::take(&mut self.prefix);
+        self.convert(&prefix)
+    }
+
+    fn convert(&mut self, data: &[u8]) -> io::Result<()> {
+        let Some(to) = self.to else {
+            return self.emit(data);
+        };
+        let mut out = Vec::with_capacity(data.len() + data.len() / 16 + 1);
+        for &b in data {
+            match to {
+                LineEnding::Lf => {
+                    if self.after_cr && b != b'\n' {
+                        out.push(b'\r');
+                    }
+                    if b != b'\r' {
+                        out.push(b);
+                    }
+                }
+                LineEnding::CrLf => {
+                    if b == b'\n' && !self.after_cr {
+                        out.push(b'\r');
+                    }
+                    out.push(b);
+                }
+            }
+            self.after_cr = b == b'\r';
+        }
+        self.emit(&out)
+    }
+
+    fn emit(&mut self, data: &[u8]) -> io::Result<()> {
+        let len = data.len() as u64;
+        if self.written + len > self.limit {
+            self.over_limit = true;
+            return Err(io::Error::other("converted output exceeds the size limit"));
+        }
+        self.inner.write_all(data)?;
+        self.written += len;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for TextWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.sniffing {
+            let take = (BINARY_SNIFF_LEN - self.prefix.len()).min(buf.len());
+            self.prefix.extend_from_slice(&buf[..take]);
+            if self.prefix.len() == BINARY_SNIFF_LEN {
+                self.end_sniff()?;
+            }
+            return Ok(take);
+        }
+        self.convert(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
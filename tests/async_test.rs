@@ -105,6 +105,24 @@ async fn test_async_destination_not_found() {
     assert!(matches!(result, Err(Error::DestinationNotFound { .. })));
 }
 
+#[tokio::test]
+async fn test_async_cancel_on() {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    let dest = tempdir().unwrap();
+    let zip_data = create_simple_zip("test.txt", b"hello");
+
+    let result = AsyncExtractor::new(dest.path())
+        .unwrap()
+        .cancel_on(Arc::new(AtomicBool::new(true)))
+        .extract_bytes(zip_data)
+        .await;
+
+    assert!(matches!(result, Err(Error::Cancelled)));
+    assert!(!dest.path().join("test.txt").exists());
+}
+
 #[tokio::test]
 async fn test_async_new_or_create() {
     let dest = tempdir().unwrap();
@@ -318,3 +336,42 @@ async fn test_async_concurrent_mixed_extractions() {
     assert!(dest2.path().join("file2.txt").exists());
     assert!(dest3.path().join("file3.txt").exists());
 }
+
+#[tokio::test]
+async fn test_async_extract_file_with_progress() {
+    use tokio_stream::StreamExt;
+
+    let temp = tempdir().unwrap();
+    let archive = temp.path().join("bundle.zip");
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    {
+        let mut zip = zip::ZipWriter::new(&mut buffer);
+        let options: zip::write::FileOptions<()> = zip::write::FileOptions::default();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(name.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+    std::fs::write(&archive, buffer.into_inner()).unwrap();
+
+    let dest = temp.path().join("out");
+    let (progress, handle) = AsyncExtractor::new_or_create(&dest)
+        .unwrap()
+        .extract_file_with_progress(&archive);
+
+    let events: Vec<_> = progress.collect().await;
+    let report = handle.await.unwrap().unwrap();
+
+    assert_eq!(report.files_extracted, 3);
+    let names: Vec<_> = events.iter().map(|p| p.entry_name.as_str()).collect();
+    assert_eq!(names, ["a.txt", "b.txt", "c.txt"]);
+    assert!(events.iter().all(|p| p.total_entries == 3));
+
+    // Errors surface through the handle; the stream just ends
+    let (progress, handle) = AsyncExtractor::new(&dest)
+        .unwrap()
+        .extract_file_with_progress(temp.path().join("missing.zip"));
+    assert!(progress.collect::<Vec<_>>().await.is_empty());
+    assert!(handle.await.unwrap().is_err());
+}
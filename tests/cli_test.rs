@@ -63,6 +63,85 @@ fn test_cli_list() {
     assert!(stdout.contains("2 entries"));
 }
 
+#[cfg(feature = "sevenz")]
+#[test]
+fn test_cli_list_7z() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("test.7z");
+    let mut writer = sevenz_rust::SevenZWriter::create(&path).unwrap();
+    let mut entry = sevenz_rust::SevenZArchiveEntry::new();
+    entry.name = "hello.txt".to_string();
+    entry.has_stream = true;
+    writer
+        .push_archive_entry(entry, Some(&b"Hello, World!"[..]))
+        .unwrap();
+    writer.finish().unwrap();
+
+    let output = cli_binary().arg(&path).arg("--list").output().unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("hello.txt"));
+    assert!(stdout.contains("1 entries"));
+}
+
+#[test]
+fn test_cli_list_long() {
+    let temp = tempfile::tempdir().unwrap();
+    let tar_path = temp.path().join("dated.tar");
+    let mut builder = tar::Builder::new(fs::File::create(&tar_path).unwrap());
+    let mut header = tar::Header::new_gnu();
+    header.set_path("dated.txt").unwrap();
+    header.set_size(4);
+    header.set_mtime(1_600_000_000);
+    header.set_cksum();
+    builder.append(&header, &b"data"[..]).unwrap();
+    builder.finish().unwrap();
+
+    let output = cli_binary()
+        .arg(&tar_path)
+        .arg("--list")
+        .arg("--long")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("2020-09-13 12:26:40  dated.txt"),
+        "{stdout}"
+    );
+
+    // ZIP entries without a timestamp extra field show a placeholder
+    let zip_path = create_test_zip(temp.path());
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("--list")
+        .arg("--long")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("-                    hello.txt"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_cli_tree() {
+    let temp = tempfile::tempdir().unwrap();
+    let zip_path = create_test_zip(temp.path());
+
+    let output = cli_binary().arg(&zip_path).arg("--tree").output().unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("├── hello.txt (13B)"));
+    assert!(stdout.contains("└── subdir/ (14B)"));
+    assert!(stdout.contains("    └── nested.txt (14B)"));
+    assert!(stdout.contains("(27B)"));
+}
+
 #[test]
 fn test_cli_verify() {
     let temp = tempfile::tempdir().unwrap();
@@ -80,6 +159,70 @@ fn test_cli_verify() {
     assert!(stdout.contains("2 entries"));
 }
 
+#[test]
+fn test_cli_verify_tar_gz() {
+    use flate2::write::GzEncoder;
+
+    let temp = tempfile::tempdir().unwrap();
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_path("hello.txt").unwrap();
+    header.set_size(5);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append(&header, &b"hello"[..]).unwrap();
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&builder.into_inner().unwrap()).unwrap();
+    let gz = encoder.finish().unwrap();
+
+    let good = temp.path().join("good.tar.gz");
+    fs::write(&good, &gz).unwrap();
+    let output = cli_binary().arg(&good).arg("--verify").output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1 entries"));
+
+    // The gzip CRC trailer is missing
+    let truncated = temp.path().join("truncated.tar.gz");
+    fs::write(&truncated, &gz[..gz.len() - 8]).unwrap();
+    let output = cli_binary()
+        .arg(&truncated)
+        .arg("--verify")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[cfg(feature = "xz")]
+#[test]
+fn test_cli_extract_txz() {
+    let temp = tempfile::tempdir().unwrap();
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_path("hello.txt").unwrap();
+    header.set_size(5);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append(&header, &b"hello"[..]).unwrap();
+    let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(&builder.into_inner().unwrap()).unwrap();
+    let archive = temp.path().join("hello.txz");
+    fs::write(&archive, encoder.finish().unwrap()).unwrap();
+
+    let output = cli_binary().arg(&archive).arg("--list").output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("hello.txt"));
+
+    let dest = temp.path().join("output");
+    let output = cli_binary()
+        .arg(&archive)
+        .arg("-d")
+        .arg(&dest)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(fs::read(dest.join("hello.txt")).unwrap(), b"hello");
+}
+
 #[test]
 fn test_cli_extract() {
     let temp = tempfile::tempdir().unwrap();
@@ -167,6 +310,28 @@ fn test_cli_max_size_limit() {
     assert!(stderr.contains("too large") || stderr.contains("limit"));
 }
 
+#[test]
+fn test_cli_max_compressed_size_limit() {
+    let temp = tempfile::tempdir().unwrap();
+    let zip_path = create_test_zip(temp.path());
+    let dest = temp.path().join("output");
+    fs::create_dir(&dest).unwrap();
+
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--max-compressed-size")
+        .arg("10")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("compressed input limit"));
+    assert_eq!(fs::read_dir(&dest).unwrap().count(), 0);
+}
+
 #[test]
 fn test_cli_max_files_limit() {
     let temp = tempfile::tempdir().unwrap();
@@ -210,6 +375,87 @@ fn test_cli_include_filter() {
     assert!(dest.join("hello.txt").exists());
 }
 
+#[test]
+fn test_cli_regex_filters() {
+    let temp = tempfile::tempdir().unwrap();
+    let zip_path = create_test_zip(temp.path());
+
+    let dest = temp.path().join("included");
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--include-regex")
+        .arg(r"\.txt$")
+        .arg("--exclude-regex")
+        .arg("^subdir/")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(dest.join("hello.txt").exists());
+    assert!(!dest.join("subdir/nested.txt").exists());
+
+    let dest = temp.path().join("invalid");
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--include-regex")
+        .arg("(unclosed")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("include_regex"));
+    assert!(!dest.join("hello.txt").exists());
+}
+
+#[test]
+fn test_cli_patterns_from_file() {
+    let temp = tempfile::tempdir().unwrap();
+    let zip_path = create_test_zip(temp.path());
+    let patterns = temp.path().join("exclude.txt");
+    fs::write(&patterns, "# generated files\n\n  **/nested.txt  \n").unwrap();
+
+    let dest = temp.path().join("excluded");
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--exclude-from")
+        .arg(&patterns)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(dest.join("hello.txt").exists());
+    assert!(!dest.join("subdir/nested.txt").exists());
+
+    let patterns = temp.path().join("include.txt");
+    fs::write(&patterns, "hello.txt\n").unwrap();
+    let dest = temp.path().join("included");
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--include-from")
+        .arg(&patterns)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(dest.join("hello.txt").exists());
+    assert!(!dest.join("subdir/nested.txt").exists());
+
+    // A missing pattern file is an error, not an empty list
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(temp.path().join("missing"))
+        .arg("--exclude-from")
+        .arg(temp.path().join("nope.txt"))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
 #[test]
 fn test_cli_only_filter() {
     let temp = tempfile::tempdir().unwrap();
@@ -232,6 +478,79 @@ fn test_cli_only_filter() {
     assert!(!dest.join("subdir/nested.txt").exists());
 }
 
+#[test]
+fn test_cli_strip_components() {
+    let temp = tempfile::tempdir().unwrap();
+    let zip_path = create_test_zip(temp.path());
+    let dest = temp.path().join("output");
+    fs::create_dir(&dest).unwrap();
+
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--strip-components")
+        .arg("1")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(dest.join("nested.txt").exists());
+    // hello.txt has nothing left once its only component is stripped
+    assert!(!dest.join("hello.txt").exists());
+    assert!(!dest.join("subdir").exists());
+}
+
+#[test]
+fn test_cli_validate_deep() {
+    let temp = tempfile::tempdir().unwrap();
+    let zip_path = temp.path().join("corrupt.zip");
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options: zip::write::FileOptions<()> =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    zip.start_file("first.txt", options).unwrap();
+    zip.write_all(b"hello").unwrap();
+    zip.start_file("second.txt", options).unwrap();
+    zip.write_all(b"world").unwrap();
+    let mut data = zip.finish().unwrap().into_inner();
+    let second = data.windows(5).position(|w| w == b"world").unwrap();
+    data[second] ^= 0xff;
+    fs::write(&zip_path, &data).unwrap();
+
+    let dest = temp.path().join("output");
+    fs::create_dir(&dest).unwrap();
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--validate-deep")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(fs::read_dir(&dest).unwrap().count(), 0);
+}
+
+#[test]
+fn test_cli_ensure_free_space() {
+    let temp = tempfile::tempdir().unwrap();
+    let zip_path = create_test_zip(temp.path());
+    let dest = temp.path().join("output");
+    fs::create_dir(&dest).unwrap();
+
+    // Implies --validate-first, so it works without it
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--ensure-free-space")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(dest.join("subdir/nested.txt").exists());
+}
+
 #[test]
 fn test_cli_overwrite_error() {
     let temp = tempfile::tempdir().unwrap();
@@ -281,6 +600,223 @@ fn test_cli_overwrite_skip() {
     assert_eq!(content, "existing");
 }
 
+#[test]
+fn test_cli_overwrite_error_if_different() {
+    let temp = tempfile::tempdir().unwrap();
+    let zip_path = create_test_zip(temp.path());
+    let dest = temp.path().join("output");
+    fs::create_dir_all(dest.join("subdir")).unwrap();
+
+    // Identical files are left alone
+    fs::write(dest.join("hello.txt"), "Hello, World!").unwrap();
+    fs::write(dest.join("subdir/nested.txt"), "Nested content").unwrap();
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--overwrite")
+        .arg("error-if-different")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Skipped 2 entries"), "{stdout}");
+
+    // A changed file is an error and is not replaced
+    fs::write(dest.join("hello.txt"), "local edit").unwrap();
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--overwrite")
+        .arg("error-if-different")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("different contents"), "{stderr}");
+    assert_eq!(
+        fs::read_to_string(dest.join("hello.txt")).unwrap(),
+        "local edit"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_symlinks_contained_modes() {
+    let temp = tempfile::tempdir().unwrap();
+    let zip_path = temp.path().join("links.zip");
+    let mut zip = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+    let options: zip::write::FileOptions<()> = zip::write::FileOptions::default();
+    zip.start_file("hello.txt", options).unwrap();
+    zip.write_all(b"hello").unwrap();
+    zip.add_symlink("inside", "hello.txt", options).unwrap();
+    zip.add_symlink("escape", "/etc/passwd", options).unwrap();
+    zip.finish().unwrap();
+
+    // contained-or-skip creates the safe link and skips the other
+    let dest = temp.path().join("skip");
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--symlinks")
+        .arg("contained-or-skip")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(fs::symlink_metadata(dest.join("inside"))
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert!(fs::symlink_metadata(dest.join("escape")).is_err());
+
+    // allow-contained fails on the escaping link
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(temp.path().join("strict"))
+        .arg("--symlinks")
+        .arg("allow-contained")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_hardened() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = tempfile::tempdir().unwrap();
+    let zip_path = temp.path().join("tool.zip");
+    let mut zip = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+    let options: zip::write::FileOptions<()> =
+        zip::write::FileOptions::default().unix_permissions(0o755);
+    zip.start_file("run.sh", options).unwrap();
+    zip.write_all(b"#!/bin/sh\n").unwrap();
+    zip.finish().unwrap();
+
+    let dest = temp.path().join("out");
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--hardened")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let mode = fs::metadata(dest.join("run.sh"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o644);
+
+    // Symlinks fail instead of being skipped, unless overridden
+    let links_path = temp.path().join("links.zip");
+    let mut zip = zip::ZipWriter::new(fs::File::create(&links_path).unwrap());
+    zip.add_symlink("link", "run.sh", zip::write::FileOptions::<()>::default())
+        .unwrap();
+    zip.finish().unwrap();
+
+    let output = cli_binary()
+        .arg(&links_path)
+        .arg("-d")
+        .arg(temp.path().join("links"))
+        .arg("--hardened")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let output = cli_binary()
+        .arg(&links_path)
+        .arg("-d")
+        .arg(temp.path().join("links"))
+        .arg("--hardened")
+        .arg("--symlinks")
+        .arg("skip")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_cli_manifest() {
+    let temp = tempfile::tempdir().unwrap();
+    let zip_path = create_test_zip(temp.path());
+    let dest = temp.path().join("output");
+    let manifest = temp.path().join("files.json");
+
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("--manifest")
+        .arg(&manifest)
+        .arg("--json")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with('{'), "{stdout}");
+    assert!(stdout.contains("\"files_extracted\": 2"), "{stdout}");
+    assert!(stdout.contains("\"bytes_written\": 27"), "{stdout}");
+    assert!(stdout.contains("files.json\""), "{stdout}");
+
+    let json = fs::read_to_string(&manifest).unwrap();
+    assert!(
+        json.contains("\"path\": \"hello.txt\", \"size\": 13"),
+        "{json}"
+    );
+    assert!(json.contains("\"subdir/nested.txt\""), "{json}");
+    // SHA-256 of "Hello, World!"
+    assert!(json.contains("dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f"));
+
+    // Format follows the extension
+    let manifest = temp.path().join("files.csv");
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(temp.path().join("csv"))
+        .arg("--manifest")
+        .arg(&manifest)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let csv = fs::read_to_string(&manifest).unwrap();
+    assert!(
+        csv.starts_with("path,size,mode,sha256\nhello.txt,13,"),
+        "{csv}"
+    );
+}
+
+#[test]
+fn test_cli_extract_single_gz() {
+    let temp = tempfile::tempdir().unwrap();
+    let gz_path = temp.path().join("notes.txt.gz");
+    let mut encoder = flate2::write::GzEncoder::new(
+        fs::File::create(&gz_path).unwrap(),
+        flate2::Compression::default(),
+    );
+    encoder.write_all(b"just gunzip this").unwrap();
+    encoder.finish().unwrap();
+    let dest = temp.path().join("output");
+
+    let output = cli_binary()
+        .arg(&gz_path)
+        .arg("-d")
+        .arg(&dest)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(dest.join("notes.txt")).unwrap(),
+        "just gunzip this"
+    );
+}
+
 #[test]
 fn test_cli_missing_archive() {
     let output = cli_binary()
@@ -315,3 +851,81 @@ fn test_cli_size_parsing() {
         assert!(output.status.success(), "Failed for size: {}", size);
     }
 }
+
+#[test]
+fn test_cli_extract_tar_verbose() {
+    let temp = tempfile::tempdir().unwrap();
+    let tar_path = temp.path().join("test.tar");
+    let mut builder = tar::Builder::new(fs::File::create(&tar_path).unwrap());
+    for (name, content) in [("hello.txt", b"hello"), ("skip.txt", b"skipp")] {
+        let mut header = tar::Header::new_gnu();
+        header.set_path(name).unwrap();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, &content[..]).unwrap();
+    }
+    builder.finish().unwrap();
+    let dest = temp.path().join("output");
+
+    let output = cli_binary()
+        .arg(&tar_path)
+        .arg("-d")
+        .arg(&dest)
+        .arg("-v")
+        .arg("--exclude")
+        .arg("skip.txt")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[1] hello.txt"), "{stdout}");
+    assert!(
+        stdout.contains("[2] skip.txt (skipped: Filtered)"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_cli_allow_methods() {
+    let temp = tempfile::tempdir().unwrap();
+    let zip_path = temp.path().join("stored.zip");
+    let mut zip = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+    let options: zip::write::FileOptions<()> =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    zip.start_file("a.txt", options).unwrap();
+    zip.write_all(b"plain").unwrap();
+    zip.finish().unwrap();
+
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(temp.path().join("rejected"))
+        .arg("--allow-methods")
+        .arg("deflate")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("compression method 'stored'"), "{}", stderr);
+
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("-d")
+        .arg(temp.path().join("ok"))
+        .arg("--allow-methods")
+        .arg("deflate,stored")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(temp.path().join("ok/a.txt").exists());
+
+    let output = cli_binary()
+        .arg(&zip_path)
+        .arg("--allow-methods")
+        .arg("shrink")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
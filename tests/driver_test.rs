@@ -1,7 +1,8 @@
 //! Tests for the new Driver-based architecture.
 
-use safe_unzip::{Driver, OverwriteMode, ValidationMode, ZipAdapter};
-use std::io::Write;
+use safe_unzip::{Driver, Durability, Order, OverwriteMode, ValidationMode, ZipAdapter};
+use std::io::{Seek, Write};
+use std::sync::{Arc, Mutex};
 use tempfile::tempdir;
 use zip::write::FileOptions;
 
@@ -208,6 +209,41 @@ fn test_driver_overwrite_overwrite() {
     println!("✅ Driver OverwriteMode::Overwrite works");
 }
 
+#[test]
+fn test_driver_overwrite_error_if_different() {
+    use safe_unzip::Error;
+
+    let dest = tempdir().unwrap();
+    let extract = |content: &[u8]| {
+        let adapter = ZipAdapter::new(create_simple_zip("test.txt", content)).unwrap();
+        Driver::new(dest.path())
+            .unwrap()
+            .overwrite(OverwriteMode::ErrorIfDifferent)
+            .extract_zip(adapter)
+    };
+
+    // Fresh destination: written normally
+    let report = extract(b"original").unwrap();
+    assert_eq!(report.files_extracted, 1);
+
+    // Identical re-run: skipped
+    let report = extract(b"original").unwrap();
+    assert_eq!(report.files_extracted, 0);
+    assert_eq!(report.entries_skipped, 1);
+
+    // Same size, different bytes
+    let result = extract(b"originaL");
+    assert!(matches!(result, Err(Error::ContentConflict { .. })));
+
+    // Different size
+    let result = extract(b"original, longer");
+    assert!(matches!(result, Err(Error::ContentConflict { .. })));
+
+    // Existing file is never touched
+    let content = std::fs::read_to_string(dest.path().join("test.txt")).unwrap();
+    assert_eq!(content, "original");
+}
+
 #[test]
 fn test_driver_filter() {
     let dest = tempdir().unwrap();
@@ -262,3 +298,1932 @@ fn test_driver_atomic_file_creation() {
 
     println!("✅ Driver atomic file creation works");
 }
+
+#[test]
+fn test_driver_durability_fsync() {
+    for durability in [Durability::FsyncFiles, Durability::FsyncDir] {
+        let dest = tempdir().unwrap();
+        let zip_file = create_multi_file_zip(&[("a.txt", b"aaa"), ("sub/b.txt", b"bbb")]);
+
+        let adapter = ZipAdapter::new(zip_file).unwrap();
+        let report = Driver::new(dest.path())
+            .unwrap()
+            .durability(durability)
+            .extract_zip(adapter)
+            .unwrap();
+
+        assert_eq!(report.files_extracted, 2);
+        assert_eq!(
+            std::fs::read_to_string(dest.path().join("sub/b.txt")).unwrap(),
+            "bbb"
+        );
+    }
+}
+
+#[test]
+fn test_driver_extract_order_sorted() {
+    let files: &[(&str, &[u8])] = &[("c.txt", b"c"), ("a/z.txt", b"z"), ("b.txt", b"b")];
+
+    let run = |order: Order| {
+        let dest = tempdir().unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let record = Arc::clone(&seen);
+
+        let adapter = ZipAdapter::new(create_multi_file_zip(files)).unwrap();
+        Driver::new(dest.path())
+            .unwrap()
+            .extract_order(order)
+            .filter(move |info| {
+                record.lock().unwrap().push(info.name.clone());
+                true
+            })
+            .extract_zip(adapter)
+            .unwrap();
+
+        let names = seen.lock().unwrap().clone();
+        names
+    };
+
+    assert_eq!(run(Order::Archive), ["c.txt", "a/z.txt", "b.txt"]);
+    assert_eq!(run(Order::SortedByName), ["a/z.txt", "b.txt", "c.txt"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_driver_zip_dir_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let dir_options: FileOptions<()> = FileOptions::default().unix_permissions(0o700);
+    zip.add_directory("private/", dir_options).unwrap();
+    zip.start_file("private/key.pem", FileOptions::<()>::default())
+        .unwrap();
+    zip.write_all(b"secret").unwrap();
+    let zip_file = zip.finish().unwrap();
+
+    let dest = tempdir().unwrap();
+    let adapter = ZipAdapter::new(zip_file).unwrap();
+    Driver::new(dest.path())
+        .unwrap()
+        .extract_zip(adapter)
+        .unwrap();
+
+    let mode = std::fs::metadata(dest.path().join("private"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o700);
+    assert!(dest.path().join("private/key.pem").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_driver_permission_modes() {
+    use safe_unzip::PermissionMode;
+    use std::os::unix::fs::PermissionsExt;
+
+    let build = || {
+        let mut zip = zip::ZipWriter::new(tempfile::tempfile().unwrap());
+        zip.add_directory("dir/", FileOptions::<()>::default().unix_permissions(0o777))
+            .unwrap();
+        zip.start_file(
+            "dir/data.txt",
+            FileOptions::<()>::default().unix_permissions(0o2666),
+        )
+        .unwrap();
+        zip.write_all(b"data").unwrap();
+        zip.finish().unwrap()
+    };
+    let modes = |permissions: PermissionMode| {
+        let dest = tempdir().unwrap();
+        Driver::new(dest.path())
+            .unwrap()
+            .permission_mode(permissions)
+            .extract_zip(ZipAdapter::new(build()).unwrap())
+            .unwrap();
+        let mode = |name: &str| {
+            std::fs::metadata(dest.path().join(name))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o7777
+        };
+        (mode("dir"), mode("dir/data.txt"))
+    };
+
+    assert_eq!(modes(PermissionMode::FromArchive), (0o777, 0o666));
+    assert_eq!(modes(PermissionMode::Mask(0o755)), (0o755, 0o644));
+    assert_eq!(
+        modes(PermissionMode::Fixed {
+            file: 0o600,
+            dir: 0o700
+        }),
+        (0o700, 0o600)
+    );
+}
+
+#[test]
+fn test_driver_windows_name_check() {
+    use safe_unzip::Error;
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .windows_name_check(true)
+        .extract_zip(ZipAdapter::new(create_simple_zip("lib/COM1.rs", b"")).unwrap());
+    assert!(
+        matches!(result, Err(Error::InvalidFilename { ref reason, .. }) if reason.contains("reserved")),
+        "{result:?}"
+    );
+
+    // Off by default except on Windows
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .extract_zip(ZipAdapter::new(create_simple_zip("aux.py", b"")).unwrap());
+    #[cfg(windows)]
+    assert!(matches!(result, Err(Error::InvalidFilename { .. })));
+    #[cfg(not(windows))]
+    {
+        assert_eq!(result.unwrap().files_extracted, 1);
+        assert!(dest.path().join("aux.py").exists());
+    }
+}
+
+#[test]
+fn test_driver_report_was_empty() {
+    let dest = tempdir().unwrap();
+
+    let empty = zip::ZipWriter::new(tempfile::tempfile().unwrap())
+        .finish()
+        .unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_zip(ZipAdapter::new(empty).unwrap())
+        .unwrap();
+    assert!(report.was_empty);
+
+    // Everything filtered out is not the same as empty
+    let zip_file = create_simple_zip("a.txt", b"a");
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .only(&["missing.txt"])
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 0);
+    assert!(!report.was_empty);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_driver_zip_symlink_allow_contained() {
+    use safe_unzip::SymlinkBehavior;
+
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let options: FileOptions<()> = FileOptions::default();
+    zip.start_file("data/v1.txt", options).unwrap();
+    zip.write_all(b"v1").unwrap();
+    zip.add_symlink("data/current.txt", "v1.txt", options)
+        .unwrap();
+    zip.add_symlink("escape", "../../etc/passwd", options)
+        .unwrap();
+    let zip_file = zip.finish().unwrap();
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .symlinks(SymlinkBehavior::AllowContained)
+        .extract_zip(ZipAdapter::new(zip_file).unwrap());
+
+    // Contained link was created before the escaping one was rejected
+    assert!(matches!(result, Err(safe_unzip::Error::PathEscape { .. })));
+    assert_eq!(
+        std::fs::read_to_string(dest.path().join("data/current.txt")).unwrap(),
+        "v1"
+    );
+    assert!(!dest.path().join("escape").exists());
+}
+
+#[test]
+fn test_driver_zip_symlink_empty_target_rejected() {
+    use safe_unzip::{Error, SymlinkBehavior};
+
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let options: FileOptions<()> = FileOptions::default();
+    zip.add_symlink("link", "", options).unwrap();
+    let zip_file = zip.finish().unwrap();
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .symlinks(SymlinkBehavior::AllowContained)
+        .extract_zip(ZipAdapter::new(zip_file).unwrap());
+    assert!(
+        matches!(result, Err(Error::InvalidFilename { ref reason, .. }) if reason == "empty symlink target"),
+        "got {:?}",
+        result
+    );
+    assert!(std::fs::symlink_metadata(dest.path().join("link")).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_driver_permissions_callback() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let exec: FileOptions<()> = FileOptions::default().unix_permissions(0o4755);
+    zip.start_file("bin/run.sh", exec).unwrap();
+    zip.write_all(b"#!/bin/sh").unwrap();
+    zip.start_file("lib/data.txt", exec).unwrap();
+    zip.write_all(b"data").unwrap();
+    let zip_file = zip.finish().unwrap();
+
+    let dest = tempdir().unwrap();
+    Driver::new(dest.path())
+        .unwrap()
+        .permissions(|entry, mode| {
+            if entry.name.starts_with("bin/") {
+                mode
+            } else {
+                mode & !0o111
+            }
+        })
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+
+    let mode = |name: &str| {
+        std::fs::metadata(dest.path().join(name))
+            .unwrap()
+            .permissions()
+            .mode()
+            & 0o7777
+    };
+    // setuid is stripped even though the callback kept it
+    assert_eq!(mode("bin/run.sh"), 0o755);
+    assert_eq!(mode("lib/data.txt"), 0o644);
+}
+
+#[test]
+fn test_driver_report_bytes_read() {
+    let dest = tempdir().unwrap();
+    let content = vec![b'x'; 64 * 1024];
+    let mut zip_file = create_simple_zip("big.bin", &content);
+    let archive_len = zip_file.metadata().unwrap().len();
+    zip_file.rewind().unwrap();
+
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+
+    // Every byte of the archive was consumed at least once
+    assert!(report.bytes_read >= archive_len, "{report:?}");
+    assert!(report.bytes_read < archive_len * 2, "{report:?}");
+}
+
+#[test]
+fn test_driver_structure_only() {
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    zip.add_directory("docs/", FileOptions::<()>::default())
+        .unwrap();
+    zip.start_file("src/bin/main.rs", FileOptions::<()>::default())
+        .unwrap();
+    zip.write_all(b"fn main() {}").unwrap();
+    zip.start_file("README.md", FileOptions::<()>::default())
+        .unwrap();
+    zip.write_all(b"# readme").unwrap();
+    let zip_file = zip.finish().unwrap();
+
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .structure_only(true)
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+
+    assert!(dest.path().join("docs").is_dir());
+    assert!(dest.path().join("src/bin").is_dir());
+    assert!(!dest.path().join("src/bin/main.rs").exists());
+    assert!(!dest.path().join("README.md").exists());
+    assert_eq!(report.dirs_created, 1);
+    assert_eq!(report.files_extracted, 0);
+    assert_eq!(report.bytes_written, 0);
+    assert_eq!(report.entries_skipped, 2);
+}
+
+#[test]
+fn test_driver_observer() {
+    use safe_unzip::{EntryAction, EntryInfo, ExtractionObserver, SkipReason};
+    use std::path::{Path, PathBuf};
+
+    #[derive(Default)]
+    struct Recorder {
+        extracted: Mutex<Vec<(String, PathBuf)>>,
+        skipped: Mutex<Vec<(String, SkipReason)>>,
+    }
+
+    impl ExtractionObserver for Recorder {
+        fn before_entry(&self, entry: &EntryInfo) -> EntryAction {
+            if entry.name.ends_with(".exe") {
+                EntryAction::Skip
+            } else {
+                EntryAction::Extract
+            }
+        }
+
+        fn after_entry(&self, entry: &EntryInfo, path: &Path) {
+            let item = (entry.name.clone(), path.to_path_buf());
+            self.extracted.lock().unwrap().push(item);
+        }
+
+        fn on_skip(&self, entry: &EntryInfo, reason: SkipReason) {
+            self.skipped
+                .lock()
+                .unwrap()
+                .push((entry.name.clone(), reason));
+        }
+    }
+
+    let zip_file = create_multi_file_zip(&[
+        ("keep.txt", b"keep"),
+        ("tool.exe", b"MZ"),
+        ("notes.md", b"notes"),
+    ]);
+    let dest = tempdir().unwrap();
+    let recorder = Arc::new(Recorder::default());
+
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .exclude_glob(&["*.md"])
+        .observe(Arc::clone(&recorder))
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+
+    assert_eq!(report.files_extracted, 1);
+    assert_eq!(report.entries_skipped, 2);
+    assert!(!dest.path().join("tool.exe").exists());
+    assert_eq!(
+        *recorder.extracted.lock().unwrap(),
+        vec![("keep.txt".to_string(), dest.path().join("keep.txt"))]
+    );
+    assert_eq!(
+        *recorder.skipped.lock().unwrap(),
+        vec![
+            ("tool.exe".to_string(), SkipReason::Vetoed),
+            ("notes.md".to_string(), SkipReason::Filtered),
+        ]
+    );
+}
+
+#[test]
+fn test_driver_strip_components() {
+    use safe_unzip::{EntryInfo, Error, ExtractionObserver, SkipReason};
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(String, SkipReason)>>);
+
+    impl ExtractionObserver for Recorder {
+        fn on_skip(&self, entry: &EntryInfo, reason: SkipReason) {
+            self.0.lock().unwrap().push((entry.name.clone(), reason));
+        }
+    }
+
+    let zip_file = create_multi_file_zip(&[
+        ("root/a.txt", b"a"),
+        ("root/sub/b.txt", b"b"),
+        ("top.txt", b"top"),
+    ]);
+    let dest = tempdir().unwrap();
+    let recorder = Arc::new(Recorder::default());
+
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .strip_components(1)
+        .observe(Arc::clone(&recorder))
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(report.entries_skipped, 1);
+    assert_eq!(std::fs::read(dest.path().join("a.txt")).unwrap(), b"a");
+    assert_eq!(std::fs::read(dest.path().join("sub/b.txt")).unwrap(), b"b");
+    assert!(!dest.path().join("root").exists());
+    assert!(!dest.path().join("top.txt").exists());
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        vec![("top.txt".to_string(), SkipReason::Stripped)]
+    );
+
+    // Stripping happens before the name is checked against the destination
+    let zip_file = create_simple_zip("evil/../../x.txt", b"x");
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .strip_components(1)
+        .extract_zip(ZipAdapter::new(zip_file).unwrap());
+    assert!(
+        matches!(result, Err(Error::PathEscape { .. })),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn test_driver_prefix() {
+    use safe_unzip::Error;
+
+    let zip_file = create_multi_file_zip(&[("hello.txt", b"hi"), ("sub/b.txt", b"b")]);
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .prefix("foo")
+        .unwrap()
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(
+        std::fs::read(dest.path().join("foo/hello.txt")).unwrap(),
+        b"hi"
+    );
+    assert!(dest.path().join("foo/sub/b.txt").exists());
+    assert!(!dest.path().join("hello.txt").exists());
+
+    // Composes with strip_components
+    let zip_file = create_simple_zip("project-1.0/src/main.rs", b"fn main() {}");
+    let dest = tempdir().unwrap();
+    Driver::new(dest.path())
+        .unwrap()
+        .strip_components(1)
+        .prefix("vendor/project")
+        .unwrap()
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+    assert!(dest.path().join("vendor/project/src/main.rs").exists());
+
+    // Entries can't use the prefix to climb into the rest of the destination
+    let zip_file = create_simple_zip("../x.txt", b"x");
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .prefix("foo")
+        .unwrap()
+        .extract_zip(ZipAdapter::new(zip_file).unwrap());
+    assert!(
+        matches!(result, Err(Error::PathEscape { .. })),
+        "{result:?}"
+    );
+
+    for bad in ["../foo", "foo/../../bar", "/abs"] {
+        let result = Driver::new(dest.path()).unwrap().prefix(bad);
+        assert!(
+            matches!(result, Err(Error::InvalidOption { ref option, .. }) if option == "prefix"),
+            "{bad}"
+        );
+    }
+}
+
+#[test]
+fn test_driver_map_name() {
+    use safe_unzip::Error;
+
+    let zip_file = create_multi_file_zip(&[
+        ("Docs/READ ME.txt", b"readme"),
+        ("__MACOSX/._junk", b"junk"),
+    ]);
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .map_name(|name| (!name.starts_with("__MACOSX/")).then(|| name.to_lowercase()))
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+    assert_eq!(report.entries_skipped, 1);
+    assert!(dest.path().join("docs/read me.txt").exists());
+    assert!(!dest.path().join("__MACOSX").exists());
+
+    // Two names mapped to one collide like any other repeat
+    let zip_file = create_multi_file_zip(&[("a.txt", b"a"), ("A.TXT", b"b")]);
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .map_name(|name| Some(name.to_lowercase()))
+        .extract_zip(ZipAdapter::new(zip_file).unwrap());
+    assert!(
+        matches!(result, Err(Error::AlreadyExists { ref entry }) if entry.ends_with("a.txt")),
+        "{result:?}"
+    );
+
+    // The mapped name goes through the path checks
+    let zip_file = create_simple_zip("innocent.txt", b"x");
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .map_name(|name| Some(format!("../{}", name)))
+        .extract_zip(ZipAdapter::new(zip_file).unwrap());
+    assert!(
+        matches!(result, Err(Error::PathEscape { .. })),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn test_driver_collect_paths() {
+    let build = || {
+        let mut zip = zip::ZipWriter::new(tempfile::tempfile().unwrap());
+        let options: FileOptions<()> = FileOptions::default();
+        zip.add_directory("docs/", options).unwrap();
+        zip.start_file("docs/guide.md", options).unwrap();
+        zip.write_all(b"guide").unwrap();
+        zip.start_file("notes.log", options).unwrap();
+        zip.write_all(b"log").unwrap();
+        zip.start_file("README.md", options).unwrap();
+        zip.write_all(b"readme").unwrap();
+        zip.finish().unwrap()
+    };
+    let dest = tempdir().unwrap();
+
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .collect_paths(true)
+        .exclude_glob(&["*.log"])
+        .extract_zip(ZipAdapter::new(build()).unwrap())
+        .unwrap();
+    assert_eq!(
+        report.extracted_paths,
+        [
+            dest.path().join("docs/"),
+            dest.path().join("docs/guide.md"),
+            dest.path().join("README.md"),
+        ]
+    );
+
+    // Off by default
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .overwrite(OverwriteMode::Overwrite)
+        .extract_zip(ZipAdapter::new(build()).unwrap())
+        .unwrap();
+    assert!(report.extracted_paths.is_empty());
+}
+
+#[test]
+fn test_driver_post_extract() {
+    let dest = tempdir().unwrap();
+    let calls = Arc::new(Mutex::new(Vec::new()));
+
+    let recorded = Arc::clone(&calls);
+    Driver::new(dest.path())
+        .unwrap()
+        .post_extract(move |path| {
+            // Runs after every file is in place
+            assert!(path.join("a.txt").exists());
+            recorded.lock().unwrap().push(path.to_path_buf());
+        })
+        .extract_zip(ZipAdapter::new(create_simple_zip("a.txt", b"a")).unwrap())
+        .unwrap();
+    assert_eq!(*calls.lock().unwrap(), vec![dest.path().to_path_buf()]);
+
+    // Not called when extraction fails
+    let recorded = Arc::clone(&calls);
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .post_extract(move |path| recorded.lock().unwrap().push(path.to_path_buf()))
+        .extract_zip(ZipAdapter::new(create_simple_zip("a.txt", b"a")).unwrap());
+    assert!(result.is_err());
+    assert_eq!(calls.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn test_driver_verify_signature() {
+    use safe_unzip::Error;
+
+    let temp = tempdir().unwrap();
+    let archive = temp.path().join("bundle.zip");
+    let mut zip_file = create_simple_zip("a.txt", b"payload");
+    zip_file.rewind().unwrap();
+    std::io::copy(&mut zip_file, &mut std::fs::File::create(&archive).unwrap()).unwrap();
+    let expected = std::fs::read(&archive).unwrap();
+
+    // Verifier sees the exact archive bytes
+    let dest = temp.path().join("ok");
+    let report = Driver::new_or_create(&dest)
+        .unwrap()
+        .verify_signature(move |data| {
+            assert_eq!(data, expected.as_slice());
+            Ok(())
+        })
+        .extract_zip_file(&archive)
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+
+    // Rejection aborts before anything is written
+    let dest = temp.path().join("rejected");
+    let result = Driver::new_or_create(&dest)
+        .unwrap()
+        .verify_signature(|_| {
+            Err(Error::InvalidFilename {
+                entry: "sig".into(),
+                reason: "bad".into(),
+            })
+        })
+        .extract_zip_file(&archive);
+    assert!(matches!(result, Err(Error::SignatureInvalid { .. })));
+    assert!(!dest.join("a.txt").exists());
+
+    // Adapters hide the raw bytes, so they cannot be verified
+    let result = Driver::new_or_create(&dest)
+        .unwrap()
+        .verify_signature(|_| Ok(()))
+        .extract_zip(ZipAdapter::open(&archive).unwrap());
+    assert!(matches!(result, Err(Error::InvalidOption { .. })));
+}
+
+#[test]
+fn test_driver_extract_many() {
+    use safe_unzip::{ArchiveSource, Error, Limits};
+
+    let temp = tempdir().unwrap();
+    let write_zip = |name: &str, files: &[(&str, &[u8])]| {
+        let path = temp.path().join(name);
+        let mut zip_file = create_multi_file_zip(files);
+        zip_file.rewind().unwrap();
+        std::io::copy(&mut zip_file, &mut std::fs::File::create(&path).unwrap()).unwrap();
+        ArchiveSource::Zip(path)
+    };
+    let first = write_zip("first.zip", &[("a.txt", b"aaaa"), ("b.txt", b"bbbb")]);
+    let second = write_zip("second.zip", &[("c.txt", b"cccc"), ("d.txt", b"dddd")]);
+    let clash = write_zip("clash.zip", &[("a.txt", b"other")]);
+
+    // Report merges both archives
+    let dest = temp.path().join("merged");
+    let report = Driver::new_or_create(&dest)
+        .unwrap()
+        .extract_many(&[first.clone(), second.clone()])
+        .unwrap();
+    assert_eq!(report.files_extracted, 4);
+    assert_eq!(report.bytes_written, 16);
+    assert!(!report.was_empty);
+    assert!(dest.join("a.txt").exists());
+    assert!(dest.join("d.txt").exists());
+
+    // Each archive fits the file limit alone, but not together
+    let result = Driver::new_or_create(temp.path().join("count"))
+        .unwrap()
+        .limits(Limits {
+            max_file_count: 3,
+            ..Limits::default()
+        })
+        .extract_many(&[first.clone(), second.clone()]);
+    assert!(matches!(result, Err(Error::FileCountExceeded { .. })));
+
+    // Same for the total size limit
+    let result = Driver::new_or_create(temp.path().join("size"))
+        .unwrap()
+        .limits(Limits {
+            max_total_bytes: 12,
+            ..Limits::default()
+        })
+        .extract_many(&[first.clone(), second]);
+    assert!(matches!(result, Err(Error::TotalSizeExceeded { .. })));
+
+    // A later archive cannot clobber an earlier one's files
+    let dest = temp.path().join("clash");
+    let result = Driver::new_or_create(&dest)
+        .unwrap()
+        .extract_many(&[first, clash]);
+    assert!(matches!(result, Err(Error::AlreadyExists { .. })));
+    assert_eq!(std::fs::read(dest.join("a.txt")).unwrap(), b"aaaa");
+}
+
+#[test]
+fn test_driver_write_index() {
+    use safe_unzip::{CsvIndex, JsonIndex};
+
+    let temp = tempdir().unwrap();
+
+    let dest = temp.path().join("json");
+    let index = temp.path().join("index.json");
+    let zip_file = create_multi_file_zip(&[("b.txt", b"bbb"), ("dir/a \"q\".txt", b"")]);
+    Driver::new_or_create(&dest)
+        .unwrap()
+        .write_index(&index, JsonIndex)
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+
+    let json = std::fs::read_to_string(&index).unwrap();
+    // Sorted by path, names escaped, digests of the extracted bytes
+    let a = json.find("dir/a \\\"q\\\".txt").unwrap();
+    let b = json.find("\"b.txt\"").unwrap();
+    assert!(b < a);
+    assert!(json.contains("\"size\": 3"));
+    // SHA-256 of "bbb" and of the empty file
+    assert!(json.contains("3e744b9dc39389baf0c5a0660589b8402f3dbb49b89b3e75f2c9355852a3c677"));
+    assert!(json.contains("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"));
+
+    let dest = temp.path().join("csv");
+    let index = temp.path().join("index.csv");
+    let zip_file = create_multi_file_zip(&[("a,b.txt", b"bbb")]);
+    Driver::new_or_create(&dest)
+        .unwrap()
+        .write_index(&index, CsvIndex)
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+
+    let csv = std::fs::read_to_string(&index).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("path,size,mode,sha256"));
+    let row = lines.next().unwrap();
+    assert!(row.starts_with("\"a,b.txt\",3,"));
+    assert!(row.ends_with(",3e744b9dc39389baf0c5a0660589b8402f3dbb49b89b3e75f2c9355852a3c677"));
+
+    // A failed extraction writes no index
+    let dest = temp.path().join("failed");
+    let index = temp.path().join("failed.json");
+    let zip_file = create_multi_file_zip(&[("ok.txt", b"x"), ("../escape.txt", b"x")]);
+    let result = Driver::new_or_create(&dest)
+        .unwrap()
+        .write_index(&index, JsonIndex)
+        .extract_zip(ZipAdapter::new(zip_file).unwrap());
+    assert!(result.is_err());
+    assert!(!index.exists());
+}
+
+#[test]
+fn test_driver_write_index_shared_driver() {
+    use safe_unzip::{EntryInfo, ExtractionObserver, JsonIndex};
+    use std::path::Path;
+    use std::sync::Barrier;
+
+    // Holds one extraction after its first file while the other runs
+    struct Pause(Arc<Barrier>);
+
+    impl ExtractionObserver for Pause {
+        fn after_entry(&self, entry: &EntryInfo, _path: &Path) {
+            if entry.name == "a1.txt" {
+                self.0.wait();
+                self.0.wait();
+            }
+        }
+    }
+
+    let temp = tempdir().unwrap();
+    let index = temp.path().join("index.json");
+    let barrier = Arc::new(Barrier::new(2));
+    let driver = Arc::new(
+        Driver::new_or_create(temp.path().join("out"))
+            .unwrap()
+            .write_index(&index, JsonIndex)
+            .observe(Pause(Arc::clone(&barrier))),
+    );
+
+    let a = {
+        let driver = Arc::clone(&driver);
+        let zip_file = create_multi_file_zip(&[("a1.txt", b"a1"), ("a2.txt", b"a2")]);
+        std::thread::spawn(move || driver.extract_zip(ZipAdapter::new(zip_file)?))
+    };
+    barrier.wait();
+    let zip_file = create_multi_file_zip(&[("b1.txt", b"b1")]);
+    driver
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+
+    // Each extraction's index lists only its own files
+    let json = std::fs::read_to_string(&index).unwrap();
+    assert!(json.contains("b1.txt"));
+    assert!(!json.contains("a1.txt"));
+    barrier.wait();
+    a.join().unwrap().unwrap();
+    let json = std::fs::read_to_string(&index).unwrap();
+    assert!(json.contains("a1.txt") && json.contains("a2.txt"));
+    assert!(!json.contains("b1.txt"));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_driver_regex_filters() {
+    use safe_unzip::Error;
+
+    let files: &[(&str, &[u8])] = &[
+        ("src/lib.rs", b"lib"),
+        ("src/parser_test.rs", b"test"),
+        ("src/gen/parser_test.rs", b"generated"),
+        ("README.md", b"readme"),
+    ];
+
+    // Composed with each other and with globs; every filter must pass
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .include_glob(&["src/**"])
+        .include_regex(&[r"_test\.rs$"])
+        .exclude_regex(&["^src/gen/"])
+        .extract_zip(ZipAdapter::new(create_multi_file_zip(files)).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+    assert!(dest.path().join("src/parser_test.rs").exists());
+    assert!(!dest.path().join("src/gen/parser_test.rs").exists());
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .exclude_regex(&["ok", "[unclosed"])
+        .extract_zip(ZipAdapter::new(create_multi_file_zip(files)).unwrap());
+    match result {
+        Err(Error::InvalidOption { option, reason }) => {
+            assert_eq!(option, "exclude_regex");
+            assert!(reason.contains("unclosed"), "{}", reason);
+        }
+        other => panic!("expected InvalidOption, got {:?}", other),
+    }
+    assert!(std::fs::read_dir(dest.path()).unwrap().next().is_none());
+}
+
+#[test]
+fn test_driver_case_collisions() {
+    use safe_unzip::{CaseCollisionPolicy, Error, RenamedEntry};
+
+    let files: &[(&str, &[u8])] = &[
+        ("README", b"upper"),
+        ("docs/Notes.txt", b"notes"),
+        ("readme", b"lower"),
+        ("DOCS/notes.TXT", b"shouted"),
+    ];
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .case_collisions(CaseCollisionPolicy::Error)
+        .extract_zip(ZipAdapter::new(create_multi_file_zip(files)).unwrap());
+    match result {
+        Err(Error::CaseCollision {
+            existing,
+            conflicting,
+        }) => {
+            assert_eq!(existing, "README");
+            assert_eq!(conflicting, "readme");
+        }
+        other => panic!("expected CaseCollision, got {:?}", other),
+    }
+
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .case_collisions(CaseCollisionPolicy::SuffixSecond)
+        .extract_zip(ZipAdapter::new(create_multi_file_zip(files)).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 4);
+    assert_eq!(
+        report.renamed_entries,
+        vec![
+            RenamedEntry {
+                name: "readme".to_string(),
+                renamed_to: "readme~1".to_string(),
+            },
+            RenamedEntry {
+                name: "DOCS/notes.TXT".to_string(),
+                renamed_to: "DOCS/notes~1.TXT".to_string(),
+            },
+        ]
+    );
+    assert_eq!(std::fs::read(dest.path().join("README")).unwrap(), b"upper");
+    assert_eq!(
+        std::fs::read(dest.path().join("readme~1")).unwrap(),
+        b"lower"
+    );
+    assert_eq!(
+        std::fs::read(dest.path().join("DOCS/notes~1.TXT")).unwrap(),
+        b"shouted"
+    );
+
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .case_collisions(CaseCollisionPolicy::KeepFirst)
+        .extract_zip(ZipAdapter::new(create_multi_file_zip(files)).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(report.entries_skipped, 2);
+    assert!(report.renamed_entries.is_empty());
+    assert!(!dest.path().join("readme").exists());
+    assert!(!dest.path().join("DOCS").exists());
+
+    // Filtered-out entries don't claim a name
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .case_collisions(CaseCollisionPolicy::Error)
+        .filter(|entry| entry.name != "README")
+        .extract_zip(ZipAdapter::new(create_multi_file_zip(&files[..3])).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+}
+
+#[test]
+fn test_driver_reject_case_collisions() {
+    use safe_unzip::{Error, ValidationMode};
+
+    let files: &[(&str, &[u8])] = &[("a/README", b"upper"), ("A/readme", b"lower")];
+
+    // ValidateFirst finds the collision before writing anything
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .validation(ValidationMode::ValidateFirst)
+        .reject_case_collisions(true)
+        .extract_zip(ZipAdapter::new(create_multi_file_zip(files)).unwrap());
+    assert!(matches!(result, Err(Error::CaseCollision { .. })));
+    assert!(!dest.path().join("a").exists());
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .reject_case_collisions(true)
+        .extract_zip(ZipAdapter::new(create_multi_file_zip(files)).unwrap());
+    assert!(matches!(result, Err(Error::CaseCollision { .. })));
+    assert!(dest.path().join("a/README").exists());
+
+    // The filter applies while validating too
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .validation(ValidationMode::ValidateFirst)
+        .reject_case_collisions(true)
+        .filter(|entry| entry.name != "A/readme")
+        .extract_zip(ZipAdapter::new(create_multi_file_zip(files)).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+
+    // Turning it off again extracts both
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .reject_case_collisions(true)
+        .reject_case_collisions(false)
+        .extract_zip(ZipAdapter::new(create_multi_file_zip(files)).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+}
+
+#[test]
+fn test_driver_completion_marker() {
+    use safe_unzip::Error;
+
+    let dest = tempdir().unwrap();
+    let marker = dest.path().join(".extraction-complete");
+
+    // Never visible while entries are being extracted
+    let seen = marker.clone();
+    let zip_file = create_multi_file_zip(&[("a.txt", b"aaa"), ("dir/b.txt", b"bb")]);
+    Driver::new(dest.path())
+        .unwrap()
+        .completion_marker(&marker)
+        .filter(move |_| {
+            assert!(!seen.exists());
+            true
+        })
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+
+    let json = std::fs::read_to_string(&marker).unwrap();
+    assert!(json.contains("\"files_extracted\": 2,"));
+    assert!(json.contains("\"bytes_written\": 5,"));
+    assert!(json.contains("\"source_digest\": null,"));
+
+    // A failed run removes the previous marker and writes none
+    let zip_file = create_multi_file_zip(&[("c.txt", b"c"), ("../escape.txt", b"x")]);
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .completion_marker(&marker)
+        .extract_zip(ZipAdapter::new(zip_file).unwrap());
+    assert!(matches!(result, Err(Error::PathEscape { .. })));
+    assert!(!marker.exists());
+
+    // The archive cannot create the marker itself
+    let zip_file = create_multi_file_zip(&[(".extraction-complete", b"{}")]);
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .completion_marker(&marker)
+        .extract_zip(ZipAdapter::new(zip_file).unwrap());
+    assert!(matches!(result, Err(Error::InvalidFilename { .. })));
+    assert!(!marker.exists());
+}
+
+#[test]
+fn test_driver_allow_content_types() {
+    use safe_unzip::{ContentCategory, Error};
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    let images_only = |dest: &std::path::Path, files: &[(&str, &[u8])]| {
+        Driver::new(dest)
+            .unwrap()
+            .allow_content_types(&[ContentCategory::Image])
+            .extract_zip(ZipAdapter::new(create_multi_file_zip(files)).unwrap())
+    };
+
+    let dest = tempdir().unwrap();
+    let report = images_only(
+        dest.path(),
+        &[("a.png", PNG), ("b.jpg", b"\xff\xd8\xff\xe0")],
+    )
+    .unwrap();
+    assert_eq!(report.files_extracted, 2);
+
+    // A renamed executable is caught by its content, not its name
+    let dest = tempdir().unwrap();
+    let result = images_only(dest.path(), &[("photo.png", b"MZ\x90\0\x03\0\0\0")]);
+    match result {
+        Err(Error::DisallowedContent { entry, detected }) => {
+            assert_eq!(entry, "photo.png");
+            assert_eq!(detected, "executable");
+        }
+        other => panic!("expected DisallowedContent, got {:?}", other),
+    }
+    assert!(!dest.path().join("photo.png").exists());
+
+    assert_eq!(
+        ContentCategory::sniff(b"plain text\n"),
+        Some(ContentCategory::Text)
+    );
+    assert_eq!(
+        ContentCategory::sniff(b"#!/bin/sh\n"),
+        Some(ContentCategory::Executable)
+    );
+    assert_eq!(ContentCategory::sniff(b"\0\x01\x02\x03"), None);
+    assert_eq!(
+        "Image".parse::<ContentCategory>().unwrap(),
+        ContentCategory::Image
+    );
+}
+
+#[test]
+fn test_zip_entries_report_unix_owner() {
+    use zip::write::ExtendedFileOptions;
+
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+
+    // Info-ZIP "ux": version 1, 4-byte uid 1000, 4-byte gid 100
+    let mut options = FileOptions::<ExtendedFileOptions>::default();
+    options
+        .add_extra_data(
+            0x7875,
+            vec![1, 4, 0xe8, 0x03, 0, 0, 4, 100, 0, 0, 0].into_boxed_slice(),
+            false,
+        )
+        .unwrap();
+    zip.start_file("owned.txt", options).unwrap();
+    zip.write_all(b"data").unwrap();
+
+    let options: FileOptions<()> = FileOptions::default();
+    zip.start_file("plain.txt", options).unwrap();
+    zip.write_all(b"data").unwrap();
+
+    let mut file = zip.finish().unwrap();
+    file.seek(std::io::SeekFrom::Start(0)).unwrap();
+
+    let entries = ZipAdapter::new(file).unwrap().entries_metadata().unwrap();
+    assert_eq!(entries[0].uid, Some(1000));
+    assert_eq!(entries[0].gid, Some(100));
+    assert_eq!(entries[1].uid, None);
+    assert_eq!(entries[1].gid, None);
+}
+
+#[test]
+fn test_zip_entries_report_mtime() {
+    use std::time::{Duration, UNIX_EPOCH};
+    use zip::write::ExtendedFileOptions;
+
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+
+    // NTFS field, written under a stand-in id (the zip crate refuses to
+    // write 0x000a) and patched below: reserved, then tag 1 with
+    // mtime/atime/ctime FILETIMEs
+    let ticks: u64 = (1_700_000_000 + 11_644_473_600) * 10_000_000 + 5_000_000;
+    let mut ntfs = vec![0, 0, 0, 0, 1, 0, 24, 0];
+    for _ in 0..3 {
+        ntfs.extend_from_slice(&ticks.to_le_bytes());
+    }
+    let mut options = FileOptions::<ExtendedFileOptions>::default();
+    options
+        .add_extra_data(0x7777, ntfs.into_boxed_slice(), false)
+        .unwrap();
+    zip.start_file("ntfs.txt", options).unwrap();
+    zip.write_all(b"data").unwrap();
+
+    // Info-ZIP extended timestamp: flags (mtime present), mtime
+    let mut unix = vec![1];
+    unix.extend_from_slice(&1_600_000_000i32.to_le_bytes());
+    let mut options = FileOptions::<ExtendedFileOptions>::default();
+    options
+        .add_extra_data(0x5455, unix.into_boxed_slice(), false)
+        .unwrap();
+    zip.start_file("unix.txt", options).unwrap();
+    zip.write_all(b"data").unwrap();
+
+    let options: FileOptions<()> = FileOptions::default();
+    zip.start_file("plain.txt", options).unwrap();
+    zip.write_all(b"data").unwrap();
+
+    let mut file = zip.finish().unwrap();
+    file.rewind().unwrap();
+    let mut bytes = Vec::new();
+    std::io::Read::read_to_end(&mut file, &mut bytes).unwrap();
+    let marker = [0x77, 0x77, 32, 0];
+    for at in 0..bytes.len() - 4 {
+        if bytes[at..at + 4] == marker {
+            bytes[at..at + 2].copy_from_slice(&[0x0a, 0]);
+        }
+    }
+
+    let entries = ZipAdapter::new(std::io::Cursor::new(bytes))
+        .unwrap()
+        .entries_metadata()
+        .unwrap();
+    assert_eq!(
+        entries[0].mtime,
+        Some(UNIX_EPOCH + Duration::from_millis(1_700_000_000_500))
+    );
+    assert_eq!(
+        entries[1].mtime,
+        Some(UNIX_EPOCH + Duration::from_secs(1_600_000_000))
+    );
+    // The DOS time alone is not reported
+    assert_eq!(entries[2].mtime, None);
+}
+
+#[test]
+fn test_zip_adapter_peek_entry() {
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let options: FileOptions<()> =
+        FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file("big.bin", options).unwrap();
+    zip.write_all(b"%PDF-1.7").unwrap();
+    zip.write_all(&vec![0u8; 1 << 20]).unwrap();
+    zip.start_file("tiny.txt", options).unwrap();
+    zip.write_all(b"hi").unwrap();
+    let mut file = zip.finish().unwrap();
+    file.rewind().unwrap();
+
+    let mut adapter = ZipAdapter::new(file).unwrap();
+    let head = adapter.peek_entry(0, 512).unwrap();
+    assert_eq!(head.len(), 512);
+    assert!(head.starts_with(b"%PDF-1.7"));
+    // Shorter entries return what there is
+    assert_eq!(adapter.peek_entry(1, 512).unwrap(), b"hi");
+    assert!(adapter.peek_entry(2, 512).is_err());
+}
+
+#[test]
+fn test_driver_cleanup_stale_staging() {
+    use std::time::Duration;
+
+    let dest = tempdir().unwrap();
+    let staged_dir = dest.path().join(".safe_unzip_staging_1_00000000000000aa");
+    std::fs::create_dir(&staged_dir).unwrap();
+    std::fs::write(staged_dir.join("partial.bin"), b"half").unwrap();
+    let staged_file = dest.path().join(".safe_unzip_staging_1_00000000000000bb");
+    std::fs::write(&staged_file, b"half").unwrap();
+    std::fs::write(dest.path().join("keep.txt"), b"user data").unwrap();
+
+    // Fresh leftovers may belong to a running extraction
+    let removed = Driver::cleanup_stale_staging(dest.path(), Duration::from_secs(3600)).unwrap();
+    assert_eq!(removed, 0);
+    assert!(staged_dir.exists());
+
+    let removed = Driver::cleanup_stale_staging(dest.path(), Duration::ZERO).unwrap();
+    assert_eq!(removed, 2);
+    assert!(!staged_dir.exists());
+    assert!(!staged_file.exists());
+    assert!(dest.path().join("keep.txt").exists());
+
+    // Writing an index leaves no staging file next to it
+    let index_dir = tempdir().unwrap();
+    let zip_file = create_simple_zip("a.txt", b"a");
+    Driver::new(dest.path())
+        .unwrap()
+        .write_index(index_dir.path().join("index.json"), safe_unzip::JsonIndex)
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+    let names: Vec<_> = std::fs::read_dir(index_dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert_eq!(names, vec![std::ffi::OsString::from("index.json")]);
+}
+
+#[test]
+fn test_driver_extract_many_into_subdirs() {
+    use safe_unzip::{ArchiveSource, Error, Limits};
+
+    let temp = tempdir().unwrap();
+    let write_zip = |name: &str, files: &[(&str, &[u8])]| {
+        let path = temp.path().join(name);
+        let mut zip_file = create_multi_file_zip(files);
+        zip_file.rewind().unwrap();
+        std::io::copy(&mut zip_file, &mut std::fs::File::create(&path).unwrap()).unwrap();
+        ArchiveSource::Zip(path)
+    };
+    let first = write_zip("first.zip", &[("a.txt", b"aaaa"), ("b.txt", b"bbbb")]);
+    let second = write_zip("Second.ZIP", &[("a.txt", b"cc")]);
+
+    // Same entry name in both archives, no collision
+    let dest = temp.path().join("out");
+    let reports = Driver::new_or_create(&dest)
+        .unwrap()
+        .extract_many_into_subdirs(&[first.clone(), second.clone()])
+        .unwrap();
+    assert_eq!(std::fs::read(dest.join("first/a.txt")).unwrap(), b"aaaa");
+    assert_eq!(std::fs::read(dest.join("Second/a.txt")).unwrap(), b"cc");
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports["first"].files_extracted, 2);
+    assert_eq!(reports["first"].bytes_written, 8);
+    assert_eq!(reports["Second"].files_extracted, 1);
+    assert_eq!(reports["Second"].bytes_written, 2);
+
+    // Limits still apply across archives
+    let result = Driver::new_or_create(temp.path().join("count"))
+        .unwrap()
+        .limits(Limits {
+            max_file_count: 2,
+            ..Limits::default()
+        })
+        .extract_many_into_subdirs(&[first.clone(), second]);
+    assert!(matches!(result, Err(Error::FileCountExceeded { .. })));
+
+    // Colliding stems are rejected up front
+    let nested = temp.path().join("nested");
+    std::fs::create_dir(&nested).unwrap();
+    let copy = nested.join("first.zip");
+    std::fs::copy(first.path(), &copy).unwrap();
+    let dest = temp.path().join("dupes");
+    let result = Driver::new_or_create(&dest)
+        .unwrap()
+        .extract_many_into_subdirs(&[first, ArchiveSource::Zip(copy)]);
+    assert!(matches!(result, Err(Error::InvalidOption { .. })));
+    assert!(!dest.join("first").exists());
+}
+
+#[test]
+fn test_driver_extract_many_into_subdirs_shared_driver() {
+    use safe_unzip::{ArchiveSource, EntryInfo, ExtractionObserver};
+    use std::path::Path;
+    use std::sync::Barrier;
+
+    // Holds the batch after its first file while another extraction runs
+    struct Pause(Arc<Barrier>);
+
+    impl ExtractionObserver for Pause {
+        fn after_entry(&self, entry: &EntryInfo, _path: &Path) {
+            if entry.name == "a1.txt" {
+                self.0.wait();
+                self.0.wait();
+            }
+        }
+    }
+
+    let temp = tempdir().unwrap();
+    let archive = temp.path().join("batch.zip");
+    let mut zip_file = create_multi_file_zip(&[("a1.txt", b"a1"), ("a2.txt", b"a2")]);
+    zip_file.rewind().unwrap();
+    std::io::copy(&mut zip_file, &mut std::fs::File::create(&archive).unwrap()).unwrap();
+
+    let dest = temp.path().join("out");
+    let barrier = Arc::new(Barrier::new(2));
+    let driver = Arc::new(
+        Driver::new_or_create(&dest)
+            .unwrap()
+            .observe(Pause(Arc::clone(&barrier))),
+    );
+
+    let batch = {
+        let driver = Arc::clone(&driver);
+        std::thread::spawn(move || driver.extract_many_into_subdirs(&[ArchiveSource::Zip(archive)]))
+    };
+    barrier.wait();
+    let zip_file = create_multi_file_zip(&[("b1.txt", b"b1")]);
+    driver
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+    barrier.wait();
+    batch.join().unwrap().unwrap();
+
+    // The batch's subdirectory never became the other extraction's
+    // destination
+    assert!(dest.join("b1.txt").exists());
+    assert!(dest.join("batch/a1.txt").exists());
+    assert!(dest.join("batch/a2.txt").exists());
+    assert!(!dest.join("batch/b1.txt").exists());
+}
+
+#[test]
+fn test_driver_absolute_path_policy_drive_letter() {
+    use safe_unzip::{AbsolutePathPolicy, Error};
+
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_zip(ZipAdapter::new(create_simple_zip("C:/evil.txt", b"evil")).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+    assert!(dest.path().join("evil.txt").exists());
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .absolute_paths(AbsolutePathPolicy::Reject)
+        .validation(ValidationMode::ValidateFirst)
+        .extract_zip(ZipAdapter::new(create_simple_zip("C:/evil.txt", b"evil")).unwrap());
+    assert!(matches!(result, Err(Error::AbsolutePath { .. })));
+
+    // Stripping the drive must not smuggle a traversal past the jail
+    for mode in [ValidationMode::Streaming, ValidationMode::ValidateFirst] {
+        let root = tempdir().unwrap();
+        let dest = root.path().join("dest");
+        let result = Driver::new_or_create(&dest)
+            .unwrap()
+            .validation(mode)
+            .extract_zip(ZipAdapter::new(create_simple_zip("C:/../evil.txt", b"evil")).unwrap());
+        assert!(
+            matches!(result, Err(Error::PathEscape { .. })),
+            "{:?}",
+            result
+        );
+        assert!(!root.path().join("evil.txt").exists());
+    }
+}
+
+#[test]
+fn test_driver_open_streams_entries() {
+    use safe_unzip::Error;
+    use std::io::Read;
+
+    let dest = tempdir().unwrap();
+    let driver = Driver::new(dest.path())
+        .unwrap()
+        .filter(|info| !info.name.ends_with(".log"));
+    let zip = create_multi_file_zip(&[
+        ("a.txt", b"hello"),
+        ("debug.log", b"noise"),
+        ("dir/b.txt", b"skipped unread"),
+        ("dir/c.txt", b"world"),
+    ]);
+
+    let mut stream = driver.open(ZipAdapter::new(zip).unwrap()).unwrap();
+    let mut seen = Vec::new();
+    while let Some(entry) = stream.next_entry() {
+        let (info, mut reader) = entry.unwrap();
+        if info.name == "dir/b.txt" {
+            seen.push((info.name, String::new()));
+            continue;
+        }
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        seen.push((info.name, content));
+    }
+    assert_eq!(
+        seen,
+        vec![
+            ("a.txt".to_string(), "hello".to_string()),
+            ("dir/b.txt".to_string(), String::new()),
+            ("dir/c.txt".to_string(), "world".to_string()),
+        ]
+    );
+    assert_eq!(stream.state().files_extracted, 3);
+    assert_eq!(stream.state().entries_skipped, 1);
+    // Nothing is written
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    // Limits apply as the stream is consumed
+    let driver = Driver::new(dest.path())
+        .unwrap()
+        .limits(safe_unzip::Limits {
+            max_file_count: 1,
+            ..Default::default()
+        });
+    let zip = create_multi_file_zip(&[("a.txt", b"a"), ("b.txt", b"b")]);
+    let mut stream = driver.open(ZipAdapter::new(zip).unwrap()).unwrap();
+    assert!(stream.next_entry().unwrap().is_ok());
+    assert!(matches!(
+        stream.next_entry(),
+        Some(Err(Error::FileCountExceeded { .. }))
+    ));
+
+    // A symlink's target is read only up to the path length limit
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let options: FileOptions<()> =
+        FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.add_symlink("link", "a".repeat(1024 * 1024), options)
+        .unwrap();
+    let zip = zip.finish().unwrap();
+    let driver = Driver::new(dest.path()).unwrap();
+    let mut stream = driver.open(ZipAdapter::new(zip).unwrap()).unwrap();
+    assert!(matches!(
+        stream.next_entry(),
+        Some(Err(Error::InvalidFilename { .. }))
+    ));
+}
+
+#[test]
+fn test_driver_hash_source_zip() {
+    use safe_unzip::DigestAlgorithm;
+    use sha2::{Digest, Sha256};
+
+    let temp = tempdir().unwrap();
+    let archive = temp.path().join("bundle.zip");
+    let mut zip_file = create_simple_zip("a.txt", b"payload");
+    zip_file.rewind().unwrap();
+    std::io::copy(&mut zip_file, &mut std::fs::File::create(&archive).unwrap()).unwrap();
+    let expected: String = Sha256::digest(std::fs::read(&archive).unwrap())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    let report = Driver::new_or_create(temp.path().join("out"))
+        .unwrap()
+        .hash_source(DigestAlgorithm::Sha256)
+        .extract_zip_file(&archive)
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+    assert_eq!(report.source_digest, Some(expected.clone()));
+
+    // The verifier's buffer is hashed rather than reading the file twice
+    let report = Driver::new_or_create(temp.path().join("verified"))
+        .unwrap()
+        .verify_signature(|_| Ok(()))
+        .hash_source(DigestAlgorithm::Sha256)
+        .extract_zip_file(&archive)
+        .unwrap();
+    assert_eq!(report.source_digest, Some(expected));
+}
+
+#[test]
+fn test_driver_dedupe_store_skips_seen_archive() {
+    use safe_unzip::MemoryDedupeStore;
+
+    let temp = tempdir().unwrap();
+    let archive = temp.path().join("batch.zip");
+    let mut zip_file = create_simple_zip("a.txt", b"payload");
+    zip_file.rewind().unwrap();
+    std::io::copy(&mut zip_file, &mut std::fs::File::create(&archive).unwrap()).unwrap();
+
+    let seen = Arc::new(MemoryDedupeStore::new());
+    let first = Driver::new_or_create(temp.path().join("first"))
+        .unwrap()
+        .dedupe_store(Arc::clone(&seen))
+        .extract_zip_file(&archive)
+        .unwrap();
+    assert_eq!(first.files_extracted, 1);
+    assert!(!first.skipped_duplicate_archive);
+    assert_eq!(seen.len(), 1);
+
+    // A redelivery is a no-op, even into a fresh destination
+    let second = Driver::new_or_create(temp.path().join("second"))
+        .unwrap()
+        .dedupe_store(Arc::clone(&seen))
+        .extract_zip_file(&archive)
+        .unwrap();
+    assert!(second.skipped_duplicate_archive);
+    assert_eq!(second.files_extracted, 0);
+    assert_eq!(second.source_digest, first.source_digest);
+    assert!(!temp.path().join("second/a.txt").exists());
+}
+
+#[test]
+fn test_driver_depth_counts_normal_components() {
+    use safe_unzip::{Error, Limits};
+
+    let limits = Limits {
+        max_path_depth: 2,
+        ..Default::default()
+    };
+    for name in ["./a/b.txt", "a//b.txt", "a/b/"] {
+        let temp = tempdir().unwrap();
+        let zip = create_simple_zip(name, b"");
+        let result = Driver::new(temp.path())
+            .unwrap()
+            .limits(limits)
+            .extract_zip(ZipAdapter::new(zip).unwrap());
+        assert!(result.is_ok(), "{name}: {result:?}");
+    }
+
+    let temp = tempdir().unwrap();
+    let zip = create_simple_zip("./a//b/c.txt", b"");
+    let result = Driver::new(temp.path())
+        .unwrap()
+        .limits(limits)
+        .extract_zip(ZipAdapter::new(zip).unwrap());
+    assert!(matches!(
+        result,
+        Err(Error::PathTooDeep {
+            depth: 3,
+            limit: 2,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_driver_depth_includes_destination() {
+    use safe_unzip::{Error, Limits};
+
+    let temp = tempdir().unwrap();
+    let dest = temp.path().join("x/y/z");
+    std::fs::create_dir_all(&dest).unwrap();
+    let dest_depth = dest.canonicalize().unwrap().components().count() - 1;
+    let limits = Limits {
+        max_path_depth: dest_depth + 2,
+        ..Default::default()
+    };
+
+    // Relative by default: the destination's depth is not counted
+    let zip = create_simple_zip("a/b/c.txt", b"deep");
+    let report = Driver::new(&dest)
+        .unwrap()
+        .limits(limits)
+        .extract_zip(ZipAdapter::new(zip).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+
+    let zip = create_simple_zip("a/b/c.txt", b"deep");
+    let result = Driver::new(&dest)
+        .unwrap()
+        .limits(limits)
+        .overwrite(OverwriteMode::Overwrite)
+        .depth_includes_destination(true)
+        .extract_zip(ZipAdapter::new(zip).unwrap());
+    match result {
+        Err(Error::PathTooDeep { depth, limit, .. }) => {
+            assert_eq!(depth, dest_depth + 3);
+            assert_eq!(limit, dest_depth + 2);
+        }
+        other => panic!("expected PathTooDeep, got {:?}", other),
+    }
+
+    let zip = create_simple_zip("a/c.txt", b"fits");
+    let report = Driver::new(&dest)
+        .unwrap()
+        .limits(limits)
+        .depth_includes_destination(true)
+        .extract_zip(ZipAdapter::new(zip).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+}
+
+#[test]
+fn test_driver_prune_empty_dirs() {
+    use safe_unzip::{EntryInfo, EntryKind};
+
+    // Directory entries pass, so filtered-out files leave them hollow
+    fn docs_only(entry: &EntryInfo) -> bool {
+        entry.kind != EntryKind::File || entry.name.ends_with(".md")
+    }
+    let build = || {
+        let file = tempfile::tempfile().unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        let options: FileOptions<()> = FileOptions::default();
+        zip.add_directory("docs/", options).unwrap();
+        zip.start_file("docs/guide.md", options).unwrap();
+        zip.write_all(b"# Guide").unwrap();
+        zip.add_directory("src/", options).unwrap();
+        zip.add_directory("src/bin/", options).unwrap();
+        zip.start_file("src/bin/main.rs", options).unwrap();
+        zip.write_all(b"fn main() {}").unwrap();
+        zip.finish().unwrap()
+    };
+
+    let dest = tempdir().unwrap();
+    // Pre-existing empty directories are never touched
+    std::fs::create_dir(dest.path().join("keep")).unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .filter(docs_only)
+        .prune_empty_dirs(true)
+        .extract_zip(ZipAdapter::new(build()).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+    assert_eq!(report.dirs_pruned, 2);
+    assert!(dest.path().join("docs/guide.md").exists());
+    assert!(!dest.path().join("src").exists());
+    assert!(dest.path().join("keep").is_dir());
+
+    // Off by default: the hollow tree stays
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .filter(docs_only)
+        .extract_zip(ZipAdapter::new(build()).unwrap())
+        .unwrap();
+    assert_eq!(report.dirs_pruned, 0);
+    assert!(dest.path().join("src/bin").is_dir());
+}
+
+#[test]
+fn test_driver_extract_to_cas() {
+    use safe_unzip::Error;
+
+    let store = tempdir().unwrap();
+    let zip_file = create_multi_file_zip(&[
+        ("a.txt", b"shared"),
+        ("dir/b.txt", b"shared"),
+        ("c.txt", b"unique"),
+    ]);
+
+    let manifest = Driver::new(store.path())
+        .unwrap()
+        .extract_to_cas(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+    assert_eq!(manifest.len(), 3);
+    assert_eq!(manifest["a.txt"], manifest["dir/b.txt"]);
+    assert_ne!(manifest["a.txt"], manifest["c.txt"]);
+    assert_eq!(
+        std::fs::read(store.path().join(&manifest["c.txt"])).unwrap(),
+        b"unique"
+    );
+    assert_eq!(std::fs::read_dir(store.path()).unwrap().count(), 2);
+
+    // Content already in the store is not written again
+    let again = Driver::new(store.path())
+        .unwrap()
+        .extract_to_cas(ZipAdapter::new(create_simple_zip("other.txt", b"shared")).unwrap())
+        .unwrap();
+    assert_eq!(again["other.txt"], manifest["a.txt"]);
+    assert_eq!(std::fs::read_dir(store.path()).unwrap().count(), 2);
+
+    // Limits apply to the decompressed bytes, and nothing is left behind
+    let result = Driver::new(store.path())
+        .unwrap()
+        .limits(safe_unzip::Limits {
+            max_single_file: 4,
+            ..Default::default()
+        })
+        .extract_to_cas(ZipAdapter::new(create_simple_zip("big.txt", b"too large")).unwrap());
+    assert!(matches!(result, Err(Error::FileTooLarge { .. })));
+    assert_eq!(std::fs::read_dir(store.path()).unwrap().count(), 2);
+}
+
+/// In-memory backend for `Driver::filesystem` tests: file contents keyed
+/// by path, plus the directories and modes it was asked for.
+#[derive(Default)]
+struct MemoryFs {
+    files: Arc<Mutex<std::collections::BTreeMap<std::path::PathBuf, Vec<u8>>>>,
+    dirs: Mutex<Vec<std::path::PathBuf>>,
+    modes: Mutex<Vec<(std::path::PathBuf, u32)>>,
+}
+
+struct MemoryFile {
+    files: Arc<Mutex<std::collections::BTreeMap<std::path::PathBuf, Vec<u8>>>>,
+    path: std::path::PathBuf,
+}
+
+impl Write for MemoryFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut files = self.files.lock().unwrap();
+        files.get_mut(&self.path).unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl safe_unzip::FileSystem for MemoryFs {
+    fn create_dir(&self, path: &std::path::Path) -> std::io::Result<()> {
+        self.dirs.lock().unwrap().push(path.to_path_buf());
+        Ok(())
+    }
+
+    fn open_write(
+        &self,
+        path: &std::path::Path,
+        create_new: bool,
+    ) -> std::io::Result<Box<dyn Write + Send>> {
+        let mut files = self.files.lock().unwrap();
+        if create_new && files.contains_key(path) {
+            return Err(std::io::ErrorKind::AlreadyExists.into());
+        }
+        files.insert(path.to_path_buf(), Vec::new());
+        Ok(Box::new(MemoryFile {
+            files: Arc::clone(&self.files),
+            path: path.to_path_buf(),
+        }))
+    }
+
+    fn set_permissions(&self, path: &std::path::Path, mode: u32) -> std::io::Result<()> {
+        self.modes.lock().unwrap().push((path.to_path_buf(), mode));
+        Ok(())
+    }
+
+    fn symlink(&self, _target: &std::path::Path, _link: &std::path::Path) -> std::io::Result<()> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+
+    fn rename(&self, from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+        let mut files = self.files.lock().unwrap();
+        let data = files.remove(from).ok_or(std::io::ErrorKind::NotFound)?;
+        files.insert(to.to_path_buf(), data);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_driver_custom_filesystem() {
+    use safe_unzip::Error;
+
+    let temp = tempdir().unwrap();
+    let memory = Arc::new(MemoryFs::default());
+    let zip_file = create_multi_file_zip(&[("a.txt", b"alpha"), ("sub/b.txt", b"beta")]);
+
+    let report = Driver::new(temp.path())
+        .unwrap()
+        .filesystem(Arc::clone(&memory))
+        .extract_zip(ZipAdapter::new(zip_file).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+    {
+        let files = memory.files.lock().unwrap();
+        assert_eq!(files[&temp.path().join("a.txt")], b"alpha");
+        assert_eq!(files[&temp.path().join("sub/b.txt")], b"beta");
+    }
+    assert!(memory
+        .dirs
+        .lock()
+        .unwrap()
+        .contains(&temp.path().join("sub")));
+    // Nothing reached the local disk
+    assert_eq!(std::fs::read_dir(temp.path()).unwrap().count(), 0);
+
+    // Overwrite modes map onto create_new
+    let again = |mode| {
+        Driver::new(temp.path())
+            .unwrap()
+            .filesystem(Arc::clone(&memory))
+            .overwrite(mode)
+            .extract_zip(ZipAdapter::new(create_simple_zip("a.txt", b"second")).unwrap())
+    };
+    assert!(matches!(
+        again(OverwriteMode::Error),
+        Err(Error::AlreadyExists { .. })
+    ));
+    assert_eq!(again(OverwriteMode::Skip).unwrap().entries_skipped, 1);
+    again(OverwriteMode::Overwrite).unwrap();
+    assert_eq!(
+        memory.files.lock().unwrap()[&temp.path().join("a.txt")],
+        b"second"
+    );
+
+    // Content-addressed extraction renames through the backend
+    let manifest = Driver::new(temp.path())
+        .unwrap()
+        .filesystem(Arc::clone(&memory))
+        .extract_to_cas(ZipAdapter::new(create_simple_zip("c.txt", b"gamma")).unwrap())
+        .unwrap();
+    assert_eq!(
+        memory.files.lock().unwrap()[&temp.path().join(&manifest["c.txt"])],
+        b"gamma"
+    );
+
+    // Options that need the local disk are refused
+    let result = Driver::new(temp.path())
+        .unwrap()
+        .filesystem(Arc::clone(&memory))
+        .durability(Durability::FsyncFiles)
+        .extract_zip(ZipAdapter::new(create_simple_zip("d.txt", b"delta")).unwrap());
+    assert!(matches!(result, Err(Error::InvalidOption { .. })));
+    assert_eq!(std::fs::read_dir(temp.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_driver_max_open_files() {
+    use safe_unzip::{Error, FileSystem, StdFileSystem};
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Local disk, recording the most writers open at once.
+    #[derive(Default)]
+    struct Counting {
+        open: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+    }
+
+    struct CountedWriter(Box<dyn Write + Send>, Arc<AtomicUsize>);
+
+    impl Write for CountedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    impl Drop for CountedWriter {
+        fn drop(&mut self) {
+            self.1.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    impl FileSystem for Counting {
+        fn create_dir(&self, path: &Path) -> std::io::Result<()> {
+            StdFileSystem.create_dir(path)
+        }
+
+        fn open_write(
+            &self,
+            path: &Path,
+            create_new: bool,
+        ) -> std::io::Result<Box<dyn Write + Send>> {
+            let writer = StdFileSystem.open_write(path, create_new)?;
+            let open = self.open.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(open, Ordering::SeqCst);
+            Ok(Box::new(CountedWriter(writer, Arc::clone(&self.open))))
+        }
+
+        fn set_permissions(&self, path: &Path, mode: u32) -> std::io::Result<()> {
+            StdFileSystem.set_permissions(path, mode)
+        }
+
+        fn symlink(&self, target: &Path, link: &Path) -> std::io::Result<()> {
+            StdFileSystem.symlink(target, link)
+        }
+
+        fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+            StdFileSystem.rename(from, to)
+        }
+    }
+
+    let dest = tempdir().unwrap();
+    let filesystem = Counting::default();
+    let peak = Arc::clone(&filesystem.peak);
+    let driver = Arc::new(
+        Driver::new(dest.path())
+            .unwrap()
+            .filesystem(filesystem)
+            .max_open_files(2),
+    );
+
+    // Threads sharing the driver never hold more than two files open
+    let workers: Vec<_> = (0..6)
+        .map(|i| {
+            let driver = Arc::clone(&driver);
+            std::thread::spawn(move || {
+                let name = format!("file{}.txt", i);
+                let zip_file = create_simple_zip(&name, b"contents");
+                driver.extract_zip(ZipAdapter::new(zip_file).unwrap())
+            })
+        })
+        .collect();
+    for worker in workers {
+        assert_eq!(worker.join().unwrap().unwrap().files_extracted, 1);
+    }
+    assert!(peak.load(Ordering::SeqCst) <= 2);
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 6);
+
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .max_open_files(0)
+        .extract_zip(ZipAdapter::new(create_simple_zip("x.txt", b"x")).unwrap());
+    assert!(matches!(result, Err(Error::InvalidOption { .. })));
+}
+
+#[test]
+fn test_driver_on_progress() {
+    let file = create_multi_file_zip(&[("a.txt", b"hello"), ("b.txt", b"world!"), ("c.log", b"x")]);
+    let dest = tempdir().unwrap();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&events);
+
+    Driver::new(dest.path())
+        .unwrap()
+        .filter(|entry| entry.name.ends_with(".txt"))
+        .on_progress(move |p| {
+            recorded.lock().unwrap().push((
+                p.entry_name.clone(),
+                p.entry_index,
+                p.total_entries,
+                p.bytes_written,
+            ));
+        })
+        .extract_zip(ZipAdapter::new(file).unwrap())
+        .unwrap();
+
+    // Filtered entries are reported too, before the filter runs
+    let events = events.lock().unwrap();
+    assert_eq!(
+        *events,
+        [
+            ("a.txt".to_string(), 0, 3, 0),
+            ("b.txt".to_string(), 1, 3, 5),
+            ("c.log".to_string(), 2, 3, 11),
+        ]
+    );
+}
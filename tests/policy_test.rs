@@ -2,8 +2,8 @@
 
 use safe_unzip::entry::EntryKind;
 use safe_unzip::policy::{
-    CountPolicy, DepthPolicy, PathPolicy, Policy, PolicyChain, PolicyConfig, SizePolicy,
-    SymlinkBehavior, SymlinkPolicy,
+    CountPolicy, DepthPolicy, DirCountPolicy, PathPolicy, Policy, PolicyChain, PolicyConfig,
+    SizePolicy, SymlinkBehavior, SymlinkPolicy,
 };
 use safe_unzip::Error;
 use tempfile::tempdir;
@@ -13,8 +13,14 @@ fn make_entry_info(name: &str, size: u64, kind: EntryKind) -> safe_unzip::entry:
     safe_unzip::entry::EntryInfo {
         name: name.to_string(),
         size,
+        compressed_size: None,
         kind,
         mode: Some(0o644),
+        uid: None,
+        gid: None,
+        mtime: None,
+        size_known: true,
+        xattrs: Vec::new(),
     }
 }
 
@@ -109,6 +115,33 @@ fn test_path_policy_blocks_control_chars() {
     assert!(matches!(result, Err(Error::InvalidFilename { .. })));
 }
 
+#[test]
+fn test_path_policy_deceptive_unicode() {
+    let dest = tempdir().unwrap();
+    let state = default_state();
+    // Displays as "invoiceexe.pdf"
+    let rlo = file_info("invoice\u{202E}fdp.exe", 100);
+    let zero_width = file_info("pay\u{200B}pal.html", 100);
+
+    // Allowed unless enabled
+    let policy = PathPolicy::new(dest.path()).unwrap();
+    assert!(policy.check(&rlo, &state).is_ok());
+
+    let policy = PathPolicy::new(dest.path())
+        .unwrap()
+        .reject_deceptive_unicode(true);
+    for entry in [&rlo, &zero_width] {
+        let result = policy.check(entry, &state);
+        assert!(
+            matches!(result, Err(Error::InvalidFilename { ref reason, .. }) if reason == "deceptive unicode")
+        );
+    }
+    // Ordinary non-ASCII names are fine
+    assert!(policy
+        .check(&file_info("café/報告.txt", 100), &state)
+        .is_ok());
+}
+
 // ============================================================================
 // SizePolicy Tests
 // ============================================================================
@@ -178,6 +211,27 @@ fn test_count_policy_zero_limit() {
     assert!(matches!(result, Err(Error::FileCountExceeded { .. })));
 }
 
+#[test]
+fn test_dir_count_policy_blocks_at_limit() {
+    let policy = DirCountPolicy::new(10);
+    let mut state = default_state();
+    state.dirs_created = 9;
+    assert!(policy.check(&dir_info("dir/"), &state).is_ok());
+
+    state.dirs_created = 10;
+    let result = policy.check(&dir_info("dir/"), &state);
+    assert!(matches!(
+        result,
+        Err(Error::DirCountExceeded {
+            limit: 10,
+            attempted: 11
+        })
+    ));
+
+    // Files are counted by CountPolicy instead
+    assert!(policy.check(&file_info("file.txt", 100), &state).is_ok());
+}
+
 // ============================================================================
 // DepthPolicy Tests
 // ============================================================================
@@ -201,6 +255,25 @@ fn test_depth_policy_blocks_deep() {
     assert!(matches!(result, Err(Error::PathTooDeep { .. })));
 }
 
+#[test]
+fn test_depth_policy_counts_normal_components() {
+    let policy = DepthPolicy::new(2);
+    let state = default_state();
+
+    for name in ["a/b.txt", "./a/b.txt", "a//b.txt", "a/./b.txt", "a/b/"] {
+        assert!(policy.check(&file_info(name, 1), &state).is_ok(), "{name}");
+    }
+    let result = policy.check(&file_info("./a//b/c.txt", 1), &state);
+    assert!(matches!(
+        result,
+        Err(Error::PathTooDeep {
+            depth: 3,
+            limit: 2,
+            ..
+        })
+    ));
+}
+
 // ============================================================================
 // SymlinkPolicy Tests
 // ============================================================================
@@ -303,8 +376,10 @@ fn test_policy_config_build() {
         max_single_file: 1000,
         max_total: 10000,
         max_files: 100,
+        max_dirs: 100_000,
         max_depth: 10,
         symlink_behavior: SymlinkBehavior::Skip,
+        windows_name_check: true,
     };
 
     let chain = config.build().unwrap();
@@ -318,6 +393,10 @@ fn test_policy_config_build() {
     let entry = file_info("big.txt", 5000);
     let result = chain.check_all(&entry, &state);
     assert!(matches!(result, Err(Error::FileTooLarge { .. })));
+
+    let entry = file_info("lib/aux.py", 10);
+    let result = chain.check_all(&entry, &state);
+    assert!(matches!(result, Err(Error::InvalidFilename { .. })));
 }
 
 #[test]
@@ -328,8 +407,10 @@ fn test_policy_config_symlink_error() {
         max_single_file: 1000,
         max_total: 10000,
         max_files: 100,
+        max_dirs: 100_000,
         max_depth: 10,
         symlink_behavior: SymlinkBehavior::Error,
+        windows_name_check: cfg!(windows),
     };
 
     let chain = config.build().unwrap();
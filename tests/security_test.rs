@@ -374,6 +374,116 @@ fn test_file_count_limit() {
     println!("✅ File count limit works");
 }
 
+#[test]
+fn test_dir_count_limit() {
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let options: FileOptions<()> = FileOptions::default();
+    for i in 0..5 {
+        zip.add_directory(format!("dir{}/", i), options).unwrap();
+    }
+    zip.start_file("file.txt", options).unwrap();
+    zip.write_all(b"data").unwrap();
+    let mut zip = zip.finish().unwrap();
+
+    let limits = Limits {
+        max_dir_count: 3,
+        ..Default::default()
+    };
+    for mode in [ExtractionMode::Streaming, ExtractionMode::ValidateFirst] {
+        let dest = tempdir().unwrap();
+        zip.seek(std::io::SeekFrom::Start(0)).unwrap();
+        let result = Extractor::new(dest.path())
+            .unwrap()
+            .limits(limits)
+            .mode(mode)
+            .extract(&mut zip);
+        assert!(
+            matches!(
+                result,
+                Err(Error::DirCountExceeded {
+                    limit: 3,
+                    attempted: 4 | 5
+                })
+            ),
+            "{:?}",
+            result
+        );
+    }
+
+    // Files don't count against it
+    let dest = tempdir().unwrap();
+    zip.seek(std::io::SeekFrom::Start(0)).unwrap();
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .limits(Limits {
+            max_dir_count: 5,
+            ..Default::default()
+        })
+        .extract(&mut zip)
+        .unwrap();
+    assert_eq!(report.dirs_created, 5);
+    assert_eq!(report.files_extracted, 1);
+}
+
+#[test]
+fn test_entry_count_limit() {
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let options: FileOptions<()> = FileOptions::default();
+    zip.add_directory("dir/", options).unwrap();
+    zip.add_symlink("dir/link", "file.txt", options).unwrap();
+    zip.start_file("dir/file.txt", options).unwrap();
+    zip.write_all(b"data").unwrap();
+    let mut zip = zip.finish().unwrap();
+
+    // Every kind of entry counts, and nothing is extracted
+    let limits = Limits {
+        max_entries: 2,
+        ..Default::default()
+    };
+    let dest = tempdir().unwrap();
+    zip.seek(std::io::SeekFrom::Start(0)).unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract(&mut zip);
+    assert!(matches!(
+        result,
+        Err(Error::EntryCountExceeded {
+            limit: 2,
+            attempted: 3
+        })
+    ));
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    zip.seek(std::io::SeekFrom::Start(0)).unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract_zip(ZipAdapter::new(&mut zip).unwrap());
+    assert!(matches!(
+        result,
+        Err(Error::EntryCountExceeded {
+            limit: 2,
+            attempted: 3
+        })
+    ));
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    let limits = Limits {
+        max_entries: 3,
+        ..Default::default()
+    };
+    zip.seek(std::io::SeekFrom::Start(0)).unwrap();
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract(&mut zip)
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+}
+
 #[test]
 fn test_path_depth_limit() {
     let dest = tempdir().unwrap();
@@ -407,6 +517,186 @@ fn test_path_depth_limit() {
     }
 }
 
+#[test]
+fn test_path_depth_counts_normal_components() {
+    let limits = Limits {
+        max_path_depth: 2,
+        ..Default::default()
+    };
+
+    // `.` segments, doubled slashes and trailing slashes add no depth
+    for name in ["./a/b.txt", "a//b.txt", "a/b/"] {
+        let dest = tempdir().unwrap();
+        let zip = create_simple_zip(name, b"");
+        let result = Extractor::new(dest.path())
+            .unwrap()
+            .limits(limits)
+            .extract(zip);
+        assert!(result.is_ok(), "{name}: {result:?}");
+    }
+
+    let dest = tempdir().unwrap();
+    let zip = create_simple_zip("./a//b/c.txt", b"");
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract(zip);
+    assert!(matches!(
+        result,
+        Err(Error::PathTooDeep {
+            depth: 3,
+            limit: 2,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_strip_components() {
+    let dest = tempdir().unwrap();
+    let zip = create_multi_file_zip(&[
+        ("root/a.txt", b"a"),
+        ("root/sub/b.txt", b"b"),
+        ("top.txt", b"top"),
+    ]);
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .strip_components(1)
+        .extract(zip)
+        .unwrap();
+
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(report.entries_skipped, 1);
+    assert_eq!(std::fs::read(dest.path().join("a.txt")).unwrap(), b"a");
+    assert_eq!(std::fs::read(dest.path().join("sub/b.txt")).unwrap(), b"b");
+    assert!(!dest.path().join("root").exists());
+    assert!(!dest.path().join("top.txt").exists());
+
+    // Stripping a component never lets `..` climb out of the destination
+    let dest = tempdir().unwrap();
+    let zip = create_simple_zip("evil/../../x.txt", b"x");
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .strip_components(1)
+        .extract(zip);
+    assert!(
+        matches!(result, Err(Error::PathEscape { .. })),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn test_prefix() {
+    let dest = tempdir().unwrap();
+    let zip = create_multi_file_zip(&[("hello.txt", b"hi"), ("root/sub/b.txt", b"b")]);
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .prefix("foo")
+        .unwrap()
+        .extract(zip)
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(
+        std::fs::read(dest.path().join("foo/hello.txt")).unwrap(),
+        b"hi"
+    );
+    assert!(dest.path().join("foo/root/sub/b.txt").exists());
+
+    // The prefix goes on after stripping
+    let dest = tempdir().unwrap();
+    let zip = create_multi_file_zip(&[("root/sub/b.txt", b"b")]);
+    Extractor::new(dest.path())
+        .unwrap()
+        .strip_components(1)
+        .prefix("./foo/bar")
+        .unwrap()
+        .extract(zip)
+        .unwrap();
+    assert!(dest.path().join("foo/bar/sub/b.txt").exists());
+
+    let dest = tempdir().unwrap();
+    let zip = create_simple_zip("../x.txt", b"x");
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .prefix("foo")
+        .unwrap()
+        .extract(zip);
+    assert!(
+        matches!(result, Err(Error::PathEscape { .. })),
+        "{result:?}"
+    );
+
+    for bad in ["..", "foo/../../bar", "/abs"] {
+        let result = Extractor::new(dest.path()).unwrap().prefix(bad);
+        assert!(matches!(result, Err(Error::InvalidOption { .. })), "{bad}");
+    }
+}
+
+#[test]
+fn test_map_name() {
+    let dest = tempdir().unwrap();
+    let zip = create_multi_file_zip(&[("My Photos/IMG 1.JPG", b"jpg"), ("Thumbs.db", b"db")]);
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .map_name(|name| (name != "Thumbs.db").then(|| name.replace(' ', "_")))
+        // The filter sees the mapped name
+        .filter(|entry| !entry.name.contains(' '))
+        .extract(zip)
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+    assert_eq!(report.entries_skipped, 1);
+    assert!(dest.path().join("My_Photos/IMG_1.JPG").exists());
+    assert!(!dest.path().join("Thumbs.db").exists());
+
+    // Two entries mapped to one name
+    let dest = tempdir().unwrap();
+    let zip = create_multi_file_zip(&[("v1/config.toml", b"1"), ("v2/config.toml", b"2")]);
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .map_name(|name| name.rsplit('/').next().map(str::to_string))
+        .extract(zip);
+    assert!(
+        matches!(result, Err(Error::AlreadyExists { ref entry }) if entry.ends_with("config.toml")),
+        "{result:?}"
+    );
+
+    // A callback can't send an entry out of the destination
+    let dest = tempdir().unwrap();
+    let zip = create_simple_zip("innocent.txt", b"x");
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .map_name(|_| Some("/etc/passwd".to_string()))
+        .extract(zip);
+    assert!(result.is_err());
+    assert!(dest.path().read_dir().unwrap().next().is_none());
+}
+
+#[test]
+fn test_collect_paths() {
+    let dest = tempdir().unwrap();
+    std::fs::write(dest.path().join("existing.txt"), b"old").unwrap();
+    let zip = create_multi_file_zip(&[
+        ("a/b.txt", b"b"),
+        ("skip.tmp", b"tmp"),
+        ("existing.txt", b"new"),
+        ("c.txt", b"c"),
+    ]);
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .collect_paths(true)
+        .overwrite(OverwritePolicy::Skip)
+        .filter(|entry| !entry.name.ends_with(".tmp"))
+        .extract(zip)
+        .unwrap();
+
+    // Filtered and skipped entries are left out
+    assert_eq!(report.entries_skipped, 2);
+    assert_eq!(
+        report.extracted_paths,
+        [dest.path().join("a/b.txt"), dest.path().join("c.txt")]
+    );
+}
+
 // ============================================================================
 // Directory Extraction Tests
 // ============================================================================
@@ -447,7 +737,10 @@ fn test_sanitize_filenames() {
     // Test Windows reserved names
     // ZIP spec allows basically anything, but we want to fail on "CON.txt"
     let zip = create_simple_zip("CON.txt", b"safe");
-    let result = Extractor::new(dest.path()).unwrap().extract(zip);
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .windows_name_check(true)
+        .extract(zip);
 
     match result {
         Err(Error::InvalidFilename { entry, reason }) => {
@@ -463,6 +756,38 @@ fn test_sanitize_filenames() {
     }
 }
 
+#[test]
+fn test_windows_name_check_default() {
+    let dest = tempdir().unwrap();
+    let zip = create_multi_file_zip(&[("pkg/aux.py", b"# helpers"), ("con.txt", b"text")]);
+    let result = Extractor::new(dest.path()).unwrap().extract(zip);
+
+    if cfg!(windows) {
+        assert!(matches!(result, Err(Error::InvalidFilename { .. })));
+    } else {
+        assert_eq!(result.unwrap().files_extracted, 2);
+        assert!(dest.path().join("pkg/aux.py").exists());
+        assert!(dest.path().join("con.txt").exists());
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_windows_name_check_disabled() {
+    let dest = tempdir().unwrap();
+    let zip = create_simple_zip("NUL", b"not a device here");
+    Extractor::new(dest.path())
+        .unwrap()
+        .hardened()
+        .windows_name_check(false)
+        .extract(zip)
+        .unwrap();
+    assert_eq!(
+        std::fs::read(dest.path().join("NUL")).unwrap(),
+        b"not a device here"
+    );
+}
+
 #[test]
 fn test_symlink_overwrite_protection() {
     #[cfg(unix)]
@@ -504,6 +829,238 @@ fn test_symlink_overwrite_protection() {
     }
 }
 
+/// Test: Directory entries get their stored mode, minus special bits
+/// Attack: setgid/sticky directory modes smuggled via the archive
+/// Defense: dir mode is masked to 0o777 (owner rwx always kept)
+#[cfg(unix)]
+#[test]
+fn test_zip_dir_mode_applied_and_masked() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    zip.add_directory(
+        "private/",
+        FileOptions::<()>::default().unix_permissions(0o700),
+    )
+    .unwrap();
+    zip.add_directory(
+        "shared/",
+        FileOptions::<()>::default().unix_permissions(0o3775),
+    )
+    .unwrap();
+    let zip_file = zip.finish().unwrap();
+
+    let dest = tempdir().unwrap();
+    Extractor::new(dest.path())
+        .unwrap()
+        .extract(zip_file)
+        .unwrap();
+
+    let mode = |name: &str| {
+        std::fs::metadata(dest.path().join(name))
+            .unwrap()
+            .permissions()
+            .mode()
+    };
+    assert_eq!(mode("private") & 0o7777, 0o700);
+    assert_eq!(mode("shared") & 0o7777, 0o775);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_permission_modes() {
+    use safe_unzip::PermissionMode;
+    use std::os::unix::fs::PermissionsExt;
+
+    let build = || {
+        let mut zip = zip::ZipWriter::new(tempfile::tempfile().unwrap());
+        zip.add_directory("dir/", FileOptions::<()>::default().unix_permissions(0o777))
+            .unwrap();
+        zip.start_file(
+            "dir/run.sh",
+            FileOptions::<()>::default().unix_permissions(0o4777),
+        )
+        .unwrap();
+        zip.write_all(b"#!/bin/sh").unwrap();
+        zip.finish().unwrap()
+    };
+    let modes = |permissions: PermissionMode| {
+        let dest = tempdir().unwrap();
+        Extractor::new(dest.path())
+            .unwrap()
+            .permission_mode(permissions)
+            .extract(build())
+            .unwrap();
+        let mode = |name: &str| {
+            std::fs::metadata(dest.path().join(name))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o7777
+        };
+        (mode("dir"), mode("dir/run.sh"))
+    };
+
+    assert_eq!(modes(PermissionMode::FromArchive), (0o777, 0o777));
+    assert_eq!(modes(PermissionMode::Mask(0o755)), (0o755, 0o755));
+    // Directories keep owner rwx whatever the mask
+    assert_eq!(modes(PermissionMode::Mask(0o600)), (0o700, 0o600));
+    assert_eq!(
+        modes(PermissionMode::Fixed {
+            file: 0o640,
+            dir: 0o750
+        }),
+        (0o750, 0o640)
+    );
+}
+
+#[test]
+fn test_list_zip_reports_mode_and_link_target() {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = FileOptions::<()>::default().unix_permissions(0o750);
+    zip.start_file("bin/tool", options).unwrap();
+    zip.write_all(b"tool").unwrap();
+    zip.add_symlink("current", "bin/tool", FileOptions::<()>::default())
+        .unwrap();
+    zip.start_file("plain.txt", FileOptions::<()>::default())
+        .unwrap();
+    let data = zip.finish().unwrap().into_inner();
+
+    let entries = safe_unzip::list_zip(std::io::Cursor::new(&data)).unwrap();
+    assert_eq!(entries[0].mode.map(|m| m & 0o7777), Some(0o750));
+    assert_eq!(entries[0].symlink_target(), None);
+    assert_eq!(entries[1].symlink_target(), Some("bin/tool"));
+    assert_eq!(entries[2].symlink_target(), None);
+}
+
+#[test]
+fn test_zip_total_uncompressed() {
+    let mut zip = zip::ZipWriter::new(tempfile::NamedTempFile::new().unwrap());
+    let stored = FileOptions::<()>::default().compression_method(zip::CompressionMethod::Stored);
+    let deflated =
+        FileOptions::<()>::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.add_directory("dir/", stored).unwrap();
+    zip.start_file("dir/a.txt", stored).unwrap();
+    zip.write_all(&[b'a'; 100]).unwrap();
+    zip.start_file("b.bin", deflated).unwrap();
+    zip.write_all(&[0; 50_000]).unwrap();
+    let file = zip.finish().unwrap();
+
+    assert_eq!(
+        safe_unzip::zip_total_uncompressed(file.path()).unwrap(),
+        50_100
+    );
+    let data = std::fs::read(file.path()).unwrap();
+    assert_eq!(
+        safe_unzip::zip_total_uncompressed_bytes(&data).unwrap(),
+        50_100
+    );
+
+    // An empty archive declares nothing
+    let data = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()))
+        .finish()
+        .unwrap()
+        .into_inner();
+    assert_eq!(safe_unzip::zip_total_uncompressed_bytes(&data).unwrap(), 0);
+}
+
+/// A one-entry ZIP whose central directory declares `size` bytes, far
+/// more than it holds.
+fn create_huge_declared_zip(size: u64) -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = FileOptions::<()>::default()
+        .compression_method(zip::CompressionMethod::Stored)
+        .large_file(true);
+    zip.start_file("huge.bin", options).unwrap();
+    zip.write_all(b"x").unwrap();
+    let mut data = zip.finish().unwrap().into_inner();
+
+    // The Zip64 extra field of the central directory header holds the
+    // uncompressed size first
+    let central = data.windows(4).rposition(|w| w == b"PK\x01\x02").unwrap();
+    let extra = central
+        + data[central..]
+            .windows(4)
+            .position(|w| w == [0x01, 0x00, 0x10, 0x00])
+            .unwrap();
+    data[extra + 4..extra + 12].copy_from_slice(&size.to_le_bytes());
+    data
+}
+
+/// Test: An archive declares more than the destination can hold
+/// Defense: ensure_free_space fails up front with InsufficientSpace
+#[test]
+fn test_ensure_free_space() {
+    let data = create_huge_declared_zip(1 << 60);
+    let limits = Limits {
+        max_total_bytes: u64::MAX,
+        max_single_file: u64::MAX,
+        ..Default::default()
+    };
+
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .mode(ExtractionMode::ValidateFirst)
+        .ensure_free_space(true)
+        .extract(std::io::Cursor::new(&data));
+    assert!(
+        matches!(
+            result,
+            Err(Error::InsufficientSpace {
+                required,
+                available
+            }) if required == 1 << 60 && available < required
+        ),
+        "{:?}",
+        result
+    );
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .validation(safe_unzip::ValidationMode::ValidateFirst)
+        .ensure_free_space(true)
+        .extract_zip(ZipAdapter::new(std::io::Cursor::new(&data)).unwrap());
+    assert!(
+        matches!(result, Err(Error::InsufficientSpace { .. })),
+        "{:?}",
+        result
+    );
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    // The total is only known up front when validating first
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .ensure_free_space(true)
+        .extract(create_multi_file_zip(&[("a.txt", b"a")]));
+    assert!(matches!(result, Err(Error::InvalidOption { .. })));
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .ensure_free_space(true)
+        .extract_zip(ZipAdapter::new(create_multi_file_zip(&[("a.txt", b"a")])).unwrap());
+    assert!(matches!(result, Err(Error::InvalidOption { .. })));
+
+    // Archives that fit extract as usual
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .mode(ExtractionMode::ValidateFirst)
+        .ensure_free_space(true)
+        .extract(create_multi_file_zip(&[("a.txt", b"a")]))
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .validation(safe_unzip::ValidationMode::ValidateFirst)
+        .ensure_free_space(true)
+        .extract_zip(ZipAdapter::new(create_multi_file_zip(&[("b.txt", b"b")])).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+}
+
 // Helper to modify zip bytes to fake size
 fn create_fake_size_zip(name: &str, content: &[u8], declared_size: u32) -> std::fs::File {
     let file = tempfile::tempfile().unwrap();
@@ -600,9 +1157,149 @@ fn test_strict_size_enforcement() {
     }
 }
 
-// ============================================================================
-// Advanced Attack Vector Tests
-// ============================================================================
+/// Deflate `content` into a zip whose headers declare `declared_size`
+/// bytes uncompressed. The compressed size and CRC stay correct, so the
+/// entry decodes cleanly to its real length.
+fn create_lying_deflate_zip(name: &str, content: &[u8], declared_size: u32) -> std::fs::File {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options: FileOptions<()> =
+        FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file(name, options).unwrap();
+    zip.write_all(content).unwrap();
+    let mut buffer = zip.finish().unwrap().into_inner();
+
+    let size = declared_size.to_le_bytes();
+    // Uncompressed size: offset 22 in the local header, 24 in the central one
+    buffer[22..26].copy_from_slice(&size);
+    let cd = buffer
+        .windows(4)
+        .position(|w| w == [0x50, 0x4b, 0x01, 0x02])
+        .unwrap();
+    buffer[cd + 24..cd + 28].copy_from_slice(&size);
+
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(&buffer).unwrap();
+    file.seek(std::io::SeekFrom::Start(0)).unwrap();
+    file
+}
+
+#[test]
+fn test_declared_size_mismatch() {
+    let content = vec![b'a'; 100_000];
+
+    // Understated: reading stops one byte past the declared size
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .extract(create_lying_deflate_zip("bomb.txt", &content, 10));
+    assert!(
+        matches!(
+            result,
+            Err(Error::SizeMismatch {
+                declared: 10,
+                actual: 11,
+                ..
+            })
+        ),
+        "{:?}",
+        result
+    );
+    assert!(!dest.path().join("bomb.txt").exists());
+
+    // Overstated: the entry ends early
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .extract(create_lying_deflate_zip("short.txt", &content, 200_000));
+    assert!(
+        matches!(
+            result,
+            Err(Error::SizeMismatch {
+                declared: 200_000,
+                actual: 100_000,
+                ..
+            })
+        ),
+        "{:?}",
+        result
+    );
+    assert!(!dest.path().join("short.txt").exists());
+
+    // The driver's ZIP path catches both the same way
+    for declared in [10, 200_000] {
+        let dest = tempdir().unwrap();
+        let zip_file = create_lying_deflate_zip("driver.txt", &content, declared);
+        let result = safe_unzip::Driver::new(dest.path())
+            .unwrap()
+            .extract_zip(safe_unzip::ZipAdapter::new(zip_file).unwrap());
+        assert!(
+            matches!(result, Err(Error::SizeMismatch { declared: d, .. }) if d == u64::from(declared)),
+            "{:?}",
+            result
+        );
+    }
+}
+
+#[test]
+fn test_compression_ratio_limit() {
+    let zip_with = |method| {
+        let mut zip = zip::ZipWriter::new(tempfile::tempfile().unwrap());
+        let options: FileOptions<()> = FileOptions::default().compression_method(method);
+        zip.start_file("zeros.bin", options).unwrap();
+        zip.write_all(&vec![0u8; 1024 * 1024]).unwrap();
+        let mut file = zip.finish().unwrap();
+        file.seek(std::io::SeekFrom::Start(0)).unwrap();
+        file
+    };
+    let limits = Limits {
+        max_compression_ratio: Some(50),
+        ..Default::default()
+    };
+
+    // Stored is 1:1, deflated zeros shrink about a thousandfold
+    let dest = tempdir().unwrap();
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract(zip_with(zip::CompressionMethod::Stored))
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract(zip_with(zip::CompressionMethod::Deflated));
+    assert!(
+        matches!(result, Err(Error::CompressionRatioExceeded { ratio, limit: 50, .. }) if ratio > 50),
+        "{:?}",
+        result
+    );
+    assert!(!dest.path().join("zeros.bin").exists());
+
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract_zip(ZipAdapter::new(zip_with(zip::CompressionMethod::Deflated)).unwrap());
+    assert!(matches!(
+        result,
+        Err(Error::CompressionRatioExceeded { limit: 50, .. })
+    ));
+
+    // Disabled, the same entry extracts
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .limits(Limits {
+            max_compression_ratio: None,
+            ..Default::default()
+        })
+        .extract_zip(ZipAdapter::new(zip_with(zip::CompressionMethod::Deflated)).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+}
+
+// ============================================================================
+// Advanced Attack Vector Tests
+// ============================================================================
 
 /// Test: Absolute paths in zip entries should be blocked or safely contained
 /// Attack: Archive contains "/tmp/evil.txt" or "C:\evil.txt"
@@ -770,6 +1467,77 @@ fn test_symlink_then_file_in_same_archive() {
     println!("✅ Symlink replaced with file safely (didn't follow symlink)");
 }
 
+/// Test: `SymlinkPolicy::Allow` creates contained links only
+/// Attack: "link -> /etc/passwd", or a relative target climbing out
+/// Defense: Targets must be relative and resolve inside the destination
+#[test]
+#[cfg(unix)]
+fn test_symlink_policy_allow() {
+    use safe_unzip::SymlinkPolicy;
+
+    let zip_with_link = |target: &str| {
+        let mut zip = zip::ZipWriter::new(tempfile::tempfile().unwrap());
+        let options: FileOptions<()> = FileOptions::default();
+        zip.start_file("subdir/file.txt", options).unwrap();
+        zip.write_all(b"hello").unwrap();
+        zip.add_symlink("link", target, options).unwrap();
+        zip.finish().unwrap()
+    };
+
+    let dest = tempdir().unwrap();
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .symlinks(SymlinkPolicy::Allow)
+        .extract(zip_with_link("subdir/file.txt"))
+        .unwrap();
+    assert_eq!(report.symlinks_created, 1);
+    let link = dest.path().join("link");
+    assert!(link.is_symlink());
+    assert_eq!(
+        std::fs::read_link(&link).unwrap(),
+        std::path::Path::new("subdir/file.txt")
+    );
+    assert_eq!(std::fs::read_to_string(&link).unwrap(), "hello");
+
+    for (target, mode) in [
+        ("/etc/passwd", ExtractionMode::Streaming),
+        ("../outside.txt", ExtractionMode::Streaming),
+        ("subdir/../../outside.txt", ExtractionMode::ValidateFirst),
+    ] {
+        let dest = tempdir().unwrap();
+        let result = Extractor::new(dest.path())
+            .unwrap()
+            .symlinks(SymlinkPolicy::Allow)
+            .mode(mode)
+            .extract(zip_with_link(target));
+        assert!(
+            matches!(result, Err(Error::PathEscape { .. })),
+            "{}: {:?}",
+            target,
+            result
+        );
+        assert!(std::fs::symlink_metadata(dest.path().join("link")).is_err());
+    }
+
+    // Links created earlier can't be chained to climb out
+    let mut zip = zip::ZipWriter::new(tempfile::tempfile().unwrap());
+    let options: FileOptions<()> = FileOptions::default();
+    zip.add_directory("a/b", options).unwrap();
+    zip.add_symlink("up", "a/b", options).unwrap();
+    zip.add_symlink("up/escape", "../../..", options).unwrap();
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .symlinks(SymlinkPolicy::Allow)
+        .extract(zip.finish().unwrap());
+    assert!(
+        matches!(result, Err(Error::PathEscape { .. })),
+        "{:?}",
+        result
+    );
+    assert!(std::fs::symlink_metadata(dest.path().join("a/b/escape")).is_err());
+}
+
 /// Test: Traversal using backslash on Windows-style paths
 /// Attack: "..\\..\secret.txt" mixing slashes
 /// Defense: Reject backslash before any path processing
@@ -1068,6 +1836,48 @@ fn test_very_long_filename() {
     }
 }
 
+/// Test: Entry name fits on its own but not once joined to the destination
+/// Attack: Short-enough names under a deep destination overflow PATH_MAX
+/// Defense: max_absolute_path counts destination + separator + name
+#[test]
+fn test_absolute_path_too_long() {
+    let dest = tempdir().unwrap();
+    let limits = Limits {
+        max_absolute_path: dest.path().as_os_str().len() + 16,
+        ..Default::default()
+    };
+
+    let zip = create_simple_zip("short.txt", b"data");
+    Extractor::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract(zip)
+        .unwrap();
+
+    let zip = create_simple_zip("dir/a_longer_name.txt", b"data");
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract(zip);
+    assert!(
+        matches!(&result, Err(Error::InvalidFilename { reason, .. }) if reason == "absolute path too long"),
+        "Expected InvalidFilename, got {:?}",
+        result
+    );
+
+    let zip = create_simple_zip("dir/a_longer_name.txt", b"data");
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract_zip(ZipAdapter::new(zip).unwrap());
+    assert!(
+        matches!(&result, Err(Error::InvalidFilename { reason, .. }) if reason == "absolute path too long"),
+        "Expected InvalidFilename, got {:?}",
+        result
+    );
+    assert!(!dest.path().join("dir").exists());
+}
+
 /// Test: Unicode normalization attack
 /// Attack: Two entries "café" (composed) and "café" (decomposed) collide
 /// Defense: Detect collision or handle consistently
@@ -1207,24 +2017,93 @@ fn test_encrypted_entry_rejected() {
     let result = Driver::new(dest.path()).unwrap().extract_zip(adapter);
 
     match result {
-        Err(Error::EncryptedEntry { entry }) => {
-            println!("✅ Encrypted entry rejected: {}", entry);
-        }
-        Err(Error::Zip(_)) => {
-            // Some zip parsing errors are also acceptable
-            println!("✅ Encrypted/malformed zip rejected");
-        }
-        Ok(_) => {
-            // If the zip crate skips or handles encrypted entries differently, that's okay
-            // The important thing is we don't silently extract garbage
-            println!("⚠️ Zip crate handled encrypted entry (check contents)");
+        Err(Error::UnsupportedEncryption { entry, scheme }) => {
+            assert_eq!(entry, "secret.txt");
+            assert_eq!(scheme, "ZipCrypto");
+            println!("✅ Encrypted entry rejected: {} ({})", entry, scheme);
         }
+        Ok(_) => panic!("❌ Encrypted entry should not be extracted"),
         Err(e) => panic!("❌ Unexpected error: {:?}", e),
     }
 }
 
-/// Create a minimal encrypted ZIP file structure
+/// Test: Encryption is checked before decompression on every ZIP path,
+/// including the validation pass, with the specific variant rather than a
+/// zip format error
+#[test]
+fn test_encrypted_entry_rejected_all_modes() {
+    for mode in [ExtractionMode::Streaming, ExtractionMode::ValidateFirst] {
+        let dest = tempdir().unwrap();
+        let result = Extractor::new(dest.path())
+            .unwrap()
+            .mode(mode)
+            .extract(std::io::Cursor::new(create_encrypted_zip()));
+        assert!(
+            matches!(result, Err(Error::UnsupportedEncryption { ref entry, ref scheme })
+                if entry == "secret.txt" && scheme == "ZipCrypto"),
+            "{:?}: {:?}",
+            mode,
+            result
+        );
+        assert!(!dest.path().join("secret.txt").exists());
+    }
+
+    let dest = tempdir().unwrap();
+    let adapter = ZipAdapter::new(std::io::Cursor::new(create_encrypted_zip())).unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .validation(safe_unzip::ValidationMode::ValidateFirst)
+        .extract_zip(adapter);
+    assert!(
+        matches!(result, Err(Error::UnsupportedEncryption { .. })),
+        "{:?}",
+        result
+    );
+    assert!(!dest.path().join("secret.txt").exists());
+}
+
+/// Test: WinZip AES entries are reported with their key size, so callers
+/// can tell an unsupported scheme from a wrong password or corrupt data
+#[test]
+fn test_aes_encrypted_entry_names_scheme() {
+    // "AE-2" extra field: vendor version 2, vendor "AE", strength 3
+    // (AES-256), actual method stored
+    let extra = [
+        0x01, 0x99, 0x07, 0x00, 0x02, 0x00, 0x41, 0x45, 0x03, 0x00, 0x00,
+    ];
+    let zip = create_encrypted_zip_with(99, &extra);
+
+    let dest = tempdir().unwrap();
+    let adapter = ZipAdapter::new(std::io::Cursor::new(zip.clone())).unwrap();
+    match Driver::new(dest.path()).unwrap().extract_zip(adapter) {
+        Err(Error::UnsupportedEncryption { entry, scheme }) => {
+            assert_eq!(entry, "secret.txt");
+            assert_eq!(scheme, "AES-256");
+        }
+        other => panic!("❌ Expected UnsupportedEncryption, got {:?}", other),
+    }
+
+    // The legacy Extractor reports the same scheme instead of a zip error
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .extract(std::io::Cursor::new(zip));
+    assert!(
+        matches!(result, Err(Error::UnsupportedEncryption { ref scheme, .. }) if scheme == "AES-256"),
+        "got {:?}",
+        result
+    );
+    assert!(!dest.path().join("secret.txt").exists());
+}
+
+/// Create a minimal ZipCrypto-flagged ZIP file structure
 fn create_encrypted_zip() -> Vec<u8> {
+    create_encrypted_zip_with(0, &[])
+}
+
+/// Like [`create_encrypted_zip`], with the given compression method and
+/// extra field in both headers.
+fn create_encrypted_zip_with(method: u16, extra: &[u8]) -> Vec<u8> {
     // Minimal ZIP with encrypted flag set in general purpose bit flag
     // Local file header for "secret.txt"
     let mut zip = Vec::new();
@@ -1238,8 +2117,8 @@ fn create_encrypted_zip() -> Vec<u8> {
     zip.extend_from_slice(&[0x14, 0x00]);
     // General purpose bit flag - bit 0 set = encrypted
     zip.extend_from_slice(&[0x01, 0x00]); // Encrypted flag!
-                                          // Compression method (0 = stored)
-    zip.extend_from_slice(&[0x00, 0x00]);
+                                          // Compression method
+    zip.extend_from_slice(&method.to_le_bytes());
     // Last mod time/date
     zip.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
     // CRC-32 (fake)
@@ -1253,9 +2132,11 @@ fn create_encrypted_zip() -> Vec<u8> {
     let name_len = filename.len() as u16;
     zip.extend_from_slice(&name_len.to_le_bytes());
     // Extra field length
-    zip.extend_from_slice(&[0x00, 0x00]);
-    // Filename
+    let extra_len = extra.len() as u16;
+    zip.extend_from_slice(&extra_len.to_le_bytes());
+    // Filename and extra field
     zip.extend_from_slice(filename);
+    zip.extend_from_slice(extra);
     // File data (would be encrypted, but we just put raw bytes)
     zip.extend_from_slice(data);
 
@@ -1269,7 +2150,7 @@ fn create_encrypted_zip() -> Vec<u8> {
     // General purpose bit flag - encrypted
     zip.extend_from_slice(&[0x01, 0x00]);
     // Compression method
-    zip.extend_from_slice(&[0x00, 0x00]);
+    zip.extend_from_slice(&method.to_le_bytes());
     // Last mod time/date
     zip.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
     // CRC-32
@@ -1281,7 +2162,7 @@ fn create_encrypted_zip() -> Vec<u8> {
     // Filename length
     zip.extend_from_slice(&name_len.to_le_bytes());
     // Extra field length
-    zip.extend_from_slice(&[0x00, 0x00]);
+    zip.extend_from_slice(&extra_len.to_le_bytes());
     // Comment length
     zip.extend_from_slice(&[0x00, 0x00]);
     // Disk number start
@@ -1292,11 +2173,12 @@ fn create_encrypted_zip() -> Vec<u8> {
     zip.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
     // Relative offset of local header
     zip.extend_from_slice(&local_header_offset.to_le_bytes());
-    // Filename
+    // Filename and extra field
     zip.extend_from_slice(filename);
+    zip.extend_from_slice(extra);
 
-    let cd_offset = (30 + filename.len() + data.len()) as u32;
-    let cd_size = (46 + filename.len()) as u32;
+    let cd_offset = (30 + filename.len() + extra.len() + data.len()) as u32;
+    let cd_size = (46 + filename.len() + extra.len()) as u32;
 
     // End of central directory
     zip.extend_from_slice(&[0x50, 0x4b, 0x05, 0x06]); // PK\x05\x06
@@ -1468,6 +2350,71 @@ fn test_duplicate_entry_with_overwrite_policy() {
     }
 }
 
+/// Test: Duplicate entry names rejected as a property of the archive
+/// Attack: Two `a.txt` entries; other tools see the first, the zip crate
+/// only the second
+/// Defense: `reject_duplicate_entries` fails with DuplicateEntry
+#[test]
+fn test_reject_duplicate_entries() {
+    let data = create_data_descriptor_zip(&[("a.txt", b"first"), ("a.txt", b"second")]);
+
+    // Off by default: the zip crate shows only the second entry
+    let dest = tempdir().unwrap();
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .extract(std::io::Cursor::new(&data))
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+    assert_eq!(std::fs::read(dest.path().join("a.txt")).unwrap(), b"second");
+
+    for mode in [ExtractionMode::Streaming, ExtractionMode::ValidateFirst] {
+        let dest = tempdir().unwrap();
+        let result = Extractor::new(dest.path())
+            .unwrap()
+            .mode(mode)
+            .reject_duplicate_entries(true)
+            .extract(std::io::Cursor::new(&data));
+        match result {
+            Err(Error::DuplicateEntry { entry }) => assert_eq!(entry, "a.txt"),
+            r => panic!("expected DuplicateEntry, got {:?}", r),
+        }
+        assert!(!dest.path().join("a.txt").exists());
+    }
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .reject_duplicate_entries(true)
+        .extract_zip(ZipAdapter::new(std::io::Cursor::new(&data)).unwrap());
+    assert!(matches!(result, Err(Error::DuplicateEntry { .. })));
+    assert!(!dest.path().join("a.txt").exists());
+
+    // Names are compared after normalization; repeated directories are fine
+    let data = create_data_descriptor_zip(&[("d/", b""), ("d/", b""), ("./d/a.txt", b"")]);
+    let dest = tempdir().unwrap();
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .reject_duplicate_entries(true)
+        .extract(std::io::Cursor::new(&data))
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+
+    let data = create_data_descriptor_zip(&[("d/a.txt", b""), ("./d//a.txt", b"")]);
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .reject_duplicate_entries(true)
+        .extract(std::io::Cursor::new(&data));
+    assert!(matches!(result, Err(Error::DuplicateEntry { .. })));
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .reject_duplicate_entries(true)
+        .extract_zip(ZipAdapter::new(std::io::Cursor::new(&data)).unwrap());
+    assert!(matches!(result, Err(Error::DuplicateEntry { .. })));
+}
+
 /// Test: Case sensitivity collision (File.txt vs file.txt)
 /// Attack: On case-insensitive FS (macOS, Windows), these collide
 /// Defense: Detect collision or use consistent behavior
@@ -1500,6 +2447,63 @@ fn test_case_sensitivity_collision() {
     }
 }
 
+/// Test: Case collisions rejected up front
+/// Attack: `README` and `readme`, where one clobbers the other on macOS and
+/// Windows
+/// Defense: `reject_case_collisions` fails with CaseCollision
+#[test]
+fn test_reject_case_collisions() {
+    let files: &[(&str, &[u8])] = &[
+        ("README", b"upper"),
+        ("docs/", b""),
+        ("DOCS/", b""),
+        ("readme", b"lower"),
+    ];
+
+    for mode in [ExtractionMode::Streaming, ExtractionMode::ValidateFirst] {
+        let dest = tempdir().unwrap();
+        let result = Extractor::new(dest.path())
+            .unwrap()
+            .mode(mode)
+            .reject_case_collisions(true)
+            .extract(create_multi_file_zip(files));
+        match result {
+            Err(Error::CaseCollision {
+                existing,
+                conflicting,
+            }) => {
+                assert_eq!(existing, "README");
+                assert_eq!(conflicting, "readme");
+            }
+            r => panic!("expected CaseCollision, got {:?}", r),
+        }
+        // ValidateFirst fails before writing anything
+        assert_eq!(
+            dest.path().join("README").exists(),
+            matches!(mode, ExtractionMode::Streaming)
+        );
+    }
+
+    // Filtered-out entries don't claim a name, in either mode
+    for mode in [ExtractionMode::Streaming, ExtractionMode::ValidateFirst] {
+        let dest = tempdir().unwrap();
+        let report = Extractor::new(dest.path())
+            .unwrap()
+            .mode(mode)
+            .reject_case_collisions(true)
+            .filter(|entry| entry.name != "README")
+            .extract(create_multi_file_zip(files))
+            .unwrap();
+        assert_eq!(report.files_extracted, 1);
+    }
+
+    let plan = Extractor::new(tempdir().unwrap().path())
+        .unwrap()
+        .reject_case_collisions(true)
+        .plan(create_multi_file_zip(files));
+    assert!(matches!(plan, Err(Error::CaseCollision { .. })));
+}
+
 /// Test: Path canonicalization attack using ./
 /// Attack: Entry "./foo/../bar.txt" should resolve to "bar.txt"
 /// Defense: Jail should handle or reject
@@ -1668,3 +2672,1365 @@ fn test_extreme_path_depth() {
         Ok(_) => panic!("❌ Should reject 100-level deep path with limit 50"),
     }
 }
+
+// ============================================================================
+// Data Descriptor Entries
+// ============================================================================
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// Build a stored zip the way streaming writers do: general-purpose bit 3
+/// set, zero CRC/sizes in the local headers, real values in a trailing data
+/// descriptor and in the central directory.
+fn create_data_descriptor_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+
+    for (name, content) in files {
+        let offset = out.len() as u32;
+        let crc = crc32(content);
+        let size = content.len() as u32;
+
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        out.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        out.extend_from_slice(&0x0008u16.to_le_bytes()); // flags: data descriptor
+        out.extend_from_slice(&0u16.to_le_bytes()); // stored
+        out.extend_from_slice(&[0; 4]); // mod time/date
+        out.extend_from_slice(&[0; 12]); // crc, sizes deferred
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(content);
+
+        out.extend_from_slice(&0x0807_4b50u32.to_le_bytes());
+        out.extend_from_slice(&crc.to_le_bytes());
+        out.extend_from_slice(&size.to_le_bytes());
+        out.extend_from_slice(&size.to_le_bytes());
+
+        central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        central.extend_from_slice(&20u16.to_le_bytes());
+        central.extend_from_slice(&0x0008u16.to_le_bytes());
+        central.extend_from_slice(&0u16.to_le_bytes());
+        central.extend_from_slice(&[0; 4]);
+        central.extend_from_slice(&crc.to_le_bytes());
+        central.extend_from_slice(&size.to_le_bytes());
+        central.extend_from_slice(&size.to_le_bytes());
+        central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        central.extend_from_slice(&[0; 12]); // extra, comment, disk, attrs
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let central_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0; 4]); // disk numbers
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out
+}
+
+/// Test: Entries whose sizes and CRC live in a data descriptor
+/// Defense: sizes come from the central directory and the CRC is still checked
+#[test]
+fn test_data_descriptor_entries() {
+    let data = create_data_descriptor_zip(&[("a.txt", b"streamed"), ("dir/b.txt", b"zip")]);
+
+    let dest = tempdir().unwrap();
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .extract(std::io::Cursor::new(&data))
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(
+        std::fs::read_to_string(dest.path().join("a.txt")).unwrap(),
+        "streamed"
+    );
+
+    let verify = Extractor::new(dest.path())
+        .unwrap()
+        .verify_bytes(&data)
+        .unwrap();
+    assert_eq!(verify.entries_verified, 2);
+    assert_eq!(verify.bytes_verified, 11);
+
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_zip(ZipAdapter::new(std::io::Cursor::new(&data)).unwrap())
+        .unwrap();
+    assert_eq!(report.bytes_written, 11);
+    assert_eq!(
+        std::fs::read_to_string(dest.path().join("dir/b.txt")).unwrap(),
+        "zip"
+    );
+}
+
+/// Test: Corrupted content behind a data descriptor
+/// Defense: CRC mismatch is reported, not silently extracted
+#[test]
+fn test_data_descriptor_crc_mismatch() {
+    let mut data = create_data_descriptor_zip(&[("a.txt", b"streamed")]);
+    // Flip a content byte (local header is 30 bytes + 5-byte name)
+    data[35] ^= 0xff;
+
+    let dest = tempdir().unwrap();
+    assert!(Extractor::new(dest.path())
+        .unwrap()
+        .verify_bytes(&data)
+        .is_err());
+
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .extract(std::io::Cursor::new(&data));
+    assert!(result.is_err(), "CRC mismatch should fail extraction");
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .extract_zip(ZipAdapter::new(std::io::Cursor::new(&data)).unwrap());
+    assert!(result.is_err(), "CRC mismatch should fail extraction");
+}
+
+/// Test: The second of two entries fails its CRC check mid-extraction
+/// Defense: cleanup_on_error removes what the extraction created, and
+/// only that
+#[test]
+fn test_cleanup_on_error() {
+    let mut data = create_data_descriptor_zip(&[
+        ("keep/first.txt", b"hello"),
+        ("new/dir/second.txt", b"world"),
+    ]);
+    let second = data.windows(5).position(|w| w == b"world").unwrap();
+    data[second] ^= 0xff;
+
+    let prepare = || {
+        let dest = tempdir().unwrap();
+        std::fs::create_dir(dest.path().join("keep")).unwrap();
+        std::fs::write(dest.path().join("keep/old.txt"), b"old").unwrap();
+        dest
+    };
+    let assert_untouched = |dest: &std::path::Path| {
+        assert!(!dest.join("keep/first.txt").exists());
+        assert!(!dest.join("new").exists());
+        assert_eq!(std::fs::read(dest.join("keep/old.txt")).unwrap(), b"old");
+    };
+
+    // Without cleanup the first file stays behind
+    let dest = prepare();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .extract(std::io::Cursor::new(&data));
+    assert!(result.is_err());
+    assert!(dest.path().join("keep/first.txt").exists());
+
+    let dest = prepare();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .cleanup_on_error(true)
+        .extract(std::io::Cursor::new(&data));
+    assert!(result.is_err());
+    assert_untouched(dest.path());
+
+    let dest = prepare();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .cleanup_on_error(true)
+        .extract_zip(ZipAdapter::new(std::io::Cursor::new(&data)).unwrap());
+    assert!(result.is_err());
+    assert_untouched(dest.path());
+
+    // A successful extraction keeps everything
+    let data = create_data_descriptor_zip(&[
+        ("keep/first.txt", b"hello"),
+        ("new/dir/second.txt", b"world"),
+    ]);
+    let dest = prepare();
+    Extractor::new(dest.path())
+        .unwrap()
+        .cleanup_on_error(true)
+        .extract(std::io::Cursor::new(&data))
+        .unwrap();
+    assert!(dest.path().join("new/dir/second.txt").exists());
+}
+
+/// Test: A failed extraction only cleans up what it created itself, even
+/// while another extraction on the same driver is running
+#[test]
+fn test_cleanup_on_error_shared_driver() {
+    use safe_unzip::{EntryInfo, ExtractionObserver};
+    use std::sync::{Arc, Barrier};
+
+    // Holds the good extraction after its first file until the bad one
+    // has failed
+    struct Pause(Arc<Barrier>);
+
+    impl ExtractionObserver for Pause {
+        fn after_entry(&self, entry: &EntryInfo, _path: &std::path::Path) {
+            if entry.name == "a1.txt" {
+                self.0.wait();
+                self.0.wait();
+            }
+        }
+    }
+
+    let good = create_data_descriptor_zip(&[("a1.txt", b"a1"), ("a2.txt", b"a2")]);
+    let mut bad = create_data_descriptor_zip(&[("b1.txt", b"hello"), ("b2.txt", b"world")]);
+    let second = bad.windows(5).position(|w| w == b"world").unwrap();
+    bad[second] ^= 0xff;
+
+    let dest = tempdir().unwrap();
+    let barrier = Arc::new(Barrier::new(2));
+    let driver = Arc::new(
+        Driver::new(dest.path())
+            .unwrap()
+            .cleanup_on_error(true)
+            .observe(Pause(Arc::clone(&barrier))),
+    );
+
+    let a = {
+        let driver = Arc::clone(&driver);
+        std::thread::spawn(move || driver.extract_zip(ZipAdapter::new(std::io::Cursor::new(good))?))
+    };
+    barrier.wait();
+    let result = driver.extract_zip(ZipAdapter::new(std::io::Cursor::new(bad)).unwrap());
+    barrier.wait();
+
+    assert!(result.is_err());
+    assert!(!dest.path().join("b1.txt").exists());
+    assert_eq!(a.join().unwrap().unwrap().files_extracted, 2);
+    assert!(dest.path().join("a1.txt").exists());
+    assert!(dest.path().join("a2.txt").exists());
+}
+
+/// Test: The second of two entries fails its CRC check
+/// Defense: verify_then_extract checks every entry before writing, so
+/// nothing is created, whether the data is kept in memory or re-read
+#[test]
+fn test_verify_then_extract() {
+    let mut data =
+        create_data_descriptor_zip(&[("first.txt", b"hello"), ("dir/second.txt", b"world")]);
+    let second = data.windows(5).position(|w| w == b"world").unwrap();
+    data[second] ^= 0xff;
+
+    for buffer_limit in [u64::MAX, 0] {
+        let dest = tempdir().unwrap();
+        let result = Extractor::new(dest.path())
+            .unwrap()
+            .verify_buffer_limit(buffer_limit)
+            .verify_then_extract(std::io::Cursor::new(&data));
+        assert!(result.is_err(), "CRC mismatch should fail extraction");
+        assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+    }
+
+    let data = create_data_descriptor_zip(&[("first.txt", b"hello"), ("dir/second.txt", b"world")]);
+    for buffer_limit in [u64::MAX, 0] {
+        let dest = tempdir().unwrap();
+        let report = Extractor::new(dest.path())
+            .unwrap()
+            .verify_buffer_limit(buffer_limit)
+            .verify_then_extract(std::io::Cursor::new(&data))
+            .unwrap();
+        assert_eq!(report.files_extracted, 2);
+        assert_eq!(report.bytes_written, 10);
+        assert_eq!(
+            std::fs::read(dest.path().join("dir/second.txt")).unwrap(),
+            b"world"
+        );
+    }
+}
+
+/// Test: verify_then_extract in Streaming mode on an archive whose second
+/// entry is corrupt and over the single-file limit, or filtered out
+/// Defense: the limits and filter are checked before anything is
+/// decompressed, so the limit error comes first and a filtered entry is
+/// never inflated
+#[test]
+fn test_verify_then_extract_checks_metadata_first() {
+    let mut data = create_data_descriptor_zip(&[("first.txt", b"hello"), ("second.bin", b"world")]);
+    let second = data.windows(5).position(|w| w == b"world").unwrap();
+    data[second] ^= 0xff;
+
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .mode(ExtractionMode::Streaming)
+        .limits(Limits {
+            max_single_file: 4,
+            ..Default::default()
+        })
+        .verify_then_extract(std::io::Cursor::new(&data));
+    assert!(
+        matches!(result, Err(Error::FileTooLarge { .. })),
+        "{result:?}"
+    );
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    for buffer_limit in [u64::MAX, 0] {
+        let dest = tempdir().unwrap();
+        let report = Extractor::new(dest.path())
+            .unwrap()
+            .mode(ExtractionMode::Streaming)
+            .verify_buffer_limit(buffer_limit)
+            .filter(|entry| entry.name.ends_with(".txt"))
+            .verify_then_extract(std::io::Cursor::new(&data))
+            .unwrap();
+        assert_eq!(report.files_extracted, 1);
+        assert_eq!(report.entries_skipped, 1);
+        assert!(!dest.path().join("second.bin").exists());
+    }
+}
+
+/// Test: The second entry's compressed data is corrupt
+/// Defense: ValidateFirst only reads metadata and leaves the first file
+/// behind; ValidateFirstDeep decompresses everything first and writes
+/// nothing
+#[test]
+fn test_validate_first_deep() {
+    let mut data =
+        create_data_descriptor_zip(&[("first.txt", b"hello"), ("dir/second.txt", b"world")]);
+    let second = data.windows(5).position(|w| w == b"world").unwrap();
+    data[second] ^= 0xff;
+
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .mode(ExtractionMode::ValidateFirst)
+        .extract(std::io::Cursor::new(&data));
+    assert!(result.is_err());
+    assert!(dest.path().join("first.txt").exists());
+
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .mode(ExtractionMode::ValidateFirstDeep)
+        .extract(std::io::Cursor::new(&data));
+    assert!(result.is_err(), "CRC mismatch should fail extraction");
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .validation(safe_unzip::ValidationMode::ValidateFirstDeep)
+        .extract_zip(ZipAdapter::new(std::io::Cursor::new(&data)).unwrap());
+    assert!(result.is_err(), "CRC mismatch should fail extraction");
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    // Intact archives extract as usual
+    let data = create_data_descriptor_zip(&[("first.txt", b"hello"), ("dir/second.txt", b"world")]);
+    let dest = tempdir().unwrap();
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .mode(ExtractionMode::ValidateFirstDeep)
+        .extract(std::io::Cursor::new(&data))
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .validation(safe_unzip::ValidationMode::ValidateFirstDeep)
+        .extract_zip(ZipAdapter::new(std::io::Cursor::new(&data)).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(
+        std::fs::read(dest.path().join("dir/second.txt")).unwrap(),
+        b"world"
+    );
+}
+
+#[test]
+fn test_deadline_exceeded() {
+    let dest = tempdir().unwrap();
+    let zip = create_multi_file_zip(&[("a.txt", b"a"), ("b.txt", b"b")]);
+
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .deadline(std::time::Instant::now())
+        .extract(zip);
+
+    assert!(matches!(result, Err(Error::DeadlineExceeded)));
+    assert!(!dest.path().join("a.txt").exists());
+
+    let zip = create_multi_file_zip(&[("a.txt", b"a")]);
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .deadline(std::time::Instant::now())
+        .extract_zip(ZipAdapter::new(zip).unwrap());
+
+    assert!(matches!(result, Err(Error::DeadlineExceeded)));
+    assert!(!dest.path().join("a.txt").exists());
+}
+
+#[test]
+fn test_cancel_on() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    // Already cancelled: nothing is written
+    let dest = tempdir().unwrap();
+    let zip = create_multi_file_zip(&[("a.txt", b"a"), ("b.txt", b"b")]);
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .cancel_on(Arc::new(AtomicBool::new(true)))
+        .extract(zip);
+    assert!(matches!(result, Err(Error::Cancelled)));
+    assert!(!dest.path().join("a.txt").exists());
+
+    // Cancelled while the second entry starts: the first stays
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&cancel);
+    let zip = create_multi_file_zip(&[("a.txt", b"a"), ("b.txt", b"b")]);
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .cancel_on(Arc::clone(&cancel))
+        .on_progress(move |p| {
+            if p.entry_index == 1 {
+                flag.store(true, Ordering::Relaxed);
+            }
+        })
+        .extract(zip);
+    assert!(matches!(result, Err(Error::Cancelled)));
+    assert!(dest.path().join("a.txt").exists());
+
+    // With cleanup_on_error, nothing is left behind
+    let dest = tempdir().unwrap();
+    let flag = Arc::new(AtomicBool::new(false));
+    let setter = Arc::clone(&flag);
+    let zip = create_multi_file_zip(&[("a.txt", b"a"), ("b.txt", b"b")]);
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .cancel_on(flag)
+        .cleanup_on_error(true)
+        .on_progress(move |p| {
+            if p.entry_index == 1 {
+                setter.store(true, Ordering::Relaxed);
+            }
+        })
+        .extract(zip);
+    assert!(matches!(result, Err(Error::Cancelled)));
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    let zip = create_multi_file_zip(&[("a.txt", b"a")]);
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .cancel_on(Arc::new(AtomicBool::new(true)))
+        .extract_zip(ZipAdapter::new(zip).unwrap());
+    assert!(matches!(result, Err(Error::Cancelled)));
+    assert!(!dest.path().join("a.txt").exists());
+}
+
+/// Reader that sleeps on every read, simulating a slow upload.
+struct SlowReader<R> {
+    inner: R,
+    delay: std::time::Duration,
+}
+
+impl<R: std::io::Read> std::io::Read for SlowReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        std::thread::sleep(self.delay);
+        self.inner.read(buf)
+    }
+}
+
+impl<R: std::io::Seek> std::io::Seek for SlowReader<R> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn test_deadline_mid_entry() {
+    use std::time::{Duration, Instant};
+
+    // Stored, so every 8 KiB written is 8 KiB read
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options: FileOptions<()> =
+        FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    zip.start_file("big.bin", options).unwrap();
+    zip.write_all(&vec![b'x'; 2 * 1024 * 1024]).unwrap();
+    let data = zip.finish().unwrap().into_inner();
+
+    // 256 reads of 8 KiB at 2ms each take over 500ms; stopping early
+    // proves the check runs inside the entry, not just between entries
+    let start = Instant::now();
+    let result = Extractor::new(tempdir().unwrap().path())
+        .unwrap()
+        .deadline(start + Duration::from_millis(100))
+        .extract(SlowReader {
+            inner: std::io::Cursor::new(&data),
+            delay: Duration::from_millis(2),
+        });
+    assert!(matches!(result, Err(Error::DeadlineExceeded)));
+    assert!(start.elapsed() < Duration::from_millis(400));
+
+    let start = Instant::now();
+    let result = Extractor::new(tempdir().unwrap().path())
+        .unwrap()
+        .deadline(start + Duration::from_millis(100))
+        .extract_stream(SlowReader {
+            inner: &data[..],
+            delay: Duration::from_millis(2),
+        });
+    assert!(matches!(result, Err(Error::DeadlineExceeded)));
+    assert!(start.elapsed() < Duration::from_millis(400));
+}
+
+#[test]
+fn test_decompress_cpu_budget() {
+    use std::io::Read;
+    use std::time::Duration;
+
+    let dest = tempdir().unwrap();
+    let zeros = vec![0u8; 4 * 1024 * 1024];
+
+    // Any decompression at all overruns an empty budget
+    let zip = create_multi_file_zip(&[("zeros.bin", &zeros)]);
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .max_decompress_cpu(Duration::ZERO)
+        .extract_zip(ZipAdapter::new(zip).unwrap());
+    assert!(matches!(
+        result,
+        Err(Error::DecompressBudgetExceeded { limit }) if limit == Duration::ZERO
+    ));
+
+    let zip = create_multi_file_zip(&[("zeros.bin", &zeros)]);
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .overwrite(safe_unzip::OverwriteMode::Overwrite)
+        .max_decompress_cpu(Duration::from_secs(60))
+        .extract_zip(ZipAdapter::new(zip).unwrap())
+        .unwrap();
+    assert_eq!(report.bytes_written, zeros.len() as u64);
+
+    // Streamed entries are charged for their reads as well
+    let zip = create_multi_file_zip(&[("zeros.bin", &zeros)]);
+    let driver = Driver::new(dest.path())
+        .unwrap()
+        .max_decompress_cpu(Duration::ZERO);
+    let mut stream = driver.open(ZipAdapter::new(zip).unwrap()).unwrap();
+    let (_, mut reader) = stream.next_entry().unwrap().unwrap();
+    let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+    assert!(matches!(
+        Error::from(err),
+        Error::DecompressBudgetExceeded { .. }
+    ));
+}
+
+#[test]
+fn test_report_bytes_read() {
+    let dest = tempdir().unwrap();
+    let mut zip = create_multi_file_zip(&[("a.txt", b"hello"), ("b.txt", b"world")]);
+    let archive_len = zip.seek(std::io::SeekFrom::End(0)).unwrap();
+    zip.rewind().unwrap();
+
+    let report = Extractor::new(dest.path()).unwrap().extract(zip).unwrap();
+
+    assert!(report.bytes_read >= archive_len, "{report:?}");
+}
+
+#[test]
+fn test_is_safe_file() {
+    use safe_unzip::{is_safe_file, SymlinkBehavior};
+
+    let temp = tempdir().unwrap();
+    let write = |name: &str, mut zip: std::fs::File| {
+        let path = temp.path().join(name);
+        zip.rewind().unwrap();
+        std::io::copy(&mut zip, &mut std::fs::File::create(&path).unwrap()).unwrap();
+        path
+    };
+    let good = write("good.zip", create_simple_zip("a.txt", b"a"));
+    let evil = write("evil.zip", create_malicious_zip().unwrap());
+    let many = write(
+        "many.zip",
+        create_multi_file_zip(&[("a", b"a"), ("b", b"b"), ("c", b"c")]),
+    );
+    let limits = Limits::default();
+
+    assert!(is_safe_file(&good, &limits, SymlinkBehavior::Skip).unwrap());
+    assert!(!is_safe_file(&evil, &limits, SymlinkBehavior::Skip).unwrap());
+    let tight = Limits {
+        max_file_count: 2,
+        ..Limits::default()
+    };
+    assert!(!is_safe_file(&many, &tight, SymlinkBehavior::Skip).unwrap());
+
+    // Unreadable input is an error, not a "no"
+    let missing = temp.path().join("missing.zip");
+    assert!(is_safe_file(&missing, &limits, SymlinkBehavior::Skip).is_err());
+    let garbage = temp.path().join("garbage.zip");
+    std::fs::write(&garbage, b"not a zip").unwrap();
+    assert!(is_safe_file(&garbage, &limits, SymlinkBehavior::Skip).is_err());
+}
+
+#[test]
+fn test_reject_deceptive_unicode() {
+    // RIGHT-TO-LEFT OVERRIDE makes this display as "exploitexe.jpg"
+    let name = "exploit\u{202E}gpj.exe";
+
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .reject_deceptive_unicode(true)
+        .extract(create_simple_zip(name, b"MZ"));
+    match result {
+        Err(Error::InvalidFilename { entry, reason }) => {
+            assert_eq!(entry, name);
+            assert_eq!(reason, "deceptive unicode");
+        }
+        other => panic!("expected InvalidFilename, got {:?}", other),
+    }
+    assert!(!dest.path().join(name).exists());
+
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .reject_deceptive_unicode(true)
+        .extract_zip(ZipAdapter::new(create_simple_zip(name, b"MZ")).unwrap());
+    assert!(matches!(result, Err(Error::InvalidFilename { .. })));
+
+    // Off by default
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .extract(create_simple_zip(name, b"MZ"))
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+}
+
+#[test]
+fn test_text_convert_line_endings() {
+    use safe_unzip::{LineEnding, TextConvert};
+
+    let crlf = "line\r\n".repeat(5000);
+    let mut binary = b"MZ\0\r\n".to_vec();
+    binary.extend_from_slice(crlf.as_bytes());
+    let zip = create_multi_file_zip(&[
+        ("src/main.RS", crlf.as_bytes()),
+        ("lone.txt", b"a\rb\r\n\r"),
+        ("tool.txt", &binary),
+        ("data.bin", crlf.as_bytes()),
+    ]);
+
+    let dest = tempdir().unwrap();
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .text_convert(TextConvert {
+            extensions: vec![".rs".to_string(), "txt".to_string()],
+            to: LineEnding::Lf,
+        })
+        .extract(zip)
+        .unwrap();
+
+    let lf = "line\n".repeat(5000);
+    let read = |name: &str| std::fs::read(dest.path().join(name)).unwrap();
+    assert_eq!(read("src/main.RS"), lf.as_bytes());
+    // A CR not followed by LF is kept, including one at the very end
+    assert_eq!(read("lone.txt"), b"a\rb\n\r");
+    // A NUL marks the file as binary despite its name
+    assert_eq!(read("tool.txt"), binary);
+    assert_eq!(read("data.bin"), crlf.as_bytes());
+    assert_eq!(
+        report.bytes_written,
+        (lf.len() + 5 + binary.len() + crlf.len()) as u64
+    );
+
+    // Limits apply to the converted size, which grows going to CRLF
+    let zip = create_simple_zip("notes.txt", "a\n".repeat(1000).as_bytes());
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .limits(Limits {
+            max_single_file: 2500,
+            ..Default::default()
+        })
+        .text_convert(TextConvert {
+            extensions: vec!["txt".to_string()],
+            to: LineEnding::CrLf,
+        })
+        .extract(zip);
+    assert!(
+        matches!(result, Err(Error::FileTooLarge { limit: 2500, .. })),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn test_sparse_extraction() {
+    // Zero runs before, between, and after the data, which starts and
+    // ends mid-block
+    let mut content = vec![0u8; 3 * 1024 * 1024];
+    content[1_000_003..1_005_003].fill(b'x');
+    content[2_000_000] = b'y';
+    let zip = create_simple_zip("disk.img", &content);
+
+    let dest = tempdir().unwrap();
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .sparse(true)
+        .extract(zip)
+        .unwrap();
+    assert_eq!(report.bytes_written, content.len() as u64);
+
+    let path = dest.path().join("disk.img");
+    assert_eq!(std::fs::read(&path).unwrap(), content);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        // Only filesystems that leave holes for unwritten ranges can show a
+        // saving
+        let probe = dest.path().join("probe");
+        std::fs::File::create(&probe)
+            .unwrap()
+            .set_len(1024 * 1024)
+            .unwrap();
+        if std::fs::metadata(&probe).unwrap().blocks() == 0 {
+            let allocated = std::fs::metadata(&path).unwrap().blocks() * 512;
+            assert!(allocated < 64 * 1024, "{} bytes allocated", allocated);
+        }
+    }
+}
+
+#[test]
+fn test_preserve_mtime_and_default() {
+    use std::time::{Duration, SystemTime};
+
+    let dated = |name: &str, dt: zip::DateTime| {
+        let file = tempfile::tempfile().unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        let options: FileOptions<()> = FileOptions::default().last_modified_time(dt);
+        zip.start_file(name, options).unwrap();
+        zip.write_all(b"data").unwrap();
+        zip.finish().unwrap()
+    };
+    let mtime = |path: std::path::PathBuf| std::fs::metadata(path).unwrap().modified().unwrap();
+
+    // Real timestamp is applied: 2020-06-15 12:30:00 UTC
+    let dest = tempdir().unwrap();
+    let dt = zip::DateTime::from_date_and_time(2020, 6, 15, 12, 30, 0).unwrap();
+    Extractor::new(dest.path())
+        .unwrap()
+        .preserve_mtime(true)
+        .extract(dated("a.txt", dt))
+        .unwrap();
+    assert_eq!(
+        mtime(dest.path().join("a.txt")),
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_592_224_200)
+    );
+
+    // The 1980 minimum falls back to the configured default
+    let fallback = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    Extractor::new(dest.path())
+        .unwrap()
+        .preserve_mtime(true)
+        .default_mtime(fallback)
+        .extract(dated("b.txt", zip::DateTime::default()))
+        .unwrap();
+    assert_eq!(mtime(dest.path().join("b.txt")), fallback);
+
+    // Without a default, it keeps the extraction time rather than 1980
+    let before = SystemTime::now() - Duration::from_secs(60);
+    Extractor::new(dest.path())
+        .unwrap()
+        .preserve_mtime(true)
+        .extract(dated("c.txt", zip::DateTime::default()))
+        .unwrap();
+    assert!(mtime(dest.path().join("c.txt")) > before);
+}
+
+#[test]
+fn test_preserve_mtime_directories() {
+    use std::time::{Duration, SystemTime};
+
+    // Directory entries come before the files written into them
+    let dt = zip::DateTime::from_date_and_time(2020, 6, 15, 12, 30, 0).unwrap();
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let options: FileOptions<()> = FileOptions::default().last_modified_time(dt);
+    zip.add_directory("outer/", options).unwrap();
+    zip.add_directory("outer/inner/", options).unwrap();
+    zip.start_file("outer/inner/a.txt", options).unwrap();
+    zip.write_all(b"data").unwrap();
+    zip.start_file("outer/b.txt", options).unwrap();
+    zip.write_all(b"data").unwrap();
+    let zip = zip.finish().unwrap();
+
+    let dest = tempdir().unwrap();
+    Extractor::new(dest.path())
+        .unwrap()
+        .preserve_mtime(true)
+        .extract(zip)
+        .unwrap();
+
+    let expected = SystemTime::UNIX_EPOCH + Duration::from_secs(1_592_224_200);
+    for dir in ["outer", "outer/inner"] {
+        let mtime = std::fs::metadata(dest.path().join(dir))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(mtime, expected, "{}", dir);
+    }
+}
+
+#[test]
+fn test_max_compressed_bytes() {
+    let limits = Limits {
+        max_compressed_bytes: 64,
+        ..Limits::default()
+    };
+    let mut zip = create_simple_zip("a.txt", b"hello");
+    let archive_len = zip.seek(std::io::SeekFrom::End(0)).unwrap();
+    zip.rewind().unwrap();
+    assert!(archive_len > 64);
+
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract(zip);
+    match result {
+        Err(Error::CompressedSizeExceeded { limit, size }) => {
+            assert_eq!(limit, 64);
+            assert_eq!(size, archive_len);
+        }
+        other => panic!("expected CompressedSizeExceeded, got {:?}", other),
+    }
+    assert!(!dest.path().join("a.txt").exists());
+
+    let zip = create_simple_zip("a.txt", b"hello");
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract_zip(ZipAdapter::new(zip).unwrap());
+    assert!(matches!(result, Err(Error::CompressedSizeExceeded { .. })));
+    assert!(!dest.path().join("a.txt").exists());
+}
+
+#[test]
+fn test_hardened_extractor() {
+    // Symlinks are an error rather than silently skipped
+    let file = tempfile::tempfile().unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let options: FileOptions<()> = FileOptions::default();
+    zip.start_file("a.txt", options).unwrap();
+    zip.write_all(b"hello").unwrap();
+    zip.add_symlink("link", "a.txt", options).unwrap();
+    let zip = zip.finish().unwrap();
+
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path()).unwrap().hardened().extract(zip);
+    assert!(
+        matches!(result, Err(Error::SymlinkNotAllowed { .. })),
+        "{:?}",
+        result
+    );
+    // ValidateFirst: the file before the symlink was not written either
+    assert!(!dest.path().join("a.txt").exists());
+
+    // CRCs are checked up front, so a corrupt entry stops the archive
+    // before the good one ahead of it is written
+    let mut data = create_data_descriptor_zip(&[("good.txt", b"hello"), ("bad.txt", b"world")]);
+    let bad = data.windows(5).position(|w| w == b"world").unwrap();
+    data[bad] ^= 0xff;
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .hardened()
+        .extract(std::io::Cursor::new(data));
+    assert!(result.is_err());
+    assert!(!dest.path().join("good.txt").exists());
+
+    // Strict limits apply, but later options override them
+    let big = vec![0u8; 11 * 1024 * 1024];
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .hardened()
+        .extract(create_simple_zip("big.bin", &big));
+    assert!(
+        matches!(result, Err(Error::FileTooLarge { .. })),
+        "{:?}",
+        result
+    );
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .hardened()
+        .limits(Limits::default())
+        .extract(create_simple_zip("big.bin", &big))
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+
+    // Execute bits are cleared
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file = tempfile::tempfile().unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        zip.start_file(
+            "run.sh",
+            FileOptions::<()>::default().unix_permissions(0o755),
+        )
+        .unwrap();
+        zip.write_all(b"#!/bin/sh\n").unwrap();
+        let zip = zip.finish().unwrap();
+
+        Extractor::new(dest.path())
+            .unwrap()
+            .hardened()
+            .extract(zip)
+            .unwrap();
+        let mode = std::fs::metadata(dest.path().join("run.sh"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o644);
+    }
+}
+
+#[test]
+fn test_allowed_compression_methods() {
+    use safe_unzip::CompressionMethod;
+
+    let build = || {
+        let file = tempfile::tempfile().unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        let deflated: FileOptions<()> =
+            FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let stored: FileOptions<()> =
+            FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("a.txt", deflated).unwrap();
+        zip.write_all(b"deflated").unwrap();
+        zip.start_file("b.txt", stored).unwrap();
+        zip.write_all(b"stored").unwrap();
+        zip.finish().unwrap()
+    };
+
+    // Both methods allowed
+    let dest = tempdir().unwrap();
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .allowed_compression_methods(&[CompressionMethod::Deflate, CompressionMethod::Stored])
+        .extract(build())
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+
+    // Streaming stops at the stored entry
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .allowed_compression_methods(&[CompressionMethod::Deflate])
+        .extract(build());
+    match result {
+        Err(Error::DisallowedCompression { entry, method }) => {
+            assert_eq!(entry, "b.txt");
+            assert_eq!(method, "stored");
+        }
+        other => panic!("expected DisallowedCompression, got {:?}", other),
+    }
+    assert!(dest.path().join("a.txt").exists());
+
+    // ValidateFirst rejects before writing anything
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .mode(ExtractionMode::ValidateFirst)
+        .allowed_compression_methods(&[CompressionMethod::Deflate])
+        .extract(build());
+    assert!(matches!(result, Err(Error::DisallowedCompression { .. })));
+    assert!(!dest.path().join("a.txt").exists());
+
+    assert_eq!(
+        "Deflated".parse::<CompressionMethod>().unwrap(),
+        CompressionMethod::Deflate
+    );
+    assert!("shrink".parse::<CompressionMethod>().is_err());
+}
+
+#[test]
+fn test_validate_against_manifest() {
+    use safe_unzip::{validate_against_manifest, Manifest, ManifestSizeMismatch};
+
+    let temp = tempdir().unwrap();
+    let path = temp.path().join("bundle.zip");
+    let file = std::fs::File::create(&path).unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    let options: FileOptions<()> = FileOptions::default();
+    zip.add_directory("bin/", options).unwrap();
+    zip.start_file("bin/tool", options).unwrap();
+    zip.write_all(b"#!/bin/sh\n").unwrap();
+    zip.start_file("README.md", options).unwrap();
+    zip.write_all(b"hello").unwrap();
+    zip.finish().unwrap();
+
+    // Directory entries need not be listed
+    let exact = Manifest::new().entry("bin/tool", 10).entry("README.md", 5);
+    validate_against_manifest(&path, &exact).unwrap();
+
+    let wrong: Manifest = [("bin/tool", 10), ("README.md", 6), ("LICENSE", 1)]
+        .into_iter()
+        .collect();
+    match validate_against_manifest(&path, &wrong) {
+        Err(Error::ManifestMismatch {
+            missing,
+            extra,
+            size_mismatches,
+        }) => {
+            assert_eq!(missing, ["LICENSE"]);
+            assert!(extra.is_empty());
+            assert_eq!(
+                size_mismatches,
+                [ManifestSizeMismatch {
+                    name: "README.md".into(),
+                    expected: 6,
+                    actual: 5,
+                }]
+            );
+        }
+        other => panic!("expected ManifestMismatch, got {:?}", other),
+    }
+
+    let short = Manifest::new().entry("bin/tool", 10);
+    match validate_against_manifest(&path, &short) {
+        Err(Error::ManifestMismatch { extra, .. }) => assert_eq!(extra, ["README.md"]),
+        other => panic!("expected ManifestMismatch, got {:?}", other),
+    }
+}
+
+/// Zip with three files whose EOCD record declares `declared` entries.
+fn create_tampered_count_zip(declared: u16) -> std::io::Cursor<Vec<u8>> {
+    use std::io::Read;
+
+    let mut file = create_multi_file_zip(&[("a.txt", b"a"), ("b.txt", b"b"), ("hidden.txt", b"c")]);
+    file.rewind().unwrap();
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).unwrap();
+
+    // No archive comment, so the EOCD record is the last 22 bytes
+    let eocd = bytes.len() - 22;
+    assert_eq!(&bytes[eocd..eocd + 4], b"PK\x05\x06");
+    bytes[eocd + 8..eocd + 10].copy_from_slice(&declared.to_le_bytes());
+    bytes[eocd + 10..eocd + 12].copy_from_slice(&declared.to_le_bytes());
+    std::io::Cursor::new(bytes)
+}
+
+#[test]
+fn test_eocd_entry_count_mismatch() {
+    let dest = tempdir().unwrap();
+
+    // Fewer declared than present would hide hidden.txt from the zip crate
+    let result = ZipAdapter::new(create_tampered_count_zip(2));
+    assert!(matches!(
+        result,
+        Err(Error::EntryCountMismatch {
+            declared: 2,
+            actual: 3
+        })
+    ));
+
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .extract(create_tampered_count_zip(2));
+    assert!(matches!(
+        result,
+        Err(Error::EntryCountMismatch {
+            declared: 2,
+            actual: 3
+        })
+    ));
+
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .verify(create_tampered_count_zip(4));
+    assert!(matches!(
+        result,
+        Err(Error::EntryCountMismatch {
+            declared: 4,
+            actual: 3
+        })
+    ));
+    assert!(!dest.path().join("a.txt").exists());
+
+    // The untampered archive still opens
+    assert_eq!(
+        ZipAdapter::new(create_tampered_count_zip(3)).unwrap().len(),
+        3
+    );
+}
+
+#[test]
+fn test_extract_entry_to() {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options: FileOptions<()> = FileOptions::default();
+    zip.start_file("app/config.toml", options).unwrap();
+    zip.write_all(b"port = 8080\n").unwrap();
+    zip.start_file("big.bin", options).unwrap();
+    zip.write_all(&[7u8; 4096]).unwrap();
+    zip.add_directory("app/data", options).unwrap();
+    zip.add_symlink("app/link", "config.toml", options).unwrap();
+    zip.start_file("../escape.txt", options).unwrap();
+    zip.write_all(b"x").unwrap();
+    let data = zip.finish().unwrap().into_inner();
+
+    let dest = tempdir().unwrap();
+    let extractor = Extractor::new(dest.path()).unwrap();
+    let extract = |extractor: &Extractor, name: &str| {
+        let mut out = Vec::new();
+        extractor
+            .extract_entry_to(std::io::Cursor::new(&data), name, &mut out)
+            .map(|written| (written, out))
+    };
+
+    let (written, out) = extract(&extractor, "app/config.toml").unwrap();
+    assert_eq!(written, 12);
+    assert_eq!(out, b"port = 8080\n");
+    // Nothing lands on disk
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    assert!(matches!(
+        extract(&extractor, "app/missing.toml"),
+        Err(Error::EntryNotFound { ref name }) if name == "app/missing.toml"
+    ));
+    assert!(matches!(
+        extract(&extractor, "app/data/"),
+        Err(Error::NotAFile { ref kind, .. }) if kind == "directory"
+    ));
+    assert!(matches!(
+        extract(&extractor, "app/link"),
+        Err(Error::NotAFile { ref kind, .. }) if kind == "symlink"
+    ));
+    assert!(matches!(
+        extract(&extractor, "../escape.txt"),
+        Err(Error::PathEscape { .. })
+    ));
+
+    let small = Extractor::new(dest.path()).unwrap().limits(Limits {
+        max_single_file: 1024,
+        ..Default::default()
+    });
+    assert!(matches!(
+        extract(&small, "big.bin"),
+        Err(Error::FileTooLarge { .. })
+    ));
+}
+
+#[test]
+fn test_extract_stream() {
+    use std::io::Read;
+
+    fn bytes(mut file: std::fs::File) -> Vec<u8> {
+        let mut data = Vec::new();
+        file.seek(std::io::SeekFrom::Start(0)).unwrap();
+        file.read_to_end(&mut data).unwrap();
+        data
+    }
+
+    // `&[u8]` is Read but not Seek, like a socket
+    let data = bytes(create_multi_file_zip(&[
+        ("a.txt", b"aaa"),
+        ("skip.txt", b"skipped"),
+        ("dir/", b""),
+        ("dir/b.txt", b"bb"),
+    ]));
+    let dest = tempdir().unwrap();
+    let report = Extractor::new(dest.path())
+        .unwrap()
+        .filter(|entry| entry.name != "skip.txt")
+        .extract_stream(&data[..])
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(report.dirs_created, 1);
+    assert_eq!(report.entries_skipped, 1);
+    assert!(!report.was_empty);
+    assert_eq!(std::fs::read(dest.path().join("a.txt")).unwrap(), b"aaa");
+    assert_eq!(std::fs::read(dest.path().join("dir/b.txt")).unwrap(), b"bb");
+    assert!(!dest.path().join("skip.txt").exists());
+
+    // Path checks apply per entry
+    let data = bytes(create_malicious_zip().unwrap());
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .extract_stream(&data[..]);
+    assert!(matches!(result, Err(Error::PathEscape { .. })));
+
+    // A local header understating the size is caught during the copy
+    let content = vec![b'a'; 100_000];
+    let data = bytes(create_lying_deflate_zip("bomb.txt", &content, 10));
+    let dest = tempdir().unwrap();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .extract_stream(&data[..]);
+    assert!(matches!(
+        result,
+        Err(Error::SizeMismatch {
+            declared: 10,
+            actual: 11,
+            ..
+        })
+    ));
+    assert!(!dest.path().join("bomb.txt").exists());
+
+    let data = bytes(create_simple_zip("big.bin", &content));
+    let result = Extractor::new(tempdir().unwrap().path())
+        .unwrap()
+        .limits(Limits {
+            max_single_file: 1024,
+            ..Default::default()
+        })
+        .extract_stream(&data[..]);
+    assert!(matches!(result, Err(Error::FileTooLarge { .. })));
+
+    // ValidateFirst would need to read the input twice
+    let result = Extractor::new(tempdir().unwrap().path())
+        .unwrap()
+        .mode(ExtractionMode::ValidateFirst)
+        .extract_stream(&data[..]);
+    assert!(matches!(result, Err(Error::InvalidOption { .. })));
+
+    // Sizes only known from a data descriptor can't be streamed
+    let data = create_data_descriptor_zip(&[("a.txt", b"aaa")]);
+    let result = Extractor::new(tempdir().unwrap().path())
+        .unwrap()
+        .extract_stream(&data[..]);
+    assert!(matches!(result, Err(Error::Zip(_))));
+
+    let data = bytes(
+        zip::ZipWriter::new(tempfile::tempfile().unwrap())
+            .finish()
+            .unwrap(),
+    );
+    let report = Extractor::new(tempdir().unwrap().path())
+        .unwrap()
+        .extract_stream(&data[..])
+        .unwrap();
+    assert!(report.was_empty);
+    assert_eq!(report.files_extracted, 0);
+}
+
+#[test]
+fn test_extract_to_memory() {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options: FileOptions<()> = FileOptions::default();
+    zip.add_directory("app/", options).unwrap();
+    zip.start_file("app/config.toml", options).unwrap();
+    zip.write_all(b"port = 8080\n").unwrap();
+    zip.start_file("readme.txt", options).unwrap();
+    zip.write_all(b"hello").unwrap();
+    zip.add_symlink("app/link", "config.toml", options).unwrap();
+    let data = zip.finish().unwrap().into_inner();
+
+    let dest = tempdir().unwrap();
+    let files = Extractor::new(dest.path())
+        .unwrap()
+        .extract_to_memory(std::io::Cursor::new(&data))
+        .unwrap();
+    // Directories and symlinks are left out
+    assert_eq!(files.len(), 2);
+    assert_eq!(files["app/config.toml"], b"port = 8080\n");
+    assert_eq!(files["readme.txt"], b"hello");
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .symlinks(safe_unzip::SymlinkPolicy::Error)
+        .extract_to_memory(std::io::Cursor::new(&data));
+    assert!(matches!(result, Err(Error::SymlinkNotAllowed { .. })));
+
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .limits(Limits {
+            max_total_bytes: 15,
+            ..Default::default()
+        })
+        .extract_to_memory(std::io::Cursor::new(&data));
+    assert!(matches!(result, Err(Error::TotalSizeExceeded { .. })));
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file("../escape.txt", options).unwrap();
+    zip.write_all(b"x").unwrap();
+    let data = zip.finish().unwrap().into_inner();
+    let result = Extractor::new(dest.path())
+        .unwrap()
+        .extract_to_memory(std::io::Cursor::new(&data));
+    assert!(matches!(result, Err(Error::PathEscape { .. })));
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_extraction_plan() {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options: FileOptions<()> = FileOptions::default();
+    zip.add_directory("docs/", options).unwrap();
+    zip.start_file("docs/guide.md", options).unwrap();
+    zip.write_all(b"# Guide\n").unwrap();
+    zip.start_file("docs/big.log", options).unwrap();
+    zip.write_all(&[b'x'; 1000]).unwrap();
+    zip.start_file("README.md", options).unwrap();
+    zip.write_all(b"hello").unwrap();
+    zip.add_symlink("docs/latest", "guide.md", options).unwrap();
+    let data = zip.finish().unwrap().into_inner();
+
+    let dest = tempdir().unwrap();
+    let extractor = Extractor::new(dest.path())
+        .unwrap()
+        .filter(|entry| !entry.name.ends_with(".log"));
+    let plan = extractor.plan(std::io::Cursor::new(&data)).unwrap();
+    assert_eq!(plan.total_files, 2);
+    assert_eq!(plan.total_bytes, 13);
+    assert_eq!(plan.total_dirs, 1);
+    // Skipped by the default symlink policy
+    assert_eq!(plan.symlink_count, 0);
+    assert_eq!(plan.skipped_by_filter, 1);
+    let names: Vec<_> = plan.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["docs/", "docs/guide.md", "README.md"]);
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    // The plan matches what extraction then does
+    let report = extractor.extract(std::io::Cursor::new(&data)).unwrap();
+    assert_eq!(report.files_extracted, plan.total_files);
+    assert_eq!(report.bytes_written, plan.total_bytes);
+    assert_eq!(report.dirs_created, plan.total_dirs);
+
+    #[cfg(unix)]
+    {
+        let plan = Extractor::new(dest.path())
+            .unwrap()
+            .symlinks(safe_unzip::SymlinkPolicy::Allow)
+            .plan(std::io::Cursor::new(&data))
+            .unwrap();
+        assert_eq!(plan.symlink_count, 1);
+        assert!(matches!(
+            plan.entries.last().unwrap().kind,
+            safe_unzip::EntryKind::Symlink { ref target } if target == "guide.md"
+        ));
+    }
+
+    // Limits apply to what the filter lets through
+    let limited = |max_total_bytes| {
+        Extractor::new(dest.path())
+            .unwrap()
+            .filter(|entry| !entry.name.ends_with(".log"))
+            .limits(Limits {
+                max_total_bytes,
+                ..Default::default()
+            })
+            .plan(std::io::Cursor::new(&data))
+    };
+    assert!(limited(13).is_ok());
+    assert!(matches!(limited(12), Err(Error::TotalSizeExceeded { .. })));
+}
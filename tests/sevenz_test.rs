@@ -0,0 +1,268 @@
+//! Tests for 7z archive extraction.
+#![cfg(feature = "sevenz")]
+
+use safe_unzip::{Driver, Error, SevenZAdapter, ValidationMode};
+use sevenz_rust::{AesEncoderOptions, SevenZArchiveEntry, SevenZMethod, SevenZWriter};
+use tempfile::tempdir;
+
+/// Create a 7z archive (LZMA2) with the given files.
+fn create_7z(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = SevenZWriter::new(std::io::Cursor::new(Vec::new())).unwrap();
+    for (name, content) in files {
+        let mut entry = SevenZArchiveEntry::new();
+        entry.name = name.to_string();
+        entry.has_stream = true;
+        writer.push_archive_entry(entry, Some(*content)).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+/// Create a 7z archive with AES-encrypted contents, and headers too if
+/// `encrypt_header`.
+fn create_encrypted_7z(
+    path: &std::path::Path,
+    files: &[(&str, &[u8])],
+    password: &str,
+    encrypt_header: bool,
+) {
+    let mut writer = SevenZWriter::create(path).unwrap();
+    writer.set_content_methods(vec![
+        AesEncoderOptions::new(password.into()).into(),
+        SevenZMethod::LZMA2.into(),
+    ]);
+    writer.set_encrypt_header(encrypt_header);
+    for (name, content) in files {
+        let mut entry = SevenZArchiveEntry::new();
+        entry.name = name.to_string();
+        entry.has_stream = true;
+        writer.push_archive_entry(entry, Some(*content)).unwrap();
+    }
+    writer.finish().unwrap();
+}
+
+#[test]
+fn test_7z_basic_extraction() {
+    let dest = tempdir().unwrap();
+    let data = create_7z(&[("a.txt", b"hello"), ("dir/b.txt", b"world")]);
+
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_7z_bytes(&data)
+        .unwrap();
+
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(
+        std::fs::read_to_string(dest.path().join("dir/b.txt")).unwrap(),
+        "world"
+    );
+}
+
+#[test]
+fn test_7z_peek_entry() {
+    let data = create_7z(&[("a.txt", b"hello world"), ("b.txt", b"xy")]);
+    let adapter = SevenZAdapter::from_bytes(&data).unwrap();
+
+    assert_eq!(adapter.peek_entry(0, 5).unwrap(), b"hello");
+    assert_eq!(adapter.peek_entry(1, 512).unwrap(), b"xy");
+    assert!(adapter.peek_entry(2, 5).is_err());
+}
+
+#[test]
+fn test_list_7z_entries() {
+    let data = create_7z(&[("a.txt", b"hello"), ("dir/b.txt", b"world!")]);
+
+    let summary = |entries: Vec<safe_unzip::EntryInfo>| -> Vec<(String, u64)> {
+        entries.into_iter().map(|e| (e.name, e.size)).collect()
+    };
+    let expected = vec![("a.txt".to_string(), 5), ("dir/b.txt".to_string(), 6)];
+
+    assert_eq!(summary(safe_unzip::list_7z_bytes(&data).unwrap()), expected);
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("archive.7z");
+    std::fs::write(&path, &data).unwrap();
+    assert_eq!(
+        summary(safe_unzip::list_7z_entries(&path).unwrap()),
+        expected
+    );
+
+    // Header sizes agree with what decompression produces
+    let adapter = SevenZAdapter::from_bytes(&data).unwrap();
+    assert_eq!(summary(adapter.entries_metadata().unwrap()), expected);
+}
+
+#[test]
+fn test_7z_max_decode_memory() {
+    let data = create_7z(&[("a.txt", b"hello")]);
+
+    // Any LZMA2 dictionary is at least 4 KB
+    let dest = tempdir().unwrap();
+    let adapter = SevenZAdapter::from_bytes(&data)
+        .unwrap()
+        .max_decode_memory(1024);
+    let result = Driver::new(dest.path()).unwrap().extract_7z(adapter);
+    assert!(matches!(
+        result,
+        Err(Error::DecodeMemoryExceeded { limit: 1024, .. })
+    ));
+    assert!(!dest.path().join("a.txt").exists());
+
+    let adapter = SevenZAdapter::from_bytes(&data)
+        .unwrap()
+        .max_decode_memory(1024 * 1024 * 1024);
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_7z(adapter)
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+}
+
+#[test]
+fn test_7z_encrypted() {
+    let dir = tempdir().unwrap();
+    let files: &[(&str, &[u8])] = &[("secret.txt", b"top secret")];
+
+    // Encrypted contents, readable headers: the entry is named
+    let path = dir.path().join("contents.7z");
+    create_encrypted_7z(&path, files, "hunter2", false);
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path()).unwrap().extract_7z_file(&path);
+    assert!(
+        matches!(result, Err(Error::EncryptedEntry { ref entry }) if entry == "secret.txt"),
+        "{result:?}"
+    );
+    assert!(!dest.path().join("secret.txt").exists());
+
+    // Encrypted headers: only the archive can be named. The writer keeps
+    // small headers raw, so pad the listing until it compresses
+    let names: Vec<_> = (0..64).map(|i| format!("padding/entry-{i:02}")).collect();
+    let mut padded = files.to_vec();
+    padded.extend(names.iter().map(|n| (n.as_str(), &b""[..])));
+    let path = dir.path().join("headers.7z");
+    create_encrypted_7z(&path, &padded, "hunter2", true);
+    let result = SevenZAdapter::open(&path);
+    assert!(
+        matches!(result, Err(Error::EncryptedEntry { ref entry }) if entry == "headers.7z"),
+        "{:?}",
+        result.err()
+    );
+    let result = SevenZAdapter::from_bytes(&std::fs::read(&path).unwrap());
+    assert!(matches!(result, Err(Error::EncryptedEntry { .. })));
+
+    // With the password, both extract
+    for name in ["contents.7z", "headers.7z"] {
+        let dest = tempdir().unwrap();
+        let adapter = SevenZAdapter::open_with_password(dir.path().join(name), "hunter2").unwrap();
+        let report = Driver::new(dest.path())
+            .unwrap()
+            .extract_7z(adapter)
+            .unwrap();
+        assert!(report.files_extracted >= 1);
+        assert_eq!(
+            std::fs::read(dest.path().join("secret.txt")).unwrap(),
+            b"top secret"
+        );
+    }
+
+    // Limits still apply to decrypted entries
+    let dest = tempdir().unwrap();
+    let adapter =
+        SevenZAdapter::open_with_password(dir.path().join("contents.7z"), "hunter2").unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .limits(safe_unzip::Limits {
+            max_single_file: 4,
+            ..Default::default()
+        })
+        .extract_7z(adapter);
+    assert!(matches!(result, Err(Error::FileTooLarge { .. })));
+}
+
+#[test]
+fn test_7z_limits_during_decompression() {
+    use safe_unzip::Limits;
+
+    let big = vec![0u8; 1024 * 1024];
+    let data = create_7z(&[("small.txt", b"hello"), ("big.bin", &big)]);
+
+    // The adapter stops decompressing instead of buffering the whole entry
+    let adapter = SevenZAdapter::from_bytes(&data).unwrap().limits(Limits {
+        max_single_file: 64 * 1024,
+        ..Default::default()
+    });
+    let result = adapter.entries_metadata();
+    assert!(
+        matches!(result, Err(Error::FileTooLarge { ref entry, limit: 65536, .. }) if entry == "big.bin"),
+        "{:?}",
+        result.err()
+    );
+
+    let adapter = SevenZAdapter::from_bytes(&data).unwrap().limits(Limits {
+        max_total_bytes: 512 * 1024,
+        ..Default::default()
+    });
+    assert!(matches!(
+        adapter.entries_metadata(),
+        Err(Error::TotalSizeExceeded { limit: 524288, .. })
+    ));
+
+    // The driver passes its own limits down when it caches entries
+    let dest = tempdir().unwrap();
+    let adapter = SevenZAdapter::from_bytes(&data).unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .limits(Limits {
+            max_single_file: 64 * 1024,
+            ..Default::default()
+        })
+        .validation(ValidationMode::ValidateFirst)
+        .extract_7z(adapter);
+    assert!(matches!(result, Err(Error::FileTooLarge { .. })));
+    assert!(!dest.path().join("small.txt").exists());
+}
+
+#[test]
+fn test_7z_streams_large_entry() {
+    // Streamed, the entry never sits in memory whole. Kept to 8 MiB since
+    // the LZMA encoder is slow in debug builds
+    let big = vec![0u8; 8 * 1024 * 1024];
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("big.7z");
+    std::fs::write(&path, create_7z(&[("a.txt", b"hello"), ("big.bin", &big)])).unwrap();
+    drop(big);
+
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_7z_file(&path)
+        .unwrap();
+
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(report.bytes_written, 8 * 1024 * 1024 + 5);
+    assert_eq!(
+        std::fs::metadata(dest.path().join("big.bin"))
+            .unwrap()
+            .len(),
+        8 * 1024 * 1024
+    );
+    assert_eq!(std::fs::read(dest.path().join("a.txt")).unwrap(), b"hello");
+
+    // Callers can stream through the adapter directly
+    let mut adapter = SevenZAdapter::open(&path).unwrap();
+    let mut sizes = Vec::new();
+    adapter
+        .extract_each(|info, reader| {
+            let read = std::io::copy(reader, &mut std::io::sink())?;
+            sizes.push((info.name.clone(), read));
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(
+        sizes,
+        [
+            ("a.txt".to_string(), 5),
+            ("big.bin".to_string(), 8 * 1024 * 1024)
+        ]
+    );
+    assert_eq!(adapter.bytes_read(), adapter.archive_len());
+}
@@ -1,8 +1,11 @@
 //! Tests for TAR archive extraction.
 #![cfg(feature = "tar")]
 
-use safe_unzip::{Driver, Limits, TarAdapter, ValidationMode};
+use safe_unzip::{
+    Driver, Error, Limits, Order, SkippedUnsupported, TarAdapter, UnsupportedPolicy, ValidationMode,
+};
 use std::io::Write;
+use std::sync::{Arc, Mutex};
 use tempfile::tempdir;
 
 /// Create a simple tar archive with one file.
@@ -198,6 +201,36 @@ fn test_tar_validate_first_mode() {
     println!("✅ TAR ValidateFirst mode works");
 }
 
+#[test]
+fn test_tar_reject_duplicate_entries() {
+    let tar_data = create_multi_file_tar(&[("a.txt", b"first"), ("./a.txt", b"second")]);
+
+    // Off by default: the second entry runs into the first
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)));
+    assert!(matches!(result, Err(Error::AlreadyExists { .. })));
+
+    for mode in [ValidationMode::Streaming, ValidationMode::ValidateFirst] {
+        let dest = tempdir().unwrap();
+        let result = Driver::new(dest.path())
+            .unwrap()
+            .validation(mode)
+            .reject_duplicate_entries(true)
+            .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)));
+        match result {
+            Err(Error::DuplicateEntry { entry }) => assert_eq!(entry, "a.txt"),
+            r => panic!("expected DuplicateEntry, got {:?}", r),
+        }
+        // Streaming has written the first entry by then
+        assert_eq!(
+            dest.path().join("a.txt").exists(),
+            mode == ValidationMode::Streaming
+        );
+    }
+}
+
 #[test]
 fn test_tar_filter() {
     let dest = tempdir().unwrap();
@@ -354,6 +387,81 @@ fn test_tar_rejects_fifo() {
     println!("✅ TAR rejects FIFO");
 }
 
+#[test]
+fn test_tar_unsupported_entries_skip() {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (name, entry_type) in [
+        ("dev/null", tar::EntryType::Char),
+        ("my_pipe", tar::EntryType::Fifo),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_path(name).unwrap();
+        header.set_size(0);
+        header.set_mode(0o644);
+        header.set_entry_type(entry_type);
+        header.set_cksum();
+        builder.append(&header, &[][..]).unwrap();
+    }
+    let mut header = tar::Header::new_gnu();
+    header.set_path("etc/hosts").unwrap();
+    header.set_size(9);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append(&header, &b"127.0.0.1"[..]).unwrap();
+    let tar_data = builder.into_inner().unwrap();
+
+    for mode in [ValidationMode::Streaming, ValidationMode::ValidateFirst] {
+        let dest = tempdir().unwrap();
+        let report = Driver::new(dest.path())
+            .unwrap()
+            .validation(mode)
+            .unsupported_entries(UnsupportedPolicy::Skip)
+            .extract_tar(TarAdapter::new(std::io::Cursor::new(tar_data.clone())))
+            .unwrap();
+
+        assert_eq!(report.files_extracted, 1);
+        assert_eq!(report.entries_skipped, 2);
+        assert_eq!(
+            report.skipped_unsupported,
+            vec![
+                SkippedUnsupported {
+                    name: "dev/null".into(),
+                    entry_type: "character device".into(),
+                },
+                SkippedUnsupported {
+                    name: "my_pipe".into(),
+                    entry_type: "fifo (named pipe)".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            std::fs::read(dest.path().join("etc/hosts")).unwrap(),
+            b"127.0.0.1"
+        );
+        assert!(!dest.path().join("my_pipe").exists());
+        assert!(!dest.path().join("dev/null").exists());
+    }
+
+    // Only unsupported entries: not empty, nothing extracted
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_path("my_pipe").unwrap();
+    header.set_size(0);
+    header.set_entry_type(tar::EntryType::Fifo);
+    header.set_cksum();
+    builder.append(&header, &[][..]).unwrap();
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .unsupported_entries(UnsupportedPolicy::Skip)
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(
+            builder.into_inner().unwrap(),
+        )))
+        .unwrap();
+    assert!(!report.was_empty);
+    assert_eq!(report.files_extracted, 0);
+}
+
 #[test]
 fn test_tar_blocks_absolute_path() {
     let dest = tempdir().unwrap();
@@ -575,6 +683,114 @@ fn test_tar_single_file_size_limit() {
     println!("✅ TAR single file size limit works");
 }
 
+#[test]
+fn test_tar_dir_count_limit() {
+    let mut builder = tar::Builder::new(Vec::new());
+    for i in 0..1000 {
+        let mut header = tar::Header::new_gnu();
+        header.set_path(format!("d{}/", i)).unwrap();
+        header.set_size(0);
+        header.set_mode(0o755);
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_cksum();
+        builder.append(&header, &[][..]).unwrap();
+    }
+    let tar_data = builder.into_inner().unwrap();
+
+    let limits = Limits {
+        max_dir_count: 100,
+        ..Default::default()
+    };
+    for mode in [ValidationMode::Streaming, ValidationMode::ValidateFirst] {
+        let dest = tempdir().unwrap();
+        let result = Driver::new(dest.path())
+            .unwrap()
+            .limits(limits)
+            .validation(mode)
+            .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)));
+        assert!(
+            matches!(
+                result,
+                Err(Error::DirCountExceeded {
+                    limit: 100,
+                    attempted: 101
+                })
+            ),
+            "{:?}",
+            result
+        );
+        let created = std::fs::read_dir(dest.path()).unwrap().count();
+        match mode {
+            ValidationMode::Streaming => assert_eq!(created, 100),
+            _ => assert_eq!(created, 0),
+        }
+    }
+
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)))
+        .unwrap();
+    assert_eq!(report.dirs_created, 1000);
+}
+
+#[test]
+fn test_tar_entry_count_limit() {
+    let tar_data = create_multi_file_tar(&[
+        ("a.txt", b"a"),
+        ("b.txt", b"b"),
+        ("c.txt", b"c"),
+        ("d.txt", b"d"),
+    ]);
+    let limits = Limits {
+        max_entries: 2,
+        ..Default::default()
+    };
+
+    // Streaming stops when the third entry is reached
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)));
+    assert!(
+        matches!(
+            result,
+            Err(Error::EntryCountExceeded {
+                limit: 2,
+                attempted: 3
+            })
+        ),
+        "{:?}",
+        result
+    );
+    assert!(dest.path().join("b.txt").exists());
+    assert!(!dest.path().join("c.txt").exists());
+
+    // ValidateFirst stops caching there too, before the garbage where the
+    // fourth header should be
+    let mut truncated = tar_data[..3 * 1024].to_vec();
+    truncated.extend_from_slice(&[0xff; 512]);
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .limits(limits)
+        .validation(ValidationMode::ValidateFirst)
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&truncated)));
+    assert!(
+        matches!(
+            result,
+            Err(Error::EntryCountExceeded {
+                limit: 2,
+                attempted: 3
+            })
+        ),
+        "{:?}",
+        result
+    );
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+}
+
 #[test]
 fn test_tar_file_count_limit() {
     let dest = tempdir().unwrap();
@@ -688,3 +904,1397 @@ fn test_tar_hard_link_treated_as_symlink() {
 
     println!("✅ TAR hard link handled as symlink");
 }
+
+#[test]
+fn test_tar_extract_order_sorted() {
+    let dest = tempdir().unwrap();
+    let tar_data = create_multi_file_tar(&[("z.txt", b"z"), ("m.txt", b"m"), ("a.txt", b"a")]);
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let record = Arc::clone(&seen);
+
+    let adapter = TarAdapter::new(std::io::Cursor::new(tar_data));
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .validation(ValidationMode::ValidateFirst)
+        .extract_order(Order::SortedByName)
+        .filter(move |info| {
+            record.lock().unwrap().push(info.name.clone());
+            true
+        })
+        .extract_tar(adapter)
+        .unwrap();
+
+    assert_eq!(report.files_extracted, 3);
+    assert_eq!(*seen.lock().unwrap(), ["a.txt", "m.txt", "z.txt"]);
+}
+
+#[test]
+fn test_tar_extract_order_sorted_rejects_streaming() {
+    let dest = tempdir().unwrap();
+    let tar_data = create_simple_tar("a.txt", b"a");
+
+    let adapter = TarAdapter::new(std::io::Cursor::new(tar_data));
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .extract_order(Order::SortedByName)
+        .extract_tar(adapter);
+
+    assert!(matches!(result, Err(Error::InvalidOption { .. })));
+    assert!(!dest.path().join("a.txt").exists());
+}
+
+#[test]
+fn test_tar_report_was_empty() {
+    // A tar of nothing but end-of-archive padding
+    let tar_data = tar::Builder::new(Vec::new()).into_inner().unwrap();
+
+    for mode in [ValidationMode::Streaming, ValidationMode::ValidateFirst] {
+        let dest = tempdir().unwrap();
+        let adapter = TarAdapter::new(std::io::Cursor::new(tar_data.clone()));
+        let report = Driver::new(dest.path())
+            .unwrap()
+            .validation(mode)
+            .extract_tar(adapter)
+            .unwrap();
+        assert!(report.was_empty);
+    }
+
+    let dest = tempdir().unwrap();
+    let adapter = TarAdapter::new(std::io::Cursor::new(create_simple_tar("a.txt", b"a")));
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_tar(adapter)
+        .unwrap();
+    assert!(!report.was_empty);
+}
+
+/// Create a tar archive of symlinks.
+#[cfg(unix)]
+fn create_symlink_tar(links: &[(&str, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (name, target) in links {
+        let mut header = tar::Header::new_gnu();
+        header.set_path(name).unwrap();
+        header.set_size(0);
+        header.set_mode(0o777);
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_link_name(target).unwrap();
+        header.set_cksum();
+        builder.append(&header, &[][..]).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_tar_symlink_allow_contained() {
+    use safe_unzip::{SymlinkBehavior, SymlinkRewrite};
+
+    let dest = tempdir().unwrap();
+    let absolute = dest.path().join("opt/tool");
+    let tar_data = create_symlink_tar(&[
+        ("lib/libfoo.so", "libfoo.so.1"),
+        ("bin/tool", absolute.to_str().unwrap()),
+    ]);
+
+    let adapter = TarAdapter::new(std::io::Cursor::new(tar_data.clone()));
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .symlinks(SymlinkBehavior::AllowContained)
+        .extract_tar(adapter)
+        .unwrap();
+
+    assert_eq!(report.symlinks_created, 2);
+    let read_link = |name: &str| std::fs::read_link(dest.path().join(name)).unwrap();
+    assert_eq!(
+        read_link("lib/libfoo.so"),
+        std::path::Path::new("libfoo.so.1")
+    );
+    assert_eq!(read_link("bin/tool"), absolute);
+
+    // Relativized, the tree no longer depends on where it was extracted
+    let dest = tempdir().unwrap();
+    let absolute = dest.path().join("opt/tool");
+    let tar_data = create_symlink_tar(&[("bin/tool", absolute.to_str().unwrap())]);
+    let adapter = TarAdapter::new(std::io::Cursor::new(tar_data));
+    Driver::new(dest.path())
+        .unwrap()
+        .symlinks(SymlinkBehavior::AllowContained)
+        .symlink_rewrite(SymlinkRewrite::RelativizeContained)
+        .extract_tar(adapter)
+        .unwrap();
+
+    assert_eq!(
+        std::fs::read_link(dest.path().join("bin/tool")).unwrap(),
+        std::path::Path::new("../opt/tool")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_tar_symlink_allow_contained_rejects_escapes() {
+    use safe_unzip::SymlinkBehavior;
+
+    let cases: &[&[(&str, &str)]] = &[
+        &[("link", "/etc/passwd")],
+        &[("sub/link", "../../outside")],
+        // `lnk/..` is the destination lexically but its parent on disk
+        &[("lnk", "."), ("evil", "lnk/..")],
+        // A link created later at `future` could redirect `future/..`
+        &[("evil", "future/../x")],
+    ];
+
+    for links in cases {
+        let dest = tempdir().unwrap();
+        let adapter = TarAdapter::new(std::io::Cursor::new(create_symlink_tar(links)));
+        let result = Driver::new(dest.path())
+            .unwrap()
+            .symlinks(SymlinkBehavior::AllowContained)
+            .extract_tar(adapter);
+
+        assert!(
+            matches!(result, Err(Error::PathEscape { .. })),
+            "{:?} should be rejected, got {:?}",
+            links,
+            result
+        );
+        assert!(!dest.path().join("evil").exists());
+    }
+}
+
+#[test]
+fn test_tar_peek_entry_leaves_content_readable() {
+    use safe_unzip::ArchiveReader;
+    use std::io::Read;
+
+    let data = create_multi_file_tar(&[("a.txt", b"hello world"), ("b.txt", b"second")]);
+    let mut adapter = TarAdapter::new(std::io::Cursor::new(data));
+
+    assert_eq!(adapter.next_entry().unwrap().unwrap().name, "a.txt");
+    assert_eq!(adapter.peek_entry(5).unwrap(), b"hello");
+    // A longer peek continues where the last one stopped
+    assert_eq!(adapter.peek_entry(512).unwrap(), b"hello world");
+    let mut content = String::new();
+    adapter
+        .entry_reader()
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "hello world");
+
+    // Peeking without reading does not disturb the next entry
+    assert_eq!(adapter.next_entry().unwrap().unwrap().name, "b.txt");
+    assert_eq!(adapter.peek_entry(3).unwrap(), b"sec");
+    assert!(adapter.next_entry().is_none());
+}
+
+#[test]
+fn test_tar_symlink_empty_target_rejected() {
+    use safe_unzip::SymlinkBehavior;
+
+    // `set_link_name` refuses an empty target; a fresh header has none
+    let mut header = tar::Header::new_gnu();
+    header.set_path("link").unwrap();
+    header.set_size(0);
+    header.set_entry_type(tar::EntryType::Symlink);
+    header.set_cksum();
+    let mut builder = tar::Builder::new(Vec::new());
+    builder.append(&header, &[][..]).unwrap();
+    let tar_data = builder.into_inner().unwrap();
+
+    for behavior in [
+        SymlinkBehavior::AllowContained,
+        SymlinkBehavior::ContainedOrSkip,
+    ] {
+        let dest = tempdir().unwrap();
+        let adapter = TarAdapter::new(std::io::Cursor::new(&tar_data));
+        let result = Driver::new(dest.path())
+            .unwrap()
+            .symlinks(behavior)
+            .extract_tar(adapter);
+        assert!(
+            matches!(result, Err(Error::InvalidFilename { ref reason, .. }) if reason == "empty symlink target"),
+            "{:?}: got {:?}",
+            behavior,
+            result
+        );
+        assert!(std::fs::symlink_metadata(dest.path().join("link")).is_err());
+
+        // Streaming gives the same answer without creating anything
+        let adapter = TarAdapter::new(std::io::Cursor::new(&tar_data));
+        let driver = Driver::new(dest.path()).unwrap().symlinks(behavior);
+        let mut stream = driver.open(adapter).unwrap();
+        assert!(matches!(
+            stream.next_entry(),
+            Some(Err(Error::InvalidFilename { .. }))
+        ));
+    }
+
+    // Skipped like any other symlink when symlinks aren't created
+    let dest = tempdir().unwrap();
+    let adapter = TarAdapter::new(std::io::Cursor::new(&tar_data));
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_tar(adapter)
+        .unwrap();
+    assert_eq!(report.entries_skipped, 1);
+}
+
+/// Reader that sleeps on every read, simulating a slow upload.
+struct SlowReader<R> {
+    inner: R,
+    delay: std::time::Duration,
+}
+
+impl<R: std::io::Read> std::io::Read for SlowReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        std::thread::sleep(self.delay);
+        self.inner.read(buf)
+    }
+}
+
+#[test]
+fn test_tar_deadline_mid_entry() {
+    let dest = tempdir().unwrap();
+    let content = vec![b'x'; 2 * 1024 * 1024];
+    let tar_data = create_simple_tar("big.bin", &content);
+    let reader = SlowReader {
+        inner: std::io::Cursor::new(tar_data),
+        delay: std::time::Duration::from_millis(2),
+    };
+
+    let start = std::time::Instant::now();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .deadline(start + std::time::Duration::from_millis(100))
+        .extract_tar(TarAdapter::new(reader));
+
+    assert!(matches!(result, Err(Error::DeadlineExceeded)));
+    // 256 reads of 8 KiB at 2ms each take over 500ms; stopping early
+    // proves the check runs inside the entry, not just between entries.
+    assert!(start.elapsed() < std::time::Duration::from_millis(400));
+}
+
+#[test]
+fn test_tar_report_bytes_read() {
+    let dest = tempdir().unwrap();
+    let tar_data = create_multi_file_tar(&[("a.txt", b"hello"), ("b.txt", &[b'x'; 2000])]);
+
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)))
+        .unwrap();
+
+    // Everything up to the end-of-archive marker is read; the final
+    // zero-filled block may be left unread.
+    assert!(
+        report.bytes_read >= tar_data.len() as u64 - 512,
+        "{report:?}"
+    );
+    assert!(report.bytes_read <= tar_data.len() as u64);
+
+    // A stream cut off before the end-of-archive marker still extracts,
+    // but reads less than was expected
+    let truncated = &tar_data[..tar_data.len() - 1024];
+    let report = Driver::new(tempdir().unwrap().path())
+        .unwrap()
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(truncated)))
+        .unwrap();
+    assert_eq!(report.bytes_read, truncated.len() as u64);
+}
+
+#[test]
+fn test_tar_structure_only() {
+    let dest = tempdir().unwrap();
+    let tar_data = create_multi_file_tar(&[("a/b/c.txt", b"c"), ("d.txt", b"d")]);
+
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .structure_only(true)
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(tar_data)))
+        .unwrap();
+
+    assert!(dest.path().join("a/b").is_dir());
+    assert!(!dest.path().join("a/b/c.txt").exists());
+    assert!(!dest.path().join("d.txt").exists());
+    assert_eq!(report.files_extracted, 0);
+    assert_eq!(report.entries_skipped, 2);
+}
+
+#[test]
+fn test_tar_is_safe_file() {
+    use safe_unzip::{is_safe_file, SymlinkBehavior};
+
+    let temp = tempdir().unwrap();
+    let good = temp.path().join("good.tar");
+    std::fs::write(&good, create_simple_tar("a.txt", b"a")).unwrap();
+    let linked = temp.path().join("linked.tar");
+    std::fs::write(&linked, create_symlink_tar(&[("link", "/etc/passwd")])).unwrap();
+
+    let limits = Limits::default();
+    assert!(is_safe_file(&good, &limits, SymlinkBehavior::Error).unwrap());
+    assert!(is_safe_file(&linked, &limits, SymlinkBehavior::Skip).unwrap());
+    assert!(!is_safe_file(&linked, &limits, SymlinkBehavior::Error).unwrap());
+}
+
+#[test]
+fn test_tar_overwrite_error_if_different() {
+    use safe_unzip::OverwriteMode;
+
+    let dest = tempdir().unwrap();
+    let extract = |files: &[(&str, &[u8])]| {
+        let adapter = TarAdapter::new(std::io::Cursor::new(create_multi_file_tar(files)));
+        Driver::new(dest.path())
+            .unwrap()
+            .overwrite(OverwriteMode::ErrorIfDifferent)
+            .extract_tar(adapter)
+    };
+
+    extract(&[("a.txt", b"alpha"), ("b.txt", b"beta")]).unwrap();
+
+    // Identical entries are skipped; new entries after them still extract
+    let report = extract(&[("a.txt", b"alpha"), ("b.txt", b"beta"), ("c.txt", b"gamma")]).unwrap();
+    assert_eq!(report.entries_skipped, 2);
+    assert_eq!(report.files_extracted, 1);
+
+    let result = extract(&[("a.txt", b"alpha"), ("b.txt", b"BETA")]);
+    assert!(matches!(result, Err(Error::ContentConflict { .. })));
+    assert_eq!(std::fs::read(dest.path().join("b.txt")).unwrap(), b"beta");
+}
+
+#[test]
+fn test_tar_allow_content_types() {
+    use safe_unzip::ContentCategory;
+
+    let tar_data =
+        create_multi_file_tar(&[("notes.txt", b"hello\n"), ("evil.txt", b"\x7fELF\x02\x01")]);
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .allow_content_types(&[ContentCategory::Text])
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(tar_data)));
+
+    assert!(matches!(result, Err(Error::DisallowedContent { .. })));
+    // Streaming keeps what passed; the sniffed prefix is still written in full
+    assert_eq!(
+        std::fs::read(dest.path().join("notes.txt")).unwrap(),
+        b"hello\n"
+    );
+    assert!(!dest.path().join("evil.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_tar_symlink_contained_or_skip() {
+    use safe_unzip::{SkippedSymlink, SymlinkBehavior};
+
+    let tar_data = create_symlink_tar(&[
+        ("good", "target.txt"),
+        ("abs", "/etc/passwd"),
+        ("sub/up", "../../outside"),
+    ]);
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .symlinks(SymlinkBehavior::ContainedOrSkip)
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(tar_data)))
+        .unwrap();
+
+    assert_eq!(report.symlinks_created, 1);
+    assert!(dest.path().join("good").is_symlink());
+    assert!(!dest.path().join("abs").exists());
+    assert!(!dest.path().join("sub/up").is_symlink());
+    assert_eq!(report.entries_skipped, 2);
+    assert_eq!(
+        report.skipped_symlinks,
+        vec![
+            SkippedSymlink {
+                name: "abs".into(),
+                target: "/etc/passwd".into()
+            },
+            SkippedSymlink {
+                name: "sub/up".into(),
+                target: "../../outside".into()
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_tar_max_compressed_bytes_streaming() {
+    // Each entry is 512 bytes of header plus 512 of data
+    let tar_data = create_multi_file_tar(&[("a.txt", b"a"), ("b.txt", b"b"), ("c.txt", b"c")]);
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .limits(Limits {
+            max_compressed_bytes: 1500,
+            ..Limits::default()
+        })
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)));
+
+    assert!(matches!(result, Err(Error::CompressedSizeExceeded { .. })));
+    assert!(dest.path().join("a.txt").exists());
+    assert!(!dest.path().join("c.txt").exists());
+
+    // ValidateFirst stops caching at the second entry, before the garbage
+    // where the third header should be
+    let mut truncated = tar_data[..2 * 1024].to_vec();
+    truncated.extend_from_slice(&[0xff; 512]);
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .validation(ValidationMode::ValidateFirst)
+        .limits(Limits {
+            max_compressed_bytes: 1500,
+            ..Limits::default()
+        })
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(truncated)));
+    assert!(
+        matches!(result, Err(Error::CompressedSizeExceeded { .. })),
+        "{:?}",
+        result
+    );
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_tar_gz_max_compressed_bytes_counts_compressed_input() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    // 5 MiB of zeros compresses to a few KiB
+    let tar_data = create_simple_tar("zeros.bin", &vec![0u8; 5 * 1024 * 1024]);
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&tar_data).unwrap();
+    let gz_data = encoder.finish().unwrap();
+    let temp = tempdir().unwrap();
+    let gz_path = temp.path().join("zeros.tar.gz");
+    std::fs::write(&gz_path, &gz_data).unwrap();
+
+    for mode in [ValidationMode::Streaming, ValidationMode::ValidateFirst] {
+        let dest = tempdir().unwrap();
+        let report = Driver::new(dest.path())
+            .unwrap()
+            .validation(mode)
+            .limits(Limits {
+                max_compressed_bytes: 1024 * 1024,
+                ..Limits::default()
+            })
+            .extract_tar_gz_file(&gz_path)
+            .unwrap();
+        assert_eq!(report.files_extracted, 1, "{mode:?}");
+
+        let dest = tempdir().unwrap();
+        let result = Driver::new(dest.path())
+            .unwrap()
+            .validation(mode)
+            .limits(Limits {
+                max_compressed_bytes: gz_data.len() as u64 - 1,
+                ..Limits::default()
+            })
+            .extract_tar_gz_file(&gz_path);
+        assert!(
+            matches!(result, Err(Error::CompressedSizeExceeded { .. })),
+            "{mode:?}"
+        );
+    }
+}
+
+#[test]
+fn test_tar_entries_report_unix_owner() {
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_path("owned.txt").unwrap();
+    header.set_size(4);
+    header.set_mode(0o644);
+    header.set_uid(1000);
+    header.set_gid(100);
+    header.set_cksum();
+    builder.append(&header, &b"data"[..]).unwrap();
+    let tar_data = builder.into_inner().unwrap();
+
+    let entries = safe_unzip::list_tar(std::io::Cursor::new(tar_data)).unwrap();
+    assert_eq!(entries[0].uid, Some(1000));
+    assert_eq!(entries[0].gid, Some(100));
+}
+
+#[test]
+fn test_list_tar_reports_mode_and_link_target() {
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_ustar();
+    header.set_path("bin/tool").unwrap();
+    header.set_size(4);
+    header.set_mode(0o4755);
+    header.set_cksum();
+    builder.append(&header, &b"tool"[..]).unwrap();
+
+    let mut header = tar::Header::new_ustar();
+    header.set_entry_type(tar::EntryType::Symlink);
+    header.set_path("current").unwrap();
+    header.set_link_name("bin/tool").unwrap();
+    header.set_size(0);
+    header.set_mode(0o777);
+    header.set_cksum();
+    builder.append(&header, &b""[..]).unwrap();
+    let tar_data = builder.into_inner().unwrap();
+
+    let entries = safe_unzip::list_tar(std::io::Cursor::new(tar_data)).unwrap();
+    assert_eq!(entries[0].mode, Some(0o4755));
+    assert_eq!(entries[0].symlink_target(), None);
+    assert_eq!(entries[1].symlink_target(), Some("bin/tool"));
+}
+
+#[test]
+fn test_tar_entries_report_mtime() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_ustar();
+    header.set_path("header.txt").unwrap();
+    header.set_size(0);
+    header.set_mtime(1_600_000_000);
+    header.set_cksum();
+    builder.append(&header, &b""[..]).unwrap();
+
+    // A PAX record is more precise and can predate the epoch
+    for (name, mtime) in [("pax.txt", "1700000000.25"), ("old.txt", "-1.25")] {
+        builder
+            .append_pax_extensions([("mtime", mtime.as_bytes())])
+            .unwrap();
+        let mut header = tar::Header::new_ustar();
+        header.set_path(name).unwrap();
+        header.set_size(0);
+        header.set_cksum();
+        builder.append(&header, &b""[..]).unwrap();
+    }
+    let tar_data = builder.into_inner().unwrap();
+
+    let entries = safe_unzip::list_tar(std::io::Cursor::new(&tar_data)).unwrap();
+    assert_eq!(
+        entries[0].mtime,
+        Some(UNIX_EPOCH + Duration::from_secs(1_600_000_000))
+    );
+    assert_eq!(
+        entries[1].mtime,
+        Some(UNIX_EPOCH + Duration::from_millis(1_700_000_000_250))
+    );
+    assert_eq!(
+        entries[2].mtime,
+        Some(UNIX_EPOCH - Duration::from_millis(1_250))
+    );
+
+    // The streaming adapter reports the same
+    let mut adapter = TarAdapter::new(std::io::Cursor::new(&tar_data));
+    let first = safe_unzip::ArchiveReader::next_entry(&mut adapter)
+        .unwrap()
+        .unwrap();
+    assert_eq!(first.mtime, entries[0].mtime);
+}
+
+/// Tar whose entries declare 0 bytes in the ustar header but carry
+/// `content` via a PAX `size` record.
+fn create_pax_sized_tar(names: &[&str], content: &[u8]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for name in names {
+        let record = format!(" size={}\n", content.len());
+        let record = format!("{}{}", record.len() + 2, record);
+        let mut pax = tar::Header::new_ustar();
+        pax.set_path("PaxHeader").unwrap();
+        pax.set_entry_type(tar::EntryType::XHeader);
+        pax.set_size(record.len() as u64);
+        pax.set_cksum();
+        builder.append(&pax, record.as_bytes()).unwrap();
+
+        let mut header = tar::Header::new_ustar();
+        header.set_path(name).unwrap();
+        header.set_size(0);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, content).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+#[test]
+fn test_tar_open_streams_entries() {
+    use std::io::Read;
+
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_path("my_pipe").unwrap();
+    header.set_size(0);
+    header.set_entry_type(tar::EntryType::Fifo);
+    header.set_cksum();
+    builder.append(&header, &[][..]).unwrap();
+    for (name, content) in [
+        ("a.txt", &b"first"[..]),
+        ("big.bin", &[7u8; 1500][..]),
+        ("c.txt", b"third"),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_path(name).unwrap();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, content).unwrap();
+    }
+    let tar_data = builder.into_inner().unwrap();
+
+    let dest = tempdir().unwrap();
+    let driver = Driver::new(dest.path())
+        .unwrap()
+        .unsupported_entries(UnsupportedPolicy::Skip);
+    let mut stream = driver
+        .open(TarAdapter::new(std::io::Cursor::new(&tar_data)))
+        .unwrap();
+    let mut seen = Vec::new();
+    while let Some(entry) = stream.next_entry() {
+        let (info, mut reader) = entry.unwrap();
+        // Read only part of the large entry; the rest is skipped
+        let mut content = vec![0u8; 3];
+        reader.read_exact(&mut content).unwrap();
+        seen.push((info.name, content));
+    }
+    assert_eq!(
+        seen,
+        vec![
+            ("a.txt".to_string(), b"fir".to_vec()),
+            ("big.bin".to_string(), vec![7u8; 3]),
+            ("c.txt".to_string(), b"thi".to_vec()),
+        ]
+    );
+    assert_eq!(stream.state().skipped_unsupported.len(), 1);
+    assert!(!dest.path().join("a.txt").exists());
+
+    // A size only known from a PAX record is still bounded while reading
+    let tar_data = create_pax_sized_tar(&["a.txt"], &[1u8; 64]);
+    let driver = Driver::new(dest.path()).unwrap().limits(Limits {
+        max_single_file: 16,
+        ..Limits::default()
+    });
+    let read = || -> Result<Vec<u8>, Error> {
+        let mut stream = driver.open(TarAdapter::new(std::io::Cursor::new(&tar_data)))?;
+        let (_, mut reader) = stream.next_entry().unwrap()?;
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        Ok(content)
+    };
+    assert!(matches!(
+        read(),
+        Err(Error::FileTooLarge { entry, limit: 16, .. }) if entry == "a.txt"
+    ));
+}
+
+#[test]
+fn test_tar_unknown_size_policy() {
+    use safe_unzip::UnknownSizePolicy;
+
+    let tar_data = create_pax_sized_tar(&["a.txt"], b"hello");
+    let entries = safe_unzip::list_tar(std::io::Cursor::new(&tar_data)).unwrap();
+    assert!(!entries[0].size_known);
+
+    // Allow (default): extracted, write-time limits still apply
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)))
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+    assert_eq!(std::fs::read(dest.path().join("a.txt")).unwrap(), b"hello");
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .unknown_size(UnknownSizePolicy::Reject)
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)));
+    assert!(matches!(result, Err(Error::UnknownEntrySize { entry }) if entry == "a.txt"));
+    assert!(!dest.path().join("a.txt").exists());
+
+    // AssumeMax budgets each entry at max_single_file during validation
+    let tar_data = create_pax_sized_tar(&["a.txt", "b.txt"], b"hello");
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .limits(Limits {
+            max_single_file: 100,
+            max_total_bytes: 150,
+            ..Default::default()
+        })
+        .validation(ValidationMode::ValidateFirst)
+        .unknown_size(UnknownSizePolicy::AssumeMax)
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)));
+    assert!(matches!(result, Err(Error::TotalSizeExceeded { .. })));
+    assert!(!dest.path().join("a.txt").exists());
+}
+
+#[test]
+fn test_tar_absolute_path_policy() {
+    use safe_unzip::AbsolutePathPolicy;
+
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_path("placeholder").unwrap();
+    header.set_size(4);
+    header.set_mode(0o644);
+    let evil_path = b"/etc/passwd";
+    header.as_mut_bytes()[..evil_path.len()].copy_from_slice(evil_path);
+    header.as_mut_bytes()[evil_path.len()] = 0;
+    header.set_cksum();
+    builder.append(&header, &b"pwnd"[..]).unwrap();
+    let tar_data = builder.into_inner().unwrap();
+
+    // Strip (default): root removed, filters see the stripped name
+    let dest = tempdir().unwrap();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen_clone = seen.clone();
+    Driver::new(dest.path())
+        .unwrap()
+        .filter(move |info| {
+            seen_clone.lock().unwrap().push(info.name.clone());
+            true
+        })
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)))
+        .unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec!["etc/passwd".to_string()]);
+    assert_eq!(
+        std::fs::read(dest.path().join("etc/passwd")).unwrap(),
+        b"pwnd"
+    );
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .absolute_paths(AbsolutePathPolicy::Reject)
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)));
+    assert!(matches!(result, Err(Error::AbsolutePath { entry }) if entry == "/etc/passwd"));
+    assert!(!dest.path().join("etc").exists());
+}
+
+/// TAR with one file per `(raw name, content)`, names written straight
+/// into the header so absolute paths survive.
+fn create_raw_name_tar(files: &[(&[u8], &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (name, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_path("placeholder").unwrap();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.as_mut_bytes()[..name.len()].copy_from_slice(name);
+        header.as_mut_bytes()[name.len()] = 0;
+        header.set_cksum();
+        builder.append(&header, *content).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+#[test]
+fn test_tar_absolute_root_map() {
+    let tar_data = create_raw_name_tar(&[
+        (b"/etc/hosts", b"127.0.0.1"),
+        (b"//usr/bin/env", b"#!"),
+        (b"README", b"docs"),
+    ]);
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .absolute_root_map("sysroot")
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)))
+        .unwrap();
+    assert_eq!(report.files_extracted, 3);
+    assert_eq!(
+        std::fs::read(dest.path().join("sysroot/etc/hosts")).unwrap(),
+        b"127.0.0.1"
+    );
+    assert!(dest.path().join("sysroot/usr/bin/env").exists());
+    assert!(dest.path().join("README").exists());
+    assert!(!dest.path().join("etc").exists());
+
+    // An absolute root inside the destination works the same
+    let dest = tempdir().unwrap();
+    Driver::new(dest.path())
+        .unwrap()
+        .absolute_root_map(dest.path().join("sysroot"))
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)))
+        .unwrap();
+    assert!(dest.path().join("sysroot/etc/hosts").exists());
+
+    // Mapped names may not climb out of the root, even within the destination
+    for evil in [&b"/../escape.txt"[..], b"/etc/../../escape.txt"] {
+        let dest = tempdir().unwrap();
+        let result = Driver::new(dest.path())
+            .unwrap()
+            .absolute_root_map("sysroot")
+            .extract_tar(TarAdapter::new(std::io::Cursor::new(create_raw_name_tar(
+                &[(evil, b"pwnd")],
+            ))));
+        assert!(
+            matches!(result, Err(Error::PathEscape { .. })),
+            "{}: {:?}",
+            String::from_utf8_lossy(evil),
+            result
+        );
+        assert!(!dest.path().join("escape.txt").exists());
+    }
+
+    // Roots outside the destination are refused up front
+    for root in [
+        std::path::PathBuf::from("../elsewhere"),
+        std::env::temp_dir(),
+    ] {
+        let dest = tempdir().unwrap();
+        let result = Driver::new(dest.path())
+            .unwrap()
+            .absolute_root_map(&root)
+            .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)));
+        assert!(
+            matches!(result, Err(Error::InvalidOption { .. })),
+            "{:?}",
+            result
+        );
+    }
+}
+
+#[test]
+fn test_tar_dot_prefixed_names() {
+    // What `tar -cf x.tar .` produces: a `./` root and `./`-prefixed names
+    let mut builder = tar::Builder::new(Vec::new());
+    for (name, content) in [
+        (&b"./"[..], None),
+        (b"./a/", None),
+        (b"./a/b.txt", Some(&b"hello"[..])),
+        (b".//./c.txt", Some(b"c")),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_path("placeholder").unwrap();
+        header.set_size(content.map_or(0, |c| c.len() as u64));
+        header.set_mode(0o755);
+        header.set_entry_type(if content.is_some() {
+            tar::EntryType::Regular
+        } else {
+            tar::EntryType::Directory
+        });
+        header.as_mut_bytes()[..name.len()].copy_from_slice(name);
+        header.as_mut_bytes()[name.len()] = 0;
+        header.set_cksum();
+        builder
+            .append(&header, content.unwrap_or_default())
+            .unwrap();
+    }
+    let tar_data = builder.into_inner().unwrap();
+
+    for mode in [ValidationMode::Streaming, ValidationMode::ValidateFirst] {
+        let dest = tempdir().unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = seen.clone();
+        let report = Driver::new(dest.path())
+            .unwrap()
+            .validation(mode)
+            // `./a/b.txt` would be three components deep
+            .limits(Limits {
+                max_path_depth: 2,
+                ..Default::default()
+            })
+            .filter(move |info| {
+                seen_clone.lock().unwrap().push(info.name.clone());
+                true
+            })
+            .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)))
+            .unwrap();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec!["a/".to_string(), "a/b.txt".to_string(), "c.txt".to_string()]
+        );
+        assert_eq!(report.files_extracted, 2);
+        assert_eq!(report.dirs_created, 1);
+        assert_eq!(report.entries_skipped, 0);
+        assert_eq!(
+            std::fs::read(dest.path().join("a/b.txt")).unwrap(),
+            b"hello"
+        );
+        assert!(dest.path().join("c.txt").exists());
+    }
+}
+
+#[test]
+fn test_tar_stripped_name_is_revalidated() {
+    // Stripping the root from each of these leaves a traversal
+    for evil_path in [
+        &b"/../escape.txt"[..],
+        b"///../../escape.txt",
+        b"C:../escape.txt",
+        b"C:/../escape.txt",
+    ] {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_path("placeholder").unwrap();
+        header.set_size(4);
+        header.set_mode(0o644);
+        header.as_mut_bytes()[..evil_path.len()].copy_from_slice(evil_path);
+        header.as_mut_bytes()[evil_path.len()] = 0;
+        header.set_cksum();
+        builder.append(&header, &b"pwnd"[..]).unwrap();
+        let tar_data = builder.into_inner().unwrap();
+
+        for mode in [ValidationMode::Streaming, ValidationMode::ValidateFirst] {
+            let root = tempdir().unwrap();
+            let dest = root.path().join("dest");
+            let result = Driver::new_or_create(&dest)
+                .unwrap()
+                .validation(mode)
+                .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)));
+            assert!(
+                matches!(result, Err(Error::PathEscape { .. })),
+                "{} ({:?}): {:?}",
+                String::from_utf8_lossy(evil_path),
+                mode,
+                result
+            );
+            assert!(!root.path().join("escape.txt").exists());
+            assert!(!root.path().parent().unwrap().join("escape.txt").exists());
+        }
+    }
+}
+
+#[test]
+fn test_extract_gz_file() {
+    use flate2::write::GzEncoder;
+    use flate2::{Compression, GzBuilder};
+
+    let temp = tempdir().unwrap();
+
+    // Stored name wins, reduced to its last component
+    let gz_path = temp.path().join("download.gz");
+    let mut encoder = GzBuilder::new().filename("../../etc/report.csv").write(
+        std::fs::File::create(&gz_path).unwrap(),
+        Compression::default(),
+    );
+    encoder.write_all(b"a,b\n1,2\n").unwrap();
+    encoder.finish().unwrap();
+
+    let dest = temp.path().join("named");
+    let report = Driver::new_or_create(&dest)
+        .unwrap()
+        .extract_gz_file(&gz_path)
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+    assert_eq!(report.bytes_written, 8);
+    assert_eq!(
+        std::fs::read(dest.join("report.csv")).unwrap(),
+        b"a,b\n1,2\n"
+    );
+
+    // No stored name: archive name minus .gz
+    let gz_path = temp.path().join("notes.txt.gz");
+    let mut encoder = GzEncoder::new(
+        std::fs::File::create(&gz_path).unwrap(),
+        Compression::default(),
+    );
+    encoder.write_all(&[b'x'; 4096]).unwrap();
+    encoder.finish().unwrap();
+
+    let dest = temp.path().join("unnamed");
+    Driver::new_or_create(&dest)
+        .unwrap()
+        .extract_gz_file(&gz_path)
+        .unwrap();
+    assert_eq!(std::fs::read(dest.join("notes.txt")).unwrap().len(), 4096);
+
+    // The single-file limit is enforced while decompressing
+    let result = Driver::new_or_create(temp.path().join("limited"))
+        .unwrap()
+        .limits(Limits {
+            max_single_file: 1024,
+            ..Default::default()
+        })
+        .extract_gz_file(&gz_path);
+    assert!(matches!(result, Err(Error::FileTooLarge { .. })));
+
+    // A tarball behind a plain .gz name is extracted as TAR
+    let gz_path = temp.path().join("bundle.gz");
+    let mut encoder = GzEncoder::new(
+        std::fs::File::create(&gz_path).unwrap(),
+        Compression::default(),
+    );
+    encoder
+        .write_all(&create_multi_file_tar(&[
+            ("a.txt", b"aaa"),
+            ("b.txt", b"bbb"),
+        ]))
+        .unwrap();
+    encoder.finish().unwrap();
+
+    let dest = temp.path().join("tarball");
+    let report = Driver::new_or_create(&dest)
+        .unwrap()
+        .extract_gz_file(&gz_path)
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+    assert!(dest.join("b.txt").exists());
+}
+
+#[test]
+fn test_tar_dedupe_store_records_after_success() {
+    use safe_unzip::MemoryDedupeStore;
+
+    let temp = tempdir().unwrap();
+    let tar_path = temp.path().join("batch.tar");
+    std::fs::write(&tar_path, create_multi_file_tar(&[("a.txt", b"alpha")])).unwrap();
+    std::fs::create_dir(temp.path().join("blocked")).unwrap();
+    std::fs::write(temp.path().join("blocked/a.txt"), b"existing").unwrap();
+
+    // A failed extraction is not recorded, so the archive is retried
+    let seen = Arc::new(MemoryDedupeStore::new());
+    let result = Driver::new(temp.path().join("blocked"))
+        .unwrap()
+        .dedupe_store(Arc::clone(&seen))
+        .extract_tar_file(&tar_path);
+    assert!(matches!(result, Err(Error::AlreadyExists { .. })));
+    assert!(seen.is_empty());
+
+    let report = Driver::new_or_create(temp.path().join("out"))
+        .unwrap()
+        .dedupe_store(Arc::clone(&seen))
+        .extract_tar_file(&tar_path)
+        .unwrap();
+    assert_eq!(report.files_extracted, 1);
+    assert_eq!(seen.len(), 1);
+
+    let report = Driver::new_or_create(temp.path().join("again"))
+        .unwrap()
+        .dedupe_store(seen)
+        .extract_tar_file(&tar_path)
+        .unwrap();
+    assert!(report.skipped_duplicate_archive);
+    assert!(!temp.path().join("again/a.txt").exists());
+}
+
+#[test]
+fn test_tar_hash_source_covers_whole_file() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use safe_unzip::DigestAlgorithm;
+    use sha2::{Digest, Sha256, Sha512};
+
+    let temp = tempdir().unwrap();
+    let hex = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    };
+
+    // Trailing blocks after the end marker are never read by the extractor
+    let mut tar_data = create_multi_file_tar(&[("a.txt", b"alpha"), ("b.txt", b"beta")]);
+    tar_data.extend_from_slice(&[0u8; 8192]);
+    let tar_path = temp.path().join("bundle.tar");
+    std::fs::write(&tar_path, &tar_data).unwrap();
+
+    let report = Driver::new_or_create(temp.path().join("tar"))
+        .unwrap()
+        .hash_source(DigestAlgorithm::Sha256)
+        .extract_tar_file(&tar_path)
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(
+        report.source_digest.as_deref(),
+        Some(hex(&Sha256::digest(&tar_data)).as_str())
+    );
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&tar_data).unwrap();
+    let gz_data = encoder.finish().unwrap();
+    let gz_path = temp.path().join("bundle.tar.gz");
+    std::fs::write(&gz_path, &gz_data).unwrap();
+
+    let report = Driver::new_or_create(temp.path().join("tgz"))
+        .unwrap()
+        .hash_source(DigestAlgorithm::Sha512)
+        .extract_tar_gz_file(&gz_path)
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(
+        report.source_digest.as_deref(),
+        Some(hex(&Sha512::digest(&gz_data)).as_str())
+    );
+
+    // Off by default
+    let report = Driver::new_or_create(temp.path().join("plain"))
+        .unwrap()
+        .extract_tar_file(&tar_path)
+        .unwrap();
+    assert!(report.source_digest.is_none());
+}
+
+#[test]
+fn test_tar_per_entry_deadline() {
+    use std::io::Read;
+    use std::time::Duration;
+
+    /// Hands out at most 512 bytes per read, sleeping before each.
+    struct SlowReader<R>(R);
+
+    impl<R: Read> Read for SlowReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            std::thread::sleep(Duration::from_millis(20));
+            let len = buf.len().min(512);
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    let tar_data =
+        create_multi_file_tar(&[("fast.txt", b"quick"), ("slow.bin", &[7u8; 16 * 1024])]);
+
+    let dest = tempdir().unwrap();
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .per_entry_deadline(Duration::from_millis(150))
+        .extract_tar(TarAdapter::new(SlowReader(std::io::Cursor::new(
+            tar_data.clone(),
+        ))));
+    match result {
+        Err(Error::Timeout { entry }) => assert_eq!(entry, "slow.bin"),
+        other => panic!("expected Timeout, got {:?}", other),
+    }
+    assert!(dest.path().join("fast.txt").exists());
+
+    // A generous window lets the same archive through
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .per_entry_deadline(Duration::from_secs(60))
+        .extract_tar(TarAdapter::new(SlowReader(std::io::Cursor::new(tar_data))))
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+}
+
+/// Tar with one file carrying a `user.origin` xattr in a PAX record.
+fn create_xattr_tar() -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    builder
+        .append_pax_extensions([("SCHILY.xattr.user.origin", &b"mirror"[..])])
+        .unwrap();
+    let mut header = tar::Header::new_ustar();
+    header.set_path("labelled.txt").unwrap();
+    header.set_size(4);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append(&header, &b"data"[..]).unwrap();
+    builder.into_inner().unwrap()
+}
+
+#[test]
+fn test_tar_pax_xattrs_listed() {
+    let entries = safe_unzip::list_tar(std::io::Cursor::new(create_xattr_tar())).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        entries[0].xattrs,
+        vec![("user.origin".to_string(), b"mirror".to_vec())]
+    );
+}
+
+#[cfg(all(unix, feature = "xattr"))]
+#[test]
+fn test_tar_restore_xattrs() {
+    // Off by default
+    let dest = tempdir().unwrap();
+    Driver::new(dest.path())
+        .unwrap()
+        .extract_tar(TarAdapter::new(std::io::Cursor::new(create_xattr_tar())))
+        .unwrap();
+    let path = dest.path().join("labelled.txt");
+    assert_eq!(xattr::get(&path, "user.origin").unwrap(), None);
+
+    // Extraction succeeds either way; only check the attribute where the
+    // filesystem can hold one
+    let supported = xattr::set(&path, "user.probe", b"1").is_ok();
+
+    for mode in [ValidationMode::Streaming, ValidationMode::ValidateFirst] {
+        let dest = tempdir().unwrap();
+        let report = Driver::new(dest.path())
+            .unwrap()
+            .validation(mode)
+            .restore_xattrs(true)
+            .extract_tar(TarAdapter::new(std::io::Cursor::new(create_xattr_tar())))
+            .unwrap();
+        assert_eq!(report.files_extracted, 1);
+        if supported {
+            assert_eq!(
+                xattr::get(dest.path().join("labelled.txt"), "user.origin").unwrap(),
+                Some(b"mirror".to_vec())
+            );
+        }
+    }
+}
+
+#[test]
+fn test_verify_tar_files() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use safe_unzip::{verify_tar_file, verify_tar_gz_file};
+
+    let temp = tempdir().unwrap();
+    let content = vec![b'x'; 5000];
+    let tar_data = create_multi_file_tar(&[("a.txt", b"hello"), ("b.bin", &content)]);
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&tar_data).unwrap();
+    let gz_data = encoder.finish().unwrap();
+
+    let tar_path = temp.path().join("ok.tar");
+    let gz_path = temp.path().join("ok.tar.gz");
+    std::fs::write(&tar_path, &tar_data).unwrap();
+    std::fs::write(&gz_path, &gz_data).unwrap();
+    for report in [
+        verify_tar_file(&tar_path).unwrap(),
+        verify_tar_gz_file(&gz_path).unwrap(),
+    ] {
+        assert_eq!(report.entries_verified, 2);
+        assert_eq!(report.bytes_verified, 5005);
+    }
+
+    // Losing only the gzip trailer is caught, though the TAR data is whole
+    let truncated = temp.path().join("truncated.tar.gz");
+    std::fs::write(&truncated, &gz_data[..gz_data.len() - 4]).unwrap();
+    let result = verify_tar_gz_file(&truncated);
+    assert!(matches!(result, Err(Error::Io(_))), "{:?}", result);
+
+    // Content cut short mid-entry
+    let short = temp.path().join("short.tar");
+    std::fs::write(&short, &tar_data[..512 + 512 + 512 + 1000]).unwrap();
+    let result = verify_tar_file(&short);
+    assert!(matches!(result, Err(Error::Io(_))), "{:?}", result);
+
+    // A damaged header fails its checksum
+    let mut damaged = tar_data.clone();
+    damaged[0] ^= 0x20;
+    let damaged_path = temp.path().join("damaged.tar");
+    std::fs::write(&damaged_path, &damaged).unwrap();
+    let result = verify_tar_file(&damaged_path);
+    assert!(matches!(result, Err(Error::Io(_))), "{:?}", result);
+}
+
+#[test]
+fn test_tar_extract_to_memory() {
+    use safe_unzip::OverwriteMode;
+
+    let dest = tempdir().unwrap();
+    let tar_data = create_tar_with_dir("docs/", "guide.md", b"# Guide\n");
+    let files = Driver::new(dest.path())
+        .unwrap()
+        .extract_to_memory(TarAdapter::new(std::io::Cursor::new(&tar_data)))
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files["docs/guide.md"], b"# Guide\n");
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+
+    // Repeated names follow the overwrite mode
+    let tar_data = create_multi_file_tar(&[("a.txt", b"first"), ("a.txt", b"second")]);
+    let extract = |mode| {
+        Driver::new(dest.path())
+            .unwrap()
+            .overwrite(mode)
+            .extract_to_memory(TarAdapter::new(std::io::Cursor::new(&tar_data)))
+    };
+    assert!(matches!(
+        extract(OverwriteMode::Error),
+        Err(Error::AlreadyExists { .. })
+    ));
+    assert_eq!(extract(OverwriteMode::Skip).unwrap()["a.txt"], b"first");
+    assert_eq!(
+        extract(OverwriteMode::Overwrite).unwrap()["a.txt"],
+        b"second"
+    );
+
+    let result = Driver::new(dest.path())
+        .unwrap()
+        .limits(Limits {
+            max_total_bytes: 8,
+            ..Default::default()
+        })
+        .extract_to_memory(TarAdapter::new(std::io::Cursor::new(&tar_data)));
+    assert!(matches!(result, Err(Error::TotalSizeExceeded { .. })));
+}
+
+#[cfg(feature = "xz")]
+#[test]
+fn test_tar_xz() {
+    use safe_unzip::{list_tar_xz_entries, verify_tar_xz_file};
+    use xz2::write::XzEncoder;
+
+    let temp = tempdir().unwrap();
+    let tar_data = create_multi_file_tar(&[("a.txt", b"hello"), ("src/b.rs", b"fn main() {}")]);
+    let mut encoder = XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(&tar_data).unwrap();
+    let xz_data = encoder.finish().unwrap();
+    let xz_path = temp.path().join("src.tar.xz");
+    std::fs::write(&xz_path, &xz_data).unwrap();
+
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_tar_xz_file(&xz_path)
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+    assert_eq!(
+        std::fs::read(dest.path().join("src/b.rs")).unwrap(),
+        b"fn main() {}"
+    );
+
+    let dest = tempdir().unwrap();
+    let report = Driver::new(dest.path())
+        .unwrap()
+        .extract_tar(TarAdapter::open_xz(&xz_path).unwrap())
+        .unwrap();
+    assert_eq!(report.files_extracted, 2);
+
+    let entries = list_tar_xz_entries(&xz_path).unwrap();
+    let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["a.txt", "src/b.rs"]);
+
+    let report = verify_tar_xz_file(&xz_path).unwrap();
+    assert_eq!(report.entries_verified, 2);
+    assert_eq!(report.bytes_verified, 17);
+
+    // Losing the stream footer is caught, though the TAR data is whole
+    let truncated = temp.path().join("truncated.tar.xz");
+    std::fs::write(&truncated, &xz_data[..xz_data.len() - 12]).unwrap();
+    let result = verify_tar_xz_file(&truncated);
+    assert!(matches!(result, Err(Error::Io(_))), "{:?}", result);
+}
+
+#[test]
+fn test_tar_on_progress() {
+    let tar_data = create_multi_file_tar(&[("a.txt", b"hello"), ("b.txt", b"world!")]);
+    for (mode, total) in [
+        (ValidationMode::Streaming, 0),
+        (ValidationMode::ValidateFirst, 2),
+    ] {
+        let dest = tempdir().unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+        Driver::new(dest.path())
+            .unwrap()
+            .validation(mode)
+            .on_progress(move |p| {
+                recorded.lock().unwrap().push((
+                    p.entry_name.clone(),
+                    p.entry_index,
+                    p.total_entries,
+                    p.bytes_written,
+                    p.files_extracted,
+                ));
+            })
+            .extract_tar(TarAdapter::new(std::io::Cursor::new(&tar_data)))
+            .unwrap();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                ("a.txt".to_string(), 0, total, 0, 0),
+                ("b.txt".to_string(), 1, total, 5, 1),
+            ]
+        );
+    }
+}